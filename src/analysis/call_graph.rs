@@ -0,0 +1,247 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::json;
+
+use crate::{BNLFile, analysis::find_aid_strings, game::AssetType};
+
+/// A graph of which scripts and cutscenes reference each other by AID, across every bundle it was
+/// built from.
+///
+/// The script format itself isn't decoded yet, so references are found by scanning the descriptor
+/// and resource data of each script/cutscene for `aid_` strings. Only references to other scripts
+/// or cutscenes found in the given bundles become edges.
+#[derive(Debug, Clone, Default)]
+pub struct CallGraph {
+    /// Every script/cutscene AID found, mapped to the first bundle index it was found in.
+    nodes: BTreeMap<String, usize>,
+    edges: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl CallGraph {
+    /// Builds a [`CallGraph`] from the scripts and cutscenes of a set of bundles.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use bnl::{BNLFile, analysis::call_graph::CallGraph};
+    ///
+    /// # fn load_bundles() -> Vec<BNLFile> { Vec::new() }
+    /// let bundles: Vec<BNLFile> = load_bundles();
+    /// let graph = CallGraph::build(&bundles);
+    ///
+    /// std::fs::write("./scripts.dot", graph.to_dot()).expect("Unable to write graph.");
+    /// ```
+    pub fn build<'a>(bundles: impl IntoIterator<Item = &'a BNLFile>) -> CallGraph {
+        let mut graph = CallGraph::default();
+        let mut references: Vec<(String, Vec<String>)> = Vec::new();
+
+        for (bundle_index, bnl) in bundles.into_iter().enumerate() {
+            for desc in bnl.asset_descriptions() {
+                if !is_sequencing_type(desc.asset_type()) {
+                    continue;
+                }
+
+                let name = desc.name().to_string();
+                graph.nodes.entry(name.clone()).or_insert(bundle_index);
+
+                let Ok(raw_asset) = bnl.get_raw_asset(&name) else {
+                    continue;
+                };
+
+                let mut aids = find_aid_strings(&raw_asset.descriptor_bytes);
+
+                for slice in &raw_asset.data_slices {
+                    aids.extend(find_aid_strings(slice));
+                }
+
+                references.push((name, aids));
+            }
+        }
+
+        // Edges are only resolved once every bundle is loaded, as scripts regularly reference
+        // scripts stored in other bundles
+        for (name, aids) in references {
+            let targets = graph.edges.entry(name.clone()).or_default();
+
+            for aid in aids {
+                if aid != name && graph.nodes.contains_key(&aid) {
+                    targets.insert(aid);
+                }
+            }
+        }
+
+        graph
+    }
+
+    /// Returns the names of every script and cutscene in the graph.
+    pub fn nodes(&self) -> impl Iterator<Item = &str> {
+        self.nodes.keys().map(String::as_str)
+    }
+
+    /// Returns the index of the first bundle `name` was found in, in the order the bundles were
+    /// given to [`CallGraph::build`], or None if it isn't a script or cutscene in the graph.
+    pub fn bundle_index(&self, name: &str) -> Option<usize> {
+        self.nodes.get(name).copied()
+    }
+
+    /// Returns the scripts and cutscenes directly referenced by `name`.
+    pub fn callees(&self, name: &str) -> impl Iterator<Item = &str> {
+        self.edges
+            .get(name)
+            .into_iter()
+            .flat_map(|targets| targets.iter().map(String::as_str))
+    }
+
+    /// Returns the scripts and cutscenes that directly reference `name`.
+    pub fn callers<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.edges
+            .iter()
+            .filter(move |(_, targets)| targets.contains(name))
+            .map(|(caller, _)| caller.as_str())
+    }
+
+    /// Renders the graph in Graphviz DOT format.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph scripts {\n");
+
+        for node in self.nodes.keys() {
+            dot.push_str(&format!("    \"{}\";\n", node));
+        }
+
+        for (caller, targets) in &self.edges {
+            for target in targets {
+                dot.push_str(&format!("    \"{}\" -> \"{}\";\n", caller, target));
+            }
+        }
+
+        dot.push_str("}\n");
+
+        dot
+    }
+
+    /// Renders the graph as JSON, in the form `{ "nodes": [...], "edges": [[from, to], ...] }`.
+    pub fn to_json(&self) -> serde_json::Value {
+        let edges: Vec<[&str; 2]> = self
+            .edges
+            .iter()
+            .flat_map(|(caller, targets)| {
+                targets
+                    .iter()
+                    .map(move |target| [caller.as_str(), target.as_str()])
+            })
+            .collect();
+
+        json!({
+            "nodes": self.nodes.keys().collect::<Vec<_>>(),
+            "edges": edges,
+        })
+    }
+}
+
+fn is_sequencing_type(asset_type: AssetType) -> bool {
+    matches!(asset_type, AssetType::ResScript | AssetType::ResCutscene)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::RawAsset;
+
+    fn raw_asset(name: &str, asset_type: AssetType, descriptor: &[u8], data: &[u8]) -> RawAsset {
+        RawAsset {
+            name: name.to_string(),
+            asset_type,
+            descriptor_bytes: descriptor.to_vec(),
+            data_slices: vec![data.to_vec()],
+        }
+    }
+
+    fn bundles() -> [BNLFile; 2] {
+        let mut common = BNLFile::new();
+        common
+            .add_asset(&raw_asset(
+                "aid_script_a",
+                AssetType::ResScript,
+                b"aid_script_b\0aid_script_missing\0aid_script_a\0",
+                &[0; 4],
+            ))
+            .unwrap();
+        common
+            .add_asset(&raw_asset(
+                "aid_cutscene_c",
+                AssetType::ResCutscene,
+                &[0; 4],
+                &[0; 4],
+            ))
+            .unwrap();
+        common
+            .add_asset(&raw_asset(
+                "aid_texture_d",
+                AssetType::ResTexture,
+                &[0; 4],
+                &[0; 4],
+            ))
+            .unwrap();
+
+        let mut level = BNLFile::new();
+        level
+            .add_asset(&raw_asset(
+                "aid_script_b",
+                AssetType::ResScript,
+                &[0; 4],
+                b"aid_cutscene_c\0aid_texture_d\0",
+            ))
+            .unwrap();
+
+        [common, level]
+    }
+
+    #[test]
+    fn builds_edges_across_bundles() {
+        let graph = CallGraph::build(&bundles());
+
+        assert_eq!(
+            graph.nodes().collect::<Vec<_>>(),
+            ["aid_cutscene_c", "aid_script_a", "aid_script_b"]
+        );
+        assert_eq!(graph.bundle_index("aid_script_a"), Some(0));
+        assert_eq!(graph.bundle_index("aid_script_b"), Some(1));
+        assert_eq!(graph.bundle_index("aid_texture_d"), None);
+
+        // References found in the data slices count as well as the descriptor
+        assert_eq!(
+            graph.callees("aid_script_b").collect::<Vec<_>>(),
+            ["aid_cutscene_c"]
+        );
+        assert_eq!(
+            graph.callers("aid_cutscene_c").collect::<Vec<_>>(),
+            ["aid_script_b"]
+        );
+        assert_eq!(
+            graph.to_json()["edges"],
+            json!([
+                ["aid_script_a", "aid_script_b"],
+                ["aid_script_b", "aid_cutscene_c"]
+            ])
+        );
+    }
+
+    #[test]
+    fn skips_missing_targets() {
+        let graph = CallGraph::build(&bundles());
+
+        // Neither the missing script nor the self reference become edges
+        assert_eq!(
+            graph.callees("aid_script_a").collect::<Vec<_>>(),
+            ["aid_script_b"]
+        );
+        assert_eq!(graph.callers("aid_script_missing").count(), 0);
+        assert!(!graph.to_dot().contains("aid_script_missing"));
+
+        // A bundle on its own can't resolve references into other bundles
+        let [common, _] = bundles();
+        let graph = CallGraph::build([&common]);
+
+        assert_eq!(graph.callees("aid_script_a").count(), 0);
+        assert_eq!(graph.bundle_index("aid_script_b"), None);
+    }
+}
@@ -0,0 +1,335 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::json;
+
+use crate::{
+    BNLFile,
+    analysis::find_aid_strings,
+    asset::{
+        RawAsset,
+        aid_list::AidList,
+        ghoulybox::{Ghoulybox, Ghoulyspawn},
+        particle::{Emitter, Particle},
+    },
+    game::AssetType,
+};
+
+/// How a dependency between two assets was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ReferenceKind {
+    /// An `aid_` string inside of a script or cutscene.
+    Script,
+    /// An entry of an AID list.
+    AidList,
+    /// An `aid_` string inside of a model, such as a texture reference.
+    Model,
+    /// A typed AID field of a parsed descriptor, such as the particle of an emitter.
+    Descriptor,
+}
+
+/// A graph of which assets reference which other assets by AID, across every bundle it was built
+/// from.
+///
+/// References are taken from the typed AID fields of the assets that have parsers (AID lists,
+/// ghoulyboxes, ghoulyspawns, emitters and particles), and from `aid_` strings found in the data
+/// of scripts, cutscenes and models. References to assets that aren't in any of the bundles are
+/// kept, see [`DependencyGraph::unresolved`].
+#[derive(Debug, Clone, Default)]
+pub struct DependencyGraph {
+    nodes: BTreeMap<String, AssetType>,
+    edges: BTreeMap<String, BTreeMap<String, ReferenceKind>>,
+}
+
+impl DependencyGraph {
+    /// Builds a [`DependencyGraph`] from every asset of a set of bundles.
+    ///
+    /// # Examples
+    /// ```
+    /// use bnl::{BNLFile, analysis::dependency_graph::DependencyGraph};
+    ///
+    /// # fn load_bundles() -> Vec<BNLFile> { Vec::new() }
+    /// let bundles: Vec<BNLFile> = load_bundles();
+    /// let graph = DependencyGraph::build(&bundles);
+    ///
+    /// for missing in graph.unresolved() {
+    ///     println!("{} is referenced but not in any bundle", missing);
+    /// }
+    /// ```
+    pub fn build<'a>(bundles: impl IntoIterator<Item = &'a BNLFile>) -> DependencyGraph {
+        let mut graph = DependencyGraph::default();
+
+        for bnl in bundles {
+            for desc in bnl.asset_descriptions() {
+                let name = desc.name().to_string();
+                graph.nodes.entry(name.clone()).or_insert(desc.asset_type());
+
+                let references = references_of(bnl, desc.asset_type(), &name);
+
+                let targets = graph.edges.entry(name.clone()).or_default();
+
+                for (aid, kind) in references {
+                    if aid != name {
+                        targets.entry(aid).or_insert(kind);
+                    }
+                }
+            }
+        }
+
+        graph.edges.retain(|_, targets| !targets.is_empty());
+
+        graph
+    }
+
+    /// Returns the name and type of every asset found in the bundles.
+    pub fn nodes(&self) -> impl Iterator<Item = (&str, AssetType)> {
+        self.nodes
+            .iter()
+            .map(|(name, asset_type)| (name.as_str(), *asset_type))
+    }
+
+    /// Returns the assets directly referenced by `name`, and how each reference was found.
+    pub fn dependencies(&self, name: &str) -> impl Iterator<Item = (&str, ReferenceKind)> {
+        self.edges.get(name).into_iter().flat_map(|targets| {
+            targets
+                .iter()
+                .map(|(target, kind)| (target.as_str(), *kind))
+        })
+    }
+
+    /// Returns the assets that directly reference `name`.
+    pub fn dependents<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.edges
+            .iter()
+            .filter(move |(_, targets)| targets.contains_key(name))
+            .map(|(source, _)| source.as_str())
+    }
+
+    /// Returns every referenced AID that isn't an asset in any of the bundles.
+    pub fn unresolved(&self) -> BTreeSet<&str> {
+        self.edges
+            .values()
+            .flat_map(|targets| targets.keys())
+            .filter(|target| !self.nodes.contains_key(*target))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Renders the graph in Graphviz DOT format. Only assets with references (either way) are
+    /// included, and unresolved references are drawn dashed.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph dependencies {\n");
+
+        let unresolved = self.unresolved();
+
+        let mut connected: BTreeSet<&str> = self.edges.keys().map(String::as_str).collect();
+        connected.extend(
+            self.edges
+                .values()
+                .flat_map(|t| t.keys().map(String::as_str)),
+        );
+
+        for node in connected {
+            match self.nodes.get(node) {
+                Some(asset_type) => {
+                    dot.push_str(&format!(
+                        "    \"{}\" [tooltip=\"{:?}\"];\n",
+                        node, asset_type
+                    ));
+                }
+                None if unresolved.contains(node) => {
+                    dot.push_str(&format!("    \"{}\" [style=dashed];\n", node));
+                }
+                None => {}
+            }
+        }
+
+        for (source, targets) in &self.edges {
+            for (target, kind) in targets {
+                dot.push_str(&format!(
+                    "    \"{}\" -> \"{}\" [label=\"{:?}\"];\n",
+                    source, target, kind
+                ));
+            }
+        }
+
+        dot.push_str("}\n");
+
+        dot
+    }
+
+    /// Renders the graph as JSON, in the form
+    /// `{ "nodes": [{ name, type }], "edges": [{ from, to, kind }], "unresolved": [...] }`.
+    pub fn to_json(&self) -> serde_json::Value {
+        let nodes: Vec<_> = self
+            .nodes
+            .iter()
+            .map(|(name, asset_type)| json!({ "name": name, "type": format!("{:?}", asset_type) }))
+            .collect();
+
+        let edges: Vec<_> = self
+            .edges
+            .iter()
+            .flat_map(|(source, targets)| {
+                targets.iter().map(move |(target, kind)| {
+                    json!({ "from": source, "to": target, "kind": format!("{:?}", kind) })
+                })
+            })
+            .collect();
+
+        json!({
+            "nodes": nodes,
+            "edges": edges,
+            "unresolved": self.unresolved(),
+        })
+    }
+}
+
+/// Finds the AIDs referenced by a single asset.
+fn references_of(bnl: &BNLFile, asset_type: AssetType, name: &str) -> Vec<(String, ReferenceKind)> {
+    let with_kind = |aids: Vec<String>, kind| aids.into_iter().map(|aid| (aid, kind)).collect();
+
+    match asset_type {
+        AssetType::ResScript | AssetType::ResCutscene => bnl
+            .get_raw_asset(name)
+            .map(|raw_asset| with_kind(scan_aids(&raw_asset), ReferenceKind::Script))
+            .unwrap_or_default(),
+        AssetType::ResModel => bnl
+            .get_raw_asset(name)
+            .map(|raw_asset| with_kind(scan_aids(&raw_asset), ReferenceKind::Model))
+            .unwrap_or_default(),
+        AssetType::ResAidList => bnl
+            .get_asset::<AidList>(name)
+            .map(|aid_list| with_kind(aid_list.aids().to_vec(), ReferenceKind::AidList))
+            .unwrap_or_default(),
+        _ => with_kind(
+            descriptor_aids(bnl, asset_type, name),
+            ReferenceKind::Descriptor,
+        ),
+    }
+}
+
+/// Returns the AIDs stored in the typed fields of a parsed descriptor.
+fn descriptor_aids(bnl: &BNLFile, asset_type: AssetType, name: &str) -> Vec<String> {
+    match asset_type {
+        AssetType::ResGhoulybox => bnl
+            .get_asset::<Ghoulybox>(name)
+            .map(|ghoulybox| {
+                ghoulybox
+                    .entries()
+                    .iter()
+                    .map(|entry| entry.actor_attribs.clone())
+                    .collect()
+            })
+            .unwrap_or_default(),
+        AssetType::ResGhoulyspawn => bnl
+            .get_asset::<Ghoulyspawn>(name)
+            .map(|ghoulyspawn| vec![ghoulyspawn.ghoulybox().to_string()])
+            .unwrap_or_default(),
+        AssetType::ResEmitter => bnl
+            .get_asset::<Emitter>(name)
+            .map(|emitter| vec![emitter.particle().to_string()])
+            .unwrap_or_default(),
+        AssetType::ResParticle => bnl
+            .get_asset::<Particle>(name)
+            .map(|particle| vec![particle.texture().to_string()])
+            .unwrap_or_default(),
+        _ => vec![],
+    }
+    .into_iter()
+    .filter(|aid| !aid.is_empty())
+    .collect()
+}
+
+fn scan_aids(raw_asset: &RawAsset) -> Vec<String> {
+    let mut aids = find_aid_strings(&raw_asset.descriptor_bytes);
+
+    for slice in &raw_asset.data_slices {
+        aids.extend(find_aid_strings(slice));
+    }
+
+    aids
+}
+
+impl BNLFile {
+    /// Builds a [`DependencyGraph`] of the assets in this file, see [`DependencyGraph::build`] for
+    /// graphs spanning several bundles.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # let bnl = bnl::BNLFile::new();
+    /// let graph = bnl.dependency_graph();
+    /// std::fs::write("./dependencies.dot", graph.to_dot()).expect("Unable to write graph.");
+    /// ```
+    pub fn dependency_graph(&self) -> DependencyGraph {
+        DependencyGraph::build([self])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{asset::aid_to_bytes, tests::bnl_from_raw_assets};
+
+    fn raw_asset(name: &str, asset_type: AssetType, descriptor_bytes: Vec<u8>) -> RawAsset {
+        RawAsset {
+            name: name.to_string(),
+            asset_type,
+            descriptor_bytes,
+            data_slices: vec![],
+        }
+    }
+
+    #[test]
+    fn finds_references() {
+        let mut aid_list = vec![];
+        aid_list.extend_from_slice(&2u32.to_le_bytes());
+        aid_list.extend_from_slice(&8u32.to_le_bytes());
+        aid_list.extend_from_slice(&aid_to_bytes("aid_emitter_smoke").unwrap());
+        aid_list.extend_from_slice(&aid_to_bytes("aid_script_missing").unwrap());
+
+        let mut emitter = aid_to_bytes("aid_particle_smoke").unwrap().to_vec();
+        emitter.extend_from_slice(&[0; 28]);
+
+        let mut script = raw_asset("aid_script_intro", AssetType::ResScript, vec![0; 4]);
+        script.data_slices = vec![b"\x01\0aid_emitter_smoke\0aid_script_intro\0".to_vec()];
+
+        let bnl = bnl_from_raw_assets(&[
+            raw_asset("aid_aidlist_level", AssetType::ResAidList, aid_list),
+            raw_asset("aid_emitter_smoke", AssetType::ResEmitter, emitter),
+            script,
+        ]);
+
+        let graph = bnl.dependency_graph();
+
+        assert_eq!(graph.nodes().count(), 3);
+        assert_eq!(
+            graph.dependencies("aid_aidlist_level").collect::<Vec<_>>(),
+            [
+                ("aid_emitter_smoke", ReferenceKind::AidList),
+                ("aid_script_missing", ReferenceKind::AidList)
+            ]
+        );
+        assert_eq!(
+            graph.dependencies("aid_emitter_smoke").collect::<Vec<_>>(),
+            [("aid_particle_smoke", ReferenceKind::Descriptor)]
+        );
+
+        // Scripts don't depend on themselves, even though they contain their own name
+        assert_eq!(
+            graph.dependencies("aid_script_intro").collect::<Vec<_>>(),
+            [("aid_emitter_smoke", ReferenceKind::Script)]
+        );
+        assert_eq!(
+            graph.dependents("aid_emitter_smoke").collect::<Vec<_>>(),
+            ["aid_aidlist_level", "aid_script_intro"]
+        );
+        assert_eq!(
+            graph.unresolved().into_iter().collect::<Vec<_>>(),
+            ["aid_particle_smoke", "aid_script_missing"]
+        );
+
+        let dot = graph.to_dot();
+        assert!(dot.contains("\"aid_script_missing\" [style=dashed];"));
+        assert_eq!(graph.to_json()["edges"].as_array().unwrap().len(), 4);
+    }
+}
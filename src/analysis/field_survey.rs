@@ -0,0 +1,305 @@
+//! Surveys the values stored at each offset of a descriptor across many assets, to help decode
+//! fields that are still unknown. A field that never changes, only takes a handful of small
+//! values, always points inside the descriptor or always holds a sensible float usually says a
+//! lot about what it is.
+
+use std::collections::BTreeMap;
+
+use serde_json::{Value, json};
+
+use crate::{BNLFile, asset::AID_SIZE, game::AssetType};
+
+/// The number of most common values kept in [`FieldStats::common_values`].
+pub const COMMON_VALUE_COUNT: usize = 5;
+
+/// The share of samples that must agree for a field to be classed as a pointer or float.
+const KIND_THRESHOLD: f64 = 0.9;
+
+/// The most distinct values a field can take and still be classed as [`FieldKind::EnumLike`].
+const MAX_ENUM_VALUES: usize = 16;
+
+/// What the values seen in a field look like.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldKind {
+    /// Every sample held the same value.
+    Constant(u32),
+    /// Only a handful of small values were seen, as in enums and flags.
+    EnumLike,
+    /// The values are 4 byte aligned offsets that fall inside the descriptor they were read
+    /// from, or zero.
+    PointerLike,
+    /// The values read as ordinary floats, such as `1.0` or `-250.5`, or zero.
+    FloatLike,
+    /// None of the above.
+    Varied,
+}
+
+impl FieldKind {
+    /// Returns a short lowercase name for this kind, such as `pointer`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FieldKind::Constant(_) => "constant",
+            FieldKind::EnumLike => "enum",
+            FieldKind::PointerLike => "pointer",
+            FieldKind::FloatLike => "float",
+            FieldKind::Varied => "varied",
+        }
+    }
+}
+
+/// The values seen in one 4 byte field of a [`FieldSurvey`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldStats {
+    /// The offset of the field from the start of the surveyed bytes.
+    pub offset: usize,
+    /// The number of samples long enough to hold this field.
+    pub samples: usize,
+    /// The number of different values seen.
+    pub distinct: usize,
+    pub min: u32,
+    pub max: u32,
+    /// Up to [`COMMON_VALUE_COUNT`] values with how often they were seen, most common first.
+    pub common_values: Vec<(u32, usize)>,
+    pub kind: FieldKind,
+}
+
+/// The tallies for one field while a survey is being built.
+#[derive(Debug, Clone, Default)]
+struct FieldTally {
+    values: BTreeMap<u32, usize>,
+    pointer_like: usize,
+    float_like: usize,
+}
+
+/// The values seen at each 4 byte aligned offset of a set of descriptors (or other records), see
+/// [`FieldSurvey::descriptors`]. Fields are surveyed as little endian `u32`s, so a pair of `u16`s
+/// shows up as a single field.
+#[derive(Debug, Clone, Default)]
+pub struct FieldSurvey {
+    samples: usize,
+    fields: Vec<FieldTally>,
+}
+
+impl FieldSurvey {
+    /// Surveys the descriptor of every asset of `asset_type` in the given bundles.
+    ///
+    /// # Examples
+    /// ```
+    /// use bnl::{analysis::field_survey::FieldSurvey, game::AssetType};
+    ///
+    /// # let bundles: Vec<bnl::BNLFile> = Vec::new();
+    /// let survey = FieldSurvey::descriptors(AssetType::ResTexture, &bundles);
+    ///
+    /// for field in survey.fields() {
+    ///     println!("{:#x}: {:?}", field.offset, field.kind);
+    /// }
+    /// ```
+    pub fn descriptors<'a>(
+        asset_type: AssetType,
+        bundles: impl IntoIterator<Item = &'a BNLFile>,
+    ) -> FieldSurvey {
+        let mut survey = FieldSurvey::default();
+
+        for bnl in bundles {
+            for desc in bnl.asset_descriptions() {
+                if desc.asset_type() != asset_type {
+                    continue;
+                }
+
+                if let Ok(raw_asset) = bnl.get_raw_asset_ref(desc.name()) {
+                    survey.add(raw_asset.descriptor_bytes);
+                }
+            }
+        }
+
+        survey
+    }
+
+    /// Surveys the asset table entries of every asset of `asset_type` in the given bundles,
+    /// which hold the `unk_` fields of [`crate::asset::AssetDescription`]. The name at the start
+    /// of each entry is skipped, so offsets count from the asset type field that follows it.
+    pub fn asset_descriptions<'a>(
+        asset_type: AssetType,
+        bundles: impl IntoIterator<Item = &'a BNLFile>,
+    ) -> FieldSurvey {
+        let mut survey = FieldSurvey::default();
+
+        for bnl in bundles {
+            for desc in bnl.asset_descriptions() {
+                if desc.asset_type() == asset_type {
+                    survey.add(&desc.to_bytes()[AID_SIZE..]);
+                }
+            }
+        }
+
+        survey
+    }
+
+    /// Adds one sample to this survey. Any bytes past the last whole field are ignored.
+    pub fn add(&mut self, bytes: &[u8]) {
+        let field_count = bytes.len() / 4;
+
+        if self.fields.len() < field_count {
+            self.fields.resize_with(field_count, FieldTally::default);
+        }
+
+        for (tally, field) in self.fields.iter_mut().zip(bytes.chunks_exact(4)) {
+            let value = u32::from_le_bytes(field.try_into().unwrap_or_default());
+
+            *tally.values.entry(value).or_default() += 1;
+
+            if value == 0 || (value.is_multiple_of(4) && (value as usize) < bytes.len()) {
+                tally.pointer_like += 1;
+            }
+
+            if value == 0 || is_plausible_float(f32::from_bits(value)) {
+                tally.float_like += 1;
+            }
+        }
+
+        self.samples += 1;
+    }
+
+    /// Returns the number of samples surveyed.
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// Returns the values seen in each field, in offset order.
+    pub fn fields(&self) -> Vec<FieldStats> {
+        self.fields
+            .iter()
+            .enumerate()
+            .map(|(i, tally)| {
+                let samples = tally.values.values().sum();
+
+                let mut common_values: Vec<(u32, usize)> =
+                    tally.values.iter().map(|(&v, &n)| (v, n)).collect();
+                common_values.sort_by_key(|&(value, count)| (std::cmp::Reverse(count), value));
+                common_values.truncate(COMMON_VALUE_COUNT);
+
+                FieldStats {
+                    offset: i * 4,
+                    samples,
+                    distinct: tally.values.len(),
+                    min: tally.values.keys().next().copied().unwrap_or_default(),
+                    max: tally.values.keys().next_back().copied().unwrap_or_default(),
+                    common_values,
+                    kind: classify(tally, samples),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns this survey as JSON, with one entry per field.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "samples": self.samples,
+            "fields": self
+                .fields()
+                .iter()
+                .map(|field| {
+                    json!({
+                        "offset": field.offset,
+                        "samples": field.samples,
+                        "distinct": field.distinct,
+                        "min": field.min,
+                        "max": field.max,
+                        "kind": field.kind.name(),
+                        "common_values": field
+                            .common_values
+                            .iter()
+                            .map(|(value, count)| json!({ "value": value, "count": count }))
+                            .collect::<Vec<_>>(),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+fn classify(tally: &FieldTally, samples: usize) -> FieldKind {
+    let share = |count: usize| count as f64 / samples.max(1) as f64;
+
+    let mut values = tally.values.keys();
+
+    if let (Some(&value), None) = (values.next(), values.next()) {
+        return FieldKind::Constant(value);
+    }
+
+    if tally.values.len() <= MAX_ENUM_VALUES && tally.values.keys().all(|&value| value < 0x100) {
+        FieldKind::EnumLike
+    } else if share(tally.pointer_like) >= KIND_THRESHOLD {
+        FieldKind::PointerLike
+    } else if share(tally.float_like) >= KIND_THRESHOLD {
+        FieldKind::FloatLike
+    } else {
+        FieldKind::Varied
+    }
+}
+
+/// Returns true if `value` looks like a float a game would store, rather than the bits of an
+/// integer read as one.
+fn is_plausible_float(value: f32) -> bool {
+    value.is_finite() && (1e-4..=1e7).contains(&value.abs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_fields() {
+        let mut survey = FieldSurvey::default();
+
+        for i in 0..20u32 {
+            let mut bytes = vec![];
+
+            for value in [
+                7,
+                i % 3,
+                12,
+                (i as f32 * 1.5 + 1.0).to_bits(),
+                i * 0x0101_0101,
+            ] {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+
+            survey.add(&bytes);
+        }
+
+        survey.add(&[1, 0, 0, 0, 2]);
+
+        let fields = survey.fields();
+        assert_eq!(survey.samples(), 21);
+
+        assert_eq!(fields[0].kind, FieldKind::EnumLike);
+        assert_eq!(fields[0].common_values[0], (7, 20));
+        assert_eq!(fields[0].samples, 21);
+        assert_eq!(fields[1].kind, FieldKind::EnumLike);
+        assert_eq!(fields[1].distinct, 3);
+        assert_eq!(fields[2].kind, FieldKind::Constant(12));
+        assert_eq!(fields[2].samples, 20);
+        assert_eq!(fields[3].kind, FieldKind::FloatLike);
+        assert_eq!(fields[4].kind, FieldKind::Varied);
+        assert_eq!(fields[4].max, 19 * 0x0101_0101);
+    }
+
+    #[test]
+    fn surveys_bundles() {
+        let bnl = crate::testutil::sample_bnl();
+
+        let survey = FieldSurvey::descriptors(AssetType::ResTexture, [&bnl]);
+        let fields = survey.fields();
+
+        assert_eq!(survey.samples(), 2);
+        assert_eq!(fields.len(), 7);
+        assert_eq!(fields[1].kind, FieldKind::Constant(28));
+
+        let survey = FieldSurvey::asset_descriptions(AssetType::ResScript, [&bnl]);
+        assert_eq!(
+            survey.fields()[0].kind,
+            FieldKind::Constant(AssetType::ResScript.into())
+        );
+    }
+}
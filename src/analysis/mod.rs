@@ -0,0 +1,57 @@
+//! Analysis passes that look across the assets of one or more [`crate::BNLFile`]s.
+
+pub mod call_graph;
+pub mod dependency_graph;
+pub mod field_survey;
+pub mod string_index;
+pub mod timeline;
+
+/// The prefix shared by every asset ID (AID) string in the game.
+pub(crate) const AID_PREFIX: &[u8] = b"aid_";
+
+/// Finds every NUL-terminated ASCII string starting with `aid_` inside of some bytes. Strings are
+/// only matched at the start of a string (either the start of the data, or after a NUL byte) so
+/// that AIDs embedded inside of other strings aren't picked up.
+pub(crate) fn find_aid_strings(bytes: &[u8]) -> Vec<String> {
+    let mut aids = Vec::new();
+
+    let mut i = 0;
+
+    while i + AID_PREFIX.len() <= bytes.len() {
+        let at_string_start = i == 0 || bytes[i - 1] == 0;
+
+        if !at_string_start || !bytes[i..].starts_with(AID_PREFIX) {
+            i += 1;
+            continue;
+        }
+
+        let len = bytes[i..]
+            .iter()
+            .position(|b| !b.is_ascii_graphic())
+            .unwrap_or(bytes.len() - i);
+
+        // Only accept strings that are properly terminated
+        if bytes.get(i + len).is_none_or(|b| *b == 0) {
+            aids.push(String::from_utf8_lossy(&bytes[i..i + len]).into_owned());
+        }
+
+        i += len.max(1);
+    }
+
+    aids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_terminated_aids() {
+        let bytes = b"aid_script_a\0\x01\x02\0aid_cutscene_b\0xaid_not_me\0aid_bad\x01\0aid_end";
+
+        assert_eq!(
+            find_aid_strings(bytes),
+            vec!["aid_script_a", "aid_cutscene_b", "aid_end"]
+        );
+    }
+}
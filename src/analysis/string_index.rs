@@ -0,0 +1,365 @@
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use serde_json::{Value, json};
+
+use crate::BNLFile;
+
+/// The shortest run of characters that counts as a string.
+pub const MIN_STRING_LENGTH: usize = 4;
+
+/// The version of the on-disk format written by [`StringIndex::save`].
+const INDEX_VERSION: u64 = 1;
+
+/// How a string is encoded in the data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringEncoding {
+    Ascii,
+    /// Little endian UTF-16, as used by localised text.
+    Utf16,
+}
+
+/// Where a string was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StringLocation {
+    /// The index of the bundle, see [`StringIndex::bundle_label`].
+    pub bundle: usize,
+    /// The index of the asset, see [`StringIndex::asset_name`].
+    pub asset: usize,
+    /// The data slice the string is in, or None if it's in the asset's descriptor.
+    pub slice: Option<usize>,
+    /// The byte offset of the string within its descriptor or slice.
+    pub offset: usize,
+    pub encoding: StringEncoding,
+}
+
+/// An inverted index from every string in a set of bundles to where it was found, so that
+/// searching the whole game doesn't need to rescan every file. Indexes can be saved to disk with
+/// [`StringIndex::save`] and loaded again with [`StringIndex::load`].
+///
+/// Strings are runs of at least [`MIN_STRING_LENGTH`] printable ASCII characters, either as plain
+/// ASCII or little endian UTF-16, found in the descriptors and resources of every asset.
+#[derive(Debug, Clone, Default)]
+pub struct StringIndex {
+    bundles: Vec<String>,
+    /// Every asset, as (bundle index, name).
+    assets: Vec<(usize, String)>,
+    strings: BTreeMap<String, Vec<StringLocation>>,
+}
+
+impl StringIndex {
+    /// Builds a [`StringIndex`] from a set of labelled bundles, such as their paths.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use std::path::Path;
+    ///
+    /// use bnl::{BNLFile, analysis::string_index::StringIndex};
+    ///
+    /// # fn load_bundle(_path: &str) -> BNLFile { BNLFile::new() }
+    /// let common: BNLFile = load_bundle("./common.bnl");
+    /// let index = StringIndex::build([("common.bnl", &common)]);
+    ///
+    /// index.save(Path::new("./strings.json")).expect("Unable to save index.");
+    /// ```
+    pub fn build<'a>(bundles: impl IntoIterator<Item = (&'a str, &'a BNLFile)>) -> StringIndex {
+        let mut index = StringIndex::default();
+
+        for (label, bnl) in bundles {
+            let bundle = index.bundles.len();
+            index.bundles.push(label.to_string());
+
+            for raw_asset in bnl.get_raw_assets() {
+                let asset = index.assets.len();
+                index.assets.push((bundle, raw_asset.name.clone()));
+
+                let sections = std::iter::once((None, &raw_asset.descriptor_bytes)).chain(
+                    raw_asset
+                        .data_slices
+                        .iter()
+                        .enumerate()
+                        .map(|(i, slice)| (Some(i), slice)),
+                );
+
+                for (slice, bytes) in sections {
+                    for (offset, encoding, string) in find_strings(bytes) {
+                        index
+                            .strings
+                            .entry(string)
+                            .or_default()
+                            .push(StringLocation {
+                                bundle,
+                                asset,
+                                slice,
+                                offset,
+                                encoding,
+                            });
+                    }
+                }
+            }
+        }
+
+        index
+    }
+
+    pub fn bundle_label(&self, bundle: usize) -> Option<&str> {
+        self.bundles.get(bundle).map(String::as_str)
+    }
+
+    pub fn asset_name(&self, asset: usize) -> Option<&str> {
+        self.assets.get(asset).map(|(_, name)| name.as_str())
+    }
+
+    /// Returns the number of unique strings in the index.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Returns every location of exactly `string`.
+    pub fn find(&self, string: &str) -> &[StringLocation] {
+        self.strings
+            .get(string)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Returns every indexed string starting with `prefix`, and where each was found.
+    pub fn find_prefix<'a>(
+        &'a self,
+        prefix: &'a str,
+    ) -> impl Iterator<Item = (&'a str, &'a [StringLocation])> {
+        self.strings
+            .range(prefix.to_string()..)
+            .take_while(move |(string, _)| string.starts_with(prefix))
+            .map(|(string, locations)| (string.as_str(), locations.as_slice()))
+    }
+
+    /// Returns every indexed string containing `needle`, and where each was found.
+    pub fn grep<'a>(
+        &'a self,
+        needle: &'a str,
+    ) -> impl Iterator<Item = (&'a str, &'a [StringLocation])> {
+        self.strings
+            .iter()
+            .filter(move |(string, _)| string.contains(needle))
+            .map(|(string, locations)| (string.as_str(), locations.as_slice()))
+    }
+
+    /// Writes this index to `path` as JSON.
+    pub fn save(&self, path: &Path) -> Result<(), io::Error> {
+        let strings: serde_json::Map<String, Value> = self
+            .strings
+            .iter()
+            .map(|(string, locations)| {
+                let locations: Vec<Value> = locations
+                    .iter()
+                    .map(|location| {
+                        json!([
+                            location.asset,
+                            location.slice,
+                            location.offset,
+                            matches!(location.encoding, StringEncoding::Utf16),
+                        ])
+                    })
+                    .collect();
+
+                (string.clone(), Value::Array(locations))
+            })
+            .collect();
+
+        let json = json!({
+            "version": INDEX_VERSION,
+            "bundles": self.bundles,
+            "assets": self.assets,
+            "strings": strings,
+        });
+
+        fs::write(path, json.to_string())
+    }
+
+    /// Loads an index written by [`StringIndex::save`].
+    ///
+    /// # Errors
+    /// Returns an error if the file can't be read, or isn't an index of a supported version.
+    pub fn load(path: &Path) -> Result<StringIndex, io::Error> {
+        let invalid = |message: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid string index {}: {}", path.display(), message),
+            )
+        };
+
+        let json: Value =
+            serde_json::from_slice(&fs::read(path)?).map_err(|e| invalid(&e.to_string()))?;
+
+        if json["version"].as_u64() != Some(INDEX_VERSION) {
+            return Err(invalid("unsupported version"));
+        }
+
+        let mut index = StringIndex::default();
+
+        for bundle in json["bundles"]
+            .as_array()
+            .ok_or_else(|| invalid("missing bundles"))?
+        {
+            index.bundles.push(
+                bundle
+                    .as_str()
+                    .ok_or_else(|| invalid("bad bundle"))?
+                    .to_string(),
+            );
+        }
+
+        for asset in json["assets"]
+            .as_array()
+            .ok_or_else(|| invalid("missing assets"))?
+        {
+            let bundle = asset[0].as_u64().ok_or_else(|| invalid("bad asset"))? as usize;
+            let name = asset[1].as_str().ok_or_else(|| invalid("bad asset"))?;
+
+            index.assets.push((bundle, name.to_string()));
+        }
+
+        let strings = json["strings"]
+            .as_object()
+            .ok_or_else(|| invalid("missing strings"))?;
+
+        for (string, locations) in strings {
+            let locations = locations
+                .as_array()
+                .ok_or_else(|| invalid("bad locations"))?
+                .iter()
+                .map(|location| {
+                    let asset = location[0]
+                        .as_u64()
+                        .ok_or_else(|| invalid("bad location"))?
+                        as usize;
+
+                    Ok(StringLocation {
+                        bundle: index
+                            .assets
+                            .get(asset)
+                            .ok_or_else(|| invalid("bad location"))?
+                            .0,
+                        asset,
+                        slice: location[1].as_u64().map(|slice| slice as usize),
+                        offset: location[2]
+                            .as_u64()
+                            .ok_or_else(|| invalid("bad location"))?
+                            as usize,
+                        encoding: match location[3].as_bool() {
+                            Some(true) => StringEncoding::Utf16,
+                            _ => StringEncoding::Ascii,
+                        },
+                    })
+                })
+                .collect::<Result<_, io::Error>>()?;
+
+            index.strings.insert(string.clone(), locations);
+        }
+
+        Ok(index)
+    }
+}
+
+/// Finds every ASCII and little endian UTF-16 string of at least [`MIN_STRING_LENGTH`] printable
+/// characters, as (offset, encoding, string).
+pub(crate) fn find_strings(bytes: &[u8]) -> Vec<(usize, StringEncoding, String)> {
+    let printable = |b: u8| (0x20..0x7f).contains(&b);
+
+    let mut strings = Vec::new();
+
+    let mut start = 0;
+
+    for i in 0..=bytes.len() {
+        if i < bytes.len() && printable(bytes[i]) {
+            continue;
+        }
+
+        if i - start >= MIN_STRING_LENGTH {
+            let string = String::from_utf8_lossy(&bytes[start..i]).into_owned();
+            strings.push((start, StringEncoding::Ascii, string));
+        }
+
+        start = i + 1;
+    }
+
+    // UTF-16 strings may start at either alignment
+    for alignment in 0..2 {
+        let units: Vec<u16> = bytes
+            .get(alignment..)
+            .unwrap_or_default()
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+
+        let mut start = 0;
+
+        for i in 0..=units.len() {
+            if i < units.len() && units[i] < 0x80 && printable(units[i] as u8) {
+                continue;
+            }
+
+            if i - start >= MIN_STRING_LENGTH {
+                let string = String::from_utf16_lossy(&units[start..i]);
+                strings.push((alignment + start * 2, StringEncoding::Utf16, string));
+            }
+
+            start = i + 1;
+        }
+    }
+
+    strings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{asset::RawAsset, game::AssetType, tests::bnl_from_raw_assets};
+
+    #[test]
+    fn finds_strings() {
+        let mut bytes = b"\x01aid_script_a\0ab\0\0".to_vec();
+        bytes.extend("Hello".encode_utf16().flat_map(u16::to_le_bytes));
+
+        assert_eq!(
+            find_strings(&bytes),
+            [
+                (1, StringEncoding::Ascii, "aid_script_a".to_string()),
+                (18, StringEncoding::Utf16, "Hello".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn save_and_load() {
+        let bnl = bnl_from_raw_assets(&[RawAsset {
+            name: "aid_script_a".to_string(),
+            asset_type: AssetType::ResScript,
+            descriptor_bytes: b"desc\0".to_vec(),
+            data_slices: vec![b"\0\0aid_texture_x\0".to_vec()],
+        }]);
+
+        let index = StringIndex::build([("common.bnl", &bnl)]);
+
+        let location = index.find("aid_texture_x")[0];
+        assert_eq!(index.asset_name(location.asset), Some("aid_script_a"));
+        assert_eq!(index.bundle_label(location.bundle), Some("common.bnl"));
+        assert_eq!((location.slice, location.offset), (Some(0), 2));
+        assert_eq!(index.find("desc")[0].slice, None);
+        assert_eq!(index.find_prefix("aid_").count(), 1);
+        assert_eq!(index.grep("text").count(), 1);
+
+        let path = std::env::temp_dir().join("bnl_string_index_test.json");
+        index.save(&path).unwrap();
+
+        let loaded = StringIndex::load(&path).unwrap();
+        assert_eq!(loaded.len(), index.len());
+        assert_eq!(loaded.find("aid_texture_x"), index.find("aid_texture_x"));
+
+        fs::remove_file(path).unwrap();
+    }
+}
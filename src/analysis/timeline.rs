@@ -0,0 +1,159 @@
+use std::{
+    collections::BTreeMap,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use serde_json::{Value, json};
+
+use crate::{BNLFile, game::AssetType};
+
+/// One copy of an asset within a bundle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetOccurrence {
+    /// The label of the bundle this copy was found in, as given to [`AssetTimeline::build`].
+    pub bundle: String,
+    pub asset_type: AssetType,
+    /// The combined size of the descriptor and resource data.
+    pub size: usize,
+    /// A hash of the descriptor and resource data, used to tell copies apart. Only comparable
+    /// within the same run.
+    pub content_hash: u64,
+}
+
+/// Records, for every asset name, which bundles contain a copy of it and whether those copies are
+/// identical. Assets that differ between bundles are usually per-level overrides of a shared
+/// asset.
+#[derive(Debug, Clone, Default)]
+pub struct AssetTimeline {
+    assets: BTreeMap<String, Vec<AssetOccurrence>>,
+}
+
+impl AssetTimeline {
+    /// Builds an [`AssetTimeline`] from a set of labelled bundles, in the order given.
+    ///
+    /// # Examples
+    /// ```
+    /// use bnl::{BNLFile, analysis::timeline::AssetTimeline};
+    ///
+    /// # fn load_bundle(_path: &str) -> BNLFile { BNLFile::new() }
+    /// let common: BNLFile = load_bundle("./common.bnl");
+    /// let level1: BNLFile = load_bundle("./level1.bnl");
+    ///
+    /// let timeline = AssetTimeline::build([("common", &common), ("level1", &level1)]);
+    ///
+    /// for name in timeline.overridden() {
+    ///     println!("{} differs between bundles", name);
+    /// }
+    /// ```
+    pub fn build<'a>(bundles: impl IntoIterator<Item = (&'a str, &'a BNLFile)>) -> AssetTimeline {
+        let mut timeline = AssetTimeline::default();
+
+        for (label, bnl) in bundles {
+            for desc in bnl.asset_descriptions() {
+                let Ok(raw_asset) = bnl.get_raw_asset(desc.name()) else {
+                    continue;
+                };
+
+                let mut hasher = DefaultHasher::new();
+                raw_asset.descriptor_bytes.hash(&mut hasher);
+                raw_asset.data_slices.concat().hash(&mut hasher);
+
+                timeline
+                    .assets
+                    .entry(raw_asset.name)
+                    .or_default()
+                    .push(AssetOccurrence {
+                        bundle: label.to_string(),
+                        asset_type: raw_asset.asset_type,
+                        size: raw_asset.descriptor_bytes.len()
+                            + raw_asset.data_slices.iter().map(Vec::len).sum::<usize>(),
+                        content_hash: hasher.finish(),
+                    });
+            }
+        }
+
+        timeline
+    }
+
+    /// Returns the name of every asset found.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.assets.keys().map(String::as_str)
+    }
+
+    /// Returns every copy of an asset, in bundle order. The slice is empty for unknown names.
+    pub fn occurrences(&self, name: &str) -> &[AssetOccurrence] {
+        self.assets.get(name).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Returns true if every copy of an asset has the same type and contents.
+    pub fn is_identical(&self, name: &str) -> bool {
+        let occurrences = self.occurrences(name);
+
+        occurrences.windows(2).all(|pair| {
+            pair[0].asset_type == pair[1].asset_type && pair[0].content_hash == pair[1].content_hash
+        })
+    }
+
+    /// Returns the assets found in more than one bundle.
+    pub fn shared(&self) -> impl Iterator<Item = &str> {
+        self.assets
+            .iter()
+            .filter(|(_, occurrences)| occurrences.len() > 1)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Returns the assets found in more than one bundle whose copies aren't all identical.
+    pub fn overridden(&self) -> impl Iterator<Item = &str> {
+        self.shared().filter(|name| !self.is_identical(name))
+    }
+
+    /// Returns this timeline as JSON, with one entry per asset listing its bundles.
+    pub fn to_json(&self) -> Value {
+        self.assets
+            .iter()
+            .map(|(name, occurrences)| {
+                json!({
+                    "name": name,
+                    "identical": self.is_identical(name),
+                    "bundles": occurrences
+                        .iter()
+                        .map(|occurrence| {
+                            json!({
+                                "bundle": occurrence.bundle,
+                                "type": format!("{:?}", occurrence.asset_type),
+                                "size": occurrence.size,
+                            })
+                        })
+                        .collect::<Vec<_>>(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::make_bnl;
+
+    #[test]
+    fn finds_overrides() {
+        let common = BNLFile::from_bytes(&make_bnl()).unwrap();
+        let mut level = BNLFile::from_bytes(&make_bnl()).unwrap();
+
+        let timeline = AssetTimeline::build([("common", &common), ("level", &level)]);
+        assert_eq!(timeline.shared().collect::<Vec<_>>(), ["aid_texture_test"]);
+        assert_eq!(timeline.overridden().count(), 0);
+
+        let mut raw_asset = level.get_raw_asset("aid_texture_test").unwrap();
+        raw_asset.data_slices[0] = vec![9, 9, 9, 9];
+        level.update_asset("aid_texture_test", &raw_asset).unwrap();
+
+        let timeline = AssetTimeline::build([("common", &common), ("level", &level)]);
+        assert_eq!(
+            timeline.overridden().collect::<Vec<_>>(),
+            ["aid_texture_test"]
+        );
+        assert_eq!(timeline.occurrences("aid_texture_test")[1].bundle, "level");
+    }
+}
@@ -0,0 +1,271 @@
+use std::{
+    fmt::{self, Display},
+    io::{self, Cursor},
+};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use crate::{
+    VirtualResource,
+    asset::{Asset, AssetDescriptor, AssetParseError, RawAsset, WritableAsset, write_fields},
+    game::AssetType,
+};
+
+/// The size of the known fields of an [`ActorAttribsDescriptor`].
+pub(crate) const ACTOR_ATTRIBS_DESCRIPTOR_SIZE: usize = 48;
+
+/// The attributes of an actor type, such as its health and movement speeds.
+///
+/// Fields whose meaning isn't known yet are kept as raw values named after their offset, and any
+/// bytes after the known fields are kept as-is so that the descriptor round trips exactly.
+///
+/// None of these fields have been checked against game files, so the names below are guesses
+/// from the order the values are likely to be stored in. A descriptor shorter than the assumed
+/// layout is still read, with the missing fields left as zero.
+///
+/// Assumed layout:
+/// - health (f32)
+/// - walk speed (f32)
+/// - run speed (f32)
+/// - turn speed in radians per second (f32)
+/// - attack damage (f32)
+/// - attack range (f32)
+/// - sight range (f32)
+/// - behaviour flags (u32)
+/// - team (u32)
+/// - 3 unknown u32s
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ActorAttribsDescriptor {
+    pub health: f32,
+    pub walk_speed: f32,
+    pub run_speed: f32,
+    pub turn_speed: f32,
+    pub attack_damage: f32,
+    pub attack_range: f32,
+    pub sight_range: f32,
+    pub behavior_flags: u32,
+    pub team: u32,
+    pub unknown_0x24: u32,
+    pub unknown_0x28: u32,
+    pub unknown_0x2c: u32,
+    /// Any bytes following the known fields.
+    pub trailing: Vec<u8>,
+    /// The size of the original descriptor if it was shorter than the known fields, so that
+    /// [`ActorAttribs::to_bytes`] writes back the same number of bytes.
+    pub short_len: Option<usize>,
+}
+
+impl AssetDescriptor for ActorAttribsDescriptor {
+    fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError> {
+        let short_len = (data.len() < ACTOR_ATTRIBS_DESCRIPTOR_SIZE).then_some(data.len());
+
+        let mut known = [0u8; ACTOR_ATTRIBS_DESCRIPTOR_SIZE];
+        let known_len = data.len().min(ACTOR_ATTRIBS_DESCRIPTOR_SIZE);
+        known[..known_len].copy_from_slice(&data[..known_len]);
+
+        let mut cur = Cursor::new(known.as_slice());
+
+        Ok(ActorAttribsDescriptor {
+            health: cur.read_f32::<LittleEndian>()?,
+            walk_speed: cur.read_f32::<LittleEndian>()?,
+            run_speed: cur.read_f32::<LittleEndian>()?,
+            turn_speed: cur.read_f32::<LittleEndian>()?,
+            attack_damage: cur.read_f32::<LittleEndian>()?,
+            attack_range: cur.read_f32::<LittleEndian>()?,
+            sight_range: cur.read_f32::<LittleEndian>()?,
+            behavior_flags: cur.read_u32::<LittleEndian>()?,
+            team: cur.read_u32::<LittleEndian>()?,
+            unknown_0x24: cur.read_u32::<LittleEndian>()?,
+            unknown_0x28: cur.read_u32::<LittleEndian>()?,
+            unknown_0x2c: cur.read_u32::<LittleEndian>()?,
+            trailing: data[known_len..].to_vec(),
+            short_len,
+        })
+    }
+}
+
+impl Display for ActorAttribsDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_fields(
+            f,
+            &[
+                ("health", self.health.to_string()),
+                ("walk_speed", self.walk_speed.to_string()),
+                ("run_speed", self.run_speed.to_string()),
+                ("turn_speed", self.turn_speed.to_string()),
+                ("attack_damage", self.attack_damage.to_string()),
+                ("attack_range", self.attack_range.to_string()),
+                ("sight_range", self.sight_range.to_string()),
+                ("behavior_flags", format!("{:#010x}", self.behavior_flags)),
+                ("team", self.team.to_string()),
+                ("unknown_0x24", format!("{:#010x}", self.unknown_0x24)),
+                ("unknown_0x28", format!("{:#010x}", self.unknown_0x28)),
+                ("unknown_0x2c", format!("{:#010x}", self.unknown_0x2c)),
+                ("trailing_bytes", self.trailing.len().to_string()),
+            ],
+        )
+    }
+}
+
+/// The gameplay attributes of an actor type, referenced by ghoulyboxes when spawning actors.
+#[derive(Debug)]
+pub struct ActorAttribs {
+    name: String,
+    descriptor: ActorAttribsDescriptor,
+}
+
+impl Asset for ActorAttribs {
+    type Descriptor = ActorAttribsDescriptor;
+
+    fn new(
+        name: &str,
+        descriptor: &Self::Descriptor,
+        _virtual_res: &VirtualResource,
+    ) -> Result<Self, AssetParseError> {
+        Ok(ActorAttribs {
+            name: name.to_string(),
+            descriptor: descriptor.clone(),
+        })
+    }
+
+    fn descriptor(&self) -> &Self::Descriptor {
+        &self.descriptor
+    }
+
+    fn asset_type() -> AssetType {
+        AssetType::ResActorAttribs
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl ActorAttribs {
+    pub fn health(&self) -> f32 {
+        self.descriptor.health
+    }
+
+    pub fn walk_speed(&self) -> f32 {
+        self.descriptor.walk_speed
+    }
+
+    pub fn run_speed(&self) -> f32 {
+        self.descriptor.run_speed
+    }
+
+    pub fn behavior_flags(&self) -> u32 {
+        self.descriptor.behavior_flags
+    }
+
+    /// Returns the attributes for editing, see [`ActorAttribs::to_bytes`].
+    pub fn descriptor_mut(&mut self) -> &mut ActorAttribsDescriptor {
+        &mut self.descriptor
+    }
+
+    /// Serialises these attributes back into descriptor bytes. The result is the same size as the
+    /// original descriptor, so it can be written back with [`crate::BNLFile::update_asset`].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bnl::{BNLFile, asset::actor_attribs::ActorAttribs};
+    /// # let mut bnl = BNLFile::new();
+    /// let mut attribs: ActorAttribs = bnl.get_asset("aid_actorattribs_imp").expect("Unable to get attribs.");
+    /// attribs.descriptor_mut().health *= 2.0;
+    ///
+    /// let mut raw_asset = bnl.get_raw_asset("aid_actorattribs_imp").expect("Unable to get raw asset.");
+    /// raw_asset.descriptor_bytes = attribs.to_bytes();
+    /// bnl.update_asset("aid_actorattribs_imp", &raw_asset).expect("Unable to update attribs.");
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let desc = &self.descriptor;
+
+        let mut bytes = Vec::with_capacity(ACTOR_ATTRIBS_DESCRIPTOR_SIZE + desc.trailing.len());
+
+        for value in [
+            desc.health,
+            desc.walk_speed,
+            desc.run_speed,
+            desc.turn_speed,
+            desc.attack_damage,
+            desc.attack_range,
+            desc.sight_range,
+        ] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        for value in [
+            desc.behavior_flags,
+            desc.team,
+            desc.unknown_0x24,
+            desc.unknown_0x28,
+            desc.unknown_0x2c,
+        ] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        bytes.extend_from_slice(&desc.trailing);
+
+        if let Some(len) = desc.short_len {
+            bytes.truncate(len);
+        }
+
+        bytes
+    }
+}
+
+impl WritableAsset for ActorAttribs {
+    fn write_back(&self, raw_asset: &mut RawAsset) -> Result<(), io::Error> {
+        raw_asset.descriptor_bytes = self.to_bytes();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut data = vec![];
+
+        for value in [100.0f32, 1.5, 4.0, 3.0, 10.0, 2.0, 25.0] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+
+        for value in [0x11u32, 2, 0, 0xdead, 7] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+
+        data.extend_from_slice(&[1, 2, 3, 4]);
+
+        let descriptor = ActorAttribsDescriptor::from_bytes(&data).unwrap();
+        let slices: [&[u8]; 0] = [];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let mut attribs =
+            ActorAttribs::new("aid_actorattribs_test", &descriptor, &virtual_res).unwrap();
+
+        assert_eq!(attribs.health(), 100.0);
+        assert_eq!(attribs.behavior_flags(), 0x11);
+        assert_eq!(attribs.descriptor().unknown_0x28, 0xdead);
+        assert_eq!(attribs.descriptor().trailing, [1, 2, 3, 4]);
+        assert_eq!(attribs.to_bytes(), data);
+
+        attribs.descriptor_mut().health = 50.0;
+        let descriptor = ActorAttribsDescriptor::from_bytes(&attribs.to_bytes()).unwrap();
+        assert_eq!(descriptor.health, 50.0);
+        assert_eq!(descriptor.team, 2);
+
+        // A shorter descriptor keeps the fields it has and writes back at the same size
+        let descriptor = ActorAttribsDescriptor::from_bytes(&data[..40]).unwrap();
+        assert_eq!(descriptor.team, 2);
+        assert_eq!(descriptor.unknown_0x28, 0);
+        assert!(descriptor.trailing.is_empty());
+
+        let attribs =
+            ActorAttribs::new("aid_actorattribs_test", &descriptor, &virtual_res).unwrap();
+        assert_eq!(attribs.to_bytes(), data[..40]);
+    }
+}
@@ -0,0 +1,146 @@
+use std::{
+    fmt::{self, Display},
+    io::Cursor,
+};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use crate::{
+    VirtualResource,
+    asset::{
+        AID_SIZE, Asset, AssetDescriptor, AssetParseError, aid_from_bytes, records_that_fit,
+        write_fields,
+    },
+    game::AssetType,
+};
+
+/// The size of the fixed part of an [`AidListDescriptor`].
+pub(crate) const AID_LIST_DESCRIPTOR_SIZE: usize = 8;
+
+/// A list of asset IDs.
+///
+/// This layout is an unconfirmed guess based on how AIDs are stored elsewhere in BNL files, and
+/// hasn't been checked against game files. AIDs that would run past the end of the descriptor are
+/// dropped rather than failing the asset.
+///
+/// Assumed layout:
+/// - AID count (u32)
+/// - offset of the AIDs within the descriptor (u32), each NUL padded to 128 bytes and stored back
+///   to back
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AidListDescriptor {
+    aids: Vec<String>,
+}
+
+impl AssetDescriptor for AidListDescriptor {
+    fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError> {
+        if data.len() < AID_LIST_DESCRIPTOR_SIZE {
+            return Err(AssetParseError::InputTooSmall);
+        }
+
+        let mut cur = Cursor::new(data);
+
+        let aid_count = cur.read_u32::<LittleEndian>()? as usize;
+        let aids_offset = cur.read_u32::<LittleEndian>()? as usize;
+
+        let aid_count = records_that_fit(data.len(), aid_count, aids_offset, AID_SIZE);
+
+        Ok(AidListDescriptor {
+            aids: data[aids_offset.min(data.len())..]
+                .chunks_exact(AID_SIZE)
+                .take(aid_count)
+                .map(aid_from_bytes)
+                .collect(),
+        })
+    }
+}
+
+impl Display for AidListDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_fields(f, &[("aid_count", self.aids.len().to_string())])?;
+
+        for aid in &self.aids {
+            writeln!(f, "{}", aid)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A list of asset IDs, typically naming the assets that a level or another asset depends on.
+#[derive(Debug)]
+pub struct AidList {
+    name: String,
+    descriptor: AidListDescriptor,
+}
+
+impl Asset for AidList {
+    type Descriptor = AidListDescriptor;
+
+    fn new(
+        name: &str,
+        descriptor: &Self::Descriptor,
+        _virtual_res: &VirtualResource,
+    ) -> Result<Self, AssetParseError> {
+        Ok(AidList {
+            name: name.to_string(),
+            descriptor: descriptor.clone(),
+        })
+    }
+
+    fn descriptor(&self) -> &Self::Descriptor {
+        &self.descriptor
+    }
+
+    fn asset_type() -> AssetType {
+        AssetType::ResAidList
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl AidList {
+    /// Returns the asset IDs in this list, in order.
+    pub fn aids(&self) -> &[String] {
+        &self.descriptor.aids
+    }
+
+    /// Returns true if this list contains the given asset ID.
+    pub fn contains(&self, aid: &str) -> bool {
+        self.descriptor.aids.iter().any(|listed| listed == aid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::aid_to_bytes;
+
+    #[test]
+    fn reads_aids() {
+        let mut data = vec![];
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&8u32.to_le_bytes());
+        data.extend_from_slice(&aid_to_bytes("aid_texture_a").unwrap());
+        data.extend_from_slice(&aid_to_bytes("aid_model_b").unwrap());
+
+        let descriptor = AidListDescriptor::from_bytes(&data).unwrap();
+        let slices: [&[u8]; 0] = [];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let aid_list = AidList::new("aid_aidlist_test", &descriptor, &virtual_res).unwrap();
+
+        assert_eq!(aid_list.aids(), ["aid_texture_a", "aid_model_b"]);
+        assert!(aid_list.contains("aid_model_b"));
+        assert!(!aid_list.contains("aid_model_c"));
+
+        // A count larger than the descriptor keeps the AIDs that fit
+        data[0] = 3;
+        let descriptor = AidListDescriptor::from_bytes(&data).unwrap();
+        assert_eq!(descriptor.aids, ["aid_texture_a", "aid_model_b"]);
+        assert!(AidListDescriptor::from_bytes(&data[..AID_LIST_DESCRIPTOR_SIZE - 1]).is_err());
+    }
+}
@@ -0,0 +1,322 @@
+use std::{
+    fmt::{self, Display},
+    io::{Cursor, Seek, SeekFrom},
+};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+use num_enum::TryFromPrimitive;
+
+use crate::{
+    VirtualResource,
+    asset::{Asset, AssetDescriptor, AssetParseError, records_that_fit, write_fields},
+    game::AssetType,
+};
+
+/// The size of the fixed part of an [`AnimationDescriptor`].
+const ANIMATION_DESCRIPTOR_SIZE: usize = 12;
+
+/// The size of a single [`TrackDescriptor`] within the track list.
+const TRACK_DESCRIPTOR_SIZE: usize = 20;
+
+/// The property of a bone that a [`Track`] animates. These values are a guess, and are yet to be
+/// matched against real animations.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChannelType {
+    Rotation = 0,
+    Translation = 1,
+    Scale = 2,
+}
+
+/// Describes where the keyframes of one animation track live in the animation's resource.
+///
+/// Assumed layout, see [`AnimationDescriptor`] (all little endian u32):
+/// - target bone index
+/// - channel type (see [`ChannelType`])
+/// - keyframe count
+/// - keyframe data offset into the resource
+/// - keyframe data size in bytes
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrackDescriptor {
+    target: u32,
+    channel_type: u32,
+    keyframe_count: u32,
+    data_offset: u32,
+    data_size: u32,
+}
+
+impl TrackDescriptor {
+    fn from_cursor(cur: &mut Cursor<&[u8]>) -> Result<Self, AssetParseError> {
+        Ok(TrackDescriptor {
+            target: cur.read_u32::<LittleEndian>()?,
+            channel_type: cur.read_u32::<LittleEndian>()?,
+            keyframe_count: cur.read_u32::<LittleEndian>()?,
+            data_offset: cur.read_u32::<LittleEndian>()?,
+            data_size: cur.read_u32::<LittleEndian>()?,
+        })
+    }
+}
+
+/// The header of an animation and its list of tracks.
+///
+/// Neither this layout nor that of [`TrackDescriptor`] has been confirmed against game files.
+/// Tracks that would run past the end of the descriptor are dropped, and [`Animation`] skips
+/// tracks whose data can't be read, rather than rejecting the animation.
+///
+/// Assumed layout:
+/// - duration in seconds (f32)
+/// - track count (u32)
+/// - offset of the track list within the descriptor (u32), holding one [`TrackDescriptor`] per
+///   track
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnimationDescriptor {
+    duration: f32,
+    track_count: u32,
+    track_descriptors: Vec<TrackDescriptor>,
+}
+
+impl AnimationDescriptor {
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    pub fn track_count(&self) -> u32 {
+        self.track_count
+    }
+}
+
+impl Display for AnimationDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_fields(
+            f,
+            &[
+                ("duration", format!("{}s", self.duration)),
+                ("track_count", self.track_count.to_string()),
+            ],
+        )?;
+
+        for (i, track) in self.track_descriptors.iter().enumerate() {
+            writeln!(
+                f,
+                "track {}: bone {}, channel {}, {} keyframes, {:#x} bytes at {:#x}",
+                i,
+                track.target,
+                track.channel_type,
+                track.keyframe_count,
+                track.data_size,
+                track.data_offset
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl AssetDescriptor for AnimationDescriptor {
+    fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError> {
+        if data.len() < ANIMATION_DESCRIPTOR_SIZE {
+            return Err(AssetParseError::InputTooSmall);
+        }
+
+        let mut cur = Cursor::new(data);
+
+        let duration = cur.read_f32::<LittleEndian>()?;
+        let track_count = cur.read_u32::<LittleEndian>()?;
+        let track_list_offset = cur.read_u32::<LittleEndian>()?;
+
+        let listed = records_that_fit(
+            data.len(),
+            track_count as usize,
+            track_list_offset as usize,
+            TRACK_DESCRIPTOR_SIZE,
+        );
+
+        cur.seek(SeekFrom::Start(track_list_offset as u64))?;
+
+        let track_descriptors = (0..listed)
+            .map(|_| TrackDescriptor::from_cursor(&mut cur))
+            .collect::<Result<_, _>>()?;
+
+        Ok(AnimationDescriptor {
+            duration,
+            track_count,
+            track_descriptors,
+        })
+    }
+}
+
+/// A single animated channel of an [`Animation`].
+///
+/// The keyframe encoding isn't fully understood yet, so the data is exposed raw.
+#[derive(Debug, Clone)]
+pub struct Track {
+    descriptor: TrackDescriptor,
+    data: Vec<u8>,
+}
+
+impl Track {
+    /// Returns the index of the bone animated by this track.
+    pub fn target(&self) -> u32 {
+        self.descriptor.target
+    }
+
+    /// Returns the animated property, or None if the channel type hasn't been identified yet.
+    pub fn channel_type(&self) -> Option<ChannelType> {
+        ChannelType::try_from(self.descriptor.channel_type).ok()
+    }
+
+    pub fn raw_channel_type(&self) -> u32 {
+        self.descriptor.channel_type
+    }
+
+    pub fn keyframe_count(&self) -> u32 {
+        self.descriptor.keyframe_count
+    }
+
+    /// Returns the undecoded keyframe data of this track.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+#[derive(Debug)]
+pub struct Animation {
+    name: String,
+    descriptor: AnimationDescriptor,
+    tracks: Vec<Track>,
+    track_errors: Vec<(usize, AssetParseError)>,
+}
+
+impl Asset for Animation {
+    type Descriptor = AnimationDescriptor;
+
+    fn new(
+        name: &str,
+        descriptor: &Self::Descriptor,
+        virtual_res: &VirtualResource,
+    ) -> Result<Self, AssetParseError> {
+        let mut tracks = vec![];
+        let mut track_errors = vec![];
+
+        for (i, track_desc) in descriptor.track_descriptors.iter().enumerate() {
+            match virtual_res.get_bytes(
+                track_desc.data_offset as usize,
+                track_desc.data_size as usize,
+            ) {
+                Ok(data) => tracks.push(Track {
+                    descriptor: track_desc.clone(),
+                    data,
+                }),
+                Err(e) => track_errors.push((
+                    i,
+                    AssetParseError::InvalidDataViews(format!(
+                        "Unable to read track data at offset {}: {}",
+                        track_desc.data_offset, e
+                    )),
+                )),
+            }
+        }
+
+        Ok(Animation {
+            name: name.to_string(),
+            descriptor: descriptor.clone(),
+            tracks,
+            track_errors,
+        })
+    }
+
+    fn descriptor(&self) -> &Self::Descriptor {
+        &self.descriptor
+    }
+
+    fn asset_type() -> AssetType {
+        AssetType::ResAnim
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Animation {
+    /// Returns the length of this animation in seconds.
+    pub fn duration(&self) -> f32 {
+        self.descriptor.duration
+    }
+
+    pub fn track_count(&self) -> usize {
+        self.tracks.len()
+    }
+
+    /// Returns the tracks of this animation, leaving out any whose data couldn't be read, see
+    /// [`Animation::track_errors`].
+    pub fn tracks(&self) -> &[Track] {
+        &self.tracks
+    }
+
+    /// Returns the index of each listed track whose data couldn't be read, along with the reason.
+    pub fn track_errors(&self) -> &[(usize, AssetParseError)] {
+        &self.track_errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_tracks() {
+        let mut descriptor = vec![];
+        descriptor.extend_from_slice(&2.5f32.to_le_bytes());
+
+        for value in [2u32, 12, 0, 0, 2, 0, 8, 7, 9, 1, 8, 8] {
+            descriptor.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let descriptor = AnimationDescriptor::from_bytes(&descriptor).unwrap();
+        assert_eq!(descriptor.track_count(), 2);
+
+        let resource: Vec<u8> = (0..16).collect();
+        let slices = [resource.as_slice()];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let anim = Animation::new("aid_anim_test", &descriptor, &virtual_res).unwrap();
+
+        assert_eq!(anim.duration(), 2.5);
+        assert_eq!(anim.tracks()[0].channel_type(), Some(ChannelType::Rotation));
+        assert_eq!(anim.tracks()[0].data(), (0..8).collect::<Vec<u8>>());
+        assert_eq!(anim.tracks()[1].target(), 7);
+        assert_eq!(anim.tracks()[1].channel_type(), None);
+        assert_eq!(anim.tracks()[1].data(), (8..16).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn keeps_readable_tracks() {
+        let mut descriptor = vec![];
+        descriptor.extend_from_slice(&1.0f32.to_le_bytes());
+
+        // Four tracks listed, but only two fit, and the second has its data out of range
+        for value in [4u32, 12, 0, 0, 2, 0, 8, 1, 1, 2, 0x100, 8] {
+            descriptor.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let descriptor = AnimationDescriptor::from_bytes(&descriptor).unwrap();
+        assert_eq!(descriptor.track_count(), 4);
+
+        let resource = vec![0; 16];
+        let slices = [resource.as_slice()];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let anim = Animation::new("aid_anim_test", &descriptor, &virtual_res).unwrap();
+
+        assert_eq!(anim.track_count(), 1);
+        assert!(matches!(
+            anim.track_errors(),
+            [(1, AssetParseError::InvalidDataViews(_))]
+        ));
+        assert!(AnimationDescriptor::from_bytes(&[0; ANIMATION_DESCRIPTOR_SIZE - 1]).is_err());
+    }
+}
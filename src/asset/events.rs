@@ -0,0 +1,337 @@
+use std::{
+    fmt::{self, Display},
+    io::{self, Cursor, Seek, SeekFrom},
+};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use crate::{
+    VirtualResource,
+    asset::{
+        Asset, AssetDescriptor, AssetParseError, DescriptorTemplate, RawAsset, WritableAsset,
+        records_that_fit, write_fields,
+    },
+    game::AssetType,
+};
+
+/// The size of the fixed part of an [`EventsDescriptor`].
+pub(crate) const EVENTS_DESCRIPTOR_SIZE: usize = 8;
+
+/// The number of parameters stored with every [`Event`], used or not.
+pub const EVENT_PARAM_COUNT: usize = 4;
+
+/// The size of a single [`Event`].
+pub(crate) const EVENT_SIZE: usize = 8 + EVENT_PARAM_COUNT * 4;
+
+/// A single entry of an event timeline, such as a footstep sound partway through an animation.
+///
+/// Assumed layout, see [`EventsDescriptor`]:
+/// - time since the start of the animation or cutscene in seconds (f32)
+/// - opcode selecting what the event does (u32)
+/// - parameters (4 x u32), whose meaning depends on the opcode
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Event {
+    pub time: f32,
+    pub opcode: u32,
+    pub params: [u32; EVENT_PARAM_COUNT],
+}
+
+impl Event {
+    fn from_cursor(cur: &mut Cursor<&[u8]>) -> Result<Self, io::Error> {
+        let time = cur.read_f32::<LittleEndian>()?;
+        let opcode = cur.read_u32::<LittleEndian>()?;
+
+        let mut params = [0; EVENT_PARAM_COUNT];
+        cur.read_u32_into::<LittleEndian>(&mut params)?;
+
+        Ok(Event {
+            time,
+            opcode,
+            params,
+        })
+    }
+
+    /// Returns the parameter at `index` read as a float, for opcodes that take volumes, scales
+    /// and the like.
+    pub fn param_f32(&self, index: usize) -> Option<f32> {
+        self.params.get(index).map(|&param| f32::from_bits(param))
+    }
+}
+
+/// A timeline of events, shared by [`AnimEvents`] and [`CutsceneEvents`].
+///
+/// This layout and that of [`Event`] are unconfirmed guesses that haven't been checked against
+/// game files. Events that would run past the end of the descriptor are dropped rather than
+/// failing the asset, and [`EventsDescriptor::to_bytes`] only writes the fields described here.
+///
+/// Assumed layout:
+/// - event count (u32)
+/// - offset of the events within the descriptor (u32), stored back to back
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EventsDescriptor {
+    events: Vec<Event>,
+}
+
+impl AssetDescriptor for EventsDescriptor {
+    fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError> {
+        if data.len() < EVENTS_DESCRIPTOR_SIZE {
+            return Err(AssetParseError::InputTooSmall);
+        }
+
+        let mut cur = Cursor::new(data);
+
+        let event_count = cur.read_u32::<LittleEndian>()? as usize;
+        let events_offset = cur.read_u32::<LittleEndian>()? as usize;
+
+        let event_count = records_that_fit(data.len(), event_count, events_offset, EVENT_SIZE);
+
+        cur.seek(SeekFrom::Start(events_offset as u64))?;
+
+        let events = (0..event_count)
+            .map(|_| Event::from_cursor(&mut cur))
+            .collect::<Result<_, _>>()?;
+
+        Ok(EventsDescriptor { events })
+    }
+}
+
+impl DescriptorTemplate for EventsDescriptor {
+    /// An empty timeline.
+    fn template() -> Self {
+        EventsDescriptor { events: vec![] }
+    }
+}
+
+impl EventsDescriptor {
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Returns the events for editing. Events may be added or removed, as
+    /// [`EventsDescriptor::to_bytes`] rebuilds the whole descriptor.
+    pub fn events_mut(&mut self) -> &mut Vec<Event> {
+        &mut self.events
+    }
+
+    /// Returns the events that fire from `start` up to but not including `end`, in seconds.
+    pub fn events_between(&self, start: f32, end: f32) -> impl Iterator<Item = &Event> {
+        self.events
+            .iter()
+            .filter(move |event| (start..end).contains(&event.time))
+    }
+
+    /// Serialises this descriptor, with the events directly after the header.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(EVENTS_DESCRIPTOR_SIZE + self.events.len() * EVENT_SIZE);
+
+        bytes.extend_from_slice(&(self.events.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(EVENTS_DESCRIPTOR_SIZE as u32).to_le_bytes());
+
+        for event in &self.events {
+            bytes.extend_from_slice(&event.time.to_le_bytes());
+            bytes.extend_from_slice(&event.opcode.to_le_bytes());
+
+            for param in event.params {
+                bytes.extend_from_slice(&param.to_le_bytes());
+            }
+        }
+
+        bytes
+    }
+}
+
+impl Display for EventsDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_fields(f, &[("event_count", self.events.len().to_string())])?;
+
+        for event in &self.events {
+            writeln!(
+                f,
+                "{:>8.3}s opcode {:#x}: {:x?}",
+                event.time, event.opcode, event.params
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Events fired during an animation, such as footstep sounds and effect triggers.
+#[derive(Debug)]
+pub struct AnimEvents {
+    name: String,
+    descriptor: EventsDescriptor,
+}
+
+impl Asset for AnimEvents {
+    type Descriptor = EventsDescriptor;
+
+    fn new(
+        name: &str,
+        descriptor: &Self::Descriptor,
+        _virtual_res: &VirtualResource,
+    ) -> Result<Self, AssetParseError> {
+        Ok(AnimEvents {
+            name: name.to_string(),
+            descriptor: descriptor.clone(),
+        })
+    }
+
+    fn descriptor(&self) -> &Self::Descriptor {
+        &self.descriptor
+    }
+
+    fn asset_type() -> AssetType {
+        AssetType::ResAnimEvents
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl AnimEvents {
+    pub fn events(&self) -> &[Event] {
+        self.descriptor.events()
+    }
+
+    /// Returns the events for editing, see [`EventsDescriptor::events_mut`].
+    pub fn events_mut(&mut self) -> &mut Vec<Event> {
+        self.descriptor.events_mut()
+    }
+}
+
+impl WritableAsset for AnimEvents {
+    fn write_back(&self, raw_asset: &mut RawAsset) -> Result<(), io::Error> {
+        raw_asset.descriptor_bytes = self.descriptor.to_bytes();
+        Ok(())
+    }
+}
+
+/// Events fired during a cutscene, such as sounds, effects and camera cuts.
+#[derive(Debug)]
+pub struct CutsceneEvents {
+    name: String,
+    descriptor: EventsDescriptor,
+}
+
+impl Asset for CutsceneEvents {
+    type Descriptor = EventsDescriptor;
+
+    fn new(
+        name: &str,
+        descriptor: &Self::Descriptor,
+        _virtual_res: &VirtualResource,
+    ) -> Result<Self, AssetParseError> {
+        Ok(CutsceneEvents {
+            name: name.to_string(),
+            descriptor: descriptor.clone(),
+        })
+    }
+
+    fn descriptor(&self) -> &Self::Descriptor {
+        &self.descriptor
+    }
+
+    fn asset_type() -> AssetType {
+        AssetType::ResCutsceneEvents
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl CutsceneEvents {
+    pub fn events(&self) -> &[Event] {
+        self.descriptor.events()
+    }
+
+    /// Returns the events for editing, see [`EventsDescriptor::events_mut`].
+    pub fn events_mut(&mut self) -> &mut Vec<Event> {
+        self.descriptor.events_mut()
+    }
+}
+
+impl WritableAsset for CutsceneEvents {
+    fn write_back(&self, raw_asset: &mut RawAsset) -> Result<(), io::Error> {
+        raw_asset.descriptor_bytes = self.descriptor.to_bytes();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_round_trip() {
+        let mut data = vec![];
+
+        for value in [2u32, 8] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+
+        for (time, opcode, params) in [(0.5f32, 1u32, [7u32, 0, 0, 0]), (1.25, 3, [1, 2, 3, 4])] {
+            data.extend_from_slice(&time.to_le_bytes());
+            data.extend_from_slice(&opcode.to_le_bytes());
+
+            for param in params {
+                data.extend_from_slice(&param.to_le_bytes());
+            }
+        }
+
+        let descriptor = EventsDescriptor::from_bytes(&data).unwrap();
+        let slices: [&[u8]; 0] = [];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let mut events =
+            CutsceneEvents::new("aid_cutsceneevents_test", &descriptor, &virtual_res).unwrap();
+
+        assert_eq!(events.events()[1].time, 1.25);
+        assert_eq!(events.events()[1].params, [1, 2, 3, 4]);
+        assert_eq!(descriptor.events_between(0.0, 1.0).count(), 1);
+        assert_eq!(descriptor.to_bytes(), data);
+
+        events.events_mut().push(Event {
+            time: 2.0,
+            opcode: 5,
+            params: [1.0f32.to_bits(), 0, 0, 0],
+        });
+
+        let mut raw_asset = RawAsset {
+            name: "aid_cutsceneevents_test".to_string(),
+            asset_type: AssetType::ResCutsceneEvents,
+            descriptor_bytes: data.clone(),
+            data_slices: vec![],
+        };
+        events.write_back(&mut raw_asset).unwrap();
+
+        let events = raw_asset.parse::<CutsceneEvents>().unwrap();
+        assert_eq!(events.events().len(), 3);
+        assert_eq!(events.events()[2].param_f32(0), Some(1.0));
+
+        // An event cut short by the end of the descriptor is dropped
+        let truncated = &data[..EVENTS_DESCRIPTOR_SIZE + EVENT_SIZE + 4];
+        assert_eq!(
+            EventsDescriptor::from_bytes(truncated)
+                .unwrap()
+                .events()
+                .len(),
+            1
+        );
+        assert!(EventsDescriptor::from_bytes(&data[..EVENTS_DESCRIPTOR_SIZE - 1]).is_err());
+        assert!(
+            AnimEvents::new(
+                "aid_animevents_test",
+                &EventsDescriptor::template(),
+                &virtual_res
+            )
+            .unwrap()
+            .events()
+            .is_empty()
+        );
+    }
+}
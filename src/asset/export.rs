@@ -0,0 +1,345 @@
+//! Pluggable exporters that convert assets into formats other tools can open, chosen per asset
+//! type through an [`ExporterRegistry`].
+//!
+//! Downstream crates can register their own [`Exporter`] for asset types that this crate doesn't
+//! understand yet, or to replace one of the built-in exporters.
+
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde_json::json;
+
+use crate::{
+    BNLFile,
+    asset::{AssetError, RawAsset, model::Model, texture::Texture},
+    game::AssetType,
+};
+
+/// The error returned when an [`Exporter`] fails.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ExportError {
+    /// The asset couldn't be retrieved or parsed.
+    Asset(AssetError),
+    /// The asset couldn't be converted or written.
+    Io(io::Error),
+    /// No exporter is registered for the asset's type.
+    Unsupported(AssetType),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExportError::Asset(e) => write!(f, "{}", e),
+            ExportError::Io(e) => write!(f, "Unable to export asset: {}", e),
+            ExportError::Unsupported(asset_type) => {
+                write!(f, "No exporter is registered for {:?} assets", asset_type)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ExportError::Asset(e) => Some(e),
+            ExportError::Io(e) => Some(e),
+            ExportError::Unsupported(_) => None,
+        }
+    }
+}
+
+impl From<AssetError> for ExportError {
+    fn from(value: AssetError) -> Self {
+        ExportError::Asset(value)
+    }
+}
+
+impl From<io::Error> for ExportError {
+    fn from(value: io::Error) -> Self {
+        ExportError::Io(value)
+    }
+}
+
+/// Converts assets into a file format, see the [module documentation](self).
+pub trait Exporter: Send + Sync {
+    /// The extension of the files written by this exporter, without a leading dot.
+    fn extension(&self) -> &str;
+
+    /// Writes `asset` to `writer` in this exporter's format.
+    fn export(&self, asset: &RawAsset, writer: &mut dyn Write) -> Result<(), ExportError>;
+}
+
+/// Exports textures as RGBA8 PNGs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PngExporter;
+
+impl Exporter for PngExporter {
+    fn extension(&self) -> &str {
+        "png"
+    }
+
+    fn export(&self, asset: &RawAsset, writer: &mut dyn Write) -> Result<(), ExportError> {
+        let texture: Texture = asset.parse()?;
+        writer.write_all(&texture.encode_png()?)?;
+
+        Ok(())
+    }
+}
+
+/// Exports textures as DDS files, see [`Texture::encode_dds`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DdsExporter {
+    /// Whether to include the texture's mipmaps.
+    pub mipmaps: bool,
+}
+
+impl Exporter for DdsExporter {
+    fn extension(&self) -> &str {
+        "dds"
+    }
+
+    fn export(&self, asset: &RawAsset, writer: &mut dyn Write) -> Result<(), ExportError> {
+        let texture: Texture = asset.parse()?;
+        writer.write_all(&texture.encode_dds(self.mipmaps)?)?;
+
+        Ok(())
+    }
+}
+
+/// Exports models as binary glTF, see [`Model::to_glb`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GltfExporter;
+
+impl Exporter for GltfExporter {
+    fn extension(&self) -> &str {
+        "glb"
+    }
+
+    fn export(&self, asset: &RawAsset, writer: &mut dyn Write) -> Result<(), ExportError> {
+        let model: Model = asset.parse()?;
+        writer.write_all(&model.to_glb()?)?;
+
+        Ok(())
+    }
+}
+
+/// Exports any asset as JSON holding its name, type, and its descriptor and resources encoded as
+/// base64. Used for asset types without a dedicated exporter.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonExporter;
+
+impl Exporter for JsonExporter {
+    fn extension(&self) -> &str {
+        "json"
+    }
+
+    fn export(&self, asset: &RawAsset, writer: &mut dyn Write) -> Result<(), ExportError> {
+        let json = json!({
+            "name": asset.name,
+            "type": format!("{:?}", asset.asset_type),
+            "descriptor": BASE64.encode(&asset.descriptor_bytes),
+            "resources": asset
+                .data_slices
+                .iter()
+                .map(|slice| BASE64.encode(slice))
+                .collect::<Vec<_>>(),
+        });
+
+        serde_json::to_writer_pretty(writer, &json).map_err(io::Error::from)?;
+
+        Ok(())
+    }
+}
+
+/// Chooses the [`Exporter`] for each asset type. The default registry exports textures as PNG,
+/// models as glTF, and everything else as JSON.
+///
+/// # Examples
+/// ```no_run
+/// use std::path::Path;
+///
+/// use bnl::{asset::export::{DdsExporter, ExporterRegistry}, game::AssetType};
+/// # use std::io::Write;
+/// # use bnl::asset::{RawAsset, export::{ExportError, Exporter}};
+/// # struct MyWavExporter;
+/// # impl Exporter for MyWavExporter {
+/// #     fn extension(&self) -> &str { "wav" }
+/// #     fn export(&self, _: &RawAsset, _: &mut dyn Write) -> Result<(), ExportError> { Ok(()) }
+/// # }
+/// # fn main() -> std::io::Result<()> {
+/// # let bnl_file = bnl::BNLFile::new();
+///
+/// let registry = ExporterRegistry::default()
+///     .register(AssetType::ResTexture, DdsExporter { mipmaps: true })
+///     .register(AssetType::ResXSoundbank, MyWavExporter);
+///
+/// let report = bnl_file.export_all(&registry, Path::new("./exported"))?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ExporterRegistry {
+    exporters: HashMap<AssetType, Box<dyn Exporter>>,
+    fallback: Option<Box<dyn Exporter>>,
+}
+
+impl Default for ExporterRegistry {
+    fn default() -> Self {
+        ExporterRegistry::empty()
+            .register(AssetType::ResTexture, PngExporter)
+            .register(AssetType::ResModel, GltfExporter)
+            .fallback(JsonExporter)
+    }
+}
+
+impl ExporterRegistry {
+    /// Returns a registry without any exporters, not even a fallback.
+    pub fn empty() -> Self {
+        ExporterRegistry {
+            exporters: HashMap::new(),
+            fallback: None,
+        }
+    }
+
+    /// Uses `exporter` for assets of `asset_type`, replacing any exporter already registered for
+    /// it.
+    pub fn register(mut self, asset_type: AssetType, exporter: impl Exporter + 'static) -> Self {
+        self.exporters.insert(asset_type, Box::new(exporter));
+        self
+    }
+
+    /// Uses `exporter` for asset types without an exporter of their own.
+    pub fn fallback(mut self, exporter: impl Exporter + 'static) -> Self {
+        self.fallback = Some(Box::new(exporter));
+        self
+    }
+
+    /// Returns the exporter used for assets of `asset_type`, if there is one.
+    pub fn get(&self, asset_type: AssetType) -> Option<&dyn Exporter> {
+        self.exporters
+            .get(&asset_type)
+            .or(self.fallback.as_ref())
+            .map(Box::as_ref)
+    }
+}
+
+/// What [`BNLFile::export_all`] wrote, and which assets it couldn't export.
+#[derive(Debug, Default)]
+pub struct ExportReport {
+    /// The path of every file written.
+    pub exported: Vec<PathBuf>,
+    /// The name of every asset that couldn't be exported, and why.
+    pub errors: Vec<(String, ExportError)>,
+}
+
+impl BNLFile {
+    /// Exports every asset into `out_dir` as `<name>.<extension>`, using the exporter that
+    /// `registry` gives for its type. An asset that fails to export, or has no exporter, is
+    /// recorded in [`ExportReport::errors`] without stopping the others.
+    ///
+    /// # Errors
+    /// Returns an error if `out_dir` can't be created.
+    pub fn export_all(
+        &self,
+        registry: &ExporterRegistry,
+        out_dir: &Path,
+    ) -> io::Result<ExportReport> {
+        fs::create_dir_all(out_dir)?;
+
+        let mut report = ExportReport::default();
+
+        for asset_desc in self.asset_descriptions() {
+            let name = asset_desc.name();
+
+            let result = registry
+                .get(asset_desc.asset_type())
+                .ok_or(ExportError::Unsupported(asset_desc.asset_type()))
+                .and_then(|exporter| {
+                    let raw_asset = self.get_raw_asset(name)?;
+
+                    let mut bytes = Vec::new();
+                    exporter.export(&raw_asset, &mut bytes)?;
+
+                    let path = out_dir.join(format!("{}.{}", name, exporter.extension()));
+                    fs::write(&path, bytes)?;
+
+                    Ok(path)
+                });
+
+            match result {
+                Ok(path) => report.exported.push(path),
+                Err(e) => report.errors.push((name.to_string(), e)),
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::bnl_from_raw_assets;
+
+    /// Writes the descriptor of an asset as is.
+    struct DescriptorExporter;
+
+    impl Exporter for DescriptorExporter {
+        fn extension(&self) -> &str {
+            "bin"
+        }
+
+        fn export(&self, asset: &RawAsset, writer: &mut dyn Write) -> Result<(), ExportError> {
+            Ok(writer.write_all(&asset.descriptor_bytes)?)
+        }
+    }
+
+    #[test]
+    fn exports_through_registry() {
+        let image = crate::asset::texture::Image::from_rgba8(2, 2, [0xff; 16].to_vec()).unwrap();
+        let texture = Texture::import("aid_texture_a", &image, &Default::default()).unwrap();
+
+        let bnl = bnl_from_raw_assets(&[
+            texture.to_raw_asset().unwrap(),
+            RawAsset {
+                name: "aid_script_a".to_string(),
+                asset_type: AssetType::ResScript,
+                descriptor_bytes: vec![1, 2, 3],
+                data_slices: vec![vec![4, 5]],
+            },
+        ]);
+
+        let dir = std::env::temp_dir().join(format!("bnl_export_{}", std::process::id()));
+
+        let report = bnl.export_all(&ExporterRegistry::default(), &dir).unwrap();
+        assert!(report.errors.is_empty());
+        assert!(
+            fs::read(dir.join("aid_texture_a.png"))
+                .unwrap()
+                .starts_with(b"\x89PNG")
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_slice(&fs::read(dir.join("aid_script_a.json")).unwrap()).unwrap();
+        assert_eq!(json["type"], "ResScript");
+        assert_eq!(json["descriptor"], BASE64.encode([1, 2, 3]));
+
+        // Custom exporters replace the defaults, and types without an exporter are reported
+        let registry = ExporterRegistry::empty().register(AssetType::ResScript, DescriptorExporter);
+        let report = bnl.export_all(&registry, &dir).unwrap();
+
+        assert_eq!(report.exported, [dir.join("aid_script_a.bin")]);
+        assert_eq!(fs::read(dir.join("aid_script_a.bin")).unwrap(), [1, 2, 3]);
+        assert!(matches!(
+            report.errors[..],
+            [(_, ExportError::Unsupported(AssetType::ResTexture))]
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,571 @@
+//! Controller rumble patterns and camera shakes, the two kinds of impact feedback the game plays
+//! alongside hits and explosions. Both are short curves of keyframes, sampled with
+//! [`Rumble::sample`] and [`ShakeCam::sample`].
+//!
+//! Only the asset type IDs of these come from a known source (see [`crate::game`]). The layouts
+//! below are a best guess that hasn't been checked against game files, so keyframes that would
+//! run past the end of a descriptor are dropped instead of failing the whole asset, and writing
+//! an asset back only keeps the fields described here.
+
+use std::{
+    fmt::{self, Display},
+    io::{self, Cursor, Seek, SeekFrom},
+};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use crate::{
+    VirtualResource,
+    asset::{
+        Asset, AssetDescriptor, AssetParseError, RawAsset, WritableAsset, records_that_fit,
+        write_fields,
+    },
+    game::AssetType,
+};
+
+/// The size of the fixed part of a [`RumbleDescriptor`].
+pub(crate) const RUMBLE_DESCRIPTOR_SIZE: usize = 16;
+
+/// The size of a single [`RumbleKeyframe`].
+pub(crate) const RUMBLE_KEYFRAME_SIZE: usize = 12;
+
+/// The size of the fixed part of a [`ShakeCamDescriptor`].
+pub(crate) const SHAKE_CAM_DESCRIPTOR_SIZE: usize = 20;
+
+/// The size of a single [`ShakeKeyframe`].
+pub(crate) const SHAKE_KEYFRAME_SIZE: usize = 20;
+
+/// Set in [`RumbleDescriptor::flags`] when the pattern repeats until stopped.
+pub const RUMBLE_FLAG_LOOP: u32 = 0x1;
+
+/// Reads up to `count` keyframes of `size` bytes from `offset`, stopping at the end of `data`.
+fn read_keyframes<T>(
+    data: &[u8],
+    count: usize,
+    offset: usize,
+    size: usize,
+    read: impl Fn(&mut Cursor<&[u8]>) -> Result<T, io::Error>,
+) -> Result<Vec<T>, AssetParseError> {
+    let count = records_that_fit(data.len(), count, offset, size);
+
+    let mut cur = Cursor::new(data);
+    cur.seek(SeekFrom::Start(offset as u64))?;
+
+    Ok((0..count)
+        .map(|_| read(&mut cur))
+        .collect::<Result<_, _>>()?)
+}
+
+/// Linearly interpolates between the keyframes either side of `time`, holding the first and last
+/// values outside of them. Keyframes are expected in time order.
+fn sample_keyframes<T, const N: usize>(
+    keyframes: &[T],
+    time: f32,
+    key: impl Fn(&T) -> (f32, [f32; N]),
+) -> [f32; N] {
+    let Some(next) = keyframes.iter().position(|keyframe| key(keyframe).0 > time) else {
+        return keyframes.last().map_or([0.0; N], |last| key(last).1);
+    };
+
+    let (next_time, next_values) = key(&keyframes[next]);
+
+    let Some(previous) = next.checked_sub(1) else {
+        return next_values;
+    };
+
+    let (previous_time, previous_values) = key(&keyframes[previous]);
+    let t = (time - previous_time) / (next_time - previous_time);
+
+    std::array::from_fn(|i| previous_values[i] + (next_values[i] - previous_values[i]) * t)
+}
+
+/// A point on the curve of a [`Rumble`].
+///
+/// Assumed layout:
+/// - time since the start of the pattern in seconds (f32)
+/// - speed of the low frequency (left) motor, from 0 to 1 (f32)
+/// - speed of the high frequency (right) motor, from 0 to 1 (f32)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RumbleKeyframe {
+    pub time: f32,
+    pub low_frequency: f32,
+    pub high_frequency: f32,
+}
+
+/// A controller rumble pattern.
+///
+/// Assumed layout:
+/// - duration in seconds (f32)
+/// - flags (u32), see [`RUMBLE_FLAG_LOOP`]
+/// - keyframe count (u32)
+/// - offset of the keyframes within the descriptor (u32), stored back to back
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RumbleDescriptor {
+    pub duration: f32,
+    pub flags: u32,
+    pub keyframes: Vec<RumbleKeyframe>,
+}
+
+impl AssetDescriptor for RumbleDescriptor {
+    fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError> {
+        if data.len() < RUMBLE_DESCRIPTOR_SIZE {
+            return Err(AssetParseError::InputTooSmall);
+        }
+
+        let mut cur = Cursor::new(data);
+
+        let duration = cur.read_f32::<LittleEndian>()?;
+        let flags = cur.read_u32::<LittleEndian>()?;
+        let keyframe_count = cur.read_u32::<LittleEndian>()? as usize;
+        let keyframes_offset = cur.read_u32::<LittleEndian>()? as usize;
+
+        let keyframes = read_keyframes(
+            data,
+            keyframe_count,
+            keyframes_offset,
+            RUMBLE_KEYFRAME_SIZE,
+            |cur| {
+                Ok(RumbleKeyframe {
+                    time: cur.read_f32::<LittleEndian>()?,
+                    low_frequency: cur.read_f32::<LittleEndian>()?,
+                    high_frequency: cur.read_f32::<LittleEndian>()?,
+                })
+            },
+        )?;
+
+        Ok(RumbleDescriptor {
+            duration,
+            flags,
+            keyframes,
+        })
+    }
+}
+
+impl RumbleDescriptor {
+    /// Serialises this descriptor, with the keyframes directly after the header.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            RUMBLE_DESCRIPTOR_SIZE + self.keyframes.len() * RUMBLE_KEYFRAME_SIZE,
+        );
+
+        bytes.extend_from_slice(&self.duration.to_le_bytes());
+
+        for value in [
+            self.flags,
+            self.keyframes.len() as u32,
+            RUMBLE_DESCRIPTOR_SIZE as u32,
+        ] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        for keyframe in &self.keyframes {
+            for value in [
+                keyframe.time,
+                keyframe.low_frequency,
+                keyframe.high_frequency,
+            ] {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+
+        bytes
+    }
+}
+
+impl Display for RumbleDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_fields(
+            f,
+            &[
+                ("duration", self.duration.to_string()),
+                ("flags", format!("{:#x}", self.flags)),
+                ("keyframe_count", self.keyframes.len().to_string()),
+            ],
+        )?;
+
+        for keyframe in &self.keyframes {
+            writeln!(
+                f,
+                "{:>8.3}s low {:.3}, high {:.3}",
+                keyframe.time, keyframe.low_frequency, keyframe.high_frequency
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A controller rumble pattern, driving the two motors of the controller over time.
+#[derive(Debug)]
+pub struct Rumble {
+    name: String,
+    descriptor: RumbleDescriptor,
+}
+
+impl Asset for Rumble {
+    type Descriptor = RumbleDescriptor;
+
+    fn new(
+        name: &str,
+        descriptor: &Self::Descriptor,
+        _virtual_res: &VirtualResource,
+    ) -> Result<Self, AssetParseError> {
+        Ok(Rumble {
+            name: name.to_string(),
+            descriptor: descriptor.clone(),
+        })
+    }
+
+    fn descriptor(&self) -> &Self::Descriptor {
+        &self.descriptor
+    }
+
+    fn asset_type() -> AssetType {
+        AssetType::ResRumble
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Rumble {
+    /// Returns the pattern for editing, see [`RumbleDescriptor::to_bytes`].
+    pub fn descriptor_mut(&mut self) -> &mut RumbleDescriptor {
+        &mut self.descriptor
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.descriptor.flags & RUMBLE_FLAG_LOOP != 0
+    }
+
+    /// Returns the low and high frequency motor speeds `time` seconds into the pattern. Both are
+    /// 0 once a pattern that doesn't loop has finished.
+    pub fn sample(&self, time: f32) -> [f32; 2] {
+        let duration = self.descriptor.duration;
+
+        let time = match self.is_looping() {
+            true if duration > 0.0 => time.rem_euclid(duration),
+            _ if time > duration => return [0.0; 2],
+            _ => time,
+        };
+
+        sample_keyframes(&self.descriptor.keyframes, time, |keyframe| {
+            (
+                keyframe.time,
+                [keyframe.low_frequency, keyframe.high_frequency],
+            )
+        })
+    }
+}
+
+impl WritableAsset for Rumble {
+    fn write_back(&self, raw_asset: &mut RawAsset) -> Result<(), io::Error> {
+        raw_asset.descriptor_bytes = self.descriptor.to_bytes();
+        Ok(())
+    }
+}
+
+/// A point on the curve of a [`ShakeCam`].
+///
+/// Assumed layout:
+/// - time since the start of the shake in seconds (f32)
+/// - largest offset of the camera along each axis (3 x f32)
+/// - largest roll of the camera in radians (f32)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShakeKeyframe {
+    pub time: f32,
+    pub translation: [f32; 3],
+    pub rotation: f32,
+}
+
+/// A camera shake definition.
+///
+/// Assumed layout:
+/// - duration in seconds (f32)
+/// - shakes per second (f32)
+/// - distance from the source at which the shake has faded out (f32)
+/// - keyframe count (u32)
+/// - offset of the keyframes within the descriptor (u32), stored back to back
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShakeCamDescriptor {
+    pub duration: f32,
+    pub frequency: f32,
+    pub radius: f32,
+    pub keyframes: Vec<ShakeKeyframe>,
+}
+
+impl AssetDescriptor for ShakeCamDescriptor {
+    fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError> {
+        if data.len() < SHAKE_CAM_DESCRIPTOR_SIZE {
+            return Err(AssetParseError::InputTooSmall);
+        }
+
+        let mut cur = Cursor::new(data);
+
+        let duration = cur.read_f32::<LittleEndian>()?;
+        let frequency = cur.read_f32::<LittleEndian>()?;
+        let radius = cur.read_f32::<LittleEndian>()?;
+        let keyframe_count = cur.read_u32::<LittleEndian>()? as usize;
+        let keyframes_offset = cur.read_u32::<LittleEndian>()? as usize;
+
+        let keyframes = read_keyframes(
+            data,
+            keyframe_count,
+            keyframes_offset,
+            SHAKE_KEYFRAME_SIZE,
+            |cur| {
+                let time = cur.read_f32::<LittleEndian>()?;
+
+                let mut translation = [0.0; 3];
+                cur.read_f32_into::<LittleEndian>(&mut translation)?;
+
+                Ok(ShakeKeyframe {
+                    time,
+                    translation,
+                    rotation: cur.read_f32::<LittleEndian>()?,
+                })
+            },
+        )?;
+
+        Ok(ShakeCamDescriptor {
+            duration,
+            frequency,
+            radius,
+            keyframes,
+        })
+    }
+}
+
+impl ShakeCamDescriptor {
+    /// Serialises this descriptor, with the keyframes directly after the header.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            SHAKE_CAM_DESCRIPTOR_SIZE + self.keyframes.len() * SHAKE_KEYFRAME_SIZE,
+        );
+
+        for value in [self.duration, self.frequency, self.radius] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        for value in [
+            self.keyframes.len() as u32,
+            SHAKE_CAM_DESCRIPTOR_SIZE as u32,
+        ] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        for keyframe in &self.keyframes {
+            bytes.extend_from_slice(&keyframe.time.to_le_bytes());
+
+            for value in keyframe.translation {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+
+            bytes.extend_from_slice(&keyframe.rotation.to_le_bytes());
+        }
+
+        bytes
+    }
+}
+
+impl Display for ShakeCamDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_fields(
+            f,
+            &[
+                ("duration", self.duration.to_string()),
+                ("frequency", self.frequency.to_string()),
+                ("radius", self.radius.to_string()),
+                ("keyframe_count", self.keyframes.len().to_string()),
+            ],
+        )?;
+
+        for keyframe in &self.keyframes {
+            writeln!(
+                f,
+                "{:>8.3}s translation {:?}, rotation {}",
+                keyframe.time, keyframe.translation, keyframe.rotation
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A camera shake, describing how far the camera is thrown about over time.
+#[derive(Debug)]
+pub struct ShakeCam {
+    name: String,
+    descriptor: ShakeCamDescriptor,
+}
+
+impl Asset for ShakeCam {
+    type Descriptor = ShakeCamDescriptor;
+
+    fn new(
+        name: &str,
+        descriptor: &Self::Descriptor,
+        _virtual_res: &VirtualResource,
+    ) -> Result<Self, AssetParseError> {
+        Ok(ShakeCam {
+            name: name.to_string(),
+            descriptor: descriptor.clone(),
+        })
+    }
+
+    fn descriptor(&self) -> &Self::Descriptor {
+        &self.descriptor
+    }
+
+    fn asset_type() -> AssetType {
+        AssetType::ResShakeCam
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl ShakeCam {
+    /// Returns the shake for editing, see [`ShakeCamDescriptor::to_bytes`].
+    pub fn descriptor_mut(&mut self) -> &mut ShakeCamDescriptor {
+        &mut self.descriptor
+    }
+
+    /// Returns the largest camera offset and roll `time` seconds into the shake, as a keyframe.
+    /// Both are 0 once the shake has finished.
+    pub fn sample(&self, time: f32) -> ShakeKeyframe {
+        let [x, y, z, rotation] = match time > self.descriptor.duration {
+            true => [0.0; 4],
+            false => sample_keyframes(&self.descriptor.keyframes, time, |keyframe| {
+                let [x, y, z] = keyframe.translation;
+                (keyframe.time, [x, y, z, keyframe.rotation])
+            }),
+        };
+
+        ShakeKeyframe {
+            time,
+            translation: [x, y, z],
+            rotation,
+        }
+    }
+}
+
+impl WritableAsset for ShakeCam {
+    fn write_back(&self, raw_asset: &mut RawAsset) -> Result<(), io::Error> {
+        raw_asset.descriptor_bytes = self.descriptor.to_bytes();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_resources() -> VirtualResource<'static> {
+        VirtualResource::from_slices(&[])
+    }
+
+    #[test]
+    fn rumble_round_trip() {
+        let mut data = vec![];
+
+        data.extend_from_slice(&1.0f32.to_le_bytes());
+        for value in [RUMBLE_FLAG_LOOP, 2, 16] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        for value in [0.0f32, 1.0, 0.0, 0.5, 0.0, 1.0] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let descriptor = RumbleDescriptor::from_bytes(&data).unwrap();
+        let mut rumble = Rumble::new("aid_rumble_test", &descriptor, &no_resources()).unwrap();
+
+        assert_eq!(descriptor.to_bytes(), data);
+        assert_eq!(rumble.descriptor().keyframes[1].high_frequency, 1.0);
+        assert_eq!(rumble.sample(0.25), [0.5, 0.5]);
+        assert_eq!(rumble.sample(0.75), [0.0, 1.0]);
+        // Looping patterns wrap around
+        assert_eq!(rumble.sample(1.25), [0.5, 0.5]);
+
+        rumble.descriptor_mut().flags = 0;
+        assert_eq!(rumble.sample(1.25), [0.0, 0.0]);
+
+        rumble.descriptor_mut().keyframes.push(RumbleKeyframe {
+            time: 1.0,
+            low_frequency: 0.25,
+            high_frequency: 0.25,
+        });
+
+        let mut raw_asset = RawAsset {
+            name: "aid_rumble_test".to_string(),
+            asset_type: AssetType::ResRumble,
+            descriptor_bytes: data.clone(),
+            data_slices: vec![],
+        };
+        rumble.write_back(&mut raw_asset).unwrap();
+
+        let rumble = raw_asset.parse::<Rumble>().unwrap();
+        assert_eq!(rumble.descriptor().keyframes.len(), 3);
+        assert!(!rumble.is_looping());
+
+        // A keyframe cut short by the end of the descriptor is dropped
+        let descriptor = RumbleDescriptor::from_bytes(&data[..data.len() - 4]).unwrap();
+        assert_eq!(descriptor.keyframes.len(), 1);
+        assert!(RumbleDescriptor::from_bytes(&data[..RUMBLE_DESCRIPTOR_SIZE - 1]).is_err());
+    }
+
+    #[test]
+    fn shake_cam_round_trip() {
+        let descriptor = ShakeCamDescriptor {
+            duration: 2.0,
+            frequency: 15.0,
+            radius: 10.0,
+            keyframes: vec![
+                ShakeKeyframe {
+                    time: 0.0,
+                    translation: [0.0, 2.0, 0.0],
+                    rotation: 0.1,
+                },
+                ShakeKeyframe {
+                    time: 2.0,
+                    translation: [0.0; 3],
+                    rotation: 0.0,
+                },
+            ],
+        };
+
+        let bytes = descriptor.to_bytes();
+        assert_eq!(
+            bytes.len(),
+            SHAKE_CAM_DESCRIPTOR_SIZE + 2 * SHAKE_KEYFRAME_SIZE
+        );
+        assert_eq!(ShakeCamDescriptor::from_bytes(&bytes).unwrap(), descriptor);
+
+        let shake = ShakeCam::new("aid_shakecam_test", &descriptor, &no_resources()).unwrap();
+
+        let sample = shake.sample(1.0);
+        assert_eq!(sample.translation, [0.0, 1.0, 0.0]);
+        assert_eq!(sample.rotation, 0.05);
+        assert_eq!(shake.sample(-1.0).translation, [0.0, 2.0, 0.0]);
+        assert_eq!(shake.sample(3.0).rotation, 0.0);
+
+        // Counts and offsets that don't match the guessed layout keep what fits
+        let mut bytes = bytes;
+        bytes[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(ShakeCamDescriptor::from_bytes(&bytes).unwrap(), descriptor);
+
+        bytes[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(
+            ShakeCamDescriptor::from_bytes(&bytes)
+                .unwrap()
+                .keyframes
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,387 @@
+use std::{
+    fmt::{self, Display, Write as _},
+    fs,
+    io::{self, Cursor, Seek, SeekFrom},
+    path::Path,
+};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde_json::{Value, json};
+
+use crate::{
+    VirtualResource,
+    asset::{
+        Asset, AssetDescriptor, AssetParseError, records_that_fit,
+        texture::{Texture, TextureDescriptor},
+        write_fields,
+    },
+    game::AssetType,
+};
+
+/// The size of the fixed part of a [`FontDescriptor`].
+const FONT_DESCRIPTOR_SIZE: usize = 16;
+
+/// The size of a single [`Glyph`] within the glyph list.
+const GLYPH_SIZE: usize = 16;
+
+/// The metrics of a single character, in pixels of the font's atlas texture.
+///
+/// Assumed layout, see [`FontDescriptor`]:
+/// - character (u16, UTF-16 code unit)
+/// - x, y, width, height of the glyph within the atlas (u16 each)
+/// - x offset, y offset from the pen position when drawing (i16 each)
+/// - horizontal advance (i16)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Glyph {
+    pub character: u16,
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+    pub x_offset: i16,
+    pub y_offset: i16,
+    pub advance: i16,
+}
+
+impl Glyph {
+    fn from_cursor(cur: &mut Cursor<&[u8]>) -> Result<Self, io::Error> {
+        Ok(Glyph {
+            character: cur.read_u16::<LittleEndian>()?,
+            x: cur.read_u16::<LittleEndian>()?,
+            y: cur.read_u16::<LittleEndian>()?,
+            width: cur.read_u16::<LittleEndian>()?,
+            height: cur.read_u16::<LittleEndian>()?,
+            x_offset: cur.read_i16::<LittleEndian>()?,
+            y_offset: cur.read_i16::<LittleEndian>()?,
+            advance: cur.read_i16::<LittleEndian>()?,
+        })
+    }
+
+    /// Returns the character of this glyph, or None if it is half of a UTF-16 surrogate pair.
+    pub fn char(&self) -> Option<char> {
+        char::from_u32(self.character as u32)
+    }
+}
+
+/// The metrics of a [`Font`] and the descriptor of its atlas texture.
+///
+/// Neither this layout nor that of [`Glyph`] has been checked against game files. Glyphs that
+/// would run past the end of the descriptor are dropped, and a font whose atlas can't be read is
+/// kept without one, so that a wrong guess doesn't hide the rest of the font.
+///
+/// Assumed layout:
+/// - glyph count (u32)
+/// - offset of the glyph list within the descriptor (u32)
+/// - line height (u16)
+/// - baseline, measured down from the top of a line (u16)
+/// - offset of the atlas [`TextureDescriptor`] within the descriptor (u32)
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontDescriptor {
+    line_height: u16,
+    baseline: u16,
+    glyphs: Vec<Glyph>,
+    texture_descriptor: Option<TextureDescriptor>,
+}
+
+impl Display for FontDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_fields(
+            f,
+            &[
+                ("glyph_count", self.glyphs.len().to_string()),
+                ("line_height", self.line_height.to_string()),
+                ("baseline", self.baseline.to_string()),
+                (
+                    "atlas",
+                    self.texture_descriptor
+                        .as_ref()
+                        .map_or("none".to_string(), |desc| {
+                            format!("{:?} {}x{}", desc.format(), desc.width(), desc.height())
+                        }),
+                ),
+            ],
+        )
+    }
+}
+
+impl AssetDescriptor for FontDescriptor {
+    fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError> {
+        if data.len() < FONT_DESCRIPTOR_SIZE {
+            return Err(AssetParseError::InputTooSmall);
+        }
+
+        let mut cur = Cursor::new(data);
+
+        let glyph_count = cur.read_u32::<LittleEndian>()? as usize;
+        let glyph_list_offset = cur.read_u32::<LittleEndian>()? as usize;
+        let line_height = cur.read_u16::<LittleEndian>()?;
+        let baseline = cur.read_u16::<LittleEndian>()?;
+        let texture_descriptor_offset = cur.read_u32::<LittleEndian>()? as usize;
+
+        let glyph_count = records_that_fit(data.len(), glyph_count, glyph_list_offset, GLYPH_SIZE);
+
+        cur.seek(SeekFrom::Start(glyph_list_offset as u64))?;
+
+        let glyphs = (0..glyph_count)
+            .map(|_| Glyph::from_cursor(&mut cur))
+            .collect::<Result<_, _>>()?;
+
+        let texture_descriptor = data
+            .get(texture_descriptor_offset..)
+            .and_then(|data| TextureDescriptor::from_bytes(data).ok());
+
+        Ok(FontDescriptor {
+            line_height,
+            baseline,
+            glyphs,
+            texture_descriptor,
+        })
+    }
+}
+
+/// A bitmap font, made up of per-character metrics and the atlas texture they point into.
+#[derive(Debug)]
+pub struct Font {
+    name: String,
+    descriptor: FontDescriptor,
+    texture: Option<Texture>,
+}
+
+impl Asset for Font {
+    type Descriptor = FontDescriptor;
+
+    fn new(
+        name: &str,
+        descriptor: &Self::Descriptor,
+        virtual_res: &VirtualResource,
+    ) -> Result<Self, AssetParseError> {
+        Ok(Font {
+            name: name.to_string(),
+            descriptor: descriptor.clone(),
+            texture: descriptor
+                .texture_descriptor
+                .as_ref()
+                .and_then(|desc| Texture::new(name, desc, virtual_res).ok()),
+        })
+    }
+
+    fn descriptor(&self) -> &Self::Descriptor {
+        &self.descriptor
+    }
+
+    fn asset_type() -> AssetType {
+        AssetType::ResFont
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Font {
+    pub fn glyphs(&self) -> &[Glyph] {
+        &self.descriptor.glyphs
+    }
+
+    /// Returns the glyph for a character, if this font has one.
+    pub fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs().iter().find(|glyph| glyph.char() == Some(c))
+    }
+
+    pub fn line_height(&self) -> u16 {
+        self.descriptor.line_height
+    }
+
+    pub fn baseline(&self) -> u16 {
+        self.descriptor.baseline
+    }
+
+    /// Returns the atlas texture that the glyphs are drawn from, or None if it couldn't be read.
+    pub fn texture(&self) -> Option<&Texture> {
+        self.texture.as_ref()
+    }
+
+    /// Exports the glyph atlas as a PNG, alongside its metrics as both an AngelCode BMFont text
+    /// file (.fnt) and JSON. The files are named after `path`, so exporting to
+    /// `./out/font_main.png` also writes `./out/font_main.fnt` and `./out/font_main.json`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use std::path::Path;
+    /// # use bnl::{BNLFile, asset::font::Font};
+    /// # let bnl = BNLFile::new();
+    /// let font: Font = bnl.get_asset("aid_font_main").expect("Unable to get font.");
+    /// font.export_atlas(Path::new("./font_main.png")).expect("Unable to export font.");
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if the font has no atlas texture, or if any of the files can't be written.
+    pub fn export_atlas(&self, path: &Path) -> Result<(), io::Error> {
+        let texture = self.texture().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Font {} has no readable atlas texture", self.name),
+            )
+        })?;
+
+        let png_path = path.with_extension("png");
+        let page_name = png_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+
+        texture.dump(&png_path)?;
+
+        fs::write(path.with_extension("fnt"), self.to_bmfont(&page_name))?;
+        fs::write(path.with_extension("json"), self.to_json().to_string())
+    }
+
+    /// Returns the metrics of this font in the AngelCode BMFont text format, with `page_name` as
+    /// the file name of the atlas. The atlas size is 0x0 if the font has no atlas texture.
+    pub fn to_bmfont(&self, page_name: &str) -> String {
+        let (width, height) = self.texture().map_or((0, 0), |texture| {
+            (texture.descriptor().width(), texture.descriptor().height())
+        });
+
+        let mut fnt = String::new();
+
+        let _ = writeln!(
+            fnt,
+            "info face=\"{}\" size={}",
+            self.name,
+            self.line_height()
+        );
+        let _ = writeln!(
+            fnt,
+            "common lineHeight={} base={} scaleW={} scaleH={} pages=1",
+            self.line_height(),
+            self.baseline(),
+            width,
+            height
+        );
+        let _ = writeln!(fnt, "page id=0 file=\"{}\"", page_name);
+        let _ = writeln!(fnt, "chars count={}", self.glyphs().len());
+
+        for glyph in self.glyphs() {
+            let _ = writeln!(
+                fnt,
+                "char id={} x={} y={} width={} height={} xoffset={} yoffset={} xadvance={} page=0 chnl=15",
+                glyph.character,
+                glyph.x,
+                glyph.y,
+                glyph.width,
+                glyph.height,
+                glyph.x_offset,
+                glyph.y_offset,
+                glyph.advance
+            );
+        }
+
+        fnt
+    }
+
+    /// Returns the metrics of this font as JSON.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "line_height": self.line_height(),
+            "baseline": self.baseline(),
+            "glyphs": self
+                .glyphs()
+                .iter()
+                .map(|glyph| {
+                    json!({
+                        "character": glyph.character,
+                        "x": glyph.x,
+                        "y": glyph.y,
+                        "width": glyph.width,
+                        "height": glyph.height,
+                        "x_offset": glyph.x_offset,
+                        "y_offset": glyph.y_offset,
+                        "advance": glyph.advance,
+                    })
+                })
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_glyphs() {
+        let mut descriptor = vec![];
+
+        for value in [2u32, 16] {
+            descriptor.extend_from_slice(&value.to_le_bytes());
+        }
+
+        descriptor.extend_from_slice(&12u16.to_le_bytes());
+        descriptor.extend_from_slice(&10u16.to_le_bytes());
+        descriptor.extend_from_slice(&48u32.to_le_bytes());
+
+        for glyph in [[65u16, 0, 0, 4, 4, 0, 0, 5], [66, 4, 0, 4, 4, 0, 0xffff, 5]] {
+            glyph
+                .iter()
+                .for_each(|value| descriptor.extend_from_slice(&value.to_le_bytes()));
+        }
+
+        // 4x4 A8R8G8B8 atlas
+        for value in [0x40u32, 28, 0x0004_0004, 0, 0, 0, 64] {
+            descriptor.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let descriptor = FontDescriptor::from_bytes(&descriptor).unwrap();
+
+        let resource = vec![0xffu8; 64];
+        let slices = [resource.as_slice()];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let font = Font::new("aid_font_test", &descriptor, &virtual_res).unwrap();
+
+        assert_eq!(font.glyphs().len(), 2);
+        assert_eq!(font.glyph('B').unwrap().y_offset, -1);
+        assert!(font.glyph('C').is_none());
+
+        let fnt = font.to_bmfont("font.png");
+        assert!(fnt.contains("common lineHeight=12 base=10 scaleW=4 scaleH=4 pages=1"));
+        assert!(fnt.contains("char id=66 x=4 y=0 width=4 height=4 xoffset=0 yoffset=-1"));
+
+        assert_eq!(font.to_json()["glyphs"][0]["character"], 65);
+    }
+
+    #[test]
+    fn keeps_metrics_without_atlas() {
+        let mut descriptor = vec![];
+
+        // Three glyphs listed but only one fits, and the atlas offset is past the end
+        for value in [3u32, 16] {
+            descriptor.extend_from_slice(&value.to_le_bytes());
+        }
+
+        descriptor.extend_from_slice(&12u16.to_le_bytes());
+        descriptor.extend_from_slice(&10u16.to_le_bytes());
+        descriptor.extend_from_slice(&0x100u32.to_le_bytes());
+
+        for value in [65u16, 0, 0, 4, 4, 0, 0, 5] {
+            descriptor.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let descriptor = FontDescriptor::from_bytes(&descriptor).unwrap();
+
+        let slices: [&[u8]; 0] = [];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let font = Font::new("aid_font_test", &descriptor, &virtual_res).unwrap();
+
+        assert_eq!(font.glyphs().len(), 1);
+        assert!(font.texture().is_none());
+        assert!(font.to_bmfont("font.png").contains("scaleW=0 scaleH=0"));
+        assert!(font.export_atlas(Path::new("font.png")).is_err());
+    }
+}
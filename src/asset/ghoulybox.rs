@@ -0,0 +1,490 @@
+use std::{
+    fmt::{self, Display},
+    io::{self, Cursor, Read, Seek, SeekFrom},
+};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use crate::{
+    VirtualResource,
+    asset::{
+        AID_SIZE, Asset, AssetDescriptor, AssetParseError, RawAsset, WritableAsset, aid_from_bytes,
+        aid_to_bytes, records_that_fit, write_fields,
+    },
+    game::AssetType,
+};
+
+/// The size of the fixed part of a [`GhoulyboxDescriptor`].
+const GHOULYBOX_DESCRIPTOR_SIZE: usize = 8;
+
+/// The size of a single [`SpawnEntry`].
+const SPAWN_ENTRY_SIZE: usize = AID_SIZE + 16;
+
+/// The size of a [`GhoulyspawnDescriptor`].
+const GHOULYSPAWN_DESCRIPTOR_SIZE: usize = AID_SIZE + 24;
+
+/// One kind of actor that a ghoulybox spawns.
+///
+/// Assumed layout, see [`GhoulyboxDescriptor`]:
+/// - actor attribs AID (128 bytes, NUL padded)
+/// - total number of actors to spawn (u32)
+/// - maximum number alive at once (u32)
+/// - delay between spawns in seconds (f32)
+/// - spawn radius around the spawn point (f32)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpawnEntry {
+    pub actor_attribs: String,
+    pub count: u32,
+    pub max_alive: u32,
+    pub spawn_delay: f32,
+    pub spawn_radius: f32,
+}
+
+impl SpawnEntry {
+    fn from_cursor(cur: &mut Cursor<&[u8]>) -> Result<Self, io::Error> {
+        let mut aid = [0u8; AID_SIZE];
+        cur.read_exact(&mut aid)?;
+
+        Ok(SpawnEntry {
+            actor_attribs: aid_from_bytes(&aid),
+            count: cur.read_u32::<LittleEndian>()?,
+            max_alive: cur.read_u32::<LittleEndian>()?,
+            spawn_delay: cur.read_f32::<LittleEndian>()?,
+            spawn_radius: cur.read_f32::<LittleEndian>()?,
+        })
+    }
+}
+
+/// A spawn table, listing the actors spawned by a ghoulybox.
+///
+/// Neither this layout nor that of [`SpawnEntry`] has been confirmed against game files; the
+/// field meanings are guesses. Entries that would run past the end of the descriptor are dropped
+/// rather than failing the asset, and any other bytes are kept as they are.
+///
+/// Assumed layout:
+/// - entry count (u32)
+/// - offset of the entries within the descriptor (u32), stored back to back
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GhoulyboxDescriptor {
+    entries: Vec<SpawnEntry>,
+    /// Any bytes between the fixed header and the entries.
+    header_tail: Vec<u8>,
+    /// Any bytes following the entries.
+    trailing: Vec<u8>,
+}
+
+impl AssetDescriptor for GhoulyboxDescriptor {
+    fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError> {
+        if data.len() < GHOULYBOX_DESCRIPTOR_SIZE {
+            return Err(AssetParseError::InputTooSmall);
+        }
+
+        let mut cur = Cursor::new(data);
+
+        let entry_count = cur.read_u32::<LittleEndian>()? as usize;
+        let entries_offset = cur.read_u32::<LittleEndian>()? as usize;
+
+        let entry_count =
+            records_that_fit(data.len(), entry_count, entries_offset, SPAWN_ENTRY_SIZE);
+
+        cur.seek(SeekFrom::Start(entries_offset as u64))?;
+
+        let entries = (0..entry_count)
+            .map(|_| SpawnEntry::from_cursor(&mut cur))
+            .collect::<Result<_, _>>()?;
+
+        let entries_start = entries_offset.clamp(GHOULYBOX_DESCRIPTOR_SIZE, data.len());
+        let entries_end =
+            (entries_offset + entry_count * SPAWN_ENTRY_SIZE).clamp(entries_start, data.len());
+
+        Ok(GhoulyboxDescriptor {
+            entries,
+            header_tail: data[GHOULYBOX_DESCRIPTOR_SIZE..entries_start].to_vec(),
+            trailing: data[entries_end..].to_vec(),
+        })
+    }
+}
+
+impl Display for GhoulyboxDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_fields(
+            f,
+            &[
+                ("entry_count", self.entries.len().to_string()),
+                ("trailing_bytes", self.trailing.len().to_string()),
+            ],
+        )?;
+
+        for (i, entry) in self.entries.iter().enumerate() {
+            writeln!(
+                f,
+                "entry {}: {} x{} (max {} alive), every {}s within {}",
+                i,
+                entry.actor_attribs,
+                entry.count,
+                entry.max_alive,
+                entry.spawn_delay,
+                entry.spawn_radius
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A spawn table referenced by scripts, describing which actors to spawn and how many.
+#[derive(Debug)]
+pub struct Ghoulybox {
+    name: String,
+    descriptor: GhoulyboxDescriptor,
+}
+
+impl Asset for Ghoulybox {
+    type Descriptor = GhoulyboxDescriptor;
+
+    fn new(
+        name: &str,
+        descriptor: &Self::Descriptor,
+        _virtual_res: &VirtualResource,
+    ) -> Result<Self, AssetParseError> {
+        Ok(Ghoulybox {
+            name: name.to_string(),
+            descriptor: descriptor.clone(),
+        })
+    }
+
+    fn descriptor(&self) -> &Self::Descriptor {
+        &self.descriptor
+    }
+
+    fn asset_type() -> AssetType {
+        AssetType::ResGhoulybox
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Ghoulybox {
+    pub fn entries(&self) -> &[SpawnEntry] {
+        &self.descriptor.entries
+    }
+
+    /// Returns the spawn entries for editing. Entries may be added or removed, as
+    /// [`Ghoulybox::to_bytes`] rebuilds the whole descriptor.
+    pub fn entries_mut(&mut self) -> &mut Vec<SpawnEntry> {
+        &mut self.descriptor.entries
+    }
+
+    /// Serialises this ghoulybox back into descriptor bytes. Any bytes that were between the
+    /// header and the entries, or after the entries, are written back around them unchanged.
+    ///
+    /// # Errors
+    /// Returns an error if an actor attribs AID is too long to fit in its field.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bnl::{BNLFile, asset::ghoulybox::Ghoulybox};
+    /// # let bnl = BNLFile::new();
+    /// let mut ghoulybox: Ghoulybox = bnl.get_asset("aid_ghoulybox_lvl1").expect("Unable to get ghoulybox.");
+    /// ghoulybox.entries_mut()[0].count *= 2;
+    ///
+    /// let mut raw_asset = bnl.get_raw_asset("aid_ghoulybox_lvl1").expect("Unable to get raw asset.");
+    /// raw_asset.descriptor_bytes = ghoulybox.to_bytes().expect("Unable to serialise ghoulybox.");
+    /// ```
+    pub fn to_bytes(&self) -> Result<Vec<u8>, io::Error> {
+        let entries = &self.descriptor.entries;
+
+        let header_tail = &self.descriptor.header_tail;
+        let entries_offset = GHOULYBOX_DESCRIPTOR_SIZE + header_tail.len();
+
+        let mut bytes = Vec::with_capacity(
+            entries_offset + entries.len() * SPAWN_ENTRY_SIZE + self.descriptor.trailing.len(),
+        );
+
+        bytes.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(entries_offset as u32).to_le_bytes());
+        bytes.extend_from_slice(header_tail);
+
+        for entry in entries {
+            bytes.extend_from_slice(&aid_to_bytes(&entry.actor_attribs)?);
+            bytes.extend_from_slice(&entry.count.to_le_bytes());
+            bytes.extend_from_slice(&entry.max_alive.to_le_bytes());
+            bytes.extend_from_slice(&entry.spawn_delay.to_le_bytes());
+            bytes.extend_from_slice(&entry.spawn_radius.to_le_bytes());
+        }
+
+        bytes.extend_from_slice(&self.descriptor.trailing);
+
+        Ok(bytes)
+    }
+}
+
+impl WritableAsset for Ghoulybox {
+    fn write_back(&self, raw_asset: &mut RawAsset) -> Result<(), io::Error> {
+        raw_asset.descriptor_bytes = self.to_bytes()?;
+        Ok(())
+    }
+}
+
+/// A placed spawn point in a level, triggering a ghoulybox.
+///
+/// This layout is an unconfirmed guess. Only the ghoulybox AID is likely to be right, the
+/// meaning of the remaining fields hasn't been checked against game files, and any bytes past
+/// them are kept as they are.
+///
+/// Assumed layout:
+/// - ghoulybox AID (128 bytes, NUL padded)
+/// - position (3 x f32)
+/// - rotation around the vertical axis in radians (f32)
+/// - delay before the first spawn in seconds (f32)
+/// - flags (u32)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GhoulyspawnDescriptor {
+    pub ghoulybox: String,
+    pub position: [f32; 3],
+    pub rotation: f32,
+    pub trigger_delay: f32,
+    pub flags: u32,
+    /// Any bytes following the known fields.
+    pub trailing: Vec<u8>,
+}
+
+impl AssetDescriptor for GhoulyspawnDescriptor {
+    fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError> {
+        if data.len() < GHOULYSPAWN_DESCRIPTOR_SIZE {
+            return Err(AssetParseError::InputTooSmall);
+        }
+
+        let mut cur = Cursor::new(&data[AID_SIZE..]);
+
+        Ok(GhoulyspawnDescriptor {
+            ghoulybox: aid_from_bytes(&data[..AID_SIZE]),
+            position: [
+                cur.read_f32::<LittleEndian>()?,
+                cur.read_f32::<LittleEndian>()?,
+                cur.read_f32::<LittleEndian>()?,
+            ],
+            rotation: cur.read_f32::<LittleEndian>()?,
+            trigger_delay: cur.read_f32::<LittleEndian>()?,
+            flags: cur.read_u32::<LittleEndian>()?,
+            trailing: data[GHOULYSPAWN_DESCRIPTOR_SIZE..].to_vec(),
+        })
+    }
+}
+
+impl Display for GhoulyspawnDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_fields(
+            f,
+            &[
+                ("ghoulybox", self.ghoulybox.clone()),
+                ("position", format!("{:?}", self.position)),
+                ("rotation", self.rotation.to_string()),
+                ("trigger_delay", format!("{}s", self.trigger_delay)),
+                ("flags", format!("{:#010x}", self.flags)),
+                ("trailing_bytes", self.trailing.len().to_string()),
+            ],
+        )
+    }
+}
+
+/// A spawn point placed in a level, which spawns the actors of a [`Ghoulybox`].
+#[derive(Debug)]
+pub struct Ghoulyspawn {
+    name: String,
+    descriptor: GhoulyspawnDescriptor,
+}
+
+impl Asset for Ghoulyspawn {
+    type Descriptor = GhoulyspawnDescriptor;
+
+    fn new(
+        name: &str,
+        descriptor: &Self::Descriptor,
+        _virtual_res: &VirtualResource,
+    ) -> Result<Self, AssetParseError> {
+        Ok(Ghoulyspawn {
+            name: name.to_string(),
+            descriptor: descriptor.clone(),
+        })
+    }
+
+    fn descriptor(&self) -> &Self::Descriptor {
+        &self.descriptor
+    }
+
+    fn asset_type() -> AssetType {
+        AssetType::ResGhoulyspawn
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Ghoulyspawn {
+    /// Returns the AID of the ghoulybox this spawn point triggers.
+    pub fn ghoulybox(&self) -> &str {
+        &self.descriptor.ghoulybox
+    }
+
+    pub fn position(&self) -> [f32; 3] {
+        self.descriptor.position
+    }
+
+    pub fn rotation(&self) -> f32 {
+        self.descriptor.rotation
+    }
+
+    pub fn trigger_delay(&self) -> f32 {
+        self.descriptor.trigger_delay
+    }
+
+    pub fn flags(&self) -> u32 {
+        self.descriptor.flags
+    }
+
+    /// Returns the spawn point for editing, see [`Ghoulyspawn::to_bytes`].
+    pub fn descriptor_mut(&mut self) -> &mut GhoulyspawnDescriptor {
+        &mut self.descriptor
+    }
+
+    /// Serialises this spawn point back into descriptor bytes.
+    ///
+    /// # Errors
+    /// Returns an error if the ghoulybox AID is too long to fit in its field.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, io::Error> {
+        let desc = &self.descriptor;
+
+        let mut bytes = Vec::with_capacity(GHOULYSPAWN_DESCRIPTOR_SIZE + desc.trailing.len());
+
+        bytes.extend_from_slice(&aid_to_bytes(&desc.ghoulybox)?);
+
+        for value in desc
+            .position
+            .iter()
+            .chain([desc.rotation, desc.trigger_delay].iter())
+        {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        bytes.extend_from_slice(&desc.flags.to_le_bytes());
+        bytes.extend_from_slice(&desc.trailing);
+
+        Ok(bytes)
+    }
+}
+
+impl WritableAsset for Ghoulyspawn {
+    fn write_back(&self, raw_asset: &mut RawAsset) -> Result<(), io::Error> {
+        raw_asset.descriptor_bytes = self.to_bytes()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ghoulybox_round_trip() {
+        let mut data = vec![];
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&8u32.to_le_bytes());
+        data.extend_from_slice(&aid_to_bytes("aid_actorattribs_imp").unwrap());
+
+        for value in [5u32, 2] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+
+        for value in [1.5f32, 3.0] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let descriptor = GhoulyboxDescriptor::from_bytes(&data).unwrap();
+        let slices: [&[u8]; 0] = [];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let mut ghoulybox =
+            Ghoulybox::new("aid_ghoulybox_test", &descriptor, &virtual_res).unwrap();
+
+        assert_eq!(ghoulybox.entries()[0].actor_attribs, "aid_actorattribs_imp");
+        assert_eq!(ghoulybox.entries()[0].max_alive, 2);
+        assert_eq!(ghoulybox.to_bytes().unwrap(), data);
+
+        // An entry count larger than the descriptor keeps the entries that fit
+        let mut overcounted = data.clone();
+        overcounted[..4].copy_from_slice(&3u32.to_le_bytes());
+        assert_eq!(
+            GhoulyboxDescriptor::from_bytes(&overcounted)
+                .unwrap()
+                .entries,
+            ghoulybox.entries()
+        );
+
+        ghoulybox.entries_mut().push(SpawnEntry {
+            actor_attribs: "aid_actorattribs_zombie".to_string(),
+            count: 1,
+            max_alive: 1,
+            spawn_delay: 0.0,
+            spawn_radius: 0.0,
+        });
+
+        let descriptor = GhoulyboxDescriptor::from_bytes(&ghoulybox.to_bytes().unwrap()).unwrap();
+        assert_eq!(descriptor.entries, ghoulybox.entries());
+
+        ghoulybox.entries_mut()[0].actor_attribs = "a".repeat(AID_SIZE);
+        assert!(ghoulybox.to_bytes().is_err());
+    }
+
+    #[test]
+    fn ghoulybox_keeps_unknown_bytes() {
+        let mut data = vec![];
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&12u32.to_le_bytes());
+        data.extend_from_slice(&[1, 2, 3, 4]);
+        data.extend_from_slice(&aid_to_bytes("aid_actorattribs_imp").unwrap());
+        data.extend_from_slice(&[0; 16]);
+        data.extend_from_slice(&[5, 6]);
+
+        let descriptor = GhoulyboxDescriptor::from_bytes(&data).unwrap();
+        assert_eq!(descriptor.header_tail, [1, 2, 3, 4]);
+        assert_eq!(descriptor.trailing, [5, 6]);
+
+        let slices: [&[u8]; 0] = [];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let ghoulybox = Ghoulybox::new("aid_ghoulybox_test", &descriptor, &virtual_res).unwrap();
+        assert_eq!(ghoulybox.to_bytes().unwrap(), data);
+    }
+
+    #[test]
+    fn ghoulyspawn_round_trip() {
+        let mut data = aid_to_bytes("aid_ghoulybox_test").unwrap().to_vec();
+
+        for value in [1.0f32, 2.0, 3.0, 0.5, 10.0] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+
+        data.extend_from_slice(&0x80u32.to_le_bytes());
+        data.extend_from_slice(&[7, 8, 9]);
+
+        let descriptor = GhoulyspawnDescriptor::from_bytes(&data).unwrap();
+        let slices: [&[u8]; 0] = [];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let ghoulyspawn =
+            Ghoulyspawn::new("aid_ghoulyspawn_test", &descriptor, &virtual_res).unwrap();
+
+        assert_eq!(ghoulyspawn.ghoulybox(), "aid_ghoulybox_test");
+        assert_eq!(ghoulyspawn.position(), [1.0, 2.0, 3.0]);
+        assert_eq!(ghoulyspawn.flags(), 0x80);
+        assert_eq!(ghoulyspawn.descriptor().trailing, [7, 8, 9]);
+        assert_eq!(ghoulyspawn.to_bytes().unwrap(), data);
+    }
+}
@@ -0,0 +1,196 @@
+//! Mutable handles to the assets of a [`BNLFile`], see [`BNLFile::get_asset_mut`].
+
+use std::ops::{Deref, DerefMut};
+
+use crate::{
+    BNLFile,
+    asset::{AssetError, RawAsset, WritableAsset},
+};
+
+/// A parsed asset borrowed mutably from a [`BNLFile`]. Changes made through the handle are written
+/// back into the file by [`AssetMut::commit`], or when the handle is dropped. Errors can only be
+/// seen through `commit`, so prefer it over relying on the drop.
+///
+/// Writes keep the asset where it is when its descriptor and resource stay the same size, and
+/// otherwise move it like [`BNLFile::replace_asset`].
+pub struct AssetMut<'a, A: WritableAsset> {
+    bnl: &'a mut BNLFile,
+    /// The asset as it currently is in the file.
+    raw_asset: RawAsset,
+    asset: A,
+    modified: bool,
+}
+
+impl<A: WritableAsset> AssetMut<'_, A> {
+    /// Writes the changes made to the asset back into the [`BNLFile`].
+    ///
+    /// # Errors
+    /// Returns [`AssetError::WriteError`] if the asset can't be serialised, in which case the file
+    /// is left unchanged.
+    pub fn commit(mut self) -> Result<(), AssetError> {
+        self.write()
+    }
+
+    /// Drops the handle without writing the changes made to the asset.
+    pub fn discard(mut self) {
+        self.modified = false;
+    }
+
+    fn write(&mut self) -> Result<(), AssetError> {
+        if !self.modified {
+            return Ok(());
+        }
+
+        let mut raw_asset = self.raw_asset.clone();
+
+        self.asset
+            .write_back(&mut raw_asset)
+            .map_err(|source| AssetError::WriteError {
+                name: raw_asset.name.clone(),
+                source,
+            })?;
+
+        let same_size = raw_asset.descriptor_bytes.len() == self.raw_asset.descriptor_bytes.len()
+            && raw_asset.data_slices.concat().len() == self.raw_asset.data_slices.concat().len();
+
+        match same_size {
+            true => self.bnl.update_asset(&raw_asset.name, &raw_asset)?,
+            false => self.bnl.replace_asset(&raw_asset.name, &raw_asset)?,
+        }
+
+        self.raw_asset = raw_asset;
+        self.modified = false;
+
+        Ok(())
+    }
+}
+
+impl<A: WritableAsset> Deref for AssetMut<'_, A> {
+    type Target = A;
+
+    fn deref(&self) -> &A {
+        &self.asset
+    }
+}
+
+impl<A: WritableAsset> DerefMut for AssetMut<'_, A> {
+    fn deref_mut(&mut self) -> &mut A {
+        self.modified = true;
+        &mut self.asset
+    }
+}
+
+impl<A: WritableAsset> Drop for AssetMut<'_, A> {
+    fn drop(&mut self) {
+        let _ = self.write();
+    }
+}
+
+impl BNLFile {
+    /// Retrieves an asset by name for editing. The returned handle dereferences to the parsed
+    /// asset, and writes any changes back into this file when it is committed or dropped.
+    ///
+    /// # Errors
+    /// Returns an [`AssetError`] if the asset can't be found, isn't an `A`, or can't be parsed.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use bnl::asset::actor_attribs::ActorAttribs;
+    ///
+    /// # fn main() -> Result<(), bnl::asset::AssetError> {
+    /// # let mut bnl_file = bnl::BNLFile::new();
+    /// let mut attribs = bnl_file.get_asset_mut::<ActorAttribs>("aid_actorattribs_imp")?;
+    /// attribs.descriptor_mut().health *= 2.0;
+    /// attribs.commit()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_asset_mut<A: WritableAsset>(
+        &mut self,
+        name: &str,
+    ) -> Result<AssetMut<'_, A>, AssetError> {
+        let raw_asset = self.get_raw_asset(name)?;
+        let asset = raw_asset.parse()?;
+
+        Ok(AssetMut {
+            bnl: self,
+            raw_asset,
+            asset,
+            modified: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asset::{
+            loctext::{Language, Loctext},
+            texture::{Image, Texture},
+        },
+        game::AssetType,
+        tests::make_bnl,
+    };
+
+    #[test]
+    fn writes_back_on_commit_and_drop() {
+        let mut bnl = BNLFile::from_bytes(&make_bnl()).unwrap();
+        let image = Image::from_rgba8(1, 1, vec![1, 2, 3, 4]).unwrap();
+
+        // Discarded changes aren't written
+        let mut texture = bnl.get_asset_mut::<Texture>("aid_texture_test").unwrap();
+        texture.blit(0, 0, &image).unwrap();
+        texture.discard();
+
+        let original = bnl.get_raw_asset("aid_texture_test").unwrap().data_slices;
+
+        {
+            let mut texture = bnl.get_asset_mut::<Texture>("aid_texture_test").unwrap();
+            texture.blit(0, 0, &image).unwrap();
+        }
+
+        let edited = bnl.get_raw_asset("aid_texture_test").unwrap().data_slices;
+        assert_ne!(edited, original);
+        assert_eq!(edited.concat().len(), original.concat().len());
+
+        let push_u32s = |bytes: &mut Vec<u8>, values: &[u32]| {
+            values
+                .iter()
+                .for_each(|value| bytes.extend_from_slice(&value.to_le_bytes()))
+        };
+
+        let mut descriptor_bytes = vec![];
+        push_u32s(&mut descriptor_bytes, &[1, 1, 16, 20]);
+        push_u32s(&mut descriptor_bytes, &[0x100]);
+        push_u32s(&mut descriptor_bytes, &[1, 0, 0]);
+
+        let mut resource = vec![];
+        push_u32s(&mut resource, &[4]);
+        resource.extend_from_slice(b"Hi\0");
+
+        bnl.add_asset(&RawAsset {
+            name: "aid_loctext_test".to_string(),
+            asset_type: AssetType::ResLoctext,
+            descriptor_bytes,
+            data_slices: vec![resource],
+        })
+        .unwrap();
+
+        // Longer strings grow the resource, moving the asset
+        let mut loctext = bnl.get_asset_mut::<Loctext>("aid_loctext_test").unwrap();
+        loctext
+            .set_string(0x100, Language::English, "Hello there")
+            .unwrap();
+        loctext.commit().unwrap();
+
+        let bnl = BNLFile::from_bytes(&bnl.to_bytes().unwrap()).unwrap();
+        let loctext: Loctext = bnl.get_asset("aid_loctext_test").unwrap();
+
+        assert_eq!(loctext.get(Language::English, 0x100), Some("Hello there"));
+        assert_eq!(
+            bnl.get_raw_asset("aid_texture_test").unwrap().data_slices,
+            edited
+        );
+    }
+}
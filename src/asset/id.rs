@@ -0,0 +1,308 @@
+//! Validated asset names (AIDs). Every asset in the game is named `aid_<category>_<...>`, such as
+//! `aid_texture_gribble` or `aid_cutscene_intro`, stored NUL padded in a 128 byte field.
+
+use std::{
+    fmt::{self, Display},
+    ops::Deref,
+    str::FromStr,
+};
+
+use crate::{
+    asset::{AID_SIZE, AssetDescription, AssetName, RawAsset},
+    game::AssetType,
+};
+
+/// The prefix every asset name starts with.
+pub const AID_PREFIX: &str = "aid_";
+
+/// The longest name that fits in an AID field alongside its NUL terminator.
+pub const MAX_AID_LEN: usize = AID_SIZE - 1;
+
+/// The reason a name isn't a valid [`AssetId`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AssetIdError {
+    /// The name is longer than [`MAX_AID_LEN`] bytes.
+    TooLong(usize),
+    /// The name holds a character other than ASCII letters, digits, `_`, `-` and `.`.
+    InvalidCharacter(char),
+    /// The name doesn't start with [`AID_PREFIX`].
+    MissingPrefix,
+    /// The name has nothing after [`AID_PREFIX`] to give its category.
+    MissingCategory,
+    /// The AID field has no NUL terminator, or holds something other than NULs after it.
+    NotNulPadded,
+}
+
+impl Display for AssetIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssetIdError::TooLong(len) => write!(
+                f,
+                "The name is {} bytes long, but at most {} fit in an AID",
+                len, MAX_AID_LEN
+            ),
+            AssetIdError::InvalidCharacter(c) => {
+                write!(f, "The name contains the invalid character {:?}", c)
+            }
+            AssetIdError::MissingPrefix => write!(f, "The name doesn't start with {}", AID_PREFIX),
+            AssetIdError::MissingCategory => write!(f, "The name has no category"),
+            AssetIdError::NotNulPadded => write!(f, "The AID field isn't padded with NULs"),
+        }
+    }
+}
+
+impl std::error::Error for AssetIdError {}
+
+/// A validated asset name, such as `aid_texture_gribble`. Dereferences to the name as a `str`,
+/// so it can be passed anywhere a name is taken.
+///
+/// # Examples
+/// ```no_run
+/// use bnl::asset::{id::AssetId, texture::Texture};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let bnl_file = bnl::BNLFile::new();
+/// let id: AssetId = "aid_texture_gribble".parse()?;
+/// assert_eq!(id.category(), "texture");
+///
+/// let texture: Texture = bnl_file.get_asset(&id)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
+pub struct AssetId(String);
+
+impl AssetId {
+    /// Builds the name `aid_<category>_<name>`, such as `aid_texture_gribble` from `texture` and
+    /// `gribble`.
+    ///
+    /// # Errors
+    /// Returns an [`AssetIdError`] if the result isn't a valid name.
+    pub fn from_parts(category: &str, name: &str) -> Result<AssetId, AssetIdError> {
+        if category.is_empty() || category.contains('_') {
+            return Err(AssetIdError::MissingCategory);
+        }
+
+        format!("{}{}_{}", AID_PREFIX, category, name).parse()
+    }
+
+    /// Reads a name from a NUL padded AID field, as stored in an [`AssetDescription`] or a
+    /// descriptor that references other assets.
+    ///
+    /// # Errors
+    /// Returns an [`AssetIdError`] if the field isn't NUL padded or holds an invalid name.
+    pub fn from_bytes(bytes: &[u8]) -> Result<AssetId, AssetIdError> {
+        let end = bytes
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or(AssetIdError::NotNulPadded)?;
+
+        if bytes[end..].iter().any(|&b| b != 0) {
+            return Err(AssetIdError::NotNulPadded);
+        }
+
+        match str::from_utf8(&bytes[..end]) {
+            Ok(name) => name.parse(),
+            Err(e) => {
+                let c = String::from_utf8_lossy(&bytes[e.valid_up_to()..end])
+                    .chars()
+                    .next()
+                    .unwrap_or(char::REPLACEMENT_CHARACTER);
+
+                Err(AssetIdError::InvalidCharacter(c))
+            }
+        }
+    }
+
+    /// Returns this name as a NUL padded AID field.
+    pub fn to_bytes(&self) -> AssetName {
+        let mut bytes: AssetName = [0; AID_SIZE];
+        bytes[..self.0.len()].copy_from_slice(self.0.as_bytes());
+
+        bytes
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the category of this name, such as `texture` for `aid_texture_gribble`. This is
+    /// usually, but not always, the asset's type.
+    pub fn category(&self) -> &str {
+        let rest = &self.0[AID_PREFIX.len()..];
+
+        rest.split('_').next().unwrap_or(rest)
+    }
+
+    /// Returns the asset type the category of this name stands for, or None if the category isn't
+    /// one the game uses. Categories are named after their type, such as `cutscene` for
+    /// [`AssetType::ResCutscene`], with the Xbox audio types also going without their `x`.
+    ///
+    /// # Examples
+    /// ```
+    /// use bnl::{asset::id::AssetId, game::AssetType};
+    ///
+    /// # fn main() -> Result<(), bnl::asset::id::AssetIdError> {
+    /// let id: AssetId = "aid_actorattribs_imp".parse()?;
+    /// assert_eq!(id.expected_type(), Some(AssetType::ResActorAttribs));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn expected_type(&self) -> Option<AssetType> {
+        match self.category().to_lowercase().as_str() {
+            "soundbank" => Some(AssetType::ResXSoundbank),
+            "dsp" => Some(AssetType::ResXDSP),
+            "cuelist" => Some(AssetType::ResXCueList),
+            "unknown3" => None,
+            category => AssetType::from_name(category),
+        }
+    }
+}
+
+impl FromStr for AssetId {
+    type Err = AssetIdError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        if name.len() > MAX_AID_LEN {
+            return Err(AssetIdError::TooLong(name.len()));
+        }
+
+        if let Some(c) = name
+            .chars()
+            .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')))
+        {
+            return Err(AssetIdError::InvalidCharacter(c));
+        }
+
+        let rest = name
+            .strip_prefix(AID_PREFIX)
+            .ok_or(AssetIdError::MissingPrefix)?;
+
+        if rest.split('_').next().is_none_or(str::is_empty) {
+            return Err(AssetIdError::MissingCategory);
+        }
+
+        Ok(AssetId(name.to_string()))
+    }
+}
+
+impl TryFrom<&str> for AssetId {
+    type Error = AssetIdError;
+
+    fn try_from(name: &str) -> Result<Self, Self::Error> {
+        name.parse()
+    }
+}
+
+impl TryFrom<String> for AssetId {
+    type Error = AssetIdError;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        name.parse()
+    }
+}
+
+impl From<AssetId> for String {
+    fn from(id: AssetId) -> Self {
+        id.0
+    }
+}
+
+impl Deref for AssetId {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for AssetId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for AssetId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AssetDescription {
+    /// Returns the name of this asset as an [`AssetId`].
+    ///
+    /// # Errors
+    /// Returns an [`AssetIdError`] if the name doesn't follow the game's naming conventions.
+    pub fn id(&self) -> Result<AssetId, AssetIdError> {
+        AssetId::from_bytes(&self.name)
+    }
+}
+
+impl RawAsset {
+    /// Returns the name of this asset as an [`AssetId`].
+    ///
+    /// # Errors
+    /// Returns an [`AssetIdError`] if the name doesn't follow the game's naming conventions.
+    pub fn id(&self) -> Result<AssetId, AssetIdError> {
+        self.name.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_names() {
+        let id: AssetId = "aid_texture_gribble_a".parse().unwrap();
+        assert_eq!(id.category(), "texture");
+        assert_eq!(id.to_string(), "aid_texture_gribble_a");
+        assert_eq!(AssetId::from_bytes(&id.to_bytes()), Ok(id.clone()));
+        assert_eq!(AssetId::from_parts("texture", "gribble_a"), Ok(id));
+
+        assert_eq!(AssetId::from_str("aid_list").unwrap().category(), "list");
+        assert_eq!(
+            AssetId::from_str("aid_cutscene_intro")
+                .unwrap()
+                .expected_type(),
+            Some(AssetType::ResCutscene)
+        );
+        assert_eq!(
+            AssetId::from_str("aid_soundbank_music")
+                .unwrap()
+                .expected_type(),
+            Some(AssetType::ResXSoundbank)
+        );
+        assert_eq!(AssetId::from_str("aid_list").unwrap().expected_type(), None);
+        assert_eq!(
+            AssetId::from_str("texture_gribble"),
+            Err(AssetIdError::MissingPrefix)
+        );
+        assert_eq!(
+            AssetId::from_str("aid__gribble"),
+            Err(AssetIdError::MissingCategory)
+        );
+        assert_eq!(
+            AssetId::from_str("aid_texture gribble"),
+            Err(AssetIdError::InvalidCharacter(' '))
+        );
+        assert_eq!(
+            AssetId::from_str(&format!("aid_{}", "a".repeat(124))),
+            Err(AssetIdError::TooLong(128))
+        );
+
+        let mut bytes = [0; AID_SIZE];
+        bytes[..8].copy_from_slice(b"aid_a\0bc");
+        assert_eq!(AssetId::from_bytes(&bytes), Err(AssetIdError::NotNulPadded));
+        assert_eq!(
+            AssetId::from_bytes(&[b'a'; AID_SIZE]),
+            Err(AssetIdError::NotNulPadded)
+        );
+    }
+}
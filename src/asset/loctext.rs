@@ -0,0 +1,731 @@
+use std::{
+    fmt::{self, Display},
+    io::{self, Cursor, Seek, SeekFrom},
+};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+
+use crate::{
+    VirtualResource,
+    asset::{
+        Asset, AssetDescriptor, AssetParseError, RawAsset, WritableAsset, records_that_fit,
+        write_fields,
+    },
+    game::AssetType,
+};
+
+/// The size of the fixed part of a [`LoctextDescriptor`].
+const LOCTEXT_DESCRIPTOR_SIZE: usize = 16;
+
+/// The size of a single language entry within the language table.
+const LANGUAGE_ENTRY_SIZE: usize = 12;
+
+/// The languages supported by the Xbox dashboard, using the same values as XGetLanguage().
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive, IntoPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Language {
+    English = 1,
+    Japanese = 2,
+    German = 3,
+    French = 4,
+    Spanish = 5,
+    Italian = 6,
+    Korean = 7,
+    TraditionalChinese = 8,
+    Portuguese = 9,
+}
+
+/// How the strings of a language are stored in the resource.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive, IntoPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextEncoding {
+    /// Single byte characters, NUL terminated.
+    Ascii = 0,
+    /// UTF-16LE code units, terminated by a NUL code unit.
+    Utf16 = 1,
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum LoctextError {
+    /// The table doesn't contain the requested language.
+    LanguageNotFound,
+    /// The table doesn't contain the requested string ID.
+    StringNotFound,
+    /// The text contains a character that can't be stored in the language's [`TextEncoding`].
+    UnencodableText(char),
+    /// Some languages or string IDs couldn't be read, so writing the table back would lose them.
+    /// See [`Loctext::language_errors`] and [`LoctextDescriptor::dropped_languages`].
+    IncompleteTable,
+}
+
+impl Display for LoctextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// One language of a [`LoctextDescriptor`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LanguageEntry {
+    language: u32,
+    encoding: TextEncoding,
+    strings_offset: u32,
+}
+
+impl LanguageEntry {
+    /// Returns the language of this entry, or None if the value isn't a known Xbox language.
+    pub fn language(&self) -> Option<Language> {
+        Language::try_from(self.language).ok()
+    }
+
+    pub fn raw_language(&self) -> u32 {
+        self.language
+    }
+
+    pub fn encoding(&self) -> TextEncoding {
+        self.encoding
+    }
+}
+
+/// Describes the string tables of a [`Loctext`] asset.
+///
+/// This layout hasn't been confirmed against game files. The language values follow
+/// XGetLanguage(), but whether the game stores them this way is also a guess. To cope with a
+/// mismatch, string IDs and languages that would run past the end of the descriptor are dropped,
+/// as are languages with an unknown encoding, and [`Loctext`] leaves out languages whose strings
+/// can't be read instead of rejecting the table. A table missing anything can still be read, but
+/// [`Loctext::to_bytes`] refuses to write it back.
+///
+/// Assumed layout (all little endian u32):
+/// - string count
+/// - language count
+/// - offset of the string ID table within the descriptor (one u32 ID per string)
+/// - offset of the language table within the descriptor, where each entry holds:
+///   - language (see [`Language`])
+///   - encoding (see [`TextEncoding`])
+///   - offset into the resource of that language's string offset table, which holds one u32
+///     resource offset per string
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LoctextDescriptor {
+    string_ids: Vec<u32>,
+    languages: Vec<LanguageEntry>,
+    dropped_string_ids: usize,
+    dropped_languages: usize,
+}
+
+impl LoctextDescriptor {
+    pub fn string_ids(&self) -> &[u32] {
+        &self.string_ids
+    }
+
+    /// Returns the number of string IDs left out for running past the end of the descriptor.
+    pub fn dropped_string_ids(&self) -> usize {
+        self.dropped_string_ids
+    }
+
+    /// Returns the number of languages left out for running past the end of the descriptor or
+    /// having an unknown encoding.
+    pub fn dropped_languages(&self) -> usize {
+        self.dropped_languages
+    }
+
+    pub fn languages(&self) -> &[LanguageEntry] {
+        &self.languages
+    }
+}
+
+impl Display for LoctextDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_fields(
+            f,
+            &[
+                ("string_count", self.string_ids.len().to_string()),
+                ("language_count", self.languages.len().to_string()),
+            ],
+        )?;
+
+        for entry in &self.languages {
+            let language = entry
+                .language()
+                .map_or_else(|| entry.language.to_string(), |l| format!("{:?}", l));
+
+            writeln!(
+                f,
+                "language {}: {:?}, table at {:#x}",
+                language, entry.encoding, entry.strings_offset
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl AssetDescriptor for LoctextDescriptor {
+    fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError> {
+        if data.len() < LOCTEXT_DESCRIPTOR_SIZE {
+            return Err(AssetParseError::InputTooSmall);
+        }
+
+        let mut cur = Cursor::new(data);
+
+        let string_count = cur.read_u32::<LittleEndian>()? as usize;
+        let language_count = cur.read_u32::<LittleEndian>()? as usize;
+        let string_ids_offset = cur.read_u32::<LittleEndian>()? as usize;
+        let languages_offset = cur.read_u32::<LittleEndian>()? as usize;
+
+        let listed_strings = string_count;
+        let listed_languages = language_count;
+
+        let string_count = records_that_fit(data.len(), string_count, string_ids_offset, 4);
+        let language_count = records_that_fit(
+            data.len(),
+            language_count,
+            languages_offset,
+            LANGUAGE_ENTRY_SIZE,
+        );
+
+        cur.seek(SeekFrom::Start(string_ids_offset as u64))?;
+
+        let string_ids = (0..string_count)
+            .map(|_| cur.read_u32::<LittleEndian>())
+            .collect::<Result<_, _>>()?;
+
+        cur.seek(SeekFrom::Start(languages_offset as u64))?;
+
+        let mut languages = Vec::with_capacity(language_count);
+
+        for _ in 0..language_count {
+            let language = cur.read_u32::<LittleEndian>()?;
+            let encoding = cur.read_u32::<LittleEndian>()?;
+            let strings_offset = cur.read_u32::<LittleEndian>()?;
+
+            if let Ok(encoding) = TextEncoding::try_from(encoding) {
+                languages.push(LanguageEntry {
+                    language,
+                    encoding,
+                    strings_offset,
+                });
+            }
+        }
+
+        Ok(LoctextDescriptor {
+            string_ids,
+            dropped_string_ids: listed_strings - string_count,
+            dropped_languages: listed_languages - languages.len(),
+            languages,
+        })
+    }
+}
+
+/// A table of localised strings, keyed by string ID, in one or more languages.
+#[derive(Debug)]
+pub struct Loctext {
+    name: String,
+    descriptor: LoctextDescriptor,
+    /// The decoded strings of each language, in the same order as the string IDs. Empty for a
+    /// language whose strings couldn't be read.
+    strings: Vec<Vec<String>>,
+    language_errors: Vec<(usize, AssetParseError)>,
+}
+
+impl Asset for Loctext {
+    type Descriptor = LoctextDescriptor;
+
+    fn new(
+        name: &str,
+        descriptor: &Self::Descriptor,
+        virtual_res: &VirtualResource,
+    ) -> Result<Self, AssetParseError> {
+        let resource = virtual_res.get_bytes(0, virtual_res.len()).map_err(|e| {
+            AssetParseError::InvalidDataViews(format!("Unable to read loctext resource: {}", e))
+        })?;
+
+        let mut strings = vec![];
+        let mut language_errors = vec![];
+
+        for (i, entry) in descriptor.languages.iter().enumerate() {
+            match read_strings(&resource, entry, descriptor.string_ids.len()) {
+                Ok(language_strings) => strings.push(language_strings),
+                Err(e) => {
+                    strings.push(vec![]);
+                    language_errors.push((i, e));
+                }
+            }
+        }
+
+        Ok(Loctext {
+            name: name.to_string(),
+            descriptor: descriptor.clone(),
+            strings,
+            language_errors,
+        })
+    }
+
+    fn descriptor(&self) -> &Self::Descriptor {
+        &self.descriptor
+    }
+
+    fn asset_type() -> AssetType {
+        AssetType::ResLoctext
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Reads every string of one language from the loctext resource.
+fn read_strings(
+    resource: &[u8],
+    entry: &LanguageEntry,
+    string_count: usize,
+) -> Result<Vec<String>, AssetParseError> {
+    let table_start = entry.strings_offset as usize;
+
+    let table = resource
+        .get(table_start..table_start + string_count * 4)
+        .ok_or_else(|| {
+            AssetParseError::InvalidDataViews(format!(
+                "String table at offset {} is out of bounds",
+                table_start
+            ))
+        })?;
+
+    table
+        .chunks_exact(4)
+        .map(|chunk| {
+            let offset = u32::from_le_bytes(chunk.try_into().unwrap()) as usize;
+
+            let bytes = resource.get(offset..).ok_or_else(|| {
+                AssetParseError::InvalidDataViews(format!(
+                    "String at offset {} is out of bounds",
+                    offset
+                ))
+            })?;
+
+            Ok(decode_string(bytes, entry.encoding))
+        })
+        .collect()
+}
+
+/// Encodes a string with a NUL terminator, the inverse of [`decode_string`].
+fn encode_string(text: &str, encoding: TextEncoding) -> Result<Vec<u8>, LoctextError> {
+    match encoding {
+        TextEncoding::Ascii => text
+            .chars()
+            .map(|c| u8::try_from(c).map_err(|_| LoctextError::UnencodableText(c)))
+            .chain(std::iter::once(Ok(0)))
+            .collect(),
+        TextEncoding::Utf16 => Ok(text
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .flat_map(u16::to_le_bytes)
+            .collect()),
+    }
+}
+
+/// Decodes a NUL terminated string. Strings missing a terminator run to the end of `bytes`.
+fn decode_string(bytes: &[u8], encoding: TextEncoding) -> String {
+    match encoding {
+        TextEncoding::Ascii => bytes
+            .iter()
+            .take_while(|byte| **byte != 0)
+            .map(|byte| *byte as char)
+            .collect(),
+        TextEncoding::Utf16 => {
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                .take_while(|unit| *unit != 0)
+                .collect();
+
+            String::from_utf16_lossy(&units)
+        }
+    }
+}
+
+impl Loctext {
+    /// Returns the languages available in this table.
+    pub fn languages(&self) -> &[LanguageEntry] {
+        &self.descriptor.languages
+    }
+
+    /// Returns the IDs of every string in this table.
+    pub fn string_ids(&self) -> &[u32] {
+        &self.descriptor.string_ids
+    }
+
+    /// Returns the index of each language whose strings couldn't be read, along with the reason.
+    /// Such a language has no strings, and stops the table from being written back.
+    pub fn language_errors(&self) -> &[(usize, AssetParseError)] {
+        &self.language_errors
+    }
+
+    /// Looks up a string by ID in the given language.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use bnl::asset::loctext::{Language, Loctext};
+    ///
+    /// # let bnl = bnl::BNLFile::new();
+    /// let loctext: Loctext = bnl.get_asset("aid_loctext_frontend").expect("Unable to get text.");
+    ///
+    /// if let Some(text) = loctext.get(Language::English, 0x1234) {
+    ///     println!("{}", text);
+    /// }
+    /// ```
+    pub fn get(&self, language: Language, id: u32) -> Option<&str> {
+        let language_index = self.language_index(language)?;
+        let string_index = self
+            .string_ids()
+            .iter()
+            .position(|string_id| *string_id == id)?;
+
+        self.strings[language_index]
+            .get(string_index)
+            .map(String::as_str)
+    }
+
+    /// Iterates over the (ID, string) pairs of one language, in table order. The iterator is
+    /// empty if this table doesn't contain the language.
+    pub fn iter(&self, language: Language) -> impl Iterator<Item = (u32, &str)> {
+        let strings = self
+            .language_index(language)
+            .map(|index| self.strings[index].as_slice())
+            .unwrap_or_default();
+
+        self.string_ids()
+            .iter()
+            .copied()
+            .zip(strings.iter().map(String::as_str))
+    }
+
+    /// Replaces the text of a string in the given language. The new text may be any length, as
+    /// the string tables are rebuilt by [`Loctext::to_bytes`].
+    ///
+    /// # Errors
+    /// - [`LoctextError::LanguageNotFound`] when the table doesn't contain the language
+    /// - [`LoctextError::StringNotFound`] when the table doesn't contain the string ID
+    /// - [`LoctextError::UnencodableText`] when the language uses single byte characters, and the
+    ///   text contains a character outside of that range
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use bnl::asset::loctext::{Language, Loctext};
+    ///
+    /// # let mut bnl = bnl::BNLFile::new();
+    /// let mut loctext: Loctext = bnl.get_asset("aid_loctext_frontend").expect("Unable to get text.");
+    /// loctext.set_string(0x1234, Language::English, "New Game+").expect("Unable to set text.");
+    ///
+    /// let raw_asset = loctext.to_raw_asset().expect("Unable to serialise text.");
+    /// bnl.update_asset("aid_loctext_frontend", &raw_asset)
+    ///     .expect("Unable to update text.");
+    /// ```
+    pub fn set_string(
+        &mut self,
+        id: u32,
+        language: Language,
+        text: &str,
+    ) -> Result<(), LoctextError> {
+        let language_index = self
+            .language_index(language)
+            .ok_or(LoctextError::LanguageNotFound)?;
+
+        let string_index = self
+            .string_ids()
+            .iter()
+            .position(|string_id| *string_id == id)
+            .ok_or(LoctextError::StringNotFound)?;
+
+        // Check the text can be written back out before accepting it
+        encode_string(text, self.descriptor.languages[language_index].encoding)?;
+
+        let string = self.strings[language_index]
+            .get_mut(string_index)
+            .ok_or(LoctextError::StringNotFound)?;
+        *string = text.to_string();
+
+        Ok(())
+    }
+
+    /// Serialises this table into new descriptor and resource bytes, in that order.
+    ///
+    /// The layout is rebuilt from scratch: the string IDs and language table follow the
+    /// descriptor header, and each language's string offset table is followed by its strings in
+    /// the resource. UTF-16 strings are aligned to 2 bytes.
+    ///
+    /// # Errors
+    /// Returns [`LoctextError::IncompleteTable`] if any language or string ID couldn't be read,
+    /// as rebuilding the table would lose it.
+    pub fn to_bytes(&self) -> Result<(Vec<u8>, Vec<u8>), LoctextError> {
+        if !self.language_errors.is_empty()
+            || self.descriptor.dropped_languages > 0
+            || self.descriptor.dropped_string_ids > 0
+        {
+            return Err(LoctextError::IncompleteTable);
+        }
+
+        let string_count = self.string_ids().len();
+        let language_count = self.descriptor.languages.len();
+
+        let string_ids_offset = LOCTEXT_DESCRIPTOR_SIZE;
+        let languages_offset = string_ids_offset + string_count * 4;
+
+        let mut descriptor = Vec::with_capacity(languages_offset + language_count * 12);
+        let mut resource = Vec::new();
+
+        for value in [
+            string_count,
+            language_count,
+            string_ids_offset,
+            languages_offset,
+        ] {
+            descriptor.extend_from_slice(&(value as u32).to_le_bytes());
+        }
+
+        for id in self.string_ids() {
+            descriptor.extend_from_slice(&id.to_le_bytes());
+        }
+
+        for (entry, strings) in self.descriptor.languages.iter().zip(&self.strings) {
+            let table_offset = resource.len();
+
+            for value in [entry.language, entry.encoding.into(), table_offset as u32] {
+                descriptor.extend_from_slice(&value.to_le_bytes());
+            }
+
+            resource.resize(table_offset + string_count * 4, 0);
+
+            for (i, text) in strings.iter().enumerate() {
+                if entry.encoding == TextEncoding::Utf16 && resource.len() % 2 != 0 {
+                    resource.push(0);
+                }
+
+                let offset = resource.len() as u32;
+                resource[table_offset + i * 4..table_offset + i * 4 + 4]
+                    .copy_from_slice(&offset.to_le_bytes());
+
+                // Strings were checked when they were set, and were decoded from this encoding
+                resource.extend(encode_string(text, entry.encoding)?);
+            }
+        }
+
+        Ok((descriptor, resource))
+    }
+
+    /// Serialises this table into a [`RawAsset`] with a single resource slice, ready to be
+    /// passed to [`crate::BNLFile::update_asset`].
+    ///
+    /// # Errors
+    /// See [`Loctext::to_bytes`].
+    pub fn to_raw_asset(&self) -> Result<RawAsset, LoctextError> {
+        let (descriptor_bytes, resource) = self.to_bytes()?;
+
+        Ok(RawAsset {
+            name: self.name.clone(),
+            asset_type: AssetType::ResLoctext,
+            descriptor_bytes,
+            data_slices: vec![resource],
+        })
+    }
+
+    fn language_index(&self, language: Language) -> Option<usize> {
+        self.descriptor
+            .languages
+            .iter()
+            .position(|entry| entry.language() == Some(language))
+    }
+}
+
+impl WritableAsset for Loctext {
+    /// Rebuilds the descriptor and string tables, replacing the resource with a single slice.
+    /// Fails without touching `raw_asset` if the table is incomplete, see [`Loctext::to_bytes`].
+    fn write_back(&self, raw_asset: &mut RawAsset) -> Result<(), io::Error> {
+        let (descriptor_bytes, resource) = self.to_bytes().map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unable to rebuild loctext table.\nError: {}", e),
+            )
+        })?;
+
+        raw_asset.descriptor_bytes = descriptor_bytes;
+        raw_asset.data_slices = vec![resource];
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_u32s(bytes: &mut Vec<u8>, values: &[u32]) {
+        values
+            .iter()
+            .for_each(|value| bytes.extend_from_slice(&value.to_le_bytes()));
+    }
+
+    #[test]
+    fn looks_up_strings() {
+        let mut descriptor = vec![];
+        push_u32s(&mut descriptor, &[2, 2, 16, 24]);
+        push_u32s(&mut descriptor, &[0x100, 0x200]);
+        push_u32s(&mut descriptor, &[1, 0, 0, 3, 1, 8]);
+
+        let mut resource = vec![];
+        push_u32s(&mut resource, &[16, 20, 24, 36]);
+        resource.extend_from_slice(b"Hi\0\0Bye\0");
+        resource.extend("Hallo\0Tschüss\0".encode_utf16().flat_map(u16::to_le_bytes));
+
+        let descriptor = LoctextDescriptor::from_bytes(&descriptor).unwrap();
+
+        let slices = [resource.as_slice()];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let loctext = Loctext::new("aid_loctext_test", &descriptor, &virtual_res).unwrap();
+
+        assert_eq!(loctext.get(Language::English, 0x200), Some("Bye"));
+        assert_eq!(loctext.get(Language::German, 0x100), Some("Hallo"));
+        assert_eq!(loctext.get(Language::German, 0x200), Some("Tschüss"));
+        assert_eq!(loctext.get(Language::French, 0x100), None);
+        assert_eq!(
+            loctext.iter(Language::English).collect::<Vec<_>>(),
+            vec![(0x100, "Hi"), (0x200, "Bye")]
+        );
+    }
+
+    #[test]
+    fn edits_round_trip() {
+        let mut descriptor = vec![];
+        push_u32s(&mut descriptor, &[1, 2, 16, 20]);
+        push_u32s(&mut descriptor, &[0x100]);
+        push_u32s(&mut descriptor, &[1, 0, 0, 3, 1, 4]);
+
+        let mut resource = vec![];
+        push_u32s(&mut resource, &[8, 11]);
+        resource.extend_from_slice(b"Hi\0");
+        resource.extend("Hallo\0".encode_utf16().flat_map(u16::to_le_bytes));
+
+        let descriptor = LoctextDescriptor::from_bytes(&descriptor).unwrap();
+
+        let slices = [resource.as_slice()];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let mut loctext = Loctext::new("aid_loctext_test", &descriptor, &virtual_res).unwrap();
+
+        loctext
+            .set_string(0x100, Language::English, "Hello there")
+            .unwrap();
+        loctext
+            .set_string(0x100, Language::German, "Grüß dich")
+            .unwrap();
+
+        assert!(matches!(
+            loctext.set_string(0x100, Language::English, "日本"),
+            Err(LoctextError::UnencodableText('日'))
+        ));
+        assert!(matches!(
+            loctext.set_string(0x200, Language::English, ""),
+            Err(LoctextError::StringNotFound)
+        ));
+
+        let (descriptor_bytes, resource) = loctext.to_bytes().unwrap();
+        let descriptor = LoctextDescriptor::from_bytes(&descriptor_bytes).unwrap();
+
+        let slices = [resource.as_slice()];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let loctext = Loctext::new("aid_loctext_test", &descriptor, &virtual_res).unwrap();
+
+        assert_eq!(loctext.get(Language::English, 0x100), Some("Hello there"));
+        assert_eq!(loctext.get(Language::German, 0x100), Some("Grüß dich"));
+    }
+
+    #[test]
+    fn keeps_readable_languages() {
+        let mut descriptor = vec![];
+        // Three languages listed but only two fit, and the second has its table out of range
+        push_u32s(&mut descriptor, &[1, 3, 16, 20]);
+        push_u32s(&mut descriptor, &[0x100]);
+        push_u32s(&mut descriptor, &[1, 0, 0, 3, 1, 0x100]);
+
+        let mut resource = vec![];
+        push_u32s(&mut resource, &[4]);
+        resource.extend_from_slice(b"Hi\0");
+
+        let descriptor = LoctextDescriptor::from_bytes(&descriptor).unwrap();
+        assert_eq!(descriptor.languages().len(), 2);
+
+        let slices = [resource.as_slice()];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let mut loctext = Loctext::new("aid_loctext_test", &descriptor, &virtual_res).unwrap();
+
+        assert_eq!(loctext.get(Language::English, 0x100), Some("Hi"));
+        assert_eq!(loctext.get(Language::German, 0x100), None);
+        assert!(matches!(
+            loctext.language_errors(),
+            [(1, AssetParseError::InvalidDataViews(_))]
+        ));
+        assert!(matches!(
+            loctext.set_string(0x100, Language::German, "Hallo"),
+            Err(LoctextError::StringNotFound)
+        ));
+
+        assert_eq!(descriptor.dropped_languages(), 1);
+        assert!(matches!(
+            loctext.to_bytes(),
+            Err(LoctextError::IncompleteTable)
+        ));
+
+        // Languages with an unknown encoding are dropped
+        let mut bytes = vec![];
+        push_u32s(&mut bytes, &[1, 2, 16, 20]);
+        push_u32s(&mut bytes, &[0x100]);
+        push_u32s(&mut bytes, &[1, 0, 0, 3, 7, 0]);
+
+        let descriptor = LoctextDescriptor::from_bytes(&bytes).unwrap();
+        assert_eq!(descriptor.languages().len(), 1);
+        assert_eq!(descriptor.dropped_languages(), 1);
+    }
+
+    #[test]
+    fn refuses_to_write_back_unreadable_language() {
+        let mut descriptor = vec![];
+        // German's string table is out of range of the resource
+        push_u32s(&mut descriptor, &[1, 2, 16, 20]);
+        push_u32s(&mut descriptor, &[0x100]);
+        push_u32s(&mut descriptor, &[1, 0, 0, 3, 1, 0x100]);
+
+        let mut resource = vec![];
+        push_u32s(&mut resource, &[4]);
+        resource.extend_from_slice(b"Hi\0");
+
+        let mut raw_asset = RawAsset {
+            name: "aid_loctext_test".to_string(),
+            asset_type: AssetType::ResLoctext,
+            descriptor_bytes: descriptor.clone(),
+            data_slices: vec![resource.clone()],
+        };
+
+        let mut loctext = raw_asset.parse::<Loctext>().unwrap();
+        assert_eq!(loctext.language_errors().len(), 1);
+
+        loctext
+            .set_string(0x100, Language::English, "Hello")
+            .unwrap();
+
+        // Writing back would replace every German string with nothing, so it fails instead and
+        // leaves the raw asset as it was
+        assert!(loctext.write_back(&mut raw_asset).is_err());
+        assert!(loctext.to_raw_asset().is_err());
+        assert_eq!(raw_asset.descriptor_bytes, descriptor);
+        assert_eq!(raw_asset.data_slices, [resource]);
+    }
+}
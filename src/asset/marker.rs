@@ -0,0 +1,284 @@
+use std::{
+    fmt::{self, Display},
+    io::{self, Cursor, Read, Seek, SeekFrom},
+};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use crate::{
+    VirtualResource,
+    asset::{
+        Asset, AssetDescriptor, AssetParseError, DescriptorTemplate, aid_from_bytes,
+        records_that_fit, write_fields,
+    },
+    game::AssetType,
+};
+
+/// The size of the fixed part of a [`MarkerDescriptor`].
+pub(crate) const MARKER_DESCRIPTOR_SIZE: usize = 8;
+
+/// The number of bytes reserved for the NUL padded name of a [`MarkerPoint`].
+const MARKER_NAME_SIZE: usize = 32;
+
+/// The size of a single [`MarkerPoint`].
+pub(crate) const MARKER_POINT_SIZE: usize = MARKER_NAME_SIZE + 28;
+
+/// A named placement within a level.
+///
+/// Assumed layout, see [`MarkerDescriptor`]:
+/// - name, NUL padded to 32 bytes
+/// - position (3 x f32)
+/// - rotation in radians around each axis (3 x f32)
+/// - marker type (u32)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MarkerPoint {
+    pub name: String,
+    pub position: [f32; 3],
+    pub rotation: [f32; 3],
+    pub marker_type: u32,
+}
+
+impl MarkerPoint {
+    fn from_cursor(cur: &mut Cursor<&[u8]>) -> Result<Self, io::Error> {
+        let mut name = [0u8; MARKER_NAME_SIZE];
+        cur.read_exact(&mut name)?;
+
+        let read_vec3 = |cur: &mut Cursor<&[u8]>| -> Result<[f32; 3], io::Error> {
+            Ok([
+                cur.read_f32::<LittleEndian>()?,
+                cur.read_f32::<LittleEndian>()?,
+                cur.read_f32::<LittleEndian>()?,
+            ])
+        };
+
+        Ok(MarkerPoint {
+            name: aid_from_bytes(&name),
+            position: read_vec3(cur)?,
+            rotation: read_vec3(cur)?,
+            marker_type: cur.read_u32::<LittleEndian>()?,
+        })
+    }
+}
+
+/// A set of placements within a level.
+///
+/// The layouts of this descriptor and of [`MarkerPoint`] are unconfirmed and haven't been checked
+/// against game files; the point fields in particular are guesses. Points that would run past
+/// the end of the descriptor are dropped rather than failing the asset.
+///
+/// Assumed layout:
+/// - point count (u32)
+/// - offset of the points within the descriptor (u32), stored back to back
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MarkerDescriptor {
+    points: Vec<MarkerPoint>,
+}
+
+impl AssetDescriptor for MarkerDescriptor {
+    fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError> {
+        if data.len() < MARKER_DESCRIPTOR_SIZE {
+            return Err(AssetParseError::InputTooSmall);
+        }
+
+        let mut cur = Cursor::new(data);
+
+        let point_count = cur.read_u32::<LittleEndian>()? as usize;
+        let points_offset = cur.read_u32::<LittleEndian>()? as usize;
+
+        let point_count =
+            records_that_fit(data.len(), point_count, points_offset, MARKER_POINT_SIZE);
+
+        cur.seek(SeekFrom::Start(points_offset as u64))?;
+
+        let points = (0..point_count)
+            .map(|_| MarkerPoint::from_cursor(&mut cur))
+            .collect::<Result<_, _>>()?;
+
+        Ok(MarkerDescriptor { points })
+    }
+}
+
+impl DescriptorTemplate for MarkerDescriptor {
+    /// A single point named `marker` at the origin, with no rotation and type 0.
+    fn template() -> Self {
+        MarkerDescriptor {
+            points: vec![MarkerPoint {
+                name: "marker".to_string(),
+                position: [0.0; 3],
+                rotation: [0.0; 3],
+                marker_type: 0,
+            }],
+        }
+    }
+}
+
+impl MarkerDescriptor {
+    pub fn points(&self) -> &[MarkerPoint] {
+        &self.points
+    }
+
+    /// Returns the points for editing. Points may be added or removed, as
+    /// [`MarkerDescriptor::to_bytes`] rebuilds the whole descriptor.
+    pub fn points_mut(&mut self) -> &mut Vec<MarkerPoint> {
+        &mut self.points
+    }
+
+    /// Serialises this descriptor, with the points directly after the header.
+    ///
+    /// # Errors
+    /// Returns an error if a point name doesn't fit in its 32 byte field alongside its NUL
+    /// terminator.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, io::Error> {
+        let mut bytes =
+            Vec::with_capacity(MARKER_DESCRIPTOR_SIZE + self.points.len() * MARKER_POINT_SIZE);
+
+        bytes.extend_from_slice(&(self.points.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(MARKER_DESCRIPTOR_SIZE as u32).to_le_bytes());
+
+        for point in &self.points {
+            if point.name.len() >= MARKER_NAME_SIZE {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Marker point name {} is longer than {} bytes",
+                        point.name,
+                        MARKER_NAME_SIZE - 1
+                    ),
+                ));
+            }
+
+            let mut name = [0u8; MARKER_NAME_SIZE];
+            name[..point.name.len()].copy_from_slice(point.name.as_bytes());
+            bytes.extend_from_slice(&name);
+
+            for value in point.position.iter().chain(point.rotation.iter()) {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+
+            bytes.extend_from_slice(&point.marker_type.to_le_bytes());
+        }
+
+        Ok(bytes)
+    }
+}
+
+impl Display for MarkerDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_fields(f, &[("point_count", self.points.len().to_string())])?;
+
+        for point in &self.points {
+            writeln!(
+                f,
+                "{} (type {}): position {:?}, rotation {:?}",
+                point.name, point.marker_type, point.position, point.rotation
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Level layout data, holding named points such as spawn locations and camera positions.
+#[derive(Debug)]
+pub struct Marker {
+    name: String,
+    descriptor: MarkerDescriptor,
+}
+
+impl Asset for Marker {
+    type Descriptor = MarkerDescriptor;
+
+    fn new(
+        name: &str,
+        descriptor: &Self::Descriptor,
+        _virtual_res: &VirtualResource,
+    ) -> Result<Self, AssetParseError> {
+        Ok(Marker {
+            name: name.to_string(),
+            descriptor: descriptor.clone(),
+        })
+    }
+
+    fn descriptor(&self) -> &Self::Descriptor {
+        &self.descriptor
+    }
+
+    fn asset_type() -> AssetType {
+        AssetType::ResMarker
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Marker {
+    pub fn points(&self) -> &[MarkerPoint] {
+        self.descriptor.points()
+    }
+
+    /// Returns the first point with the given name, if there is one.
+    pub fn find_point(&self, name: &str) -> Option<&MarkerPoint> {
+        self.descriptor
+            .points
+            .iter()
+            .find(|point| point.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_points() {
+        let mut data = vec![];
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&8u32.to_le_bytes());
+
+        let mut name = [0u8; MARKER_NAME_SIZE];
+        name[..12].copy_from_slice(b"player_start");
+        data.extend_from_slice(&name);
+
+        for value in [1.0f32, 2.0, 3.0, 0.0, 1.5, 0.0] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+
+        data.extend_from_slice(&4u32.to_le_bytes());
+
+        let descriptor = MarkerDescriptor::from_bytes(&data).unwrap();
+        let slices: [&[u8]; 0] = [];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let marker = Marker::new("aid_marker_test", &descriptor, &virtual_res).unwrap();
+
+        let point = marker.find_point("player_start").unwrap();
+        assert_eq!(point.position, [1.0, 2.0, 3.0]);
+        assert_eq!(point.rotation[1], 1.5);
+        assert_eq!(point.marker_type, 4);
+        assert_eq!(marker.points().len(), 1);
+
+        // A point cut short by the end of the descriptor is dropped
+        assert!(
+            MarkerDescriptor::from_bytes(&data[..60])
+                .unwrap()
+                .points()
+                .is_empty()
+        );
+        assert!(MarkerDescriptor::from_bytes(&data[..MARKER_DESCRIPTOR_SIZE - 1]).is_err());
+        assert_eq!(marker.descriptor().to_bytes().unwrap(), data);
+    }
+
+    #[test]
+    fn template_round_trips() {
+        let mut template = MarkerDescriptor::template();
+        let descriptor = MarkerDescriptor::from_bytes(&template.to_bytes().unwrap()).unwrap();
+
+        assert_eq!(descriptor.points(), template.points());
+
+        template.points_mut()[0].name = "a".repeat(MARKER_NAME_SIZE);
+        assert!(template.to_bytes().is_err());
+    }
+}
@@ -5,10 +5,26 @@ use std::{
 
 use crate::{DataView, VirtualResource, game::AssetType};
 
+pub mod actor_attribs;
+pub mod aid_list;
+pub mod anim;
+pub mod events;
+pub mod export;
+pub mod feedback;
+pub mod font;
+pub mod ghoulybox;
+pub mod handle;
+pub mod id;
+pub mod loctext;
+pub mod marker;
 pub mod model;
+pub mod particle;
+pub mod registry;
+pub mod soundbank;
 pub mod texture;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RawAsset {
     pub name: String,
     pub asset_type: AssetType,
@@ -16,14 +32,87 @@ pub struct RawAsset {
     pub data_slices: Vec<Vec<u8>>,
 }
 
+/// A borrowed version of a [`RawAsset`], whose descriptor and data slices point directly into the
+/// [`crate::BNLFile`] they were read from instead of being copied out.
 #[derive(Debug, Clone)]
+pub struct RawAssetRef<'a> {
+    pub name: &'a str,
+    pub asset_type: AssetType,
+    pub descriptor_bytes: &'a [u8],
+    pub data_slices: Vec<&'a [u8]>,
+    /// The data view list the data slices were read through.
+    pub dataview_list: DataViewList,
+}
+
+impl RawAsset {
+    /// Parses this raw asset as an `A`, as [`crate::BNLFile::get_asset`] would.
+    ///
+    /// # Errors
+    /// Returns [`AssetError::TypeMismatch`] if this isn't an asset of type `A`, and
+    /// [`AssetError::ParseError`] if it couldn't be parsed.
+    pub fn parse<A: Asset>(&self) -> Result<A, AssetError> {
+        if self.asset_type != A::asset_type() {
+            return Err(AssetError::TypeMismatch {
+                name: self.name.clone(),
+                expected: A::asset_type(),
+                found: self.asset_type,
+            });
+        }
+
+        let descriptor = A::Descriptor::from_bytes(&self.descriptor_bytes)
+            .map_err(|e| e.in_asset(&self.name))?;
+
+        let virtual_res =
+            VirtualResource::new(self.data_slices.iter().map(Vec::as_slice).collect());
+
+        A::new(&self.name, &descriptor, &virtual_res).map_err(|e| e.in_asset(&self.name))
+    }
+}
+
+impl RawAssetRef<'_> {
+    /// Copies the borrowed data out into an owned [`RawAsset`].
+    pub fn to_raw_asset(&self) -> RawAsset {
+        RawAsset {
+            name: self.name.to_string(),
+            asset_type: self.asset_type,
+            descriptor_bytes: self.descriptor_bytes.to_vec(),
+            data_slices: self.data_slices.iter().map(|s| s.to_vec()).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DataViewList {
     size: u32,
     num_views: u32,
     views: Vec<DataView>,
 }
 
+/// The size of the header of a [`DataViewList`], holding its size and view count.
+const DATAVIEW_LIST_HEADER_SIZE: u32 = 8;
+
+impl Default for DataViewList {
+    /// Returns a list without any views. The game never stores an empty list, so at least one
+    /// view should be pushed before it is written.
+    fn default() -> Self {
+        DataViewList {
+            size: DATAVIEW_LIST_HEADER_SIZE,
+            num_views: 0,
+            views: Vec::new(),
+        }
+    }
+}
+
 impl DataViewList {
+    /// Creates a list holding `views`, with its size and view count to match.
+    pub fn new(views: Vec<DataView>) -> DataViewList {
+        let mut dvl = DataViewList::default();
+        views.into_iter().for_each(|view| dvl.push_view(view));
+
+        dvl
+    }
+
     pub fn from_bytes(view_bytes: &[u8]) -> Result<DataViewList, Box<io::Error>> {
         if view_bytes.len() < 8 {
             return Err(Box::new(io::Error::new(
@@ -42,31 +131,24 @@ impl DataViewList {
             .expect("slice with incorrect length");
         let num_views = u32::from_le_bytes(b);
 
-        if num_views == 0 || size != num_views * size_of::<DataView>() as u32 + 8 {
-            return Err(Box::new(io::Error::other("Invalid size.")));
-        }
+        let expected_size =
+            (num_views as u64) * size_of::<DataView>() as u64 + DATAVIEW_LIST_HEADER_SIZE as u64;
 
-        if view_bytes.len() < num_views as usize * size_of::<DataView>() {
-            return Err(
-                io::Error::new(io::ErrorKind::InvalidData, "Input is not large enough.").into(),
-            );
+        if num_views == 0 || size as u64 != expected_size {
+            return Err(Box::new(io::Error::other("Invalid size.")));
         }
 
-        let mut views = Vec::with_capacity(num_views as usize);
-
-        let mut chunks = view_bytes[8..].chunks(size_of::<DataView>());
+        let views_bytes = view_bytes.get(8..size as usize).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "Input is not large enough.")
+        })?;
 
-        for _ in 0..num_views {
-            let chunk = chunks.next().unwrap();
-
-            let view_offset = u32::from_le_bytes(chunk[0..4].try_into().unwrap());
-            let view_size = u32::from_le_bytes(chunk[4..8].try_into().unwrap());
-
-            views.push(DataView {
-                offset: view_offset,
-                size: view_size,
-            });
-        }
+        let views = views_bytes
+            .chunks_exact(size_of::<DataView>())
+            .map(|chunk| DataView {
+                offset: u32::from_le_bytes(chunk[0..4].try_into().unwrap()),
+                size: u32::from_le_bytes(chunk[4..8].try_into().unwrap()),
+            })
+            .collect();
 
         Ok(DataViewList {
             size,
@@ -87,15 +169,186 @@ impl DataViewList {
             ));
         }
 
-        Ok(self
-            .views
+        self.views
             .iter()
-            .map(|view| -> &[u8] {
+            .map(|view| {
                 let start = view.offset as usize;
-                let end = start + view.size as usize;
-                &data[start..end]
+
+                data.get(start..start + view.size as usize).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "Data view at {:#x} ({} bytes) is out of bounds of the buffer",
+                            view.offset, view.size
+                        ),
+                    )
+                })
             })
-            .collect())
+            .collect()
+    }
+
+    /// Writes `bytes` across the views of this list, in order, into `data`.
+    ///
+    /// The total size of `bytes` must exactly match the combined size of the views, as the views
+    /// themselves are never resized.
+    pub fn write_bytes(&self, data: &mut [u8], bytes: &[u8]) -> Result<(), io::Error> {
+        let total = self.total_size() as usize;
+
+        if total != bytes.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Expected {} bytes to write across the data views, but received {}",
+                    total,
+                    bytes.len()
+                ),
+            ));
+        }
+
+        self.write_bytes_at(data, 0, bytes)
+    }
+
+    /// Writes `bytes` into `data` at `offset` within the resource described by this list, as if
+    /// its views were one contiguous buffer. Only the views covering the range are touched, so
+    /// part of a resource (such as a single mip level) can be patched in place.
+    ///
+    /// # Errors
+    /// Returns an error if the range ends past the end of the resource, or a view it covers is out
+    /// of bounds of `data`. Nothing is written in either case.
+    pub fn write_bytes_at(
+        &self,
+        data: &mut [u8],
+        offset: usize,
+        bytes: &[u8],
+    ) -> Result<(), io::Error> {
+        let total = self.total_size() as usize;
+
+        if offset > total || total - offset < bytes.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Unable to write {} bytes at {:#x} into a resource of {} bytes",
+                    bytes.len(),
+                    offset,
+                    total
+                ),
+            ));
+        }
+
+        // The part of each view the range covers, as (view, start within the view, length)
+        let mut writes = Vec::new();
+        let mut view_start = 0usize;
+
+        for view in &self.views {
+            let view_end = view_start + view.size as usize;
+            let start = offset.max(view_start);
+            let end = (offset + bytes.len()).min(view_end);
+
+            if start < end {
+                writes.push((view, start - view_start, end - start));
+            }
+
+            view_start = view_end;
+        }
+
+        // Check every view before writing any, so a failure leaves `data` unchanged
+        if writes
+            .iter()
+            .any(|(view, _, _)| view.offset as usize + view.size as usize > data.len())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Data view is out of bounds of the destination.",
+            ));
+        }
+
+        let mut written = 0usize;
+
+        for (view, start, len) in writes {
+            let start = view.offset as usize + start;
+
+            data[start..start + len].copy_from_slice(&bytes[written..written + len]);
+            written += len;
+        }
+
+        Ok(())
+    }
+
+    /// Serialises this list into the layout read by [`DataViewList::from_bytes`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.size as usize);
+
+        bytes.extend_from_slice(&self.size.to_le_bytes());
+        bytes.extend_from_slice(&self.num_views.to_le_bytes());
+
+        for view in &self.views {
+            bytes.extend_from_slice(&view.offset().to_le_bytes());
+            bytes.extend_from_slice(&view.size().to_le_bytes());
+        }
+
+        bytes
+    }
+
+    /// Adds a view to the end of this list, growing its size and view count.
+    pub fn push_view(&mut self, view: DataView) {
+        self.views.push(view);
+        self.num_views += 1;
+        self.size += size_of::<DataView>() as u32;
+    }
+
+    /// Replaces the view at `index`, such as after its data has been moved or resized.
+    ///
+    /// # Errors
+    /// Returns an error if there is no view at `index`.
+    pub fn set_view(&mut self, index: usize, view: DataView) -> Result<(), io::Error> {
+        let len = self.views.len();
+
+        let slot = self.views.get_mut(index).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("There is no data view {} in a list of {}", index, len),
+            )
+        })?;
+
+        *slot = view;
+
+        Ok(())
+    }
+
+    /// Checks that the size and view count of this list agree with the views it holds, as
+    /// [`DataViewList::from_bytes`] requires.
+    ///
+    /// # Errors
+    /// Returns an error describing the first inconsistency found.
+    pub fn validate(&self) -> Result<(), io::Error> {
+        let expected_size = DATAVIEW_LIST_HEADER_SIZE as u64
+            + self.views.len() as u64 * size_of::<DataView>() as u64;
+
+        if self.views.is_empty() {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "The data view list has no views",
+            ))
+        } else if self.num_views as usize != self.views.len() {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "The data view list counts {} views, but holds {}",
+                    self.num_views,
+                    self.views.len()
+                ),
+            ))
+        } else if self.size as u64 != expected_size {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "The data view list has a size of {} bytes, but should be {}",
+                    self.size, expected_size
+                ),
+            ))
+        } else {
+            Ok(())
+        }
     }
 
     pub fn views(&self) -> &[DataView] {
@@ -109,59 +362,184 @@ impl DataViewList {
     pub fn size(&self) -> u32 {
         self.size
     }
+
+    /// Returns the combined size of every view in this list, saturating at [`u32::MAX`].
+    pub fn total_size(&self) -> u32 {
+        self.views
+            .iter()
+            .fold(0u32, |total, view| total.saturating_add(view.size))
+    }
 }
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum AssetParseError {
     /// The parser of a given type was not implemented, and the asset was not about to be parsed.
     // TODO: Remove this and just make it required by the trait
     ParserNotImplemented,
     /// An error occurred when parsing the [`Asset::Descriptor`] of the asset.
     ErrorParsingDescriptor,
+    /// The descriptor or resource data is smaller than its layout requires.
     InputTooSmall,
+    /// The data views of the asset don't hold the data its descriptor describes.
     InvalidDataViews(String),
+    /// The descriptor of the asset lies outside of the descriptor section.
+    DescriptorOutOfBounds { offset: u32, size: u32 },
+    /// An I/O error occurred while reading the descriptor or resource data.
+    Io(io::Error),
 }
 
 impl Display for AssetParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            AssetParseError::ParserNotImplemented => write!(f, "No parser is implemented"),
+            AssetParseError::ErrorParsingDescriptor => write!(f, "Unable to parse the descriptor"),
+            AssetParseError::InputTooSmall => write!(f, "The input is too small"),
+            AssetParseError::InvalidDataViews(message) => write!(f, "{}", message),
+            AssetParseError::DescriptorOutOfBounds { offset, size } => write!(
+                f,
+                "The descriptor at {:#x} ({} bytes) is out of bounds of the descriptor section",
+                offset, size
+            ),
+            AssetParseError::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AssetParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AssetParseError::Io(e) => Some(e),
+            _ => None,
+        }
     }
 }
 
 impl From<std::io::Error> for AssetParseError {
     fn from(value: std::io::Error) -> Self {
-        AssetParseError::InvalidDataViews("IO error occurred when parsing Asset.".to_string())
+        AssetParseError::Io(value)
     }
 }
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum AssetError {
     /// The asset was found, but could not be parsed from the bytes of the [`crate::BNLFile`].
-    ParseError(AssetParseError),
+    ParseError {
+        name: String,
+        source: AssetParseError,
+    },
+    /// The data view list of the asset, at `offset` within the data view section, could not be
+    /// read.
+    InvalidDataViewList {
+        name: String,
+        offset: u32,
+        source: io::Error,
+    },
     /// The asset was found, but didn't match the expected [`AssetType`]
-    TypeMismatch,
+    TypeMismatch {
+        name: String,
+        expected: AssetType,
+        found: AssetType,
+    },
     /// The asset could not be found by name
     NotFound,
+    /// The data given to replace an asset didn't match the size of the existing asset
+    SizeMismatch,
+    /// An asset with the given name already exists
+    AlreadyExists,
+    /// The given name can't be used as an asset name (AID), see [`id::AssetId`]
+    InvalidName,
+    /// The [`crate::BNLFile`] can't hold any more assets
+    BundleFull,
+    /// The asset could not be serialised back into the [`crate::BNLFile`].
+    WriteError { name: String, source: io::Error },
 }
 
 impl fmt::Display for AssetError {
-    // This trait requires `fmt` with this exact signature.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        // Write strictly the first element into the supplied output
-        // stream: `f`. Returns `fmt::Result` which indicates whether the
-        // operation succeeded or failed. Note that `write!` uses syntax which
-        // is very similar to `println!`.
-        write!(f, "Asset error")?;
-        Ok(())
+        match self {
+            AssetError::ParseError { name, source } => {
+                write!(f, "Unable to parse asset {}: {}", name, source)
+            }
+            AssetError::InvalidDataViewList {
+                name,
+                offset,
+                source,
+            } => write!(
+                f,
+                "Unable to read the data view list of asset {} at offset {:#x}: {}",
+                name, offset, source
+            ),
+            AssetError::TypeMismatch {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Asset {} is a {:?}, but a {:?} was expected",
+                name, found, expected
+            ),
+            AssetError::NotFound => write!(f, "Asset not found"),
+            AssetError::SizeMismatch => {
+                write!(
+                    f,
+                    "The new data doesn't match the size of the existing asset"
+                )
+            }
+            AssetError::AlreadyExists => write!(f, "An asset with that name already exists"),
+            AssetError::InvalidName => write!(f, "Invalid asset name"),
+            AssetError::BundleFull => write!(f, "The bundle can't hold any more assets"),
+            AssetError::WriteError { name, source } => {
+                write!(f, "Unable to write asset {}: {}", name, source)
+            }
+        }
     }
 }
 
-impl From<AssetParseError> for AssetError {
-    fn from(err: AssetParseError) -> Self {
-        AssetError::ParseError(err)
+impl std::error::Error for AssetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AssetError::ParseError { source, .. } => Some(source),
+            AssetError::InvalidDataViewList { source, .. } => Some(source),
+            AssetError::WriteError { source, .. } => Some(source),
+            _ => None,
+        }
     }
 }
 
+impl AssetParseError {
+    /// Adds the name of the asset being parsed to this error.
+    pub(crate) fn in_asset(self, name: &str) -> AssetError {
+        AssetError::ParseError {
+            name: name.to_string(),
+            source: self,
+        }
+    }
+}
+
+/// Writes one `name: value` line per field with the values aligned, for the [`Display`]
+/// implementations of descriptors.
+pub(crate) fn write_fields(f: &mut fmt::Formatter<'_>, fields: &[(&str, String)]) -> fmt::Result {
+    let width = fields.iter().map(|(name, _)| name.len()).max().unwrap_or(0) + 1;
+
+    for (name, value) in fields {
+        writeln!(f, "{:<width$} {}", format!("{}:", name), value)?;
+    }
+
+    Ok(())
+}
+
+/// Returns how many of `count` records of `size` bytes, starting `offset` bytes into a descriptor of
+/// `len` bytes, fit within it. Decoders of unconfirmed layouts use this to keep the records that
+/// fit rather than rejecting a descriptor whose count or offset doesn't match the guess.
+pub(crate) fn records_that_fit(len: usize, count: usize, offset: usize, size: usize) -> usize {
+    len.saturating_sub(offset)
+        .checked_div(size)
+        .unwrap_or(0)
+        .min(count)
+}
+
 /// Describes how a given asset is structured. Typically, an AssetDescriptor has information about how
 /// to read an asset from its associated resources, as well as attributes of that asset. For
 /// example, a [`texture::TextureDescriptor`] knows the width and height of its associated texture
@@ -172,6 +550,14 @@ pub trait AssetDescriptor: Sized + Clone {
     fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError>;
 }
 
+/// An [`AssetDescriptor`] that can be created from scratch. Templates use the field values seen in
+/// the game's own assets (such as header sizes and flags) rather than zeros, which the game may
+/// not accept.
+pub trait DescriptorTemplate: AssetDescriptor {
+    /// Returns a minimal, valid descriptor to base a new asset on.
+    fn template() -> Self;
+}
+
 pub trait Asset: Sized {
     type Descriptor: AssetDescriptor;
 
@@ -187,20 +573,85 @@ pub trait Asset: Sized {
     fn name(&self) -> &str;
 }
 
+/// An [`Asset`] that can be written back into the [`RawAsset`] it was parsed from, see
+/// [`crate::BNLFile::get_asset_mut`].
+pub trait WritableAsset: Asset {
+    /// Writes the descriptor and resource data of this asset into `raw_asset`, which holds the
+    /// asset as it was parsed. Anything this asset doesn't model is left as it was.
+    fn write_back(&self, raw_asset: &mut RawAsset) -> Result<(), io::Error>;
+}
+
 pub type AssetName = [u8; 128];
 
+/// The size of an asset name (AID) field, both in an [`AssetDescription`] and in descriptors that
+/// reference other assets.
+pub(crate) const AID_SIZE: usize = size_of::<AssetName>();
+
+/// Reads a NUL padded asset name (AID) field.
+pub(crate) fn aid_from_bytes(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes)
+        .split('\0')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Writes an asset name (AID) into a NUL padded field.
+///
+/// # Errors
+/// Returns an error if the name doesn't fit in the field alongside its NUL terminator.
+pub(crate) fn aid_to_bytes(name: &str) -> Result<AssetName, io::Error> {
+    let mut bytes: AssetName = [0; AID_SIZE];
+
+    if name.len() >= AID_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Asset name {} is longer than the maximum of {} bytes",
+                name,
+                AID_SIZE - 1
+            ),
+        ));
+    }
+
+    bytes[..name.len()].copy_from_slice(name.as_bytes());
+
+    Ok(bytes)
+}
+
+/// (De)serialises an [`AssetName`] field as the name it holds, rather than as 128 bytes.
+#[cfg(feature = "serde")]
+mod aid_serde {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    use super::{AssetName, aid_from_bytes, aid_to_bytes};
+
+    pub fn serialize<S: Serializer>(name: &AssetName, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&aid_from_bytes(name))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<AssetName, D::Error> {
+        let name = String::deserialize(deserializer)?;
+
+        aid_to_bytes(&name).map_err(D::Error::custom)
+    }
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AssetDescription {
+    #[cfg_attr(feature = "serde", serde(with = "aid_serde"))]
     pub(crate) name: AssetName,
     pub(crate) asset_type: AssetType,
 
     pub(crate) unk_1: u32,
     pub(crate) unk_2: u32,
-    pub(crate) chunk_count: u32,
+    pub(crate) chunk_count: u32, // The number of views in the data view list of this asset
 
     pub(crate) descriptor_ptr: u32,
     pub(crate) descriptor_size: u32,
     pub(crate) dataview_list_ptr: u32,
-    pub(crate) resource_size: u32, // The total size needed for this asset, including its descriptor list
+    pub(crate) resource_size: u32, // The combined size of every data view of this asset
 }
 
 impl AssetDescription {
@@ -228,12 +679,38 @@ impl AssetDescription {
     pub fn resource_size(&self) -> u32 {
         self.resource_size
     }
+    /// The number of data views (chunks of resource data) this asset is split into.
+    pub fn chunk_count(&self) -> u32 {
+        self.chunk_count
+    }
     pub fn descriptor_ptr(&self) -> u32 {
         self.descriptor_ptr
     }
     pub fn descriptor_size(&self) -> u32 {
         self.descriptor_size
     }
+
+    /// Serialises this [`AssetDescription`] into the 160 byte layout used by the BNL file.
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(160);
+
+        bytes.extend_from_slice(&self.name);
+
+        for value in [
+            self.asset_type.into(),
+            self.unk_1,
+            self.unk_2,
+            self.chunk_count,
+            self.descriptor_ptr,
+            self.descriptor_size,
+            self.dataview_list_ptr,
+            self.resource_size,
+        ] {
+            bytes.extend_from_slice(&u32::to_le_bytes(value));
+        }
+
+        bytes
+    }
 }
 
 impl std::fmt::Debug for AssetDescription {
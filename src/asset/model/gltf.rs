@@ -0,0 +1,336 @@
+use std::{fs, io, path::Path};
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde_json::{Value, json};
+
+use crate::asset::{Asset, model::Model};
+
+const GLB_MAGIC: u32 = 0x46546c67; // "glTF"
+const GLB_VERSION: u32 = 2;
+const GLB_CHUNK_JSON: u32 = 0x4e4f534a; // "JSON"
+const GLB_CHUNK_BIN: u32 = 0x004e4942; // "BIN\0"
+
+// glTF accessor component types and buffer view targets
+const COMPONENT_FLOAT: u32 = 5126;
+const COMPONENT_UNSIGNED_SHORT: u32 = 5123;
+const TARGET_ARRAY_BUFFER: u32 = 34962;
+const TARGET_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// Accumulates the binary buffer of a glTF file, along with the buffer views and accessors that
+/// point into it.
+#[derive(Default)]
+struct GltfBuilder {
+    buffer: Vec<u8>,
+    buffer_views: Vec<Value>,
+    accessors: Vec<Value>,
+}
+
+impl GltfBuilder {
+    /// Appends `bytes` to the buffer as a new buffer view, returning its index.
+    fn push_view(&mut self, bytes: &[u8], target: Option<u32>) -> usize {
+        // Every accessor component type used here needs at most 4 byte alignment
+        while !self.buffer.len().is_multiple_of(4) {
+            self.buffer.push(0);
+        }
+
+        let mut view = json!({
+            "buffer": 0,
+            "byteOffset": self.buffer.len(),
+            "byteLength": bytes.len(),
+        });
+
+        if let Some(target) = target {
+            view["target"] = json!(target);
+        }
+
+        self.buffer.extend_from_slice(bytes);
+        self.buffer_views.push(view);
+
+        self.buffer_views.len() - 1
+    }
+
+    /// Adds an accessor for a list of float vectors, returning its index.
+    fn push_floats<const N: usize>(&mut self, values: &[[f32; N]], with_bounds: bool) -> usize {
+        let bytes: Vec<u8> = values
+            .iter()
+            .flatten()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+
+        let view = self.push_view(&bytes, Some(TARGET_ARRAY_BUFFER));
+
+        let mut accessor = json!({
+            "bufferView": view,
+            "componentType": COMPONENT_FLOAT,
+            "count": values.len(),
+            "type": format!("VEC{}", N),
+        });
+
+        // glTF requires bounds on POSITION accessors
+        if with_bounds {
+            let mut min = [f32::MAX; N];
+            let mut max = [f32::MIN; N];
+
+            for value in values {
+                for i in 0..N {
+                    min[i] = min[i].min(value[i]);
+                    max[i] = max[i].max(value[i]);
+                }
+            }
+
+            accessor["min"] = json!(min.as_slice());
+            accessor["max"] = json!(max.as_slice());
+        }
+
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn push_indices(&mut self, indices: &[u16]) -> usize {
+        let bytes: Vec<u8> = indices.iter().flat_map(|i| i.to_le_bytes()).collect();
+
+        let view = self.push_view(&bytes, Some(TARGET_ELEMENT_ARRAY_BUFFER));
+
+        self.accessors.push(json!({
+            "bufferView": view,
+            "componentType": COMPONENT_UNSIGNED_SHORT,
+            "count": indices.len(),
+            "type": "SCALAR",
+        }));
+        self.accessors.len() - 1
+    }
+}
+
+impl Model {
+    /// Exports this model as glTF 2.0, writing a binary .glb file when `path` has a `glb`
+    /// extension, and a self-contained .gltf file (with the buffer embedded as a data URI)
+    /// otherwise. Textures are embedded as PNG images, with one material per texture.
+    ///
+    /// Meshes don't yet record which texture they use, see [`Model::mesh_texture_index`].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use std::path::Path;
+    /// # use bnl::{BNLFile, asset::model::Model};
+    /// # let bnl = BNLFile::new();
+    /// let model: Model = bnl.get_asset("aid_model_gribble").expect("Unable to get model.");
+    /// model.export_gltf(Path::new("./gribble.glb")).expect("Unable to export model.");
+    /// ```
+    pub fn export_gltf(&self, path: &Path) -> Result<(), io::Error> {
+        let is_glb = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("glb"));
+
+        let bytes = match is_glb {
+            true => self.to_glb()?,
+            false => self.to_gltf_json(None)?.to_string().into_bytes(),
+        };
+
+        fs::write(path, bytes)
+    }
+
+    /// Returns this model as a binary glTF (.glb) file.
+    pub fn to_glb(&self) -> Result<Vec<u8>, io::Error> {
+        let mut buffer = Vec::new();
+        let json = self.to_gltf_json(Some(&mut buffer))?;
+
+        let mut json_bytes = json.to_string().into_bytes();
+        while !json_bytes.len().is_multiple_of(4) {
+            json_bytes.push(b' ');
+        }
+        while !buffer.len().is_multiple_of(4) {
+            buffer.push(0);
+        }
+
+        let total_len = 12 + 8 + json_bytes.len() + 8 + buffer.len();
+
+        let mut glb = Vec::with_capacity(total_len);
+
+        for value in [GLB_MAGIC, GLB_VERSION, total_len as u32] {
+            glb.extend_from_slice(&value.to_le_bytes());
+        }
+
+        for (chunk_type, chunk) in [(GLB_CHUNK_JSON, &json_bytes), (GLB_CHUNK_BIN, &buffer)] {
+            glb.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+            glb.extend_from_slice(&chunk_type.to_le_bytes());
+            glb.extend_from_slice(chunk);
+        }
+
+        Ok(glb)
+    }
+
+    /// Builds the glTF JSON document for this model. When `glb_buffer` is given, the binary
+    /// buffer is written into it for a .glb file, otherwise it is embedded as a data URI.
+    fn to_gltf_json(&self, glb_buffer: Option<&mut Vec<u8>>) -> Result<Value, io::Error> {
+        let mut builder = GltfBuilder::default();
+
+        let mut images = Vec::new();
+        let mut materials = Vec::new();
+
+        for (i, texture) in self.textures.iter().enumerate() {
+            let png = texture.encode_png()?;
+
+            let view = builder.push_view(&png, None);
+
+            images.push(json!({
+                "name": texture.name(),
+                "bufferView": view,
+                "mimeType": "image/png",
+            }));
+            materials.push(json!({
+                "name": self.material_name(i),
+                "pbrMetallicRoughness": {
+                    "baseColorTexture": { "index": i },
+                    "metallicFactor": 0.0,
+                },
+            }));
+        }
+
+        let mut meshes = Vec::new();
+
+        for (i, mesh) in self.meshes.iter().enumerate() {
+            let mut attributes = json!({
+                "POSITION": builder.push_floats(mesh.positions(), true),
+            });
+
+            if !mesh.normals().is_empty() {
+                attributes["NORMAL"] = json!(builder.push_floats(mesh.normals(), false));
+            }
+
+            if !mesh.uvs().is_empty() {
+                attributes["TEXCOORD_0"] = json!(builder.push_floats(mesh.uvs(), false));
+            }
+
+            let mut primitive = json!({
+                "attributes": attributes,
+                "indices": builder.push_indices(mesh.indices()),
+            });
+
+            if let Some(texture_index) = self.mesh_texture_index(i) {
+                primitive["material"] = json!(texture_index);
+            }
+
+            meshes.push(json!({
+                "name": format!("{}_mesh{}", self.name, i),
+                "primitives": [primitive],
+            }));
+        }
+
+        let nodes: Vec<Value> = (0..meshes.len()).map(|i| json!({ "mesh": i })).collect();
+
+        let buffer = match glb_buffer {
+            Some(glb_buffer) => {
+                let buffer = json!({ "byteLength": builder.buffer.len() });
+                *glb_buffer = builder.buffer;
+                buffer
+            }
+            None => json!({
+                "byteLength": builder.buffer.len(),
+                "uri": format!(
+                    "data:application/octet-stream;base64,{}",
+                    BASE64.encode(&builder.buffer)
+                ),
+            }),
+        };
+
+        let mut gltf = json!({
+            "asset": { "version": "2.0", "generator": "bnl" },
+            "scene": 0,
+            "scenes": [{ "name": self.name, "nodes": (0..nodes.len()).collect::<Vec<_>>() }],
+            "nodes": nodes,
+            "meshes": meshes,
+            "accessors": builder.accessors,
+            "bufferViews": builder.buffer_views,
+            "buffers": [buffer],
+        });
+
+        if !images.is_empty() {
+            gltf["samplers"] = json!([{}]);
+            gltf["textures"] = (0..images.len())
+                .map(|i| json!({ "source": i, "sampler": 0 }))
+                .collect();
+            gltf["images"] = json!(images);
+            gltf["materials"] = json!(materials);
+        }
+
+        Ok(gltf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        VirtualResource,
+        asset::{
+            AssetDescriptor,
+            model::{ModelDescriptor, mesh::Mesh},
+        },
+    };
+
+    fn make_model() -> Model {
+        let mut descriptor = vec![0u8; 64];
+
+        // One subresource at 8, of type Model (0) with its mesh list at 16
+        descriptor[0..4].copy_from_slice(&8u32.to_le_bytes());
+        descriptor[4..8].copy_from_slice(&1u32.to_le_bytes());
+        descriptor[12..16].copy_from_slice(&16u32.to_le_bytes());
+        // Mesh list of one pointer at 24, pointing to a mesh descriptor at 28
+        descriptor[16..20].copy_from_slice(&1u32.to_le_bytes());
+        descriptor[20..24].copy_from_slice(&24u32.to_le_bytes());
+        descriptor[24..28].copy_from_slice(&28u32.to_le_bytes());
+
+        for (i, value) in [3u32, 12, 0, 3, 36, 0x002].iter().enumerate() {
+            descriptor[28 + i * 4..32 + i * 4].copy_from_slice(&value.to_le_bytes());
+        }
+
+        let descriptor = ModelDescriptor::from_bytes(&descriptor).unwrap();
+
+        let mut resource: Vec<u8> = [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        resource.extend([0u16, 1, 2].iter().flat_map(|i| i.to_le_bytes()));
+
+        let slices = [resource.as_slice()];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let meshes = descriptor
+            .mesh_descriptors()
+            .map(|desc| Mesh::new(desc, &virtual_res).unwrap())
+            .collect();
+
+        Model {
+            name: "aid_model_test".to_string(),
+            descriptor,
+            textures: vec![],
+            meshes,
+            mesh_errors: vec![],
+        }
+    }
+
+    #[test]
+    fn gltf_json() {
+        let gltf = make_model().to_gltf_json(None).unwrap();
+
+        assert_eq!(gltf["meshes"].as_array().unwrap().len(), 1);
+        assert_eq!(gltf["accessors"][0]["count"], 3);
+        assert_eq!(gltf["accessors"][0]["max"], json!([1.0, 1.0, 0.0]));
+        assert_eq!(gltf["buffers"][0]["byteLength"], 36 + 6);
+        assert!(gltf.get("materials").is_none());
+    }
+
+    #[test]
+    fn glb_layout() {
+        let glb = make_model().to_glb().unwrap();
+
+        assert_eq!(&glb[0..4], b"glTF");
+        assert_eq!(
+            u32::from_le_bytes(glb[8..12].try_into().unwrap()) as usize,
+            glb.len()
+        );
+        assert_eq!(&glb[16..20], b"JSON");
+        assert_eq!(glb.len() % 4, 0);
+    }
+}
@@ -0,0 +1,345 @@
+use std::io::{Cursor, Seek, SeekFrom};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use crate::{VirtualResource, asset::AssetParseError};
+
+/// The size of a [`MeshDescriptor`] within a model descriptor.
+pub(crate) const MESH_DESCRIPTOR_SIZE: usize = 24;
+
+// Flexible vertex format (FVF) bits, as used by Direct3D 8 on the Xbox
+const FVF_POSITION_MASK: u32 = 0x00e;
+const FVF_XYZRHW: u32 = 0x004;
+const FVF_XYZB1: u32 = 0x006;
+const FVF_XYZB4: u32 = 0x00c;
+const FVF_NORMAL: u32 = 0x010;
+const FVF_DIFFUSE: u32 = 0x040;
+const FVF_SPECULAR: u32 = 0x080;
+const FVF_TEXCOUNT_MASK: u32 = 0xf00;
+const FVF_TEXCOUNT_SHIFT: u32 = 8;
+
+/// Describes where the vertex and index buffers of a single mesh live in the model's resource,
+/// and how its vertices are laid out.
+///
+/// This layout is an unconfirmed guess that hasn't been checked against game files, so
+/// [`super::Model`] skips meshes that don't decode. Assumed layout (all little endian u32):
+/// - vertex count
+/// - vertex stride in bytes
+/// - vertex buffer offset into the resource
+/// - index count (u16 indices, triangle list)
+/// - index buffer offset into the resource
+/// - FVF flags describing each vertex
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MeshDescriptor {
+    vertex_count: u32,
+    vertex_stride: u32,
+    vertex_offset: u32,
+    index_count: u32,
+    index_offset: u32,
+    fvf: u32,
+}
+
+impl MeshDescriptor {
+    pub(crate) fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError> {
+        if data.len() < MESH_DESCRIPTOR_SIZE {
+            return Err(AssetParseError::InputTooSmall);
+        }
+
+        let mut cur = Cursor::new(data);
+
+        Ok(MeshDescriptor {
+            vertex_count: cur.read_u32::<LittleEndian>()?,
+            vertex_stride: cur.read_u32::<LittleEndian>()?,
+            vertex_offset: cur.read_u32::<LittleEndian>()?,
+            index_count: cur.read_u32::<LittleEndian>()?,
+            index_offset: cur.read_u32::<LittleEndian>()?,
+            fvf: cur.read_u32::<LittleEndian>()?,
+        })
+    }
+
+    /// Serialises this descriptor back into its 24 byte form.
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        [
+            self.vertex_count,
+            self.vertex_stride,
+            self.vertex_offset,
+            self.index_count,
+            self.index_offset,
+            self.fvf,
+        ]
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+    }
+
+    pub fn vertex_count(&self) -> u32 {
+        self.vertex_count
+    }
+
+    pub fn vertex_stride(&self) -> u32 {
+        self.vertex_stride
+    }
+
+    pub fn index_count(&self) -> u32 {
+        self.index_count
+    }
+
+    pub fn fvf(&self) -> u32 {
+        self.fvf
+    }
+
+    /// Returns the number of blend weights stored in each vertex.
+    fn blend_weight_count(&self) -> usize {
+        match self.fvf & FVF_POSITION_MASK {
+            position @ FVF_XYZB1..=FVF_XYZB4 => ((position - FVF_XYZRHW) / 2) as usize,
+            _ => 0,
+        }
+    }
+
+    fn tex_coord_count(&self) -> usize {
+        ((self.fvf & FVF_TEXCOUNT_MASK) >> FVF_TEXCOUNT_SHIFT) as usize
+    }
+
+    /// Returns the number of bytes used by each vertex according to its FVF flags.
+    fn fvf_size(&self) -> usize {
+        let position = match self.fvf & FVF_POSITION_MASK {
+            FVF_XYZRHW => 16,
+            _ => 12,
+        };
+
+        let mut size = position + self.blend_weight_count() * 4;
+
+        if self.fvf & FVF_NORMAL != 0 {
+            size += 12;
+        }
+        if self.fvf & FVF_DIFFUSE != 0 {
+            size += 4;
+        }
+        if self.fvf & FVF_SPECULAR != 0 {
+            size += 4;
+        }
+
+        size + self.tex_coord_count() * 8
+    }
+}
+
+/// The geometry of a single mesh within a [`super::Model`].
+#[derive(Debug, Clone, Default)]
+pub struct Mesh {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    blend_weights: Vec<[f32; 4]>,
+    indices: Vec<u16>,
+}
+
+impl Mesh {
+    pub(crate) fn new(
+        descriptor: &MeshDescriptor,
+        virtual_res: &VirtualResource,
+    ) -> Result<Self, AssetParseError> {
+        let fvf_size = descriptor.fvf_size();
+        let stride = (descriptor.vertex_stride as usize).max(fvf_size);
+
+        let vertex_bytes = virtual_res
+            .get_bytes(
+                descriptor.vertex_offset as usize,
+                stride * descriptor.vertex_count as usize,
+            )
+            .map_err(|e| {
+                AssetParseError::InvalidDataViews(format!(
+                    "Unable to read vertex buffer at offset {}: {}",
+                    descriptor.vertex_offset, e
+                ))
+            })?;
+
+        let index_bytes = virtual_res
+            .get_bytes(
+                descriptor.index_offset as usize,
+                descriptor.index_count as usize * 2,
+            )
+            .map_err(|e| {
+                AssetParseError::InvalidDataViews(format!(
+                    "Unable to read index buffer at offset {}: {}",
+                    descriptor.index_offset, e
+                ))
+            })?;
+
+        let mut mesh = Mesh {
+            indices: index_bytes
+                .chunks_exact(2)
+                .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+                .collect(),
+            ..Default::default()
+        };
+
+        if let Some(index) = mesh
+            .indices
+            .iter()
+            .find(|index| **index as u32 >= descriptor.vertex_count)
+        {
+            return Err(AssetParseError::InvalidDataViews(format!(
+                "Index {} is out of range for a mesh with {} vertices",
+                index, descriptor.vertex_count
+            )));
+        }
+
+        let mut cur = Cursor::new(&vertex_bytes);
+
+        for i in 0..descriptor.vertex_count as u64 {
+            cur.seek(SeekFrom::Start(i * stride as u64))?;
+
+            mesh.positions.push(read_f32s(&mut cur)?);
+
+            if descriptor.fvf & FVF_POSITION_MASK == FVF_XYZRHW {
+                cur.read_f32::<LittleEndian>()?;
+            }
+
+            let weight_count = descriptor.blend_weight_count();
+
+            if weight_count > 0 {
+                let mut weights = [0.0; 4];
+
+                for weight in weights.iter_mut().take(weight_count) {
+                    *weight = cur.read_f32::<LittleEndian>()?;
+                }
+
+                mesh.blend_weights.push(weights);
+            }
+
+            if descriptor.fvf & FVF_NORMAL != 0 {
+                mesh.normals.push(read_f32s(&mut cur)?);
+            }
+
+            if descriptor.fvf & FVF_DIFFUSE != 0 {
+                cur.read_u32::<LittleEndian>()?;
+            }
+
+            if descriptor.fvf & FVF_SPECULAR != 0 {
+                cur.read_u32::<LittleEndian>()?;
+            }
+
+            // Only the first set of texture coordinates is kept
+            if descriptor.tex_coord_count() > 0 {
+                mesh.uvs.push(read_f32s(&mut cur)?);
+            }
+        }
+
+        Ok(mesh)
+    }
+
+    pub fn positions(&self) -> &[[f32; 3]] {
+        &self.positions
+    }
+
+    /// Returns the vertex normals, or an empty slice if the mesh doesn't have any.
+    pub fn normals(&self) -> &[[f32; 3]] {
+        &self.normals
+    }
+
+    /// Returns the first set of texture coordinates, or an empty slice if the mesh doesn't have
+    /// any.
+    pub fn uvs(&self) -> &[[f32; 2]] {
+        &self.uvs
+    }
+
+    /// Returns the blend (bone) weights of each vertex, or an empty slice if the mesh isn't
+    /// skinned. Unused weights are 0.
+    pub fn blend_weights(&self) -> &[[f32; 4]] {
+        &self.blend_weights
+    }
+
+    /// Returns the triangle list indices of this mesh.
+    pub fn indices(&self) -> &[u16] {
+        &self.indices
+    }
+}
+
+fn read_f32s<const N: usize>(cur: &mut Cursor<&Vec<u8>>) -> Result<[f32; N], std::io::Error> {
+    let mut values = [0.0; N];
+
+    for value in &mut values {
+        *value = cur.read_f32::<LittleEndian>()?;
+    }
+
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FVF_XYZ: u32 = 0x002;
+
+    fn push_f32s(bytes: &mut Vec<u8>, values: &[f32]) {
+        values
+            .iter()
+            .for_each(|value| bytes.extend_from_slice(&value.to_le_bytes()));
+    }
+
+    #[test]
+    fn fvf_sizes() {
+        let mut desc = MeshDescriptor::from_bytes(&[0; MESH_DESCRIPTOR_SIZE]).unwrap();
+
+        desc.fvf = FVF_XYZ | FVF_NORMAL | 0x100;
+        assert_eq!(desc.fvf_size(), 32);
+
+        desc.fvf = FVF_XYZB4 | FVF_DIFFUSE;
+        assert_eq!(desc.blend_weight_count(), 4);
+        assert_eq!(desc.fvf_size(), 32);
+    }
+
+    #[test]
+    fn reads_triangle() {
+        let mut vertex_bytes = vec![];
+
+        for i in 0..3 {
+            push_f32s(&mut vertex_bytes, &[i as f32, 1.0, 2.0]); // Position
+            push_f32s(&mut vertex_bytes, &[0.0, 1.0, 0.0]); // Normal
+            push_f32s(&mut vertex_bytes, &[0.5, i as f32]); // UV
+        }
+
+        let index_bytes: Vec<u8> = [0u16, 1, 2].iter().flat_map(|i| i.to_le_bytes()).collect();
+
+        let slices = [vertex_bytes.as_slice(), index_bytes.as_slice()];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let desc = MeshDescriptor {
+            vertex_count: 3,
+            vertex_stride: 32,
+            vertex_offset: 0,
+            index_count: 3,
+            index_offset: 96,
+            fvf: FVF_XYZ | FVF_NORMAL | 0x100,
+        };
+
+        let mesh = Mesh::new(&desc, &virtual_res).unwrap();
+
+        assert_eq!(mesh.positions()[2], [2.0, 1.0, 2.0]);
+        assert_eq!(mesh.normals().len(), 3);
+        assert_eq!(mesh.uvs()[1], [0.5, 1.0]);
+        assert!(mesh.blend_weights().is_empty());
+        assert_eq!(mesh.indices(), [0, 1, 2]);
+    }
+
+    #[test]
+    fn rejects_out_of_range_index() {
+        let vertex_bytes = vec![0u8; 12];
+        let index_bytes: Vec<u8> = 1u16.to_le_bytes().to_vec();
+
+        let slices = [vertex_bytes.as_slice(), index_bytes.as_slice()];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let desc = MeshDescriptor {
+            vertex_count: 1,
+            vertex_stride: 12,
+            vertex_offset: 0,
+            index_count: 1,
+            index_offset: 12,
+            fvf: FVF_XYZ,
+        };
+
+        assert!(Mesh::new(&desc, &virtual_res).is_err());
+    }
+}
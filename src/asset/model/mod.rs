@@ -1,6 +1,14 @@
+pub mod gltf;
+pub mod mesh;
+pub mod obj;
+pub mod skeleton;
 pub mod sub_main;
+pub mod subresource;
 
-use std::io::{Cursor, Seek, SeekFrom};
+use std::{
+    fmt::{self, Display},
+    io::{self, Cursor, Seek, SeekFrom},
+};
 
 use byteorder::{LittleEndian, ReadBytesExt};
 
@@ -10,7 +18,13 @@ use crate::{
     VirtualResource,
     asset::{
         Asset, AssetDescriptor, AssetParseError,
+        model::{
+            mesh::{Mesh, MeshDescriptor},
+            skeleton::Skeleton,
+            subresource::{ModelSubresource, RawModelSubresource},
+        },
         texture::{Texture, TextureDescriptor},
+        write_fields,
     },
     game::AssetType,
 };
@@ -21,16 +35,23 @@ pub struct Model {
     descriptor: ModelDescriptor,
     // subresource_descriptors: Vec<ModelSubresourceDescriptor>,
     textures: Vec<Texture>,
+    meshes: Vec<Mesh>,
+    mesh_errors: Vec<(usize, AssetParseError)>,
 }
 
 #[repr(u32)]
-#[derive(Debug, Clone, TryFromPrimitive, IntoPrimitive)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive, IntoPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ModelSubresType {
     Model = 0x00,
-    Unknown1 = 0x01,
-    Unknown2 = 0x02,
-    Unknown3 = 0x03,
-    Unknown4 = 0x04,
+    // Unconfirmed guess, not checked against game files. Skeletons that don't decode are kept
+    // as ModelSubresource::Unknown
+    Skeleton = 0x01,
+    // Unconfirmed guesses, not checked against game files. Subresources of these types that
+    // don't decode as the assumed layouts are kept as ModelSubresource::Unknown
+    Material = 0x02,
+    VertexBuffer = 0x03,
+    IndexBuffer = 0x04,
     Unknown5 = 0x05,
     Unknown6 = 0x06,
     Texture = 0x07,
@@ -51,27 +72,261 @@ pub enum ModelSubresType {
 }
 
 #[derive(Debug, Clone)]
-pub(crate) struct RawModelSubresource {
-    subres_type: ModelSubresType,
-    subres_param: u32,
-}
-
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ModelDescriptor {
     subresources_offset: u32,
     subresource_count: u32,
     raw_subresources: Vec<RawModelSubresource>,
-    texture_descriptors: Vec<TextureDescriptor>,
+    subresources: Vec<ModelSubresource>,
+    /// The descriptor as it was parsed, so that the data of undecoded subresources can be
+    /// written back unchanged.
+    bytes: Vec<u8>,
+}
+
+/// Where [`ModelDescriptor::to_bytes`] writes the records of a decoded subresource.
+struct ListPlacement {
+    list_ptr: usize,
+    record_ptrs: Vec<usize>,
+    /// Whether the list is new, rather than overwriting the list the descriptor was parsed with.
+    appended: bool,
+}
+
+impl ModelDescriptor {
+    /// Returns the decoded entries of the subresource table, in the order they are stored.
+    pub fn subresources(&self) -> &[ModelSubresource] {
+        &self.subresources
+    }
+
+    /// Returns the descriptors of the textures embedded in this model, from every texture
+    /// subresource in order.
+    pub fn texture_descriptors(&self) -> impl Iterator<Item = &TextureDescriptor> {
+        self.subresources.iter().flat_map(|subres| match subres {
+            ModelSubresource::Texture(descriptors) => descriptors.as_slice(),
+            _ => &[],
+        })
+    }
+
+    /// Returns the descriptors of the meshes of this model, from every model subresource in
+    /// order.
+    pub fn mesh_descriptors(&self) -> impl Iterator<Item = &MeshDescriptor> {
+        self.subresources.iter().flat_map(|subres| match subres {
+            ModelSubresource::Model(descriptors) => descriptors.as_slice(),
+            _ => &[],
+        })
+    }
+
+    /// Returns the size of this descriptor once serialised, see [`ModelDescriptor::to_bytes`].
+    pub fn size(&self) -> usize {
+        self.placements().1
+    }
+
+    /// Serialises this descriptor. The subresource table is written where it was parsed from.
+    /// Decoded subresources are written over the lists they were parsed from, unless their record
+    /// counts have changed, in which case new lists are appended to the end. Everything else,
+    /// including the data of undecoded subresources, is kept byte for byte, so an unmodified
+    /// descriptor serialises to the bytes it was parsed from.
+    ///
+    /// # Errors
+    /// Returns an error if a record can't be serialised, such as a texture in a format without a
+    /// BNL format ID or a bone name that is too long.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, io::Error> {
+        let (placements, size) = self.placements();
+
+        let mut bytes = self.bytes.clone();
+        bytes.resize(size, 0);
+
+        let put_u32 = |bytes: &mut [u8], offset: usize, value: u32| {
+            bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        };
+
+        put_u32(&mut bytes, 0, self.subresources_offset);
+        put_u32(&mut bytes, 4, self.subresources.len() as u32);
+
+        for (i, (subres, placement)) in self.subresources.iter().zip(&placements).enumerate() {
+            let entry = self.subresources_offset as usize + i * 8;
+
+            let param = match (placement, subres) {
+                (Some(placement), _) => placement.list_ptr as u32,
+                (None, ModelSubresource::Unknown(raw)) => raw.subres_param,
+                (None, _) => 0,
+            };
+
+            put_u32(&mut bytes, entry, subres.subres_type().into());
+            put_u32(&mut bytes, entry + 4, param);
+
+            let (Some(placement), Some(records)) = (placement, subres.records()?) else {
+                continue;
+            };
+
+            if placement.appended {
+                let pointers = placement.list_ptr + 8;
+
+                put_u32(&mut bytes, placement.list_ptr, records.len() as u32);
+                put_u32(&mut bytes, placement.list_ptr + 4, pointers as u32);
+
+                for (j, &ptr) in placement.record_ptrs.iter().enumerate() {
+                    put_u32(&mut bytes, pointers + j * 4, ptr as u32);
+                }
+            }
+
+            for (record, &ptr) in records.iter().zip(&placement.record_ptrs) {
+                bytes[ptr..ptr + record.len()].copy_from_slice(record);
+            }
+        }
+
+        Ok(bytes)
+    }
+
+    /// Works out where each decoded subresource's records go, and the size of the resulting
+    /// descriptor.
+    fn placements(&self) -> (Vec<Option<ListPlacement>>, usize) {
+        let table_end = self.subresources_offset as usize + self.subresources.len() * 8;
+        let mut end = self.bytes.len().max(table_end).max(8);
+
+        let placements = self
+            .subresources
+            .iter()
+            .enumerate()
+            .map(|(i, subres)| {
+                let (count, record_size) = subres.record_layout()?;
+
+                // Reuse the parsed list if it holds as many records, which it always does unless
+                // the subresource was edited
+                let original = self
+                    .raw_subresources
+                    .get(i)
+                    .filter(|raw| raw.subres_type == subres.subres_type())
+                    .and_then(|raw| {
+                        let ptrs =
+                            read_pointer_list(&self.bytes, raw.subres_param as usize).ok()?;
+
+                        (ptrs.len() == count
+                            && ptrs
+                                .iter()
+                                .all(|&ptr| ptr + record_size <= self.bytes.len()))
+                        .then_some(ListPlacement {
+                            list_ptr: raw.subres_param as usize,
+                            record_ptrs: ptrs,
+                            appended: false,
+                        })
+                    });
+
+                Some(original.unwrap_or_else(|| {
+                    let list_ptr = end.next_multiple_of(4);
+                    let records_start = list_ptr + 8 + count * 4;
+                    end = records_start + count * record_size;
+
+                    ListPlacement {
+                        list_ptr,
+                        record_ptrs: (0..count)
+                            .map(|j| records_start + j * record_size)
+                            .collect(),
+                        appended: true,
+                    }
+                }))
+            })
+            .collect();
+
+        (placements, end)
+    }
+
+    /// Returns the first skeleton subresource of this model, if it has one.
+    pub fn skeleton(&self) -> Option<&Skeleton> {
+        self.subresources.iter().find_map(|subres| match subres {
+            ModelSubresource::Skeleton(skeleton) => Some(skeleton),
+            _ => None,
+        })
+    }
+}
+
+impl Display for ModelDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_fields(
+            f,
+            &[
+                (
+                    "subresources_offset",
+                    format!("{:#x}", self.subresources_offset),
+                ),
+                ("subresource_count", self.subresource_count.to_string()),
+                ("textures", self.texture_descriptors().count().to_string()),
+                ("meshes", self.mesh_descriptors().count().to_string()),
+                (
+                    "bones",
+                    self.skeleton()
+                        .map_or(0, |skeleton| skeleton.bones().len())
+                        .to_string(),
+                ),
+            ],
+        )?;
+
+        writeln!(f, "subresources:")?;
+
+        for subres in &self.raw_subresources {
+            writeln!(
+                f,
+                "  {:<10} {:#x}",
+                format!("{:?}", subres.subres_type),
+                subres.subres_param
+            )?;
+        }
+
+        for (i, tex_desc) in self.texture_descriptors().enumerate() {
+            writeln!(
+                f,
+                "texture {}: {:?} {}x{}",
+                i,
+                tex_desc.format(),
+                tex_desc.width(),
+                tex_desc.height()
+            )?;
+        }
+
+        for (i, mesh_desc) in self.mesh_descriptors().enumerate() {
+            writeln!(
+                f,
+                "mesh {}: {} vertices, {} indices, fvf {:#x}",
+                i,
+                mesh_desc.vertex_count(),
+                mesh_desc.index_count(),
+                mesh_desc.fvf()
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads a pointer list from a model descriptor: a count, then the offset of that many `u32`
+/// pointers into the descriptor. The count comes straight from the file, so it is checked against
+/// the size of `data` before anything is read.
+pub(crate) fn read_pointer_list(
+    data: &[u8],
+    list_ptr: usize,
+) -> Result<Vec<usize>, AssetParseError> {
+    let header = data
+        .get(list_ptr..list_ptr.saturating_add(8))
+        .filter(|header| header.len() == 8)
+        .ok_or(AssetParseError::InputTooSmall)?;
+
+    let count = u32::from_le_bytes(header[0..4].try_into().unwrap_or_default()) as usize;
+    let offset = u32::from_le_bytes(header[4..8].try_into().unwrap_or_default()) as usize;
+
+    let list = data
+        .get(offset..)
+        .and_then(|rest| rest.get(..count.checked_mul(4)?))
+        .ok_or(AssetParseError::InputTooSmall)?;
+
+    Ok(list
+        .chunks_exact(4)
+        .map(|ptr| u32::from_le_bytes(ptr.try_into().unwrap_or_default()) as usize)
+        .collect())
 }
 
 impl AssetDescriptor for ModelDescriptor {
     fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError> {
         let data_size = data.len() as u32;
 
-        if data_size < size_of::<ModelDescriptor>() as u32 {
-            return Err(AssetParseError::InputTooSmall);
-        }
-
         if data_size < 8 {
             return Err(AssetParseError::InputTooSmall);
         }
@@ -80,7 +335,7 @@ impl AssetDescriptor for ModelDescriptor {
         let subresource_count = u32::from_le_bytes(data[4..8].try_into().unwrap_or_default());
 
         if subresources_offset > data_size
-            || (subresource_count * 8) > data_size - subresources_offset
+            || u64::from(subresource_count) * 8 > u64::from(data_size - subresources_offset)
         {
             return Err(AssetParseError::InputTooSmall);
         }
@@ -90,8 +345,7 @@ impl AssetDescriptor for ModelDescriptor {
         cur.seek(SeekFrom::Start(subresources_offset as u64))?;
 
         let mut raw_subresources = vec![];
-
-        let mut texture_descriptors = vec![];
+        let mut subresources = vec![];
 
         for _ in 0..subresource_count {
             let subres_type: ModelSubresType = cur
@@ -105,41 +359,23 @@ impl AssetDescriptor for ModelDescriptor {
                 .map_err(|_| AssetParseError::ErrorParsingDescriptor)?;
 
             raw_subresources.push(RawModelSubresource {
-                subres_type: subres_type
-                    .clone()
-                    .try_into()
-                    .map_err(|_| AssetParseError::ErrorParsingDescriptor)?,
+                subres_type,
                 subres_param,
             });
 
-            match subres_type {
-                ModelSubresType::Texture => {
-                    let mut tex_cur = Cursor::new(data);
-                    tex_cur.seek(SeekFrom::Start(subres_param as u64))?;
-
-                    let texture_list_count = tex_cur.read_u32::<LittleEndian>()?;
-                    let texture_list_offset = tex_cur.read_u32::<LittleEndian>()?;
-
-                    tex_cur.seek(SeekFrom::Start(texture_list_offset as u64))?;
-
-                    for _ in 0..texture_list_count {
-                        let ptr = tex_cur.read_u32::<LittleEndian>()? as usize;
-
-                        let slice = &data[ptr..];
-                        let tex_desc = TextureDescriptor::from_bytes(slice)?;
-
-                        texture_descriptors.push(tex_desc);
-                    }
-                }
-                _ => {}
-            };
+            subresources.push(ModelSubresource::from_bytes(
+                data,
+                subres_type,
+                subres_param,
+            )?);
         }
 
         Ok(ModelDescriptor {
             subresources_offset,
             subresource_count,
             raw_subresources,
-            texture_descriptors,
+            subresources,
+            bytes: data.to_vec(),
         })
     }
 }
@@ -162,14 +398,27 @@ impl Asset for Model {
             name: name.to_string(),
             descriptor: descriptor.clone(),
             textures: vec![],
+            meshes: vec![],
+            mesh_errors: vec![],
         };
 
-        for subtex_desc in &model.descriptor.texture_descriptors {
+        for (i, subtex_desc) in model.descriptor.texture_descriptors().enumerate() {
             let desc: TextureDescriptor = subtex_desc.clone().into();
 
             // Safe to pass data_slices here because models always use resource0 for the tex slot
             // on the main model
-            model.textures.push(Texture::new("", &desc, virtual_res)?);
+            model
+                .textures
+                .push(Texture::new(&model.texture_name(i), &desc, virtual_res)?);
+        }
+
+        // The mesh descriptor layout is unconfirmed, so meshes that don't decode are skipped
+        // rather than failing the whole model
+        for (i, mesh_desc) in model.descriptor.mesh_descriptors().enumerate() {
+            match Mesh::new(mesh_desc, virtual_res) {
+                Ok(mesh) => model.meshes.push(mesh),
+                Err(e) => model.mesh_errors.push((i, e)),
+            }
         }
 
         Ok(model)
@@ -191,8 +440,210 @@ impl Asset for Model {
 pub trait Subresource {}
 
 impl Model {
-    /// Returns a list of textures if the model has any, and None otherwise.
-    pub fn textures(&self) -> Option<&Vec<Texture>> {
-        Some(&self.textures)
+    /// Returns the textures embedded in this model, which is empty if it has none. Each texture is
+    /// named after the model and its index, such as `aid_model_gribble_tex0`, so that dumping them
+    /// doesn't overwrite one texture with another.
+    pub fn textures(&self) -> &[Texture] {
+        &self.textures
+    }
+
+    /// Returns the textures embedded in this model for editing in place, see
+    /// [`Model::textures`].
+    pub fn textures_mut(&mut self) -> &mut [Texture] {
+        &mut self.textures
+    }
+
+    /// Replaces the texture at `index` with `texture`, returning the texture it replaced. Only
+    /// the decoded model is changed; the model descriptor still describes the original texture.
+    ///
+    /// # Errors
+    /// Returns an error if the model has no texture at `index`.
+    pub fn replace_texture(
+        &mut self,
+        index: usize,
+        texture: Texture,
+    ) -> Result<Texture, io::Error> {
+        let count = self.textures.len();
+
+        let slot = self.textures.get_mut(index).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Unable to replace texture {} of {}, which has {} textures",
+                    index, self.name, count
+                ),
+            )
+        })?;
+
+        Ok(std::mem::replace(slot, texture))
+    }
+
+    /// Returns the meshes that make up the geometry of this model. Meshes that couldn't be
+    /// decoded are left out, see [`Model::mesh_errors`].
+    pub fn meshes(&self) -> &[Mesh] {
+        &self.meshes
+    }
+
+    /// Returns the index of each mesh descriptor that couldn't be decoded into a mesh, along with
+    /// the reason, in the order of [`ModelDescriptor::mesh_descriptors`].
+    pub fn mesh_errors(&self) -> &[(usize, AssetParseError)] {
+        &self.mesh_errors
+    }
+
+    /// Returns the bone hierarchy of this model, or None if the model isn't skinned.
+    pub fn skeleton(&self) -> Option<&Skeleton> {
+        self.descriptor.skeleton()
+    }
+
+    /// Returns the index of the texture used by a mesh, if the model has any textures. Meshes
+    /// don't yet record which texture they use, so mesh N uses texture N when the model has one,
+    /// and the first texture otherwise.
+    pub(crate) fn mesh_texture_index(&self, mesh_index: usize) -> Option<usize> {
+        match self.textures.len() {
+            0 => None,
+            count if mesh_index < count => Some(mesh_index),
+            _ => Some(0),
+        }
+    }
+
+    /// Returns the name given to the texture of this model at `texture_index`.
+    pub(crate) fn texture_name(&self, texture_index: usize) -> String {
+        format!("{}_tex{}", self.name, texture_index)
+    }
+
+    /// Returns the name of the material generated for a texture of this model on export.
+    pub(crate) fn material_name(&self, texture_index: usize) -> String {
+        format!("{}_material{}", self.name, texture_index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::d3d::{D3DFormat, StandardFormat};
+
+    fn texture_descriptor(size: u16) -> TextureDescriptor {
+        TextureDescriptor::new(
+            D3DFormat::Standard(StandardFormat::DXT1),
+            28,
+            size,
+            size,
+            0,
+            0,
+            0,
+            size as u32 * size as u32 / 2,
+        )
+    }
+
+    #[test]
+    fn names_and_replaces_textures() {
+        let mut data = vec![];
+
+        // A texture subresource listing the same 8x8 DXT1 texture twice
+        for value in [8u32, 1, 7, 16, 2, 24, 32, 32] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend(texture_descriptor(8).to_bytes().unwrap());
+
+        let descriptor = ModelDescriptor::from_bytes(&data).unwrap();
+
+        let resource = vec![0; 32];
+        let slices = [resource.as_slice()];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let mut model = Model::new("aid_model_x", &descriptor, &virtual_res).unwrap();
+        let names: Vec<&str> = model
+            .textures()
+            .iter()
+            .map(|texture| texture.name())
+            .collect();
+
+        assert_eq!(names, ["aid_model_x_tex0", "aid_model_x_tex1"]);
+
+        let replacement = Texture::new("aid_texture_new", &texture_descriptor(8), &virtual_res);
+        let replaced = model.replace_texture(1, replacement.unwrap()).unwrap();
+
+        assert_eq!(replaced.name(), "aid_model_x_tex1");
+        assert_eq!(model.textures()[1].name(), "aid_texture_new");
+        assert!(model.replace_texture(2, replaced).is_err());
+        assert_eq!(model.textures_mut().len(), 2);
+    }
+
+    #[test]
+    fn loads_textures_when_meshes_do_not_decode() {
+        let mut data = vec![];
+
+        // A model subresource pointing at data that isn't a pointer list at 108, one whose mesh
+        // lies outside the resource at 72, and a texture subresource at 32
+        for value in [8u32, 3, 0, 108, 0, 72, 7, 32, 1, 40, 44] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend(texture_descriptor(8).to_bytes().unwrap());
+        for value in [1u32, 80, 84, 3, 12, 0x1000, 3, 0, 2] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend([0xAB; 8]);
+
+        let descriptor = ModelDescriptor::from_bytes(&data).unwrap();
+        assert!(matches!(
+            descriptor.subresources()[0],
+            ModelSubresource::Unknown(RawModelSubresource {
+                subres_type: ModelSubresType::Model,
+                subres_param: 108,
+            })
+        ));
+        assert_eq!(descriptor.to_bytes().unwrap(), data);
+
+        let resource = vec![0; 32];
+        let slices = [resource.as_slice()];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let model = Model::new("aid_model_x", &descriptor, &virtual_res).unwrap();
+
+        assert_eq!(model.textures().len(), 1);
+        assert!(model.meshes().is_empty());
+        assert!(matches!(
+            model.mesh_errors(),
+            [(0, AssetParseError::InvalidDataViews(_))]
+        ));
+    }
+
+    #[test]
+    fn descriptor_round_trips() {
+        let mut data = vec![];
+
+        // A texture subresource with a one texture list at 24, and an undecoded subresource
+        // pointing at 8 bytes of its own data at 64
+        for value in [8u32, 2, 7, 24, 9, 64, 1, 32, 36] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend(texture_descriptor(8).to_bytes().unwrap());
+        data.extend([0xAB; 8]);
+
+        let mut descriptor = ModelDescriptor::from_bytes(&data).unwrap();
+        assert_eq!(descriptor.to_bytes().unwrap(), data);
+        assert_eq!(descriptor.size(), data.len());
+
+        // A texture list that grows is moved to the end, leaving everything else in place
+        match &mut descriptor.subresources[0] {
+            ModelSubresource::Texture(textures) => textures.push(texture_descriptor(16)),
+            subres => panic!("Expected a texture subresource, got {:?}", subres),
+        }
+
+        let bytes = descriptor.to_bytes().unwrap();
+        assert_eq!(bytes.len(), descriptor.size());
+        assert_eq!(bytes.len(), 72 + 8 + 2 * 4 + 2 * 28);
+        assert_eq!(bytes[36..72], data[36..72]);
+
+        let reparsed = ModelDescriptor::from_bytes(&bytes).unwrap();
+        let widths: Vec<u16> = reparsed.texture_descriptors().map(|t| t.width()).collect();
+        assert_eq!(widths, [8, 16]);
+        assert!(matches!(
+            reparsed.subresources()[1],
+            ModelSubresource::Unknown(RawModelSubresource {
+                subres_param: 64,
+                ..
+            })
+        ));
     }
 }
@@ -0,0 +1,195 @@
+use std::{
+    fmt::Write as _,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::asset::model::Model;
+
+impl Model {
+    /// Exports this model as a Wavefront OBJ file, alongside an MTL material library and one PNG
+    /// per texture, all named after `path`. For example, exporting to `./out/gribble.obj` writes
+    /// `./out/gribble.obj`, `./out/gribble.mtl`, `./out/gribble_tex0.png` and so on.
+    ///
+    /// Each mesh becomes its own object, using the same texture assignment as
+    /// [`Model::export_gltf`].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use std::path::Path;
+    /// # use bnl::{BNLFile, asset::model::Model};
+    /// # let bnl = BNLFile::new();
+    /// let model: Model = bnl.get_asset("aid_model_gribble").expect("Unable to get model.");
+    /// model.export_obj(Path::new("./gribble.obj")).expect("Unable to export model.");
+    /// ```
+    pub fn export_obj(&self, path: &Path) -> Result<(), io::Error> {
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.name.clone());
+
+        let mtl_path = path.with_extension("mtl");
+
+        let texture_paths: Vec<PathBuf> = (0..self.textures.len())
+            .map(|i| path.with_file_name(format!("{}_tex{}.png", stem, i)))
+            .collect();
+
+        for (texture, texture_path) in self.textures.iter().zip(&texture_paths) {
+            texture.dump(texture_path)?;
+        }
+
+        let texture_names: Vec<String> = texture_paths
+            .iter()
+            .map(|path| {
+                path.file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+
+        fs::write(&mtl_path, self.to_mtl(&texture_names))?;
+        fs::write(path, self.to_obj(&format!("{}.mtl", stem)))
+    }
+
+    /// Builds an MTL material library with one material per texture file name.
+    fn to_mtl(&self, texture_names: &[String]) -> String {
+        let mut mtl = String::new();
+
+        for (i, texture_name) in texture_names.iter().enumerate() {
+            let _ = writeln!(mtl, "newmtl {}", self.material_name(i));
+            let _ = writeln!(mtl, "Kd 1.000 1.000 1.000");
+            let _ = writeln!(mtl, "map_Kd {}\n", texture_name);
+        }
+
+        mtl
+    }
+
+    /// Builds the OBJ text for every mesh of this model, referencing the given MTL file.
+    fn to_obj(&self, mtl_name: &str) -> String {
+        let mut obj = String::new();
+
+        if !self.textures.is_empty() {
+            let _ = writeln!(obj, "mtllib {}", mtl_name);
+        }
+
+        // OBJ indices are 1-based and shared across the whole file
+        let mut vertex_base = 1usize;
+        let mut uv_base = 1usize;
+        let mut normal_base = 1usize;
+
+        for (i, mesh) in self.meshes.iter().enumerate() {
+            let _ = writeln!(obj, "\no {}_mesh{}", self.name, i);
+
+            for [x, y, z] in mesh.positions() {
+                let _ = writeln!(obj, "v {} {} {}", x, y, z);
+            }
+
+            // OBJ texture coordinates start at the bottom left, D3D's at the top left
+            for [u, v] in mesh.uvs() {
+                let _ = writeln!(obj, "vt {} {}", u, 1.0 - v);
+            }
+
+            for [x, y, z] in mesh.normals() {
+                let _ = writeln!(obj, "vn {} {} {}", x, y, z);
+            }
+
+            if let Some(texture_index) = self.mesh_texture_index(i) {
+                let _ = writeln!(obj, "usemtl {}", self.material_name(texture_index));
+            }
+
+            let has_uvs = !mesh.uvs().is_empty();
+            let has_normals = !mesh.normals().is_empty();
+
+            for triangle in mesh.indices().chunks_exact(3) {
+                obj.push('f');
+
+                for &index in triangle {
+                    let index = index as usize;
+                    let _ = write!(obj, " {}", vertex_base + index);
+
+                    match (has_uvs, has_normals) {
+                        (true, true) => {
+                            let _ = write!(obj, "/{}/{}", uv_base + index, normal_base + index);
+                        }
+                        (true, false) => {
+                            let _ = write!(obj, "/{}", uv_base + index);
+                        }
+                        (false, true) => {
+                            let _ = write!(obj, "//{}", normal_base + index);
+                        }
+                        (false, false) => {}
+                    }
+                }
+
+                obj.push('\n');
+            }
+
+            vertex_base += mesh.positions().len();
+            uv_base += mesh.uvs().len();
+            normal_base += mesh.normals().len();
+        }
+
+        obj
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        VirtualResource,
+        asset::{
+            AssetDescriptor,
+            model::{ModelDescriptor, mesh::Mesh},
+        },
+    };
+
+    #[test]
+    fn obj_faces_offset_per_mesh() {
+        let mut descriptor = vec![0u8; 64];
+
+        // One Model subresource whose mesh list holds the same mesh descriptor twice
+        descriptor[0..4].copy_from_slice(&8u32.to_le_bytes());
+        descriptor[4..8].copy_from_slice(&1u32.to_le_bytes());
+        descriptor[12..16].copy_from_slice(&16u32.to_le_bytes());
+        descriptor[16..20].copy_from_slice(&2u32.to_le_bytes());
+        descriptor[20..24].copy_from_slice(&24u32.to_le_bytes());
+        descriptor[24..28].copy_from_slice(&32u32.to_le_bytes());
+        descriptor[28..32].copy_from_slice(&32u32.to_le_bytes());
+
+        // 3 vertices of XYZ + 1 UV (20 bytes each), 3 indices at offset 60
+        for (i, value) in [3u32, 20, 0, 3, 60, 0x102].iter().enumerate() {
+            descriptor[32 + i * 4..36 + i * 4].copy_from_slice(&value.to_le_bytes());
+        }
+
+        let descriptor = ModelDescriptor::from_bytes(&descriptor).unwrap();
+
+        let mut resource = vec![0u8; 60];
+        resource.extend([0u16, 1, 2].iter().flat_map(|i| i.to_le_bytes()));
+
+        let slices = [resource.as_slice()];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let meshes = descriptor
+            .mesh_descriptors()
+            .map(|desc| Mesh::new(desc, &virtual_res).unwrap())
+            .collect();
+
+        let model = Model {
+            name: "aid_model_test".to_string(),
+            descriptor,
+            textures: vec![],
+            meshes,
+            mesh_errors: vec![],
+        };
+
+        let obj = model.to_obj("test.mtl");
+
+        assert!(!obj.contains("mtllib"));
+        assert!(obj.contains("f 1/1 2/2 3/3\n"));
+        assert!(obj.contains("f 4/4 5/5 6/6\n"));
+        assert_eq!(obj.matches("\nv ").count(), 6);
+    }
+}
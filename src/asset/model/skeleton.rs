@@ -0,0 +1,238 @@
+use std::io::{self, Cursor};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use crate::asset::{AssetParseError, model::read_pointer_list};
+
+/// The size of a single bone record within a model descriptor.
+pub(crate) const BONE_SIZE: usize = 100;
+
+/// The number of bytes reserved for the NUL padded name of a bone.
+const BONE_NAME_SIZE: usize = 32;
+
+/// A single bone of a [`Skeleton`].
+///
+/// This layout is an unconfirmed guess that hasn't been checked against game files, so a
+/// skeleton that doesn't decode is kept undecoded by its [`super::Model`]. Assumed layout:
+/// - name, NUL padded to 32 bytes (a name of exactly 32 bytes has no NUL)
+/// - parent bone index (i32, -1 for a root bone)
+/// - bind-pose transform relative to the parent, as a row-major 4x4 f32 matrix
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bone {
+    name: String,
+    parent: Option<usize>,
+    bind_pose: [[f32; 4]; 4],
+}
+
+impl Bone {
+    fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError> {
+        if data.len() < BONE_SIZE {
+            return Err(AssetParseError::InputTooSmall);
+        }
+
+        let name = String::from_utf8_lossy(&data[..BONE_NAME_SIZE])
+            .split('\0')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+
+        let mut cur = Cursor::new(&data[BONE_NAME_SIZE..]);
+
+        let parent = match cur.read_i32::<LittleEndian>()? {
+            -1 => None,
+            index => {
+                Some(usize::try_from(index).map_err(|_| AssetParseError::ErrorParsingDescriptor)?)
+            }
+        };
+
+        let mut bind_pose = [[0.0; 4]; 4];
+
+        for row in &mut bind_pose {
+            for value in row {
+                *value = cur.read_f32::<LittleEndian>()?;
+            }
+        }
+
+        Ok(Bone {
+            name,
+            parent,
+            bind_pose,
+        })
+    }
+
+    /// Serialises this bone back into its 100 byte form.
+    ///
+    /// # Errors
+    /// Returns an error if the name doesn't fit in its 32 byte field.
+    pub(crate) fn to_bytes(&self) -> Result<Vec<u8>, io::Error> {
+        if self.name.len() > BONE_NAME_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Bone name {} is longer than {} bytes",
+                    self.name, BONE_NAME_SIZE
+                ),
+            ));
+        }
+
+        let mut bytes = Vec::with_capacity(BONE_SIZE);
+
+        let mut name = [0u8; BONE_NAME_SIZE];
+        name[..self.name.len()].copy_from_slice(self.name.as_bytes());
+        bytes.extend_from_slice(&name);
+
+        let parent = self.parent.map_or(-1, |parent| parent as i32);
+        bytes.extend_from_slice(&parent.to_le_bytes());
+
+        for value in self.bind_pose.as_flattened() {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        Ok(bytes)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the index of the parent bone within the [`Skeleton`], or None for a root bone. The
+    /// index is kept as stored, so it isn't guaranteed to name an earlier bone, or any bone.
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    /// Returns the bind-pose transform of this bone relative to its parent, in row-major order.
+    pub fn bind_pose(&self) -> &[[f32; 4]; 4] {
+        &self.bind_pose
+    }
+}
+
+/// The bone hierarchy of a skinned [`super::Model`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Skeleton {
+    bones: Vec<Bone>,
+}
+
+impl Skeleton {
+    /// Parses a skeleton from the model descriptor, where `list_ptr` points at a bone count
+    /// followed by the offset of a list of bone pointers (the same layout as the texture list).
+    ///
+    /// Both the use of subresource type 0x01 for skeletons and the [`Bone`] layout are
+    /// unconfirmed, so the hierarchy isn't validated: parent indices are kept as stored.
+    pub(crate) fn from_bytes(data: &[u8], list_ptr: usize) -> Result<Self, AssetParseError> {
+        let ptrs = read_pointer_list(data, list_ptr)?;
+        let mut bones = Vec::with_capacity(ptrs.len());
+
+        for ptr in ptrs {
+            let slice = data.get(ptr..).ok_or(AssetParseError::InputTooSmall)?;
+            bones.push(Bone::from_bytes(slice)?);
+        }
+
+        Ok(Skeleton { bones })
+    }
+
+    pub fn bones(&self) -> &[Bone] {
+        &self.bones
+    }
+
+    /// Returns the index and bone with the given name, if this skeleton has one.
+    pub fn find_bone(&self, name: &str) -> Option<(usize, &Bone)> {
+        self.bones
+            .iter()
+            .enumerate()
+            .find(|(_, bone)| bone.name == name)
+    }
+
+    /// Returns the indices of the bones whose parent is the bone at `index`.
+    pub fn children(&self, index: usize) -> Vec<usize> {
+        self.bones
+            .iter()
+            .enumerate()
+            .filter(|(_, bone)| bone.parent == Some(index))
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_bone(data: &mut Vec<u8>, name: &str, parent: i32) {
+        let mut name_bytes = [0u8; BONE_NAME_SIZE];
+        name_bytes[..name.len()].copy_from_slice(name.as_bytes());
+
+        data.extend_from_slice(&name_bytes);
+        data.extend_from_slice(&parent.to_le_bytes());
+
+        for i in 0..16 {
+            let value: f32 = if i % 5 == 0 { 1.0 } else { 0.0 };
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    fn make_skeleton(parents: &[i32]) -> Vec<u8> {
+        let mut data = vec![];
+
+        data.extend_from_slice(&(parents.len() as u32).to_le_bytes());
+        data.extend_from_slice(&8u32.to_le_bytes());
+
+        let bones_start = 8 + parents.len() * 4;
+
+        for i in 0..parents.len() {
+            data.extend_from_slice(&((bones_start + i * BONE_SIZE) as u32).to_le_bytes());
+        }
+
+        for (i, parent) in parents.iter().enumerate() {
+            push_bone(&mut data, &format!("bone{}", i), *parent);
+        }
+
+        data
+    }
+
+    #[test]
+    fn parses_hierarchy() {
+        let skeleton = Skeleton::from_bytes(&make_skeleton(&[-1, 0, 0, 1]), 0).unwrap();
+
+        assert_eq!(skeleton.bones().len(), 4);
+        assert_eq!(skeleton.bones()[0].parent(), None);
+        assert_eq!(skeleton.children(0), vec![1, 2]);
+        assert_eq!(skeleton.find_bone("bone3").unwrap().1.parent(), Some(1));
+        assert_eq!(skeleton.bones()[2].bind_pose()[3], [0.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn keeps_forward_parent() {
+        let skeleton = Skeleton::from_bytes(&make_skeleton(&[1, -1]), 0).unwrap();
+
+        assert_eq!(skeleton.bones()[0].parent(), Some(1));
+        assert_eq!(skeleton.children(1), vec![0]);
+    }
+
+    #[test]
+    fn round_trips_full_length_name() {
+        let name = "b".repeat(BONE_NAME_SIZE);
+        let mut data = make_skeleton(&[-1]);
+        data[12..12 + BONE_NAME_SIZE].copy_from_slice(name.as_bytes());
+
+        let skeleton = Skeleton::from_bytes(&data, 0).unwrap();
+        let bone = &skeleton.bones()[0];
+
+        assert_eq!(bone.name(), name);
+        assert_eq!(bone.to_bytes().unwrap(), data[12..]);
+    }
+
+    #[test]
+    fn rejects_counts_past_the_end() {
+        let mut data = make_skeleton(&[-1]);
+        data[0..4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        assert!(matches!(
+            Skeleton::from_bytes(&data, 0),
+            Err(AssetParseError::InputTooSmall)
+        ));
+        assert!(Skeleton::from_bytes(&data, usize::MAX).is_err());
+    }
+}
@@ -0,0 +1,448 @@
+use std::io::{self, Cursor};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use crate::asset::{
+    AssetDescriptor, AssetParseError,
+    model::{
+        ModelSubresType,
+        mesh::{MESH_DESCRIPTOR_SIZE, MeshDescriptor},
+        read_pointer_list,
+        skeleton::{BONE_SIZE, Skeleton},
+    },
+    texture::{TEXTURE_DESCRIPTOR_SIZE, TextureDescriptor},
+};
+
+/// The size of a [`Material`] within a model descriptor.
+pub(crate) const MATERIAL_SIZE: usize = 44;
+
+/// The size of a [`VertexBufferDescriptor`] within a model descriptor.
+pub(crate) const VERTEX_BUFFER_SIZE: usize = 16;
+
+/// The size of an [`IndexBufferDescriptor`] within a model descriptor.
+pub(crate) const INDEX_BUFFER_SIZE: usize = 12;
+
+/// The surface properties of a part of a model, stored in the material subresource.
+///
+/// Both the subresource type and this layout are unconfirmed guesses that haven't been checked
+/// against game files, so materials that don't fit it are left undecoded. Assumed layout:
+/// - texture index into the model's texture list (i32, -1 for an untextured material)
+/// - diffuse colour, as RGBA f32s
+/// - specular colour, as RGBA f32s
+/// - specular power (f32)
+/// - flags (u32)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Material {
+    texture_index: Option<usize>,
+    diffuse: [f32; 4],
+    specular: [f32; 4],
+    specular_power: f32,
+    flags: u32,
+}
+
+impl Material {
+    pub(crate) fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError> {
+        if data.len() < MATERIAL_SIZE {
+            return Err(AssetParseError::InputTooSmall);
+        }
+
+        let mut cur = Cursor::new(data);
+
+        let texture_index = match cur.read_i32::<LittleEndian>()? {
+            -1 => None,
+            index => {
+                Some(usize::try_from(index).map_err(|_| AssetParseError::ErrorParsingDescriptor)?)
+            }
+        };
+
+        let mut diffuse = [0.0; 4];
+        cur.read_f32_into::<LittleEndian>(&mut diffuse)?;
+
+        let mut specular = [0.0; 4];
+        cur.read_f32_into::<LittleEndian>(&mut specular)?;
+
+        Ok(Material {
+            texture_index,
+            diffuse,
+            specular,
+            specular_power: cur.read_f32::<LittleEndian>()?,
+            flags: cur.read_u32::<LittleEndian>()?,
+        })
+    }
+
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(MATERIAL_SIZE);
+
+        let texture_index = self.texture_index.map_or(-1, |index| index as i32);
+        bytes.extend_from_slice(&texture_index.to_le_bytes());
+
+        for value in self.diffuse.iter().chain(&self.specular) {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        bytes.extend_from_slice(&self.specular_power.to_le_bytes());
+        bytes.extend_from_slice(&self.flags.to_le_bytes());
+
+        bytes
+    }
+
+    /// Returns the index of the model texture this material uses, or None if it is untextured.
+    pub fn texture_index(&self) -> Option<usize> {
+        self.texture_index
+    }
+
+    pub fn diffuse(&self) -> [f32; 4] {
+        self.diffuse
+    }
+
+    pub fn specular(&self) -> [f32; 4] {
+        self.specular
+    }
+
+    pub fn specular_power(&self) -> f32 {
+        self.specular_power
+    }
+
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+}
+
+/// A vertex buffer shared by the meshes of a model, stored in the vertex buffer subresource.
+///
+/// Like [`Material`], this layout is unconfirmed. Assumed layout (all little endian u32):
+/// - offset of the buffer into the resource
+/// - size of the buffer in bytes
+/// - vertex stride in bytes
+/// - FVF flags describing each vertex
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VertexBufferDescriptor {
+    offset: u32,
+    size: u32,
+    stride: u32,
+    fvf: u32,
+}
+
+impl VertexBufferDescriptor {
+    pub(crate) fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError> {
+        if data.len() < VERTEX_BUFFER_SIZE {
+            return Err(AssetParseError::InputTooSmall);
+        }
+
+        let mut cur = Cursor::new(data);
+
+        Ok(VertexBufferDescriptor {
+            offset: cur.read_u32::<LittleEndian>()?,
+            size: cur.read_u32::<LittleEndian>()?,
+            stride: cur.read_u32::<LittleEndian>()?,
+            fvf: cur.read_u32::<LittleEndian>()?,
+        })
+    }
+
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        [self.offset, self.size, self.stride, self.fvf]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn stride(&self) -> u32 {
+        self.stride
+    }
+
+    pub fn fvf(&self) -> u32 {
+        self.fvf
+    }
+
+    /// Returns the number of whole vertices in this buffer.
+    pub fn vertex_count(&self) -> u32 {
+        self.size.checked_div(self.stride).unwrap_or(0)
+    }
+}
+
+/// An index buffer shared by the meshes of a model, stored in the index buffer subresource.
+///
+/// Like [`Material`], this layout is unconfirmed. Assumed layout (all little endian u32):
+/// - offset of the buffer into the resource
+/// - index count (u16 indices)
+/// - Direct3D primitive type, such as 5 for a triangle list
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IndexBufferDescriptor {
+    offset: u32,
+    index_count: u32,
+    primitive_type: u32,
+}
+
+impl IndexBufferDescriptor {
+    pub(crate) fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError> {
+        if data.len() < INDEX_BUFFER_SIZE {
+            return Err(AssetParseError::InputTooSmall);
+        }
+
+        let mut cur = Cursor::new(data);
+
+        Ok(IndexBufferDescriptor {
+            offset: cur.read_u32::<LittleEndian>()?,
+            index_count: cur.read_u32::<LittleEndian>()?,
+            primitive_type: cur.read_u32::<LittleEndian>()?,
+        })
+    }
+
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        [self.offset, self.index_count, self.primitive_type]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    pub fn index_count(&self) -> u32 {
+        self.index_count
+    }
+
+    pub fn primitive_type(&self) -> u32 {
+        self.primitive_type
+    }
+
+    /// Returns the size of this buffer in bytes.
+    pub fn size(&self) -> u32 {
+        self.index_count.saturating_mul(2)
+    }
+}
+
+/// An entry of the subresource table of a model descriptor whose type isn't decoded yet, holding
+/// the type and parameter as stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RawModelSubresource {
+    pub(crate) subres_type: ModelSubresType,
+    pub(crate) subres_param: u32,
+}
+
+impl RawModelSubresource {
+    pub fn subres_type(&self) -> ModelSubresType {
+        self.subres_type
+    }
+
+    /// Returns the parameter of this subresource, which is usually an offset into the descriptor.
+    pub fn subres_param(&self) -> u32 {
+        self.subres_param
+    }
+}
+
+/// A decoded entry of the subresource table of a model descriptor, see
+/// [`super::ModelDescriptor::subresources`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ModelSubresource {
+    /// The main model subresource, holding the descriptors of its meshes.
+    Model(Vec<MeshDescriptor>),
+    Skeleton(Skeleton),
+    Texture(Vec<TextureDescriptor>),
+    Material(Vec<Material>),
+    VertexBuffer(Vec<VertexBufferDescriptor>),
+    IndexBuffer(Vec<IndexBufferDescriptor>),
+    /// A subresource of a type that isn't decoded yet.
+    Unknown(RawModelSubresource),
+}
+
+impl ModelSubresource {
+    /// Decodes the subresource of the given type whose parameter is `subres_param`, reading any
+    /// lists it points to from `data`, the full model descriptor.
+    ///
+    /// The layouts of meshes, skeletons, materials and vertex and index buffers are unconfirmed,
+    /// so if one of those doesn't decode, the subresource is kept as [`ModelSubresource::Unknown`]
+    /// instead.
+    pub(crate) fn from_bytes(
+        data: &[u8],
+        subres_type: ModelSubresType,
+        subres_param: u32,
+    ) -> Result<Self, AssetParseError> {
+        let list_ptr = subres_param as usize;
+
+        let assumed = match subres_type {
+            ModelSubresType::Model => {
+                read_list(data, list_ptr, MeshDescriptor::from_bytes).map(ModelSubresource::Model)
+            }
+            ModelSubresType::Skeleton => {
+                Skeleton::from_bytes(data, list_ptr).map(ModelSubresource::Skeleton)
+            }
+            ModelSubresType::Texture => {
+                return Ok(ModelSubresource::Texture(read_list(
+                    data,
+                    list_ptr,
+                    TextureDescriptor::from_bytes,
+                )?));
+            }
+            ModelSubresType::Material => {
+                read_list(data, list_ptr, Material::from_bytes).map(ModelSubresource::Material)
+            }
+            ModelSubresType::VertexBuffer => {
+                read_list(data, list_ptr, VertexBufferDescriptor::from_bytes)
+                    .map(ModelSubresource::VertexBuffer)
+            }
+            ModelSubresType::IndexBuffer => {
+                read_list(data, list_ptr, IndexBufferDescriptor::from_bytes)
+                    .map(ModelSubresource::IndexBuffer)
+            }
+            _ => Err(AssetParseError::ErrorParsingDescriptor),
+        };
+
+        Ok(
+            assumed.unwrap_or(ModelSubresource::Unknown(RawModelSubresource {
+                subres_type,
+                subres_param,
+            })),
+        )
+    }
+
+    pub fn subres_type(&self) -> ModelSubresType {
+        match self {
+            ModelSubresource::Model(_) => ModelSubresType::Model,
+            ModelSubresource::Skeleton(_) => ModelSubresType::Skeleton,
+            ModelSubresource::Texture(_) => ModelSubresType::Texture,
+            ModelSubresource::Material(_) => ModelSubresType::Material,
+            ModelSubresource::VertexBuffer(_) => ModelSubresType::VertexBuffer,
+            ModelSubresource::IndexBuffer(_) => ModelSubresType::IndexBuffer,
+            ModelSubresource::Unknown(raw) => raw.subres_type,
+        }
+    }
+
+    /// Returns the number of records in the pointer list of this subresource and the size of
+    /// each one, or None if its type isn't decoded.
+    pub(crate) fn record_layout(&self) -> Option<(usize, usize)> {
+        match self {
+            ModelSubresource::Model(meshes) => Some((meshes.len(), MESH_DESCRIPTOR_SIZE)),
+            ModelSubresource::Skeleton(skeleton) => Some((skeleton.bones().len(), BONE_SIZE)),
+            ModelSubresource::Texture(textures) => Some((textures.len(), TEXTURE_DESCRIPTOR_SIZE)),
+            ModelSubresource::Material(materials) => Some((materials.len(), MATERIAL_SIZE)),
+            ModelSubresource::VertexBuffer(buffers) => Some((buffers.len(), VERTEX_BUFFER_SIZE)),
+            ModelSubresource::IndexBuffer(buffers) => Some((buffers.len(), INDEX_BUFFER_SIZE)),
+            ModelSubresource::Unknown(_) => None,
+        }
+    }
+
+    /// Serialises the records in the pointer list of this subresource, or returns None if its
+    /// type isn't decoded.
+    ///
+    /// # Errors
+    /// Returns an error if a record can't be serialised, such as a texture in a format without a
+    /// BNL format ID.
+    pub(crate) fn records(&self) -> Result<Option<Vec<Vec<u8>>>, io::Error> {
+        Ok(Some(match self {
+            ModelSubresource::Model(meshes) => {
+                meshes.iter().map(MeshDescriptor::to_bytes).collect()
+            }
+            ModelSubresource::Skeleton(skeleton) => skeleton
+                .bones()
+                .iter()
+                .map(|bone| bone.to_bytes())
+                .collect::<Result<_, _>>()?,
+            ModelSubresource::Texture(textures) => textures
+                .iter()
+                .map(TextureDescriptor::to_bytes)
+                .collect::<Result<_, _>>()?,
+            ModelSubresource::Material(materials) => {
+                materials.iter().map(Material::to_bytes).collect()
+            }
+            ModelSubresource::VertexBuffer(buffers) => buffers
+                .iter()
+                .map(VertexBufferDescriptor::to_bytes)
+                .collect(),
+            ModelSubresource::IndexBuffer(buffers) => buffers
+                .iter()
+                .map(IndexBufferDescriptor::to_bytes)
+                .collect(),
+            ModelSubresource::Unknown(_) => return Ok(None),
+        }))
+    }
+}
+
+/// Reads the pointer list at `list_ptr`, parsing the record each pointer points at.
+fn read_list<T>(
+    data: &[u8],
+    list_ptr: usize,
+    parse: impl Fn(&[u8]) -> Result<T, AssetParseError>,
+) -> Result<Vec<T>, AssetParseError> {
+    read_pointer_list(data, list_ptr)?
+        .into_iter()
+        .map(|ptr| parse(data.get(ptr..).ok_or(AssetParseError::InputTooSmall)?))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_typed_subresources() {
+        let mut data = vec![];
+
+        // Pointer list header at 0: one entry, list at 8, pointing at the record at 12
+        for value in [1u32, 8, 12] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let record_start = data.len();
+
+        data.extend_from_slice(&2i32.to_le_bytes());
+        for value in [1.0f32, 0.5, 0.25, 1.0, 0.0, 0.0, 0.0, 1.0, 8.0] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&3u32.to_le_bytes());
+
+        let ModelSubresource::Material(materials) =
+            ModelSubresource::from_bytes(&data, ModelSubresType::Material, 0).unwrap()
+        else {
+            panic!("Expected a material subresource");
+        };
+
+        assert_eq!(materials[0].texture_index(), Some(2));
+        assert_eq!(materials[0].diffuse(), [1.0, 0.5, 0.25, 1.0]);
+        assert_eq!(materials[0].specular_power(), 8.0);
+        assert_eq!(materials[0].flags(), 3);
+
+        // The same bytes read as buffers
+        let ModelSubresource::VertexBuffer(buffers) =
+            ModelSubresource::from_bytes(&data, ModelSubresType::VertexBuffer, 0).unwrap()
+        else {
+            panic!("Expected a vertex buffer subresource");
+        };
+        assert_eq!(buffers[0].offset(), 2);
+        assert_eq!(buffers[0].stride(), 0x3f00_0000);
+
+        // A record that doesn't fit is kept undecoded rather than failing the whole model
+        let subres =
+            ModelSubresource::from_bytes(&data[..record_start], ModelSubresType::IndexBuffer, 0)
+                .unwrap();
+        assert!(matches!(subres, ModelSubresource::Unknown(_)));
+        assert_eq!(subres.subres_type(), ModelSubresType::IndexBuffer);
+
+        let subres = ModelSubresource::from_bytes(&data, ModelSubresType::Skeleton, 0).unwrap();
+        assert!(matches!(subres, ModelSubresource::Unknown(_)));
+
+        let subres = ModelSubresource::from_bytes(&data, ModelSubresType::Unknown9, 0x40).unwrap();
+        assert_eq!(subres.subres_type(), ModelSubresType::Unknown9);
+        assert!(matches!(
+            subres,
+            ModelSubresource::Unknown(RawModelSubresource {
+                subres_param: 0x40,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,453 @@
+use std::{
+    fmt::{self, Display},
+    io::{self, Cursor},
+};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use crate::{
+    VirtualResource,
+    asset::{
+        AID_SIZE, Asset, AssetDescriptor, AssetParseError, RawAsset, WritableAsset, aid_from_bytes,
+        aid_to_bytes, write_fields,
+    },
+    game::AssetType,
+};
+
+/// The size of an [`EmitterDescriptor`].
+pub(crate) const EMITTER_DESCRIPTOR_SIZE: usize = AID_SIZE + 28;
+
+/// The size of a [`ParticleDescriptor`].
+pub(crate) const PARTICLE_DESCRIPTOR_SIZE: usize = AID_SIZE + 44;
+
+/// Copies `data` into a descriptor of `N` bytes, zero filling any fields missing from a
+/// descriptor shorter than the assumed layout. Also returns the original size if it was shorter,
+/// and any bytes following the assumed layout.
+fn split_known<const N: usize>(data: &[u8]) -> ([u8; N], Option<usize>, Vec<u8>) {
+    let mut known = [0u8; N];
+    let known_len = data.len().min(N);
+    known[..known_len].copy_from_slice(&data[..known_len]);
+
+    let short_len = (data.len() < N).then_some(data.len());
+
+    (known, short_len, data[known_len..].to_vec())
+}
+
+/// Adds the bytes kept by [`split_known`] back onto a serialised descriptor.
+fn join_known(mut bytes: Vec<u8>, short_len: Option<usize>, trailing: &[u8]) -> Vec<u8> {
+    bytes.extend_from_slice(trailing);
+
+    if let Some(len) = short_len {
+        bytes.truncate(len);
+    }
+
+    bytes
+}
+
+fn read_f32s<const N: usize>(cur: &mut Cursor<&[u8]>) -> Result<[f32; N], io::Error> {
+    let mut values = [0.0; N];
+
+    for value in &mut values {
+        *value = cur.read_f32::<LittleEndian>()?;
+    }
+
+    Ok(values)
+}
+
+/// Describes how an emitter spawns particles.
+///
+/// This layout is an unconfirmed guess that hasn't been checked against game files. A shorter
+/// descriptor is still read with the missing fields as zero, and any bytes past the assumed
+/// fields are kept so that the descriptor writes back unchanged.
+///
+/// Assumed layout:
+/// - particle AID (128 bytes, NUL padded)
+/// - emission rate in particles per second (f32)
+/// - maximum number of live particles (u32)
+/// - duration in seconds, or 0 to loop forever (f32)
+/// - spawn offset from the emitter (3 x f32)
+/// - flags (u32)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EmitterDescriptor {
+    pub particle: String,
+    pub emission_rate: f32,
+    pub max_particles: u32,
+    pub duration: f32,
+    pub offset: [f32; 3],
+    pub flags: u32,
+    /// Any bytes following the assumed fields.
+    pub trailing: Vec<u8>,
+    /// The size of the original descriptor if it was shorter than the assumed fields.
+    pub short_len: Option<usize>,
+}
+
+impl AssetDescriptor for EmitterDescriptor {
+    fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError> {
+        let (known, short_len, trailing) = split_known::<EMITTER_DESCRIPTOR_SIZE>(data);
+        let mut cur = Cursor::new(&known[AID_SIZE..]);
+
+        Ok(EmitterDescriptor {
+            particle: aid_from_bytes(&known[..AID_SIZE]),
+            emission_rate: cur.read_f32::<LittleEndian>()?,
+            max_particles: cur.read_u32::<LittleEndian>()?,
+            duration: cur.read_f32::<LittleEndian>()?,
+            offset: read_f32s(&mut cur)?,
+            flags: cur.read_u32::<LittleEndian>()?,
+            trailing,
+            short_len,
+        })
+    }
+}
+
+impl Display for EmitterDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_fields(
+            f,
+            &[
+                ("particle", self.particle.clone()),
+                ("emission_rate", format!("{}/s", self.emission_rate)),
+                ("max_particles", self.max_particles.to_string()),
+                ("duration", format!("{}s", self.duration)),
+                ("offset", format!("{:?}", self.offset)),
+                ("flags", format!("{:#010x}", self.flags)),
+            ],
+        )
+    }
+}
+
+/// A particle emitter, spawning the particles described by a [`Particle`].
+#[derive(Debug)]
+pub struct Emitter {
+    name: String,
+    descriptor: EmitterDescriptor,
+}
+
+impl Asset for Emitter {
+    type Descriptor = EmitterDescriptor;
+
+    fn new(
+        name: &str,
+        descriptor: &Self::Descriptor,
+        _virtual_res: &VirtualResource,
+    ) -> Result<Self, AssetParseError> {
+        Ok(Emitter {
+            name: name.to_string(),
+            descriptor: descriptor.clone(),
+        })
+    }
+
+    fn descriptor(&self) -> &Self::Descriptor {
+        &self.descriptor
+    }
+
+    fn asset_type() -> AssetType {
+        AssetType::ResEmitter
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Emitter {
+    /// Returns the AID of the particle this emitter spawns.
+    pub fn particle(&self) -> &str {
+        &self.descriptor.particle
+    }
+
+    pub fn emission_rate(&self) -> f32 {
+        self.descriptor.emission_rate
+    }
+
+    pub fn max_particles(&self) -> u32 {
+        self.descriptor.max_particles
+    }
+
+    /// Returns the duration of the emitter in seconds, or None if it loops forever.
+    pub fn duration(&self) -> Option<f32> {
+        (self.descriptor.duration > 0.0).then_some(self.descriptor.duration)
+    }
+
+    /// Returns the emitter for editing, see [`Emitter::to_bytes`].
+    pub fn descriptor_mut(&mut self) -> &mut EmitterDescriptor {
+        &mut self.descriptor
+    }
+
+    /// Serialises this emitter back into descriptor bytes.
+    ///
+    /// # Errors
+    /// Returns an error if the particle AID is too long to fit in its field.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, io::Error> {
+        let desc = &self.descriptor;
+
+        let mut bytes = Vec::with_capacity(EMITTER_DESCRIPTOR_SIZE);
+
+        bytes.extend_from_slice(&aid_to_bytes(&desc.particle)?);
+        bytes.extend_from_slice(&desc.emission_rate.to_le_bytes());
+        bytes.extend_from_slice(&desc.max_particles.to_le_bytes());
+        bytes.extend_from_slice(&desc.duration.to_le_bytes());
+
+        for value in desc.offset {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        bytes.extend_from_slice(&desc.flags.to_le_bytes());
+
+        Ok(join_known(bytes, desc.short_len, &desc.trailing))
+    }
+}
+
+impl WritableAsset for Emitter {
+    fn write_back(&self, raw_asset: &mut RawAsset) -> Result<(), io::Error> {
+        raw_asset.descriptor_bytes = self.to_bytes()?;
+        Ok(())
+    }
+}
+
+/// Describes how a single particle looks and moves over its lifetime.
+///
+/// Like [`EmitterDescriptor`], only the texture AID is likely to be right; the other fields are
+/// guessed from their types and haven't been checked against game files. Shorter and longer
+/// descriptors are handled the same way.
+///
+/// Assumed layout:
+/// - texture AID (128 bytes, NUL padded)
+/// - minimum and maximum lifetime in seconds (2 x f32)
+/// - start and end size (2 x f32)
+/// - start and end colour (2 x u32, ARGB)
+/// - initial velocity (3 x f32)
+/// - gravity scale (f32)
+/// - flags (u32)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParticleDescriptor {
+    pub texture: String,
+    pub lifetime: [f32; 2],
+    pub size: [f32; 2],
+    pub colour: [u32; 2],
+    pub velocity: [f32; 3],
+    pub gravity: f32,
+    pub flags: u32,
+    /// Any bytes following the assumed fields.
+    pub trailing: Vec<u8>,
+    /// The size of the original descriptor if it was shorter than the assumed fields.
+    pub short_len: Option<usize>,
+}
+
+impl AssetDescriptor for ParticleDescriptor {
+    fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError> {
+        let (known, short_len, trailing) = split_known::<PARTICLE_DESCRIPTOR_SIZE>(data);
+        let mut cur = Cursor::new(&known[AID_SIZE..]);
+
+        Ok(ParticleDescriptor {
+            texture: aid_from_bytes(&known[..AID_SIZE]),
+            lifetime: read_f32s(&mut cur)?,
+            size: read_f32s(&mut cur)?,
+            colour: [
+                cur.read_u32::<LittleEndian>()?,
+                cur.read_u32::<LittleEndian>()?,
+            ],
+            velocity: read_f32s(&mut cur)?,
+            gravity: cur.read_f32::<LittleEndian>()?,
+            flags: cur.read_u32::<LittleEndian>()?,
+            trailing,
+            short_len,
+        })
+    }
+}
+
+impl Display for ParticleDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_fields(
+            f,
+            &[
+                ("texture", self.texture.clone()),
+                (
+                    "lifetime",
+                    format!("{}s - {}s", self.lifetime[0], self.lifetime[1]),
+                ),
+                ("size", format!("{} -> {}", self.size[0], self.size[1])),
+                (
+                    "colour",
+                    format!("{:#010x} -> {:#010x}", self.colour[0], self.colour[1]),
+                ),
+                ("velocity", format!("{:?}", self.velocity)),
+                ("gravity", self.gravity.to_string()),
+                ("flags", format!("{:#010x}", self.flags)),
+            ],
+        )
+    }
+}
+
+/// The appearance and motion of the particles spawned by an [`Emitter`].
+#[derive(Debug)]
+pub struct Particle {
+    name: String,
+    descriptor: ParticleDescriptor,
+}
+
+impl Asset for Particle {
+    type Descriptor = ParticleDescriptor;
+
+    fn new(
+        name: &str,
+        descriptor: &Self::Descriptor,
+        _virtual_res: &VirtualResource,
+    ) -> Result<Self, AssetParseError> {
+        Ok(Particle {
+            name: name.to_string(),
+            descriptor: descriptor.clone(),
+        })
+    }
+
+    fn descriptor(&self) -> &Self::Descriptor {
+        &self.descriptor
+    }
+
+    fn asset_type() -> AssetType {
+        AssetType::ResParticle
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Particle {
+    /// Returns the AID of the texture drawn for each particle.
+    pub fn texture(&self) -> &str {
+        &self.descriptor.texture
+    }
+
+    /// Returns the minimum and maximum lifetime of a particle in seconds.
+    pub fn lifetime(&self) -> [f32; 2] {
+        self.descriptor.lifetime
+    }
+
+    /// Returns the particle for editing, see [`Particle::to_bytes`].
+    pub fn descriptor_mut(&mut self) -> &mut ParticleDescriptor {
+        &mut self.descriptor
+    }
+
+    /// Serialises this particle back into descriptor bytes.
+    ///
+    /// # Errors
+    /// Returns an error if the texture AID is too long to fit in its field.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bnl::{BNLFile, asset::particle::Particle};
+    /// # let bnl = BNLFile::new();
+    /// let mut particle: Particle = bnl.get_asset("aid_particle_smoke").expect("Unable to get particle.");
+    /// particle.descriptor_mut().lifetime[1] = 4.0;
+    ///
+    /// let mut raw_asset = bnl.get_raw_asset("aid_particle_smoke").expect("Unable to get raw asset.");
+    /// raw_asset.descriptor_bytes = particle.to_bytes().expect("Unable to serialise particle.");
+    /// ```
+    pub fn to_bytes(&self) -> Result<Vec<u8>, io::Error> {
+        let desc = &self.descriptor;
+
+        let mut bytes = Vec::with_capacity(PARTICLE_DESCRIPTOR_SIZE);
+
+        bytes.extend_from_slice(&aid_to_bytes(&desc.texture)?);
+
+        for value in desc.lifetime.iter().chain(desc.size.iter()) {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        for value in desc.colour {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        for value in desc.velocity.iter().chain([desc.gravity].iter()) {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        bytes.extend_from_slice(&desc.flags.to_le_bytes());
+
+        Ok(join_known(bytes, desc.short_len, &desc.trailing))
+    }
+}
+
+impl WritableAsset for Particle {
+    fn write_back(&self, raw_asset: &mut RawAsset) -> Result<(), io::Error> {
+        raw_asset.descriptor_bytes = self.to_bytes()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_f32s(bytes: &mut Vec<u8>, values: &[f32]) {
+        values
+            .iter()
+            .for_each(|value| bytes.extend_from_slice(&value.to_le_bytes()));
+    }
+
+    #[test]
+    fn emitter_round_trip() {
+        let mut data = aid_to_bytes("aid_particle_smoke").unwrap().to_vec();
+        push_f32s(&mut data, &[20.0]);
+        data.extend_from_slice(&64u32.to_le_bytes());
+        push_f32s(&mut data, &[0.0, 1.0, 2.0, 3.0]);
+        data.extend_from_slice(&1u32.to_le_bytes());
+
+        let descriptor = EmitterDescriptor::from_bytes(&data).unwrap();
+        let slices: [&[u8]; 0] = [];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let emitter = Emitter::new("aid_emitter_test", &descriptor, &virtual_res).unwrap();
+
+        assert_eq!(emitter.particle(), "aid_particle_smoke");
+        assert_eq!(emitter.max_particles(), 64);
+        assert_eq!(emitter.duration(), None);
+        assert_eq!(emitter.descriptor().offset, [1.0, 2.0, 3.0]);
+        assert_eq!(emitter.to_bytes().unwrap(), data);
+
+        // Bytes past the assumed layout survive a round trip
+        data.extend_from_slice(&[9, 9]);
+        let descriptor = EmitterDescriptor::from_bytes(&data).unwrap();
+        assert_eq!(descriptor.trailing, [9, 9]);
+
+        let emitter = Emitter::new("aid_emitter_test", &descriptor, &virtual_res).unwrap();
+        assert_eq!(emitter.to_bytes().unwrap(), data);
+    }
+
+    #[test]
+    fn particle_round_trip() {
+        let mut data = aid_to_bytes("aid_texture_smoke").unwrap().to_vec();
+        push_f32s(&mut data, &[0.5, 1.5, 1.0, 4.0]);
+        data.extend_from_slice(&0xffffffffu32.to_le_bytes());
+        data.extend_from_slice(&0x00808080u32.to_le_bytes());
+        push_f32s(&mut data, &[0.0, 2.0, 0.0, -0.5]);
+        data.extend_from_slice(&2u32.to_le_bytes());
+
+        let descriptor = ParticleDescriptor::from_bytes(&data).unwrap();
+        let slices: [&[u8]; 0] = [];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let mut particle = Particle::new("aid_particle_test", &descriptor, &virtual_res).unwrap();
+
+        assert_eq!(particle.texture(), "aid_texture_smoke");
+        assert_eq!(particle.lifetime(), [0.5, 1.5]);
+        assert_eq!(particle.descriptor().colour[1], 0x00808080);
+        assert_eq!(particle.descriptor().gravity, -0.5);
+        assert_eq!(particle.to_bytes().unwrap(), data);
+
+        particle.descriptor_mut().size[1] = 8.0;
+        let descriptor = ParticleDescriptor::from_bytes(&particle.to_bytes().unwrap()).unwrap();
+        assert_eq!(descriptor.size, [1.0, 8.0]);
+
+        // A shorter descriptor keeps the fields it has and writes back at the same size
+        let short = &data[..AID_SIZE + 8];
+        let descriptor = ParticleDescriptor::from_bytes(short).unwrap();
+        assert_eq!(descriptor.lifetime, [0.5, 1.5]);
+        assert_eq!(descriptor.size, [0.0, 0.0]);
+
+        let particle = Particle::new("aid_particle_test", &descriptor, &virtual_res).unwrap();
+        assert_eq!(particle.to_bytes().unwrap(), short);
+    }
+}
@@ -0,0 +1,319 @@
+//! Parses assets whose type is only known at runtime. [`Asset`] is statically dispatched, so a
+//! tool that handles every asset in a bundle would otherwise need a `match` over every type. An
+//! [`AssetRegistry`] maps each [`AssetType`] to a parser instead, producing a [`dyn AnyAsset`]
+//! that can be inspected generically or downcast back to its concrete type.
+//!
+//! Downstream crates can register parsers for asset types this crate doesn't understand yet.
+//!
+//! [`dyn AnyAsset`]: AnyAsset
+
+use std::{any::Any, collections::HashMap, fmt::Display, marker::PhantomData};
+
+use crate::{
+    BNLFile,
+    asset::{
+        Asset, AssetDescriptor, AssetError, AssetParseError, RawAsset,
+        actor_attribs::ActorAttribs,
+        aid_list::AidList,
+        anim::Animation,
+        events::{AnimEvents, CutsceneEvents},
+        feedback::{Rumble, ShakeCam},
+        font::Font,
+        ghoulybox::{Ghoulybox, Ghoulyspawn},
+        loctext::Loctext,
+        marker::Marker,
+        model::Model,
+        particle::{Emitter, Particle},
+        soundbank::Soundbank,
+        texture::Texture,
+    },
+    game::AssetType,
+};
+
+/// An asset of any type, as returned by [`AssetRegistry::parse`].
+pub trait AnyAsset: Any {
+    fn name(&self) -> &str;
+
+    fn asset_type(&self) -> AssetType;
+
+    /// Describes the descriptor of this asset, one `name: value` line per field.
+    fn describe(&self) -> String;
+
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<A> AnyAsset for A
+where
+    A: Asset + 'static,
+    A::Descriptor: Display,
+{
+    fn name(&self) -> &str {
+        Asset::name(self)
+    }
+
+    fn asset_type(&self) -> AssetType {
+        A::asset_type()
+    }
+
+    fn describe(&self) -> String {
+        self.descriptor().to_string()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl dyn AnyAsset {
+    /// Returns this asset as an `A`, or None if it's a different type.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use std::path::Path;
+    /// # use bnl::asset::texture::Texture;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let bnl_file = bnl::BNLFile::new();
+    /// let asset = bnl_file.get_asset_dyn("aid_texture_gribble")?;
+    ///
+    /// if let Some(texture) = asset.downcast_ref::<Texture>() {
+    ///     texture.dump(Path::new("./gribble.png"))?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn downcast_ref<A: AnyAsset>(&self) -> Option<&A> {
+        self.as_any().downcast_ref()
+    }
+}
+
+/// Parses assets of a single type for an [`AssetRegistry`].
+pub trait AnyAssetParser: Send + Sync {
+    /// Parses `raw_asset` into an asset.
+    fn parse(&self, raw_asset: &RawAsset) -> Result<Box<dyn AnyAsset>, AssetError>;
+
+    /// Parses only the descriptor of an asset, and describes it like [`AnyAsset::describe`].
+    fn describe_descriptor(&self, descriptor_bytes: &[u8]) -> Result<String, AssetParseError>;
+}
+
+/// The [`AnyAssetParser`] of an [`Asset`] type.
+struct TypedParser<A>(PhantomData<fn() -> A>);
+
+impl<A> AnyAssetParser for TypedParser<A>
+where
+    A: Asset + 'static,
+    A::Descriptor: Display,
+{
+    fn parse(&self, raw_asset: &RawAsset) -> Result<Box<dyn AnyAsset>, AssetError> {
+        Ok(Box::new(raw_asset.parse::<A>()?))
+    }
+
+    fn describe_descriptor(&self, descriptor_bytes: &[u8]) -> Result<String, AssetParseError> {
+        A::Descriptor::from_bytes(descriptor_bytes).map(|descriptor| descriptor.to_string())
+    }
+}
+
+/// Maps asset types to the parsers for them. The default registry holds every asset type this
+/// crate can parse.
+///
+/// # Examples
+/// ```
+/// use bnl::{asset::registry::AssetRegistry, game::AssetType};
+/// # use bnl::asset::{AssetError, AssetParseError, RawAsset, registry::{AnyAsset, AnyAssetParser}};
+/// # struct MyScriptParser;
+/// # impl AnyAssetParser for MyScriptParser {
+/// #     fn parse(&self, _: &RawAsset) -> Result<Box<dyn AnyAsset>, AssetError> { unimplemented!() }
+/// #     fn describe_descriptor(&self, _: &[u8]) -> Result<String, AssetParseError> { unimplemented!() }
+/// # }
+/// # let bnl_file = bnl::BNLFile::new();
+///
+/// let registry = AssetRegistry::default().register_parser(AssetType::ResScript, MyScriptParser);
+///
+/// for raw_asset in bnl_file.get_raw_assets() {
+///     match registry.parse(&raw_asset) {
+///         Ok(asset) => println!("{}:\n{}", asset.name(), asset.describe()),
+///         Err(e) => eprintln!("{}", e),
+///     }
+/// }
+/// ```
+pub struct AssetRegistry {
+    parsers: HashMap<AssetType, Box<dyn AnyAssetParser>>,
+}
+
+impl Default for AssetRegistry {
+    fn default() -> Self {
+        AssetRegistry::empty()
+            .register::<ActorAttribs>()
+            .register::<AidList>()
+            .register::<Animation>()
+            .register::<AnimEvents>()
+            .register::<CutsceneEvents>()
+            .register::<Font>()
+            .register::<Ghoulybox>()
+            .register::<Ghoulyspawn>()
+            .register::<Loctext>()
+            .register::<Marker>()
+            .register::<Model>()
+            .register::<Emitter>()
+            .register::<Particle>()
+            .register::<Rumble>()
+            .register::<ShakeCam>()
+            .register::<Soundbank>()
+            .register::<Texture>()
+    }
+}
+
+impl AssetRegistry {
+    /// Returns a registry without any parsers.
+    pub fn empty() -> Self {
+        AssetRegistry {
+            parsers: HashMap::new(),
+        }
+    }
+
+    /// Parses assets of type `A::asset_type()` as an `A`.
+    pub fn register<A>(self) -> Self
+    where
+        A: Asset + 'static,
+        A::Descriptor: Display,
+    {
+        self.register_parser(A::asset_type(), TypedParser::<A>(PhantomData))
+    }
+
+    /// Parses assets of `asset_type` with `parser`, replacing any parser already registered for
+    /// it.
+    pub fn register_parser(
+        mut self,
+        asset_type: AssetType,
+        parser: impl AnyAssetParser + 'static,
+    ) -> Self {
+        self.parsers.insert(asset_type, Box::new(parser));
+        self
+    }
+
+    /// Returns the parser for assets of `asset_type`, if there is one.
+    pub fn get(&self, asset_type: AssetType) -> Option<&dyn AnyAssetParser> {
+        self.parsers.get(&asset_type).map(Box::as_ref)
+    }
+
+    /// Parses `raw_asset` with the parser registered for its type.
+    ///
+    /// # Errors
+    /// Returns [`AssetError::ParseError`] if no parser is registered for the type, or the parser
+    /// fails.
+    pub fn parse(&self, raw_asset: &RawAsset) -> Result<Box<dyn AnyAsset>, AssetError> {
+        self.get(raw_asset.asset_type)
+            .ok_or_else(|| AssetParseError::ParserNotImplemented.in_asset(&raw_asset.name))?
+            .parse(raw_asset)
+    }
+
+    /// Describes the descriptor of `raw_asset` with the parser registered for its type, without
+    /// parsing its resources. Returns None if no parser is registered for the type.
+    pub fn describe_descriptor(
+        &self,
+        raw_asset: &RawAsset,
+    ) -> Option<Result<String, AssetParseError>> {
+        self.get(raw_asset.asset_type)
+            .map(|parser| parser.describe_descriptor(&raw_asset.descriptor_bytes))
+    }
+}
+
+impl BNLFile {
+    /// Retrieves an asset by name without knowing its type ahead of time, using the default
+    /// [`AssetRegistry`].
+    ///
+    /// # Errors
+    /// Returns an [`AssetError`] if the asset can't be found, its type has no parser, or it can't
+    /// be parsed.
+    pub fn get_asset_dyn(&self, name: &str) -> Result<Box<dyn AnyAsset>, AssetError> {
+        self.get_asset_dyn_with(name, &AssetRegistry::default())
+    }
+
+    /// Retrieves an asset by name like [`BNLFile::get_asset_dyn`], using the parsers of
+    /// `registry`.
+    ///
+    /// # Errors
+    /// Returns an [`AssetError`] if the asset can't be found, its type has no parser, or it can't
+    /// be parsed.
+    pub fn get_asset_dyn_with(
+        &self,
+        name: &str,
+        registry: &AssetRegistry,
+    ) -> Result<Box<dyn AnyAsset>, AssetError> {
+        registry.parse(&self.get_raw_asset(name)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{bnl_from_raw_assets, make_bnl};
+
+    /// A stand-in for an asset type that a downstream crate has reverse-engineered.
+    struct Script {
+        name: String,
+    }
+
+    impl AnyAsset for Script {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn asset_type(&self) -> AssetType {
+            AssetType::ResScript
+        }
+
+        fn describe(&self) -> String {
+            "script\n".to_string()
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    struct ScriptParser;
+
+    impl AnyAssetParser for ScriptParser {
+        fn parse(&self, raw_asset: &RawAsset) -> Result<Box<dyn AnyAsset>, AssetError> {
+            Ok(Box::new(Script {
+                name: raw_asset.name.clone(),
+            }))
+        }
+
+        fn describe_descriptor(&self, _: &[u8]) -> Result<String, AssetParseError> {
+            Ok("script\n".to_string())
+        }
+    }
+
+    #[test]
+    fn parses_through_registry() {
+        let bnl = BNLFile::from_bytes(&make_bnl()).unwrap();
+
+        let asset = bnl.get_asset_dyn("aid_texture_test").unwrap();
+        assert_eq!(asset.name(), "aid_texture_test");
+        assert_eq!(asset.asset_type(), AssetType::ResTexture);
+        assert!(asset.describe().contains("width"));
+        assert!(asset.downcast_ref::<Texture>().is_some());
+        assert!(asset.downcast_ref::<Model>().is_none());
+
+        let bnl = bnl_from_raw_assets(&[RawAsset {
+            name: "aid_script_a".to_string(),
+            asset_type: AssetType::ResScript,
+            descriptor_bytes: vec![],
+            data_slices: vec![vec![0]],
+        }]);
+
+        assert!(matches!(
+            bnl.get_asset_dyn("aid_script_a"),
+            Err(AssetError::ParseError {
+                source: AssetParseError::ParserNotImplemented,
+                ..
+            })
+        ));
+
+        let registry = AssetRegistry::default().register_parser(AssetType::ResScript, ScriptParser);
+        let asset = bnl.get_asset_dyn_with("aid_script_a", &registry).unwrap();
+
+        assert_eq!(asset.downcast_ref::<Script>().unwrap().name, "aid_script_a");
+    }
+}
@@ -0,0 +1,522 @@
+use std::{
+    fmt::{self, Display},
+    fs,
+    io::{self, Cursor, Seek, SeekFrom},
+    path::Path,
+};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use crate::{
+    VirtualResource,
+    asset::{Asset, AssetDescriptor, AssetParseError, write_fields},
+    game::AssetType,
+};
+
+/// The size of the fixed part of a [`SoundbankDescriptor`].
+const SOUNDBANK_DESCRIPTOR_SIZE: usize = 16;
+
+/// The signature at the start of an XACT wave bank.
+const WAVE_BANK_SIGNATURE: &[u8; 4] = b"WBND";
+
+/// The size of a single wave bank entry's metadata.
+const WAVE_ENTRY_SIZE: usize = 24;
+
+/// The number of samples in each Xbox ADPCM block, per channel.
+const XBOX_ADPCM_SAMPLES_PER_BLOCK: u16 = 64;
+
+/// The size of an Xbox ADPCM block, per channel.
+const XBOX_ADPCM_BLOCK_SIZE: u16 = 36;
+
+/// The codec that a wave is stored in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaveCodec {
+    Pcm,
+    XboxAdpcm,
+    Unknown(u32),
+}
+
+/// The format of a wave, unpacked from the bitfield used by XACT (WAVEBANKMINIWAVEFORMAT).
+///
+/// Bits (from least significant):
+/// - 0-1: codec (0 = PCM, 1 = Xbox ADPCM)
+/// - 2-4: channel count
+/// - 5-22: sample rate
+/// - 23-30: block alignment
+/// - 31: bits per sample (0 = 8, 1 = 16)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaveFormat {
+    pub codec: WaveCodec,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub block_align: u16,
+    pub bits_per_sample: u16,
+}
+
+impl WaveFormat {
+    fn from_bits(bits: u32) -> WaveFormat {
+        let codec = match bits & 0x3 {
+            0 => WaveCodec::Pcm,
+            1 => WaveCodec::XboxAdpcm,
+            other => WaveCodec::Unknown(other),
+        };
+
+        WaveFormat {
+            codec,
+            channels: ((bits >> 2) & 0x7) as u16,
+            sample_rate: (bits >> 5) & 0x3ffff,
+            block_align: ((bits >> 23) & 0xff) as u16,
+            bits_per_sample: if bits >> 31 == 1 { 16 } else { 8 },
+        }
+    }
+}
+
+/// A single wave within a [`Soundbank`].
+#[derive(Debug, Clone)]
+pub struct Wave {
+    format: WaveFormat,
+    loop_start: u32,
+    loop_length: u32,
+    data: Vec<u8>,
+}
+
+impl Wave {
+    pub fn format(&self) -> WaveFormat {
+        self.format
+    }
+
+    /// Returns the loop region as (start, length) in samples, or None if the wave doesn't loop.
+    pub fn loop_region(&self) -> Option<(u32, u32)> {
+        (self.loop_length > 0).then_some((self.loop_start, self.loop_length))
+    }
+
+    /// Returns the raw sample data of this wave, encoded with its [`WaveCodec`].
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Wraps the sample data of this wave in a RIFF WAVE file. Xbox ADPCM waves are written with
+    /// format tag 0x0069, which most audio tools can decode.
+    ///
+    /// # Errors
+    /// Returns an error if the wave uses an unknown codec.
+    pub fn to_wav(&self) -> Result<Vec<u8>, io::Error> {
+        let channels = self.format.channels.max(1);
+
+        // The fmt chunk fields that depend on the codec, and any extra bytes after them
+        let (format_tag, block_align, bits_per_sample, extra): (u16, u16, u16, Vec<u8>) =
+            match self.format.codec {
+                WaveCodec::Pcm => (
+                    1,
+                    channels * self.format.bits_per_sample / 8,
+                    self.format.bits_per_sample,
+                    vec![],
+                ),
+                WaveCodec::XboxAdpcm => (
+                    0x0069,
+                    channels * XBOX_ADPCM_BLOCK_SIZE,
+                    4,
+                    [2u16, XBOX_ADPCM_SAMPLES_PER_BLOCK]
+                        .iter()
+                        .flat_map(|value| value.to_le_bytes())
+                        .collect(),
+                ),
+                WaveCodec::Unknown(codec) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        format!("Unable to write a WAV file for unknown codec {}", codec),
+                    ));
+                }
+            };
+
+        let byte_rate = match self.format.codec {
+            WaveCodec::XboxAdpcm => {
+                self.format.sample_rate * block_align as u32 / XBOX_ADPCM_SAMPLES_PER_BLOCK as u32
+            }
+            _ => self.format.sample_rate * block_align as u32,
+        };
+
+        let fmt_size = 16 + extra.len() as u32;
+
+        let mut wav = Vec::with_capacity(self.data.len() + 64);
+
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(4 + 8 + fmt_size + 8 + self.data.len() as u32).to_le_bytes());
+        wav.extend_from_slice(b"WAVE");
+
+        wav.extend_from_slice(b"fmt ");
+        wav.extend_from_slice(&fmt_size.to_le_bytes());
+        wav.extend_from_slice(&format_tag.to_le_bytes());
+        wav.extend_from_slice(&channels.to_le_bytes());
+        wav.extend_from_slice(&self.format.sample_rate.to_le_bytes());
+        wav.extend_from_slice(&byte_rate.to_le_bytes());
+        wav.extend_from_slice(&block_align.to_le_bytes());
+        wav.extend_from_slice(&bits_per_sample.to_le_bytes());
+        wav.extend_from_slice(&extra);
+
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(self.data.len() as u32).to_le_bytes());
+        wav.extend_from_slice(&self.data);
+
+        Ok(wav)
+    }
+
+    /// Writes this wave to `path` as a RIFF WAVE file, see [`Wave::to_wav`].
+    pub fn export_wav(&self, path: &Path) -> Result<(), io::Error> {
+        fs::write(path, self.to_wav()?)
+    }
+}
+
+/// The cue names of a [`Soundbank`] and the location of its wave bank.
+///
+/// This layout is a guess that hasn't been checked against game files. If it doesn't match, cue
+/// names past the end of the descriptor are dropped, and [`Soundbank`] is kept without waves
+/// rather than failing, see [`Soundbank::wave_bank_error`].
+///
+/// Assumed layout (all little endian u32):
+/// - cue count
+/// - offset of the cue names within the descriptor, stored back to back and NUL terminated
+/// - offset of the XACT wave bank within the resource
+/// - size of the wave bank
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SoundbankDescriptor {
+    cue_names: Vec<String>,
+    wave_bank_offset: u32,
+    wave_bank_size: u32,
+}
+
+impl Display for SoundbankDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_fields(
+            f,
+            &[
+                ("cue_count", self.cue_names.len().to_string()),
+                ("wave_bank_offset", format!("{:#x}", self.wave_bank_offset)),
+                ("wave_bank_size", format!("{:#x}", self.wave_bank_size)),
+            ],
+        )?;
+
+        for (i, cue_name) in self.cue_names.iter().enumerate() {
+            writeln!(f, "cue {}: {}", i, cue_name)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl AssetDescriptor for SoundbankDescriptor {
+    fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError> {
+        if data.len() < SOUNDBANK_DESCRIPTOR_SIZE {
+            return Err(AssetParseError::InputTooSmall);
+        }
+
+        let mut cur = Cursor::new(data);
+
+        let cue_count = cur.read_u32::<LittleEndian>()? as usize;
+        let cue_names_offset = cur.read_u32::<LittleEndian>()? as usize;
+        let wave_bank_offset = cur.read_u32::<LittleEndian>()?;
+        let wave_bank_size = cur.read_u32::<LittleEndian>()?;
+
+        let cue_names = data
+            .get(cue_names_offset..)
+            .unwrap_or_default()
+            .split(|byte| *byte == 0)
+            .take(cue_count)
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+            .collect();
+
+        Ok(SoundbankDescriptor {
+            cue_names,
+            wave_bank_offset,
+            wave_bank_size,
+        })
+    }
+}
+
+/// An XACT sound bank, made up of named cues and the waves they play.
+#[derive(Debug)]
+pub struct Soundbank {
+    name: String,
+    descriptor: SoundbankDescriptor,
+    wave_bank_name: String,
+    waves: Vec<Wave>,
+    wave_bank_error: Option<AssetParseError>,
+    wave_errors: Vec<(usize, AssetParseError)>,
+}
+
+impl Asset for Soundbank {
+    type Descriptor = SoundbankDescriptor;
+
+    fn new(
+        name: &str,
+        descriptor: &Self::Descriptor,
+        virtual_res: &VirtualResource,
+    ) -> Result<Self, AssetParseError> {
+        let mut soundbank = Soundbank {
+            name: name.to_string(),
+            descriptor: descriptor.clone(),
+            wave_bank_name: String::new(),
+            waves: vec![],
+            wave_bank_error: None,
+            wave_errors: vec![],
+        };
+
+        let wave_bank = virtual_res
+            .get_bytes(
+                descriptor.wave_bank_offset as usize,
+                descriptor.wave_bank_size as usize,
+            )
+            .map_err(|e| {
+                AssetParseError::InvalidDataViews(format!(
+                    "Unable to read wave bank at offset {}: {}",
+                    descriptor.wave_bank_offset, e
+                ))
+            });
+
+        match wave_bank.and_then(|wave_bank| parse_wave_bank(&wave_bank)) {
+            Ok((wave_bank_name, waves)) => {
+                soundbank.wave_bank_name = wave_bank_name;
+
+                for (i, wave) in waves.into_iter().enumerate() {
+                    match wave {
+                        Ok(wave) => soundbank.waves.push(wave),
+                        Err(e) => soundbank.wave_errors.push((i, e)),
+                    }
+                }
+            }
+            Err(e) => soundbank.wave_bank_error = Some(e),
+        }
+
+        Ok(soundbank)
+    }
+
+    fn descriptor(&self) -> &Self::Descriptor {
+        &self.descriptor
+    }
+
+    fn asset_type() -> AssetType {
+        AssetType::ResXSoundbank
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Parses an XACT wave bank, returning its name and the result of reading each wave.
+///
+/// This follows the wave bank layout of later XACT versions, which hasn't been checked against
+/// the wave banks in game files. Assumed layout:
+/// - "WBND" signature, version (u32)
+/// - (offset, length) u32 pairs for the bank data, entry metadata and wave data segments
+/// - Bank data: flags (u32), entry count (u32), name (16 bytes, NUL padded)
+/// - Entry metadata, per entry: flags and duration, [`WaveFormat`] bits, play region offset
+///   (relative to the wave data segment) and length, loop start and length (all u32)
+fn parse_wave_bank(
+    bytes: &[u8],
+) -> Result<(String, Vec<Result<Wave, AssetParseError>>), AssetParseError> {
+    if bytes.len() < 32 || &bytes[..4] != WAVE_BANK_SIGNATURE {
+        return Err(AssetParseError::InvalidDataViews(
+            "Wave bank doesn't start with WBND".to_string(),
+        ));
+    }
+
+    let mut cur = Cursor::new(bytes);
+    cur.seek(SeekFrom::Start(8))?;
+
+    let mut segments = [(0usize, 0usize); 3];
+
+    for segment in &mut segments {
+        *segment = (
+            cur.read_u32::<LittleEndian>()? as usize,
+            cur.read_u32::<LittleEndian>()? as usize,
+        );
+    }
+
+    let [bank_data, metadata, wave_data] = segments;
+
+    let segment = |(offset, length): (usize, usize)| {
+        bytes
+            .get(offset..offset.saturating_add(length))
+            .ok_or_else(|| {
+                AssetParseError::InvalidDataViews(format!(
+                    "Wave bank segment at offset {} is out of bounds",
+                    offset
+                ))
+            })
+    };
+
+    let bank_data = segment(bank_data)?;
+    let metadata = segment(metadata)?;
+    let wave_data = segment(wave_data)?;
+
+    if bank_data.len() < 24 {
+        return Err(AssetParseError::InputTooSmall);
+    }
+
+    let entry_count = u32::from_le_bytes(bank_data[4..8].try_into().unwrap()) as usize;
+
+    let bank_name = String::from_utf8_lossy(&bank_data[8..24])
+        .split('\0')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+
+    // Entries that don't fit in the metadata segment are dropped
+    let waves = metadata
+        .chunks_exact(WAVE_ENTRY_SIZE)
+        .take(entry_count)
+        .map(|entry| {
+            let field = |i: usize| u32::from_le_bytes(entry[i * 4..i * 4 + 4].try_into().unwrap());
+
+            let (play_offset, play_length) = (field(2) as usize, field(3) as usize);
+
+            let data = wave_data
+                .get(play_offset..play_offset.saturating_add(play_length))
+                .ok_or_else(|| {
+                    AssetParseError::InvalidDataViews(format!(
+                        "Wave data at offset {} is out of bounds",
+                        play_offset
+                    ))
+                })?;
+
+            Ok(Wave {
+                format: WaveFormat::from_bits(field(1)),
+                loop_start: field(4),
+                loop_length: field(5),
+                data: data.to_vec(),
+            })
+        })
+        .collect();
+
+    Ok((bank_name, waves))
+}
+
+impl Soundbank {
+    /// Returns the names of the cues in this sound bank.
+    pub fn cue_names(&self) -> &[String] {
+        &self.descriptor.cue_names
+    }
+
+    /// Returns the name stored in the wave bank header.
+    pub fn wave_bank_name(&self) -> &str {
+        &self.wave_bank_name
+    }
+
+    /// Returns the waves of this sound bank, leaving out any that couldn't be read, see
+    /// [`Soundbank::wave_errors`].
+    pub fn waves(&self) -> &[Wave] {
+        &self.waves
+    }
+
+    /// Returns the reason the wave bank couldn't be read, if it couldn't. The sound bank then has
+    /// no waves.
+    pub fn wave_bank_error(&self) -> Option<&AssetParseError> {
+        self.wave_bank_error.as_ref()
+    }
+
+    /// Returns the index of each wave bank entry whose data couldn't be read, along with the
+    /// reason.
+    pub fn wave_errors(&self) -> &[(usize, AssetParseError)] {
+        &self.wave_errors
+    }
+
+    /// Writes every wave of this sound bank into `dir` as `<wave bank name>_<index>.wav`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use std::path::Path;
+    /// # use bnl::{BNLFile, asset::soundbank::Soundbank};
+    /// # let bnl = BNLFile::new();
+    /// let soundbank: Soundbank = bnl.get_asset("aid_xsoundbank_music").expect("Unable to get sounds.");
+    /// soundbank.export_wavs(Path::new("./music")).expect("Unable to export sounds.");
+    /// ```
+    pub fn export_wavs(&self, dir: &Path) -> Result<(), io::Error> {
+        fs::create_dir_all(dir)?;
+
+        for (i, wave) in self.waves.iter().enumerate() {
+            wave.export_wav(&dir.join(format!("{}_{}.wav", self.wave_bank_name, i)))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_u32s(bytes: &mut Vec<u8>, values: &[u32]) {
+        values
+            .iter()
+            .for_each(|value| bytes.extend_from_slice(&value.to_le_bytes()));
+    }
+
+    #[test]
+    fn parses_wave_bank() {
+        let mut descriptor = vec![];
+        push_u32s(&mut descriptor, &[2, 16, 0, 84]);
+        descriptor.extend_from_slice(b"music_intro\0music_loop\0");
+
+        // 16 bit mono PCM at 22050Hz, with a block alignment of 2
+        let format = (1 << 31) | (2 << 23) | (22050 << 5) | (1 << 2);
+
+        let mut resource = vec![];
+        resource.extend_from_slice(b"WBND");
+        push_u32s(&mut resource, &[1, 32, 24, 56, 24, 80, 4]);
+
+        resource.extend_from_slice(&[0; 8]);
+        resource.extend_from_slice(b"music\0\0\0\0\0\0\0\0\0\0\0");
+        resource[36..40].copy_from_slice(&1u32.to_le_bytes());
+
+        push_u32s(&mut resource, &[0, format, 0, 4, 0, 0]);
+        resource.extend_from_slice(&[1, 2, 3, 4]);
+
+        let descriptor = SoundbankDescriptor::from_bytes(&descriptor).unwrap();
+
+        let slices = [resource.as_slice()];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let soundbank = Soundbank::new("aid_xsoundbank_test", &descriptor, &virtual_res).unwrap();
+
+        assert_eq!(soundbank.cue_names(), ["music_intro", "music_loop"]);
+        assert_eq!(soundbank.wave_bank_name(), "music");
+        assert_eq!(soundbank.waves().len(), 1);
+
+        let wave = &soundbank.waves()[0];
+        assert_eq!(wave.format().codec, WaveCodec::Pcm);
+        assert_eq!(wave.format().sample_rate, 22050);
+        assert_eq!(wave.format().bits_per_sample, 16);
+        assert_eq!(wave.loop_region(), None);
+
+        let wav = wave.to_wav().unwrap();
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(wav[28..32].try_into().unwrap()), 44100);
+        assert_eq!(&wav[wav.len() - 4..], [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn keeps_cues_without_wave_bank() {
+        let mut descriptor = vec![];
+        push_u32s(&mut descriptor, &[3, 16, 0, 84]);
+        descriptor.extend_from_slice(b"music_intro");
+
+        let descriptor = SoundbankDescriptor::from_bytes(&descriptor).unwrap();
+        assert_eq!(descriptor.cue_names, ["music_intro"]);
+
+        // The wave bank isn't where the descriptor says
+        let resource = vec![0; 84];
+        let slices = [resource.as_slice()];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let soundbank = Soundbank::new("aid_xsoundbank_test", &descriptor, &virtual_res).unwrap();
+
+        assert_eq!(soundbank.cue_names(), ["music_intro"]);
+        assert!(soundbank.waves().is_empty());
+        assert!(matches!(
+            soundbank.wave_bank_error(),
+            Some(AssetParseError::InvalidDataViews(_))
+        ));
+    }
+}
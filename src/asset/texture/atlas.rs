@@ -0,0 +1,418 @@
+//! Packs the textures of a bundle into a few large atlas images, along with the position of each
+//! texture within them. Useful for eyeballing a bundle's art at full size, and for web viewers
+//! that would rather load one image than hundreds.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{Value, json};
+
+use crate::{
+    BNLFile,
+    asset::{
+        Asset,
+        texture::{Image, Texture},
+    },
+    filter::AssetFilter,
+    images,
+};
+
+/// Settings for [`pack`] and [`BNLFile::write_texture_atlas`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct AtlasOptions {
+    /// The width and maximum height of each page. Textures larger than this are given a page of
+    /// their own, sized to fit.
+    pub page_size: usize,
+    /// The gap left around each texture, in pixels.
+    pub padding: usize,
+}
+
+impl Default for AtlasOptions {
+    fn default() -> Self {
+        AtlasOptions {
+            page_size: 2048,
+            padding: 1,
+        }
+    }
+}
+
+impl AtlasOptions {
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    pub fn padding(mut self, padding: usize) -> Self {
+        self.padding = padding;
+        self
+    }
+}
+
+/// Where a texture was placed within an [`Atlas`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AtlasEntry {
+    pub name: String,
+    /// The index of the page in [`Atlas::pages`] holding the texture.
+    pub page: usize,
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// A set of textures packed into one or more pages.
+pub struct Atlas {
+    pub pages: Vec<Image>,
+    pub entries: Vec<AtlasEntry>,
+    /// The names of the textures that couldn't be decoded, and were left out.
+    pub skipped: Vec<String>,
+}
+
+/// A page being filled with rows ("shelves") of textures from left to right.
+struct Page {
+    width: usize,
+    height: usize,
+    bytes: Vec<u8>,
+    shelf_x: usize,
+    shelf_y: usize,
+    shelf_height: usize,
+    /// The right edge of the widest shelf so far.
+    used_width: usize,
+}
+
+impl Page {
+    fn new(width: usize, height: usize) -> Page {
+        Page {
+            width,
+            height,
+            bytes: vec![0; width * height * 4],
+            shelf_x: 0,
+            shelf_y: 0,
+            shelf_height: 0,
+            used_width: 0,
+        }
+    }
+
+    /// Finds space for a `width` by `height` box on this page, starting a new shelf if the
+    /// current one is full. Returns None if the page has no room left.
+    fn allocate(&mut self, width: usize, height: usize) -> Option<(usize, usize)> {
+        if self.shelf_x + width > self.width {
+            self.shelf_y += self.shelf_height;
+            self.shelf_x = 0;
+            self.shelf_height = 0;
+        }
+
+        if width > self.width || self.shelf_y + height > self.height {
+            return None;
+        }
+
+        let position = (self.shelf_x, self.shelf_y);
+
+        self.shelf_x += width;
+        self.shelf_height = self.shelf_height.max(height);
+        self.used_width = self.used_width.max(self.shelf_x);
+
+        Some(position)
+    }
+
+    fn draw_image(&mut self, x: usize, y: usize, image: &Image) {
+        let row_size = image.width() * 4;
+
+        for (row, pixels) in image.bytes().chunks_exact(row_size).enumerate() {
+            let start = ((y + row) * self.width + x) * 4;
+            self.bytes[start..start + row_size].copy_from_slice(pixels);
+        }
+    }
+
+    /// Returns the page as an image, with the unused space right of the widest shelf and below
+    /// the last shelf cut off.
+    fn into_image(self) -> Image {
+        let width = self.used_width.max(1);
+        let height = (self.shelf_y + self.shelf_height).max(1);
+
+        let bytes = self
+            .bytes
+            .chunks_exact(self.width * 4)
+            .take(height)
+            .flat_map(|row| &row[..width * 4])
+            .copied()
+            .collect();
+
+        Image::from_rgba8(width, height, bytes).expect("Page size matches its dimensions")
+    }
+}
+
+/// Packs `textures` into pages of at most [`AtlasOptions::page_size`] square, tallest textures
+/// first. Textures that can't be decoded are listed in [`Atlas::skipped`].
+///
+/// # Examples
+/// ```
+/// use bnl::asset::texture::{Texture, atlas::{self, AtlasOptions}};
+///
+/// # let bnl = bnl::BNLFile::new();
+/// let textures: Vec<Texture> = bnl.get_assets();
+/// let atlas = atlas::pack(&textures, &AtlasOptions::default());
+/// ```
+pub fn pack(textures: &[Texture], options: &AtlasOptions) -> Atlas {
+    let mut images = Vec::new();
+    let mut skipped = Vec::new();
+
+    for texture in textures {
+        match texture.to_rgba_image() {
+            Ok(image) => images.push((texture.name(), image)),
+            Err(_) => skipped.push(texture.name().to_string()),
+        }
+    }
+
+    images.sort_by(|(_, a), (_, b)| b.height().cmp(&a.height()).then(b.width().cmp(&a.width())));
+
+    let padding = options.padding;
+    let page_size = options.page_size.max(1);
+
+    let mut full_pages = Vec::new();
+    let mut page: Option<Page> = None;
+    let mut entries = Vec::new();
+
+    for (name, image) in images {
+        let box_width = image.width() + padding * 2;
+        let box_height = image.height() + padding * 2;
+
+        let position = match page
+            .as_mut()
+            .and_then(|page| page.allocate(box_width, box_height))
+        {
+            Some(position) => position,
+            None => {
+                full_pages.extend(page.take());
+
+                let mut new_page = Page::new(page_size.max(box_width), page_size.max(box_height));
+                let position = new_page
+                    .allocate(box_width, box_height)
+                    .expect("A new page fits the texture it was sized for");
+
+                page = Some(new_page);
+                position
+            }
+        };
+
+        let (x, y) = (position.0 + padding, position.1 + padding);
+
+        page.as_mut()
+            .expect("A page was made for the texture")
+            .draw_image(x, y, &image);
+
+        entries.push(AtlasEntry {
+            name: name.to_string(),
+            page: full_pages.len(),
+            x,
+            y,
+            width: image.width(),
+            height: image.height(),
+        });
+    }
+
+    full_pages.extend(page);
+
+    Atlas {
+        pages: full_pages.into_iter().map(Page::into_image).collect(),
+        entries,
+        skipped,
+    }
+}
+
+impl Atlas {
+    /// Returns the name of the file that [`Atlas::write`] writes a page to.
+    pub fn page_file_name(stem: &str, page: usize) -> String {
+        format!("{}_{}.png", stem, page)
+    }
+
+    /// Returns the coordinates of every texture as JSON, with pages named as written by
+    /// [`Atlas::write`].
+    pub fn to_json(&self, stem: &str) -> Value {
+        let pages: Vec<Value> = self
+            .pages
+            .iter()
+            .enumerate()
+            .map(|(i, page)| {
+                json!({
+                    "file": Atlas::page_file_name(stem, i),
+                    "width": page.width(),
+                    "height": page.height(),
+                })
+            })
+            .collect();
+
+        let textures: Vec<Value> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                json!({
+                    "name": entry.name,
+                    "page": entry.page,
+                    "x": entry.x,
+                    "y": entry.y,
+                    "width": entry.width,
+                    "height": entry.height,
+                })
+            })
+            .collect();
+
+        json!({
+            "pages": pages,
+            "textures": textures,
+            "skipped": self.skipped,
+        })
+    }
+
+    /// Writes each page to `<stem>_<page>.png` in `out_dir`, and the coordinates of every texture
+    /// to `<stem>.json`. Returns the paths written, the JSON file last.
+    ///
+    /// # Errors
+    /// Returns an error if any of the files can't be written.
+    pub fn write(&self, out_dir: &Path, stem: &str) -> io::Result<Vec<PathBuf>> {
+        fs::create_dir_all(out_dir)?;
+
+        let mut paths = Vec::new();
+
+        for (i, page) in self.pages.iter().enumerate() {
+            let path = out_dir.join(Atlas::page_file_name(stem, i));
+            fs::write(
+                &path,
+                images::encode_png(page.width(), page.height(), page.bytes())?,
+            )?;
+            paths.push(path);
+        }
+
+        let json_path = out_dir.join(format!("{}.json", stem));
+        fs::write(&json_path, self.to_json(stem).to_string())?;
+        paths.push(json_path);
+
+        Ok(paths)
+    }
+}
+
+impl BNLFile {
+    /// Packs every texture that passes `filter` into an atlas (see [`pack`]), and writes it to
+    /// `out_dir` as `atlas_<page>.png` and `atlas.json`. Textures that can't be parsed are listed
+    /// in [`Atlas::skipped`].
+    ///
+    /// # Errors
+    /// Returns an error if the atlas can't be written.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use std::path::Path;
+    /// # use bnl::filter::AssetFilter;
+    /// # let bnl_file = bnl::BNLFile::new();
+    /// let atlas = bnl_file
+    ///     .write_texture_atlas(Path::new("./atlas"), &AssetFilter::default(), &Default::default())
+    ///     .expect("Unable to write atlas.");
+    /// ```
+    pub fn write_texture_atlas(
+        &self,
+        out_dir: &Path,
+        filter: &AssetFilter,
+        options: &AtlasOptions,
+    ) -> io::Result<Atlas> {
+        let (textures, errors) = self.get_assets_filtered::<Texture>(filter);
+
+        let mut atlas = pack(&textures, options);
+        atlas
+            .skipped
+            .extend(errors.into_iter().map(|(name, _)| name));
+
+        atlas.write(out_dir, "atlas")?;
+
+        Ok(atlas)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::bnl_from_raw_assets;
+
+    fn solid_texture(name: &str, width: usize, height: usize, colour: [u8; 4]) -> Texture {
+        let image = Image::from_rgba8(width, height, colour.repeat(width * height)).unwrap();
+        Texture::import(name, &image, &Default::default()).unwrap()
+    }
+
+    #[test]
+    fn packs_textures_onto_pages() {
+        let textures = [
+            solid_texture("aid_texture_small", 4, 4, [0xff, 0x00, 0x00, 0xff]),
+            solid_texture("aid_texture_tall", 8, 16, [0x00, 0xff, 0x00, 0xff]),
+            solid_texture("aid_texture_wide", 16, 8, [0x00, 0x00, 0xff, 0xff]),
+            solid_texture("aid_texture_huge", 64, 64, [0xff, 0xff, 0xff, 0xff]),
+        ];
+
+        let atlas = pack(&textures, &AtlasOptions::default().page_size(32));
+
+        assert!(atlas.skipped.is_empty());
+        assert_eq!(atlas.entries.len(), 4);
+
+        // The huge texture gets a page of its own, and everything else shares the next one
+        assert_eq!(atlas.pages.len(), 2);
+        assert_eq!((atlas.pages[0].width(), atlas.pages[0].height()), (66, 66));
+
+        let entry = |name: &str| atlas.entries.iter().find(|e| e.name == name).unwrap();
+        assert_eq!(entry("aid_texture_huge").page, 0);
+
+        for name in ["aid_texture_small", "aid_texture_tall", "aid_texture_wide"] {
+            assert_eq!(entry(name).page, 1);
+        }
+
+        // Each texture is drawn where its entry says
+        let page = &atlas.pages[1];
+        for (name, colour) in [
+            ("aid_texture_small", [0xff, 0x00, 0x00, 0xff]),
+            ("aid_texture_wide", [0x00, 0x00, 0xff, 0xff]),
+        ] {
+            let entry = entry(name);
+            let i = ((entry.y + entry.height - 1) * page.width() + entry.x) * 4;
+            assert_eq!(page.bytes()[i..i + 4], colour);
+        }
+
+        // No two entries on a page overlap
+        for a in &atlas.entries {
+            for b in &atlas.entries {
+                if a.name != b.name && a.page == b.page {
+                    assert!(
+                        a.x + a.width <= b.x
+                            || b.x + b.width <= a.x
+                            || a.y + a.height <= b.y
+                            || b.y + b.height <= a.y
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn writes_bundle_atlas() {
+        let texture = solid_texture("aid_texture_a", 4, 4, [0xff; 4]);
+        let bnl = bnl_from_raw_assets(&[texture.to_raw_asset().unwrap()]);
+
+        let dir = std::env::temp_dir().join(format!("bnl_atlas_{}", std::process::id()));
+        let atlas = bnl
+            .write_texture_atlas(&dir, &AssetFilter::default(), &Default::default())
+            .unwrap();
+
+        assert_eq!(atlas.entries.len(), 1);
+        assert!(
+            fs::read(dir.join("atlas_0.png"))
+                .unwrap()
+                .starts_with(b"\x89PNG")
+        );
+
+        let json: Value =
+            serde_json::from_slice(&fs::read(dir.join("atlas.json")).unwrap()).unwrap();
+        assert_eq!(json["textures"][0]["name"], "aid_texture_a");
+        assert_eq!(json["pages"][0]["file"], "atlas_0.png");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,1931 @@
+pub mod atlas;
+
+use std::{
+    fmt::{self, Display},
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    VirtualResource, VirtualResourceError,
+    asset::{
+        Asset, AssetDescriptor, AssetParseError, DescriptorTemplate, RawAsset, WritableAsset,
+        write_fields,
+    },
+    constraints::{self, ConstraintViolation},
+    d3d::{D3DFormat, LinearColour, PixelBits, StandardFormat, Swizzled},
+    game::AssetType,
+    images,
+};
+
+pub(crate) const TEXTURE_DESCRIPTOR_SIZE: usize = 28;
+
+/// The size of a P8 palette, which holds 256 D3DCOLOR (B8G8R8A8 in memory) entries.
+const PALETTE_SIZE: usize = 256 * 4;
+
+/// The texture format IDs used by BNL texture descriptors, and the formats they map to.
+pub(crate) const TEXTURE_FORMATS: [(u32, D3DFormat); 15] = [
+    (0x00000012, D3DFormat::Swizzled(Swizzled::B8G8R8A8)),
+    (0x0000003f, D3DFormat::Swizzled(Swizzled::A8B8G8R8)),
+    (0x00000040, D3DFormat::Linear(LinearColour::A8R8G8B8)),
+    (0x0000000c, D3DFormat::Standard(StandardFormat::DXT1)),
+    (0x0000000e, D3DFormat::Standard(StandardFormat::DXT2Or3)),
+    (0x0000000f, D3DFormat::Standard(StandardFormat::DXT4Or5)),
+    (0x0000000b, D3DFormat::Standard(StandardFormat::P8)),
+    (0x00000002, D3DFormat::Swizzled(Swizzled::A1R5G5B5)),
+    (0x00000003, D3DFormat::Swizzled(Swizzled::X1R5G5B5)),
+    (0x00000004, D3DFormat::Swizzled(Swizzled::A4R4G4B4)),
+    (0x00000005, D3DFormat::Swizzled(Swizzled::R5G6B5)),
+    (0x00000010, D3DFormat::Linear(LinearColour::A1R5G5B5)),
+    (0x00000011, D3DFormat::Linear(LinearColour::R5G6B5)),
+    (0x0000001c, D3DFormat::Linear(LinearColour::X1R5G5B5)),
+    (0x0000001d, D3DFormat::Linear(LinearColour::A4R4G4B4)),
+];
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextureDescriptor {
+    format: D3DFormat,
+    header_size: u32, // 28
+    width: u16,
+    height: u16,
+    flags: u32, // 0x00000001
+    unknown_3a: u32,
+    texture_offset: u32,
+    texture_size: u32,
+}
+
+impl TextureDescriptor {
+    pub fn new(
+        format: D3DFormat,
+        header_size: u32,
+        width: u16,
+        height: u16,
+        flags: u32,
+        unknown_3a: u32,
+        texture_offset: u32,
+        texture_size: u32,
+    ) -> Self {
+        Self {
+            format,
+            header_size,
+            width,
+            height,
+            flags,
+            unknown_3a,
+            texture_offset,
+            texture_size,
+        }
+    }
+
+    pub fn format(&self) -> D3DFormat {
+        self.format
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn required_size(&self) -> usize {
+        (self.width as usize * self.height as usize * self.format.bits_per_pixel()).div_ceil(8)
+    }
+
+    /// Serialises this descriptor back into its 28 byte form.
+    ///
+    /// # Errors
+    /// Returns an error if the format doesn't have a BNL texture format ID.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, io::Error> {
+        let (format_id, _) = TEXTURE_FORMATS
+            .iter()
+            .find(|(_, format)| *format == self.format)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{:?} has no BNL texture format ID", self.format),
+                )
+            })?;
+
+        let mut bytes = Vec::with_capacity(TEXTURE_DESCRIPTOR_SIZE);
+
+        bytes.extend_from_slice(&format_id.to_le_bytes());
+        bytes.extend_from_slice(&self.header_size.to_le_bytes());
+        bytes.extend_from_slice(&self.width.to_le_bytes());
+        bytes.extend_from_slice(&self.height.to_le_bytes());
+
+        for value in [
+            self.flags,
+            self.unknown_3a,
+            self.texture_offset,
+            self.texture_size,
+        ] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        Ok(bytes)
+    }
+}
+
+/// Returns the width and height of the blocks a format is encoded in, which is 4 for the DXT
+/// formats and 1 for everything else.
+fn block_dimension(format: D3DFormat) -> usize {
+    match format {
+        D3DFormat::Standard(
+            StandardFormat::DXT1 | StandardFormat::DXT2Or3 | StandardFormat::DXT4Or5,
+        ) => 4,
+        _ => 1,
+    }
+}
+
+#[derive(Debug)]
+pub struct Texture {
+    name: String,
+    descriptor: TextureDescriptor,
+    data: Vec<u8>,
+    palette: Option<Vec<u8>>,
+}
+
+impl Display for TextureDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_fields(
+            f,
+            &[
+                ("format", format!("{:?}", self.format)),
+                ("header_size", self.header_size.to_string()),
+                ("width", self.width.to_string()),
+                ("height", self.height.to_string()),
+                ("flags", format!("{:#010x}", self.flags)),
+                ("unknown_3a", format!("{:#010x}", self.unknown_3a)),
+                ("texture_offset", format!("{:#x}", self.texture_offset)),
+                ("texture_size", format!("{:#x}", self.texture_size)),
+            ],
+        )
+    }
+}
+
+impl AssetDescriptor for TextureDescriptor {
+    fn from_bytes(data: &[u8]) -> Result<Self, AssetParseError> {
+        if data.len() < TEXTURE_DESCRIPTOR_SIZE {
+            return Err(AssetParseError::InputTooSmall);
+        }
+
+        let format_id = u32::from_le_bytes(data[0..4].try_into().unwrap());
+
+        let format = match TEXTURE_FORMATS.iter().find(|(id, _)| *id == format_id) {
+            Some((_, format)) => *format,
+            // Unimplemented formats are assumed to be A8R8G8B8
+            None => D3DFormat::Linear(LinearColour::A8R8G8B8),
+        };
+
+        let header_size = u32::from_le_bytes(data[4..8].try_into().unwrap());
+        let width = u16::from_le_bytes(data[8..10].try_into().unwrap());
+        let height = u16::from_le_bytes(data[10..12].try_into().unwrap());
+        let flags = u32::from_le_bytes(data[12..16].try_into().unwrap());
+        let unknown_3a = u32::from_le_bytes(data[16..20].try_into().unwrap());
+        let texture_offset = u32::from_le_bytes(data[20..24].try_into().unwrap());
+        let texture_size = u32::from_le_bytes(data[24..28].try_into().unwrap());
+
+        Ok(TextureDescriptor {
+            format,
+            header_size,
+            width,
+            height,
+            flags,
+            unknown_3a,
+            texture_offset,
+            texture_size,
+        })
+    }
+}
+
+impl DescriptorTemplate for TextureDescriptor {
+    /// A 4x4 B8G8R8A8 texture, the most common uncompressed format in the game's bundles, with its
+    /// data at the start of the resource.
+    fn template() -> Self {
+        let format = D3DFormat::Swizzled(Swizzled::B8G8R8A8);
+
+        TextureDescriptor::new(
+            format,
+            TEXTURE_DESCRIPTOR_SIZE as u32,
+            4,
+            4,
+            0x00000001,
+            0,
+            0,
+            (4 * 4 * format.bits_per_pixel() / 8) as u32,
+        )
+    }
+}
+
+impl Asset for Texture {
+    type Descriptor = TextureDescriptor;
+
+    fn new(
+        name: &str,
+        descriptor: &Self::Descriptor,
+        virtual_res: &VirtualResource,
+    ) -> Result<Self, AssetParseError> {
+        if virtual_res.is_empty() {
+            return Err(AssetParseError::InvalidDataViews(
+                "Unable to create a Texture using 0 data views".to_string(),
+            ));
+        }
+
+        let offset = descriptor.texture_offset as usize;
+        let size = descriptor.texture_size as usize;
+
+        let bytes = match virtual_res.get_bytes(offset, size) {
+            Ok(b) => b,
+            Err(e) => {
+                match e {
+                    VirtualResourceError::OffsetOutOfBounds => {
+                        return Err(AssetParseError::InvalidDataViews(format!(
+                            "Offset {} is out of bounds for virtual resource of size {}",
+                            offset,
+                            virtual_res.len()
+                        )));
+                    }
+
+                    VirtualResourceError::SizeOutOfBounds => {
+                        return Err(AssetParseError::InvalidDataViews(format!(
+                            "Size would reach offset {}, which is out of bounds for virtual resource of size {}",
+                            offset + size,
+                            virtual_res.len()
+                        )));
+                    }
+                };
+            }
+        };
+
+        let mut texture = Texture {
+            name: name.to_string(),
+            descriptor: descriptor.clone(),
+            data: bytes,
+            palette: None,
+        };
+
+        if descriptor.format == D3DFormat::Standard(StandardFormat::P8) {
+            texture.palette = Some(texture.extract_palette(virtual_res)?);
+        }
+
+        Ok(texture)
+    }
+
+    fn descriptor(&self) -> &Self::Descriptor {
+        &self.descriptor
+    }
+
+    fn asset_type() -> AssetType {
+        AssetType::ResTexture
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// The file formats [`Texture::export`] can write.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExportFormat {
+    #[default]
+    Png,
+    Dds,
+    Tga,
+    Bmp,
+}
+
+impl ExportFormat {
+    /// Returns the file extension of this format, without a leading dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Png => "png",
+            ExportFormat::Dds => "dds",
+            ExportFormat::Tga => "tga",
+            ExportFormat::Bmp => "bmp",
+        }
+    }
+
+    /// Returns the format with the given file extension, ignoring case, or None if no format
+    /// uses it.
+    pub fn from_extension(extension: &str) -> Option<ExportFormat> {
+        [
+            ExportFormat::Png,
+            ExportFormat::Dds,
+            ExportFormat::Tga,
+            ExportFormat::Bmp,
+        ]
+        .into_iter()
+        .find(|format| format.extension().eq_ignore_ascii_case(extension))
+    }
+
+    /// Encodes RGBA8 pixels in this format. DDS files are written as plain 32-bit A8R8G8B8.
+    fn encode(self, image: &Image) -> Result<Vec<u8>, io::Error> {
+        match self {
+            ExportFormat::Png => images::encode_png(image.width, image.height, &image.bytes),
+            ExportFormat::Tga => images::encode_tga(image.width, image.height, &image.bytes),
+            ExportFormat::Bmp => images::encode_bmp(image.width, image.height, &image.bytes),
+            ExportFormat::Dds => Ok(images::encode_dds(
+                image.width,
+                image.height,
+                &images::DdsLevels::Rgba(vec![image.bytes.clone()]),
+            )),
+        }
+    }
+}
+
+/// An error that occurred while writing a [`Texture`] to disk with [`Texture::dump`] or
+/// [`Texture::export`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum TextureDumpError {
+    /// The texture data could not be decoded into RGBA8 pixels.
+    Decode(io::Error),
+    /// The decoded pixels could not be encoded in the output format.
+    Encode(io::Error),
+    /// The encoded file could not be written to the given path.
+    Write { path: PathBuf, source: io::Error },
+}
+
+impl Display for TextureDumpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureDumpError::Decode(e) => write!(f, "Unable to decode texture: {}", e),
+            TextureDumpError::Encode(e) => write!(f, "Unable to encode texture: {}", e),
+            TextureDumpError::Write { path, source } => {
+                write!(f, "Unable to write {}: {}", path.display(), source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TextureDumpError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TextureDumpError::Decode(e)
+            | TextureDumpError::Encode(e)
+            | TextureDumpError::Write { source: e, .. } => Some(e),
+        }
+    }
+}
+
+impl From<TextureDumpError> for io::Error {
+    fn from(value: TextureDumpError) -> Self {
+        let kind = match &value {
+            TextureDumpError::Decode(e)
+            | TextureDumpError::Encode(e)
+            | TextureDumpError::Write { source: e, .. } => e.kind(),
+        };
+
+        io::Error::new(kind, value)
+    }
+}
+
+/// The colour channels of a PNG written with [`DumpOptions`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PngColour {
+    #[default]
+    Rgba,
+    /// Drops the alpha channel, for textures whose alpha holds something other than opacity.
+    Rgb,
+}
+
+/// The bits per channel of a PNG written with [`DumpOptions`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PngBitDepth {
+    #[default]
+    Eight,
+    /// Each 8-bit channel is scaled up to fill 16 bits, for tools that only take 16-bit input.
+    Sixteen,
+}
+
+/// How [`DumpOptions`] treats the alpha channel of a decoded texture.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AlphaMode {
+    /// Writes the alpha channel as it was decoded.
+    #[default]
+    Keep,
+    /// Multiplies the colour channels by alpha, for viewing textures drawn with premultiplied
+    /// blending.
+    Premultiply,
+    /// Divides the colour channels by alpha, recovering the colours of textures stored
+    /// premultiplied, such as DXT2.
+    Unpremultiply,
+}
+
+/// Settings for [`Texture::dump_with`]. The default matches [`Texture::dump`], writing an 8-bit
+/// RGBA PNG as it is stored in the texture.
+///
+/// # Examples
+/// ```no_run
+/// # use std::path::Path;
+/// # use bnl::asset::texture::{DumpOptions, PngColour, Texture, TextureDumpError};
+/// # fn dump(texture: &Texture) -> Result<(), TextureDumpError> {
+/// let options = DumpOptions::default()
+///     .colour(PngColour::Rgb)
+///     .flip_vertical(true);
+///
+/// texture.dump_with(Path::new("./gribble.png"), &options)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct DumpOptions {
+    pub colour: PngColour,
+    pub bit_depth: PngBitDepth,
+    /// Marks the image as sRGB, writing the sRGB and matching gAMA chunks.
+    pub srgb: bool,
+    /// Stores the rows bottom first, for textures the game samples upside down.
+    pub flip_vertical: bool,
+    pub alpha: AlphaMode,
+    /// Treats DXT1 textures as opaque, ignoring their punch-through alpha. Other formats are
+    /// unaffected.
+    pub dxt1_opaque: bool,
+    /// Also writes the alpha channel to a greyscale PNG next to the image, see
+    /// [`Texture::dump_with`].
+    pub alpha_mask: bool,
+}
+
+impl DumpOptions {
+    pub fn colour(mut self, colour: PngColour) -> Self {
+        self.colour = colour;
+        self
+    }
+
+    pub fn bit_depth(mut self, bit_depth: PngBitDepth) -> Self {
+        self.bit_depth = bit_depth;
+        self
+    }
+
+    pub fn srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
+
+    pub fn flip_vertical(mut self, flip_vertical: bool) -> Self {
+        self.flip_vertical = flip_vertical;
+        self
+    }
+
+    pub fn alpha(mut self, alpha: AlphaMode) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    pub fn dxt1_opaque(mut self, dxt1_opaque: bool) -> Self {
+        self.dxt1_opaque = dxt1_opaque;
+        self
+    }
+
+    pub fn alpha_mask(mut self, alpha_mask: bool) -> Self {
+        self.alpha_mask = alpha_mask;
+        self
+    }
+}
+
+/// How [`Texture::from_image`] handles images whose dimensions aren't a multiple of the 4x4 block
+/// size of a DXT format.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DxtAlignment {
+    /// Fail with [`TextureImportError::UnalignedDimensions`].
+    #[default]
+    Reject,
+    /// Extend the right and bottom edges with transparent pixels, up to the next multiple of 4.
+    Pad,
+    /// Scale the image up to the next multiple of 4 in each dimension.
+    Resize,
+}
+
+/// Settings for [`Texture::from_image`] and [`Texture::import`].
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct TextureImportOptions {
+    pub dxt_alignment: DxtAlignment,
+    /// Overrides the format chosen by [`infer_format`] in [`Texture::import`].
+    pub format: Option<D3DFormat>,
+    /// Overrides the flags of the generated [`TextureDescriptor`].
+    pub flags: Option<u32>,
+}
+
+impl TextureImportOptions {
+    pub fn dxt_alignment(mut self, dxt_alignment: DxtAlignment) -> Self {
+        self.dxt_alignment = dxt_alignment;
+        self
+    }
+
+    pub fn format(mut self, format: D3DFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    pub fn flags(mut self, flags: u32) -> Self {
+        self.flags = Some(flags);
+        self
+    }
+}
+
+/// Images no larger than this in either dimension are treated as UI images by [`infer_format`].
+pub const SMALL_IMAGE_DIMENSION: usize = 64;
+
+/// Picks a sensible format to store `image` in. Small UI images are kept uncompressed in a linear
+/// format, as DXT artifacts are most visible on them and linear formats don't need power of two
+/// dimensions. Everything else uses DXT1 when fully opaque, or DXT5 when it has any transparency.
+///
+/// The linear formats are 16-bit, as they're the linear formats this crate can encode: R5G6B5 when
+/// opaque, or A4R4G4B4 otherwise.
+pub fn infer_format(image: &Image) -> D3DFormat {
+    let small = image.width <= SMALL_IMAGE_DIMENSION && image.height <= SMALL_IMAGE_DIMENSION;
+
+    match (small, image.has_alpha()) {
+        (true, false) => D3DFormat::Linear(LinearColour::R5G6B5),
+        (true, true) => D3DFormat::Linear(LinearColour::A4R4G4B4),
+        (false, false) => D3DFormat::Standard(StandardFormat::DXT1),
+        (false, true) => D3DFormat::Standard(StandardFormat::DXT4Or5),
+    }
+}
+
+/// An error that occurred while creating a [`Texture`] from an image with [`Texture::from_image`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum TextureImportError {
+    /// The image dimensions aren't a multiple of 4, which DXT formats require.
+    UnalignedDimensions {
+        width: usize,
+        height: usize,
+        format: D3DFormat,
+    },
+    /// The image breaks a limit of the game, such as its maximum texture size.
+    Constraint(ConstraintViolation),
+    /// Images can't be encoded into this format.
+    UnsupportedFormat(D3DFormat),
+    /// The pixels could not be encoded into the target format.
+    Encode(io::Error),
+}
+
+impl Display for TextureImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureImportError::UnalignedDimensions {
+                width,
+                height,
+                format,
+            } => write!(
+                f,
+                "A {}x{} image can't be stored as {:?}, as its dimensions must be multiples of 4. \
+                 Pad or resize the image first, or set a DxtAlignment to do so automatically.",
+                width, height, format
+            ),
+            TextureImportError::Constraint(violation) => write!(f, "{}", violation),
+            TextureImportError::UnsupportedFormat(format) => {
+                write!(f, "Images can't be encoded as {:?}", format)
+            }
+            TextureImportError::Encode(e) => write!(f, "Unable to encode texture: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TextureImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TextureImportError::Encode(e) => Some(e),
+            TextureImportError::Constraint(violation) => Some(violation),
+            _ => None,
+        }
+    }
+}
+
+impl From<TextureImportError> for io::Error {
+    fn from(value: TextureImportError) -> Self {
+        match value {
+            TextureImportError::Encode(e) => e,
+            value => io::Error::new(io::ErrorKind::InvalidInput, value),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    bytes: Vec<u8>,
+}
+
+impl Image {
+    /// Creates an image from tightly packed RGBA8 pixels, row by row from the top left.
+    ///
+    /// # Errors
+    /// Returns an error if `bytes` doesn't hold exactly `width * height` pixels.
+    pub fn from_rgba8(width: usize, height: usize, bytes: Vec<u8>) -> Result<Image, io::Error> {
+        if bytes.len() != width * height * 4 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Expected {} bytes of RGBA8 pixels for a {}x{} image, but received {}",
+                    width * height * 4,
+                    width,
+                    height,
+                    bytes.len()
+                ),
+            ));
+        }
+
+        Ok(Image {
+            width,
+            height,
+            bytes,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns true if any pixel of this image isn't fully opaque.
+    pub fn has_alpha(&self) -> bool {
+        self.bytes.chunks_exact(4).any(|pixel| pixel[3] != 0xff)
+    }
+
+    /// Returns a copy of this image extended to `width` x `height` with transparent pixels.
+    fn padded(&self, width: usize, height: usize) -> Image {
+        let mut bytes = vec![0; width * height * 4];
+
+        for (row, src) in self.bytes.chunks_exact(self.width * 4).enumerate() {
+            bytes[row * width * 4..row * width * 4 + src.len()].copy_from_slice(src);
+        }
+
+        Image {
+            width,
+            height,
+            bytes,
+        }
+    }
+
+    /// Returns a copy of this image scaled to `width` x `height` using nearest neighbour sampling.
+    fn resized(&self, width: usize, height: usize) -> Image {
+        let mut bytes = Vec::with_capacity(width * height * 4);
+
+        for y in 0..height {
+            let src_y = y * self.height / height;
+
+            for x in 0..width {
+                let i = (src_y * self.width + x * self.width / width) * 4;
+                bytes.extend_from_slice(&self.bytes[i..i + 4]);
+            }
+        }
+
+        Image {
+            width,
+            height,
+            bytes,
+        }
+    }
+}
+
+impl Texture {
+    /// Returns the palette of a P8 texture as 256 B8G8R8A8 entries, or None for any other format.
+    pub fn palette(&self) -> Option<&[u8]> {
+        self.palette.as_deref()
+    }
+
+    /// Finds the palette of a P8 texture.
+    ///
+    /// Assumed layout: the palette directly follows the texture data in the resource, at
+    /// texture_offset + texture_size. No P8 texture has been checked against the game files yet,
+    /// and the descriptor has no known field pointing at the palette.
+    fn extract_palette(&self, virtual_res: &VirtualResource) -> Result<Vec<u8>, AssetParseError> {
+        let palette_offset =
+            self.descriptor.texture_offset as usize + self.descriptor.texture_size as usize;
+
+        virtual_res
+            .get_bytes(palette_offset, PALETTE_SIZE)
+            .map_err(|_| {
+                AssetParseError::InvalidDataViews(format!(
+                    "Unable to find a palette for P8 texture {} at offset {}",
+                    self.name, palette_offset
+                ))
+            })
+    }
+
+    pub fn to_rgba_image(&self) -> Result<Image, std::io::Error> {
+        // Any mipmaps after the full size image aren't part of it
+        let mut bytes: Vec<u8> = match self.mip_level_data(0) {
+            Ok((_, _, data)) => data.to_vec(),
+            Err(_) => self.data.clone(),
+        };
+
+        if bytes.len() < self.descriptor.required_size() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Texture data is smaller than its dimensions",
+            ));
+        }
+
+        let desired_format: D3DFormat = match self.descriptor.format {
+            D3DFormat::Linear(LinearColour::R8G8B8A8)
+            | D3DFormat::Swizzled(Swizzled::A8B8G8R8)
+            | D3DFormat::Swizzled(Swizzled::A8R8G8B8) => D3DFormat::Linear(LinearColour::R8G8B8A8),
+            _ => {
+                /*
+                eprintln!(
+                    "Unexpected format found during dump: {:?}. Attempting to dump anyway.",
+                    self.descriptor.format
+                );
+                */
+
+                D3DFormat::Linear(LinearColour::R8G8B8A8)
+            }
+        };
+
+        if desired_format != self.descriptor.format {
+            bytes = images::transcode(
+                self.descriptor.width.into(),
+                self.descriptor.height.into(),
+                self.descriptor.format,
+                desired_format,
+                bytes.as_ref(),
+            )?;
+        }
+
+        Ok(Image {
+            width: self.descriptor.width as usize,
+            height: self.descriptor.height as usize,
+            bytes,
+        })
+    }
+
+    /// Creates a reduced resolution RGBA8 preview of this texture, whose largest side is at most
+    /// `max_dimension` pixels. Only the rows (or DXT block rows) that are actually sampled get
+    /// decoded, which makes this much cheaper than [`Texture::to_rgba_image`] for large textures.
+    ///
+    /// If a `time_budget` is given and runs out part way through, the remaining rows of the
+    /// preview repeat the last decoded row rather than failing, so callers always get an image
+    /// of the expected size back.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// # use bnl::asset::texture::Texture;
+    /// # let bnl = bnl::BNLFile::new();
+    /// let tex: Texture = bnl.get_asset("aid_texture_gribble").expect("Unable to get texture.");
+    /// let preview = tex.to_preview_image(128, Some(Duration::from_millis(5)))
+    ///                  .expect("Unable to create preview.");
+    /// ```
+    pub fn to_preview_image(
+        &self,
+        max_dimension: usize,
+        time_budget: Option<Duration>,
+    ) -> Result<Image, std::io::Error> {
+        let start = Instant::now();
+
+        let width = self.descriptor.width as usize;
+        let height = self.descriptor.height as usize;
+
+        let step = width.max(height).div_ceil(max_dimension.max(1)).max(1);
+
+        let preview_width = width.div_ceil(step);
+        let preview_height = height.div_ceil(step);
+
+        let (band_height, band_size) = match self.descriptor.format {
+            D3DFormat::Standard(StandardFormat::DXT1) => (4, width.div_ceil(4) * 8),
+            D3DFormat::Standard(StandardFormat::DXT2Or3 | StandardFormat::DXT4Or5) => {
+                (4, width.div_ceil(4) * 16)
+            }
+            format => (1, (width * format.bits_per_pixel()).div_ceil(8)),
+        };
+
+        let mut bytes = Vec::with_capacity(preview_width * preview_height * 4);
+
+        // The most recently decoded band, as (band index, RGBA8 pixels)
+        let mut band: Option<(usize, Vec<u8>)> = None;
+
+        for y in 0..preview_height {
+            let out_of_time = time_budget.is_some_and(|budget| start.elapsed() > budget);
+
+            if out_of_time && y > 0 {
+                let last_row = bytes[(y - 1) * preview_width * 4..].to_vec();
+                bytes.extend_from_slice(&last_row);
+                continue;
+            }
+
+            let src_y = y * step;
+            let band_index = src_y / band_height;
+
+            if band.as_ref().is_none_or(|(index, _)| *index != band_index) {
+                let rows = band_height.min(height - band_index * band_height);
+
+                let band_bytes = self
+                    .data
+                    .get(band_index * band_size..(band_index + 1) * band_size)
+                    .ok_or_else(|| {
+                        std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            "Texture data is smaller than its dimensions",
+                        )
+                    })?;
+
+                band = Some((band_index, self.decode_rows(band_bytes, rows)?));
+            }
+
+            let (_, pixels) = band.as_ref().expect("Band was decoded above");
+            let row_start = (src_y % band_height) * width * 4;
+
+            for x in 0..preview_width {
+                let i = row_start + x * step * 4;
+                bytes.extend_from_slice(&pixels[i..i + 4]);
+            }
+        }
+
+        Ok(Image {
+            width: preview_width,
+            height: preview_height,
+            bytes,
+        })
+    }
+
+    /// Decodes a number of full-width rows of this texture into RGBA8 pixels.
+    fn decode_rows(&self, bytes: &[u8], rows: usize) -> Result<Vec<u8>, std::io::Error> {
+        let rgba = D3DFormat::Linear(LinearColour::R8G8B8A8);
+
+        if self.descriptor.format == rgba {
+            return Ok(bytes.to_vec());
+        }
+
+        images::transcode(
+            self.descriptor.width.into(),
+            rows,
+            self.descriptor.format,
+            rgba,
+            bytes,
+        )
+    }
+
+    /// Decodes this texture and shrinks it so that neither side is larger than `max_dimension`,
+    /// averaging the pixels that are merged together. Textures that already fit are returned at
+    /// full size. See [`Texture::to_preview_image`] for a faster but blockier preview.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bnl::asset::texture::Texture;
+    /// # let bnl = bnl::BNLFile::new();
+    /// let tex: Texture = bnl.get_asset("aid_texture_gribble").expect("Unable to get texture.");
+    /// let thumbnail = tex.thumbnail(64).expect("Unable to create thumbnail.");
+    /// ```
+    pub fn thumbnail(&self, max_dimension: usize) -> Result<Image, std::io::Error> {
+        let image = self.to_rgba_image()?;
+
+        let (width, height, bytes) =
+            images::downscale(image.width, image.height, &image.bytes, max_dimension)?;
+
+        Ok(Image {
+            width,
+            height,
+            bytes,
+        })
+    }
+
+    /// Decodes this texture into tightly packed RGBA8 pixels, row by row from the top left, and
+    /// returns them along with the width and height of the texture. Nothing is written to disk,
+    /// so this suits GUIs and engines uploading straight to the GPU.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bnl::asset::texture::Texture;
+    /// # let bnl = bnl::BNLFile::new();
+    /// let tex: Texture = bnl.get_asset("aid_texture_gribble").expect("Unable to get texture.");
+    /// let (width, height, pixels) = tex.to_rgba8().expect("Unable to decode texture.");
+    ///
+    /// assert_eq!(pixels.len(), width * height * 4);
+    /// ```
+    pub fn to_rgba8(&self) -> Result<(usize, usize, Vec<u8>), std::io::Error> {
+        let image = self.to_rgba_image()?;
+
+        Ok((image.width, image.height, image.bytes))
+    }
+
+    /// Encodes this texture as an RGBA8 PNG in memory.
+    pub fn encode_png(&self) -> Result<Vec<u8>, std::io::Error> {
+        let (width, height, rgba) = self.to_rgba8()?;
+
+        images::encode_png(width, height, &rgba)
+    }
+
+    /// Encodes this texture as a PNG in memory with the given [`DumpOptions`]. The alpha mask
+    /// isn't included, see [`Texture::encode_alpha_mask`].
+    pub fn encode_png_with(&self, options: &DumpOptions) -> Result<Vec<u8>, std::io::Error> {
+        let image = self.to_dump_image(options)?;
+
+        images::encode_png_with(image.width, image.height, &image.bytes, options)
+    }
+
+    /// Encodes the alpha channel of this texture as a greyscale PNG in memory, with the given
+    /// [`DumpOptions`] applied first.
+    pub fn encode_alpha_mask(&self, options: &DumpOptions) -> Result<Vec<u8>, std::io::Error> {
+        let image = self.to_dump_image(options)?;
+
+        images::encode_alpha_mask(image.width, image.height, &image.bytes, options)
+    }
+
+    /// Decodes this texture and applies the alpha settings of `options`.
+    fn to_dump_image(&self, options: &DumpOptions) -> Result<Image, std::io::Error> {
+        let mut image = self.to_rgba_image()?;
+
+        if options.dxt1_opaque
+            && self.descriptor.format == D3DFormat::Standard(StandardFormat::DXT1)
+        {
+            image
+                .bytes
+                .chunks_exact_mut(4)
+                .for_each(|pixel| pixel[3] = 0xFF);
+        }
+
+        match options.alpha {
+            AlphaMode::Keep => {}
+            AlphaMode::Premultiply => images::premultiply_alpha(&mut image.bytes),
+            AlphaMode::Unpremultiply => images::unpremultiply_alpha(&mut image.bytes),
+        }
+
+        Ok(image)
+    }
+
+    /// Encodes this texture as an uncompressed 32-bit TGA in memory.
+    pub fn encode_tga(&self) -> Result<Vec<u8>, std::io::Error> {
+        let (width, height, rgba) = self.to_rgba8()?;
+
+        images::encode_tga(width, height, &rgba)
+    }
+
+    /// Encodes this texture as an uncompressed 32-bit BMP in memory.
+    pub fn encode_bmp(&self) -> Result<Vec<u8>, std::io::Error> {
+        let (width, height, rgba) = self.to_rgba8()?;
+
+        images::encode_bmp(width, height, &rgba)
+    }
+
+    /// Encodes this texture as a DDS file in memory, optionally including its mipmaps. DXT
+    /// textures keep their compressed blocks, and every other format is decoded to 32-bit
+    /// A8R8G8B8.
+    pub fn encode_dds(&self, mipmaps: bool) -> Result<Vec<u8>, std::io::Error> {
+        let level_count = match mipmaps {
+            true => self.mip_levels().max(1),
+            false => 1,
+        };
+
+        let four_cc = match self.descriptor.format {
+            D3DFormat::Standard(StandardFormat::DXT1) => Some(*b"DXT1"),
+            D3DFormat::Standard(StandardFormat::DXT2Or3) => Some(*b"DXT3"),
+            D3DFormat::Standard(StandardFormat::DXT4Or5) => Some(*b"DXT5"),
+            _ => None,
+        };
+
+        let levels = match four_cc {
+            Some(four_cc) => images::DdsLevels::Compressed {
+                four_cc,
+                levels: (0..level_count)
+                    .map(|level| self.mip_level_data(level).map(|(_, _, data)| data))
+                    .collect::<Result<_, _>>()?,
+            },
+            None => images::DdsLevels::Rgba(
+                (0..level_count)
+                    .map(|level| self.mip_image(level).map(|image| image.bytes))
+                    .collect::<Result<_, _>>()?,
+            ),
+        };
+
+        Ok(images::encode_dds(
+            self.descriptor.width as usize,
+            self.descriptor.height as usize,
+            &levels,
+        ))
+    }
+
+    /// Returns the number of complete mipmap levels in this texture's data, counting the full
+    /// size image as the first. When the texture data is larger than the full size image, the
+    /// rest is read as a chain of mipmaps, each half the size of the one before.
+    pub fn mip_levels(&self) -> usize {
+        let mut levels = 0;
+
+        while self.mip_level_data(levels).is_ok() {
+            levels += 1;
+
+            let (width, height) = self.mip_dimensions(levels - 1);
+
+            if width == 1 && height == 1 {
+                break;
+            }
+        }
+
+        levels
+    }
+
+    /// Returns the width and height of a mipmap level.
+    fn mip_dimensions(&self, level: usize) -> (usize, usize) {
+        let width = (self.descriptor.width as usize >> level).max(1);
+        let height = (self.descriptor.height as usize >> level).max(1);
+
+        (width, height)
+    }
+
+    /// Returns the width and height a mipmap level is stored at, padded out to whole blocks.
+    fn mip_stored_dimensions(&self, level: usize) -> (usize, usize) {
+        let (width, height) = self.mip_dimensions(level);
+        let block = block_dimension(self.descriptor.format);
+
+        (
+            width.next_multiple_of(block),
+            height.next_multiple_of(block),
+        )
+    }
+
+    /// Returns the stored dimensions and data of a mipmap level.
+    fn mip_level_data(&self, level: usize) -> Result<(usize, usize, &[u8]), std::io::Error> {
+        let level_size = |level: usize| {
+            let (width, height) = self.mip_stored_dimensions(level);
+            (width * height * self.descriptor.format.bits_per_pixel()).div_ceil(8)
+        };
+
+        let start: usize = (0..level).map(level_size).sum();
+        let (width, height) = self.mip_stored_dimensions(level);
+
+        self.data
+            .get(start..start + level_size(level))
+            .map(|data| (width, height, data))
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("{} has no data for mipmap level {}", self.name, level),
+                )
+            })
+    }
+
+    /// Decodes a mipmap level of this texture, see [`Texture::mip_levels`]. Level 0 is the full
+    /// size image.
+    pub fn mip_image(&self, level: usize) -> Result<Image, std::io::Error> {
+        if level == 0 {
+            return self.to_rgba_image();
+        }
+
+        let (stored_width, stored_height, data) = self.mip_level_data(level)?;
+        let (width, height) = self.mip_dimensions(level);
+
+        let rgba = images::transcode(
+            stored_width,
+            stored_height,
+            self.descriptor.format,
+            D3DFormat::Linear(LinearColour::R8G8B8A8),
+            data,
+        )?;
+
+        // Drop the padding of levels smaller than a DXT block
+        let bytes = rgba
+            .chunks_exact(stored_width * 4)
+            .take(height)
+            .flat_map(|row| &row[..width * 4])
+            .copied()
+            .collect();
+
+        Ok(Image {
+            width,
+            height,
+            bytes,
+        })
+    }
+
+    /// Writes this texture into `dir` as `<name>.<extension>` in the given format, returning the
+    /// paths written. With `mipmaps` set, DDS files include every mipmap level, and each extra
+    /// level of the other formats is written to its own `<name>_mipN.<extension>` file.
+    ///
+    /// # Errors
+    /// Returns a [`TextureDumpError`] describing which step failed.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use std::path::Path;
+    /// # use bnl::asset::texture::{ExportFormat, Texture};
+    /// # fn export(texture: &Texture) {
+    /// let paths = texture.export(Path::new("./out/textures"), ExportFormat::Dds, true)
+    ///     .expect("Unable to export texture.");
+    /// # }
+    /// ```
+    pub fn export(
+        &self,
+        dir: &Path,
+        format: ExportFormat,
+        mipmaps: bool,
+    ) -> Result<Vec<PathBuf>, TextureDumpError> {
+        let path =
+            |suffix: &str| dir.join(format!("{}{}.{}", self.name, suffix, format.extension()));
+        let write = |path: PathBuf, bytes: Vec<u8>| {
+            fs::write(&path, bytes)
+                .map(|_| path.clone())
+                .map_err(|source| TextureDumpError::Write { path, source })
+        };
+
+        if format == ExportFormat::Dds {
+            let dds = self.encode_dds(mipmaps).map_err(TextureDumpError::Decode)?;
+            return Ok(vec![write(path(""), dds)?]);
+        }
+
+        let level_count = match mipmaps {
+            true => self.mip_levels().max(1),
+            false => 1,
+        };
+
+        let mut paths = vec![];
+
+        for level in 0..level_count {
+            let image = self.mip_image(level).map_err(TextureDumpError::Decode)?;
+
+            let bytes = format.encode(&image).map_err(TextureDumpError::Encode)?;
+
+            let suffix = match level {
+                0 => String::new(),
+                level => format!("_mip{}", level),
+            };
+
+            paths.push(write(path(&suffix), bytes)?);
+        }
+
+        Ok(paths)
+    }
+
+    /// Creates a texture by encoding `image` into `format`, with its data at the start of a single
+    /// resource. Use [`Texture::to_raw_asset`] to get the bytes to store in a BNL file.
+    ///
+    /// # Errors
+    /// DXT formats need dimensions that are multiples of 4. Unless
+    /// [`TextureImportOptions::dxt_alignment`] allows padding or resizing, other images are
+    /// rejected with [`TextureImportError::UnalignedDimensions`] rather than producing corrupt
+    /// block data. Images breaking a limit of the game, such as its maximum texture size, are
+    /// rejected with [`TextureImportError::Constraint`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use bnl::{
+    /// #     asset::texture::{DxtAlignment, Image, Texture, TextureImportOptions},
+    /// #     stable::{D3DFormat, StandardFormat},
+    /// # };
+    /// # let image = Image::from_rgba8(6, 6, vec![0xff; 6 * 6 * 4]).unwrap();
+    /// let options = TextureImportOptions::default().dxt_alignment(DxtAlignment::Pad);
+    ///
+    /// let texture = Texture::from_image(
+    ///     "aid_texture_mytexture",
+    ///     &image,
+    ///     D3DFormat::Standard(StandardFormat::DXT1),
+    ///     &options,
+    /// )
+    /// .expect("Unable to import texture.");
+    /// ```
+    pub fn from_image(
+        name: &str,
+        image: &Image,
+        format: D3DFormat,
+        options: &TextureImportOptions,
+    ) -> Result<Texture, TextureImportError> {
+        if format == D3DFormat::Standard(StandardFormat::P8)
+            || !TEXTURE_FORMATS.iter().any(|(_, known)| *known == format)
+        {
+            return Err(TextureImportError::UnsupportedFormat(format));
+        }
+
+        let block = block_dimension(format);
+
+        let aligned;
+        let image = if image.width.is_multiple_of(block) && image.height.is_multiple_of(block) {
+            image
+        } else {
+            let width = image.width.next_multiple_of(block);
+            let height = image.height.next_multiple_of(block);
+
+            aligned = match options.dxt_alignment {
+                DxtAlignment::Reject => {
+                    return Err(TextureImportError::UnalignedDimensions {
+                        width: image.width,
+                        height: image.height,
+                        format,
+                    });
+                }
+                DxtAlignment::Pad => image.padded(width, height),
+                DxtAlignment::Resize => image.resized(width, height),
+            };
+
+            &aligned
+        };
+
+        constraints::check_texture(image.width, image.height, format)
+            .map_err(TextureImportError::Constraint)?;
+
+        let data = images::encode(image.width, image.height, format, &image.bytes)
+            .map_err(TextureImportError::Encode)?;
+
+        Ok(Texture {
+            name: name.to_string(),
+            descriptor: TextureDescriptor::new(
+                format,
+                TEXTURE_DESCRIPTOR_SIZE as u32,
+                image.width as u16,
+                image.height as u16,
+                0x00000001,
+                0,
+                0,
+                data.len() as u32,
+            ),
+            data,
+            palette: None,
+        })
+    }
+
+    /// Creates a texture for a brand new asset, like [`Texture::from_image`], but with the format
+    /// picked by [`infer_format`] unless [`TextureImportOptions::format`] overrides it. The
+    /// generated descriptor is complete, with [`TextureImportOptions::flags`] overriding the
+    /// default flags.
+    ///
+    /// # Errors
+    /// See [`Texture::from_image`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use bnl::asset::{Asset, texture::{Image, Texture}};
+    /// # let image = Image::from_rgba8(8, 8, vec![0xff; 8 * 8 * 4]).unwrap();
+    /// let texture = Texture::import("aid_texture_mytexture", &image, &Default::default())
+    ///     .expect("Unable to import texture.");
+    ///
+    /// println!("Imported as {:?}", texture.descriptor().format());
+    /// ```
+    pub fn import(
+        name: &str,
+        image: &Image,
+        options: &TextureImportOptions,
+    ) -> Result<Texture, TextureImportError> {
+        let format = options.format.unwrap_or_else(|| infer_format(image));
+
+        let mut texture = Texture::from_image(name, image, format, options)?;
+
+        if let Some(flags) = options.flags {
+            texture.descriptor.flags = flags;
+        }
+
+        Ok(texture)
+    }
+
+    /// Builds a [`RawAsset`] holding this texture's descriptor and data, see
+    /// [`Texture::write_to`] for updating an existing asset in place instead.
+    ///
+    /// # Errors
+    /// Returns an error if the texture's format doesn't have a BNL texture format ID.
+    pub fn to_raw_asset(&self) -> Result<RawAsset, io::Error> {
+        let mut resource = vec![0; self.descriptor.texture_offset as usize];
+        resource.extend_from_slice(&self.data);
+
+        if let Some(palette) = &self.palette {
+            resource.extend_from_slice(palette);
+        }
+
+        Ok(RawAsset {
+            name: self.name.clone(),
+            asset_type: AssetType::ResTexture,
+            descriptor_bytes: self.descriptor.to_bytes()?,
+            data_slices: vec![resource],
+        })
+    }
+
+    /// Replaces a region of this texture with `image`, placing its top left corner at (`x`, `y`).
+    ///
+    /// Only the pixels (or for DXT textures, the 4x4 blocks) covered by `image` are re-encoded,
+    /// so the rest of the texture is left byte for byte identical. Use [`Texture::write_to`] to
+    /// put the result back into a [`RawAsset`].
+    ///
+    /// # Errors
+    /// Returns an error if `image` doesn't fit within the texture at the given position, or if
+    /// the texture's format can't be decoded or encoded.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bnl::asset::texture::{Image, Texture};
+    /// # let mut bnl = bnl::BNLFile::new();
+    /// # let rgba = vec![0; 16 * 16 * 4];
+    /// let mut atlas: Texture = bnl.get_asset("aid_texture_hud_atlas").expect("Unable to get texture.");
+    /// let sprite = Image::from_rgba8(16, 16, rgba).expect("Invalid sprite.");
+    ///
+    /// atlas.blit(32, 48, &sprite).expect("Unable to blit sprite.");
+    ///
+    /// let mut raw_asset = bnl.get_raw_asset("aid_texture_hud_atlas").expect("Unable to get raw asset.");
+    /// atlas.write_to(&mut raw_asset).expect("Unable to write texture.");
+    /// bnl.update_asset("aid_texture_hud_atlas", &raw_asset).expect("Unable to update texture.");
+    /// ```
+    pub fn blit(&mut self, x: usize, y: usize, image: &Image) -> Result<(), io::Error> {
+        let width = self.descriptor.width as usize;
+        let height = self.descriptor.height as usize;
+
+        if x + image.width > width || y + image.height > height {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "A {}x{} image at ({}, {}) doesn't fit within the {}x{} texture {}",
+                    image.width, image.height, x, y, width, height, self.name
+                ),
+            ));
+        }
+
+        if image.width == 0 || image.height == 0 {
+            return Ok(());
+        }
+
+        let (_, _, mut rgba) = self.to_rgba8()?;
+
+        for row in 0..image.height {
+            let dst = ((y + row) * width + x) * 4;
+            let src = row * image.width * 4;
+
+            rgba[dst..dst + image.width * 4]
+                .copy_from_slice(&image.bytes[src..src + image.width * 4]);
+        }
+
+        // Grow the region out to whole blocks, which are the smallest unit that can be encoded
+        let block = block_dimension(self.descriptor.format);
+
+        let x0 = x / block * block;
+        let y0 = y / block * block;
+        let x1 = (x + image.width).next_multiple_of(block).min(width);
+        let y1 = (y + image.height).next_multiple_of(block).min(height);
+
+        let region: Vec<u8> = (y0..y1)
+            .flat_map(|row| rgba[(row * width + x0) * 4..(row * width + x1) * 4].to_vec())
+            .collect();
+
+        let encoded = images::encode(x1 - x0, y1 - y0, self.descriptor.format, &region)?;
+
+        // Copy each encoded row of pixels (or blocks) into place
+        let unit_size = (block * block * self.descriptor.format.bits_per_pixel()).div_ceil(8);
+        let row_size = (x1 - x0).div_ceil(block) * unit_size;
+        let stride = width.div_ceil(block) * unit_size;
+
+        for (i, row) in encoded.chunks_exact(row_size).enumerate() {
+            let start = (y0 / block + i) * stride + x0 / block * unit_size;
+
+            self.data
+                .get_mut(start..start + row_size)
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Texture data is smaller than its dimensions",
+                    )
+                })?
+                .copy_from_slice(row);
+        }
+
+        Ok(())
+    }
+
+    /// Writes the texture data of this texture (including any changes made by
+    /// [`Texture::blit`]) back into the resource of `raw_asset`, ready for
+    /// [`crate::BNLFile::update_asset`].
+    ///
+    /// # Errors
+    /// Returns an error if the resource of `raw_asset` is too small to hold the texture data.
+    pub fn write_to(&self, raw_asset: &mut RawAsset) -> Result<(), io::Error> {
+        let mut offset = self.descriptor.texture_offset as usize;
+        let mut remaining = self.data.as_slice();
+
+        for slice in &mut raw_asset.data_slices {
+            if remaining.is_empty() {
+                break;
+            }
+
+            if offset >= slice.len() {
+                offset -= slice.len();
+                continue;
+            }
+
+            let len = (slice.len() - offset).min(remaining.len());
+            slice[offset..offset + len].copy_from_slice(&remaining[..len]);
+
+            remaining = &remaining[len..];
+            offset = 0;
+        }
+
+        if !remaining.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "The resource of {} is too small to hold the texture data",
+                    raw_asset.name
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Writes this texture to `path` as a PNG, see [`Texture::encode_png`].
+    ///
+    /// # Errors
+    /// Returns a [`TextureDumpError`] describing which step failed, including the path when the
+    /// file can't be written (for example, in a read-only directory).
+    pub fn dump(&self, path: &Path) -> Result<(), TextureDumpError> {
+        self.dump_as(path, ExportFormat::Png)
+    }
+
+    /// Writes this texture to `path` as a PNG with the given [`DumpOptions`], see
+    /// [`Texture::encode_png_with`]. With [`DumpOptions::alpha_mask`] set, the alpha channel is
+    /// also written next to it with `_alpha` added to the file name, so `gribble.png` gets a
+    /// `gribble_alpha.png`.
+    ///
+    /// # Errors
+    /// Returns a [`TextureDumpError`] describing which step failed.
+    pub fn dump_with(&self, path: &Path, options: &DumpOptions) -> Result<(), TextureDumpError> {
+        let image = self
+            .to_dump_image(options)
+            .map_err(TextureDumpError::Decode)?;
+
+        let mut outputs = vec![(
+            path.to_path_buf(),
+            images::encode_png_with(image.width, image.height, &image.bytes, options)
+                .map_err(TextureDumpError::Encode)?,
+        )];
+
+        if options.alpha_mask {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+
+            outputs.push((
+                path.with_file_name(format!("{}_alpha.png", stem)),
+                images::encode_alpha_mask(image.width, image.height, &image.bytes, options)
+                    .map_err(TextureDumpError::Encode)?,
+            ));
+        }
+
+        for (path, png) in outputs {
+            fs::write(&path, png).map_err(|source| TextureDumpError::Write { path, source })?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes this texture to `path` as a TGA, see [`Texture::encode_tga`].
+    ///
+    /// # Errors
+    /// Returns a [`TextureDumpError`] describing which step failed.
+    pub fn dump_tga(&self, path: &Path) -> Result<(), TextureDumpError> {
+        self.dump_as(path, ExportFormat::Tga)
+    }
+
+    /// Writes this texture to `path` as a BMP, see [`Texture::encode_bmp`].
+    ///
+    /// # Errors
+    /// Returns a [`TextureDumpError`] describing which step failed.
+    pub fn dump_bmp(&self, path: &Path) -> Result<(), TextureDumpError> {
+        self.dump_as(path, ExportFormat::Bmp)
+    }
+
+    /// Writes the full size image of this texture to `path` in the given format, without its
+    /// mipmaps. See [`Texture::export`] to include them.
+    ///
+    /// # Errors
+    /// Returns a [`TextureDumpError`] describing which step failed.
+    pub fn dump_as(&self, path: &Path, format: ExportFormat) -> Result<(), TextureDumpError> {
+        let bytes = match format {
+            ExportFormat::Dds => self.encode_dds(false).map_err(TextureDumpError::Decode)?,
+            format => {
+                let image = self.to_rgba_image().map_err(TextureDumpError::Decode)?;
+                format.encode(&image).map_err(TextureDumpError::Encode)?
+            }
+        };
+
+        fs::write(path, bytes).map_err(|source| TextureDumpError::Write {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+impl WritableAsset for Texture {
+    /// Writes the descriptor, and the texture data in place within the existing resource, see
+    /// [`Texture::write_to`].
+    fn write_back(&self, raw_asset: &mut RawAsset) -> Result<(), io::Error> {
+        raw_asset.descriptor_bytes = self.descriptor.to_bytes()?;
+        self.write_to(raw_asset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /*
+    #[test]
+    fn texture_descriptor_size() {
+        assert_eq!(size_of::<TextureDescriptor>(), 28);
+    }
+    */
+
+    #[test]
+    fn from_bytes_non_zero_offset() {
+        let data: [u8; 0x1C] = [
+            0x0C, 0x00, 0x00, 0x00, // DXT1
+            0x1C, 0x00, 0x00, 0x00, // Header size
+            0x80, 0x00, // 0x80 wide
+            0x80, 0x00, // 0x80 high
+            0x00, 0x00, 0x00, 0x08, // Flags
+            0x00, 0x01, 0x00, 0x00, // Unknown
+            0x00, 0x52, 0x01, 0x00, // Offset
+            0x00, 0x2B, 0x00, 0x00, // Size
+        ];
+
+        let tex_desc = TextureDescriptor::from_bytes(&data).unwrap();
+        assert_eq!(tex_desc.format, D3DFormat::Standard(StandardFormat::DXT1));
+        assert_eq!(tex_desc.header_size, 0x1c);
+        assert_eq!(tex_desc.width, 0x80);
+        assert_eq!(tex_desc.height, 0x80);
+        assert_eq!(tex_desc.texture_offset, 0x15200);
+        assert_eq!(tex_desc.texture_size, 0x2b00);
+    }
+
+    #[test]
+    fn from_bytes_zero_offset() {
+        let data: [u8; 0x1C] = [
+            0x0C, 0x00, 0x00, 0x00, // DXT1
+            0x1C, 0x00, 0x00, 0x00, // Header size
+            0x80, 0x00, // 0x80 wide
+            0x80, 0x00, // 0x80 high
+            0x00, 0x00, 0x00, 0x08, // Flags
+            0x00, 0x01, 0x00, 0x00, // Unknown
+            0x00, 0x00, 0x00, 0x00, // Offset
+            0x00, 0x2B, 0x00, 0x00, // Size
+        ];
+
+        let tex_desc = TextureDescriptor::from_bytes(&data).unwrap();
+        assert_eq!(tex_desc.format, D3DFormat::Standard(StandardFormat::DXT1));
+        assert_eq!(tex_desc.header_size, 0x1c);
+        assert_eq!(tex_desc.width, 0x80);
+        assert_eq!(tex_desc.height, 0x80);
+        assert_eq!(tex_desc.texture_offset, 0);
+        assert_eq!(tex_desc.texture_size, 0x2b00);
+    }
+
+    #[test]
+    fn preview_samples_rows() {
+        let mut data = vec![];
+
+        // 8x8 A4R4G4B4 texture, where the red channel of each pixel is its row
+        for y in 0..8u16 {
+            for _ in 0..8 {
+                data.extend_from_slice(&(0xf000 | (y << 8)).to_le_bytes());
+            }
+        }
+
+        let texture = Texture {
+            name: "aid_texture_preview".to_string(),
+            descriptor: TextureDescriptor::new(
+                D3DFormat::Linear(LinearColour::A4R4G4B4),
+                28,
+                8,
+                8,
+                0,
+                0,
+                0,
+                data.len() as u32,
+            ),
+            data,
+            palette: None,
+        };
+
+        let preview = texture.to_preview_image(3, None).unwrap();
+
+        assert_eq!((preview.width(), preview.height()), (3, 3));
+        assert_eq!(preview.bytes().len(), 3 * 3 * 4);
+
+        // Rows 0, 3 and 6 are sampled
+        assert_eq!(preview.bytes()[0], 0x00);
+        assert_eq!(preview.bytes()[3 * 4], 0x33);
+        assert_eq!(preview.bytes()[6 * 4], 0x66);
+    }
+
+    #[test]
+    fn dump_reports_write_path() {
+        let texture = Texture {
+            name: "aid_texture_dump".to_string(),
+            descriptor: TextureDescriptor::new(
+                D3DFormat::Linear(LinearColour::A4R4G4B4),
+                28,
+                1,
+                1,
+                0,
+                0,
+                0,
+                2,
+            ),
+            data: vec![0xff; 2],
+            palette: None,
+        };
+
+        let path = std::env::temp_dir()
+            .join("bnl_missing_dir")
+            .join("texture.png");
+
+        match texture.dump(&path) {
+            Err(TextureDumpError::Write {
+                path: error_path, ..
+            }) => assert_eq!(error_path, path),
+            result => panic!("Expected a write error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn dump_writes_alpha_mask() {
+        let texture = Texture {
+            name: "aid_texture_mask".to_string(),
+            descriptor: TextureDescriptor::new(
+                D3DFormat::Standard(StandardFormat::DXT1),
+                28,
+                4,
+                4,
+                0,
+                0,
+                0,
+                8,
+            ),
+            data: vec![0; 8],
+            palette: None,
+        };
+
+        let dir = std::env::temp_dir().join(format!("bnl_mask_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let options = DumpOptions::default()
+            .dxt1_opaque(true)
+            .alpha(AlphaMode::Premultiply)
+            .alpha_mask(true);
+        texture.dump_with(&dir.join("mask.png"), &options).unwrap();
+
+        assert!(dir.join("mask.png").exists());
+
+        let mask = fs::read(dir.join("mask_alpha.png")).unwrap();
+        assert_eq!(mask, texture.encode_alpha_mask(&options).unwrap());
+
+        let mut reader = png::Decoder::new(mask.as_slice()).read_info().unwrap();
+        let mut data = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut data).unwrap();
+        assert_eq!(data, [0xFF; 16]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn exports_mipmaps() {
+        let texture = |format: D3DFormat, size: u16, data: Vec<u8>| Texture {
+            name: "aid_texture_mips".to_string(),
+            descriptor: TextureDescriptor::new(format, 28, size, size, 0, 0, 0, data.len() as u32),
+            data,
+            palette: None,
+        };
+
+        // 4x4, 2x2 and 1x1 levels of 16-bit pixels, plus a partial level that doesn't count
+        let a4r4g4b4 = texture(
+            D3DFormat::Linear(LinearColour::A4R4G4B4),
+            4,
+            [0xff; 2].repeat(16 + 4 + 1 + 1),
+        );
+        assert_eq!(a4r4g4b4.mip_levels(), 3);
+
+        let level = a4r4g4b4.mip_image(1).unwrap();
+        assert_eq!((level.width(), level.height()), (2, 2));
+        assert_eq!(level.bytes(), [0xff; 4].repeat(4));
+
+        // Decoding in memory only returns the full size level
+        let (width, height, rgba) = a4r4g4b4.to_rgba8().unwrap();
+        assert_eq!((width, height), (4, 4));
+        assert_eq!(rgba, [0xff; 4].repeat(16));
+
+        let dir = std::env::temp_dir().join(format!("bnl_mips_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let paths = a4r4g4b4.export(&dir, ExportFormat::Tga, true).unwrap();
+        assert_eq!(paths.len(), 3);
+        assert_eq!(paths[2], dir.join("aid_texture_mips_mip2.tga"));
+        assert_eq!(fs::read(&paths[2]).unwrap().len(), 18 + 4);
+
+        let paths = a4r4g4b4.export(&dir, ExportFormat::Dds, true).unwrap();
+        let dds = fs::read(&paths[0]).unwrap();
+        assert_eq!(&dds[..4], b"DDS ");
+        assert_eq!(dds.len(), 128 + (16 + 4 + 1) * 4);
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        // DXT levels are stored as whole 4x4 blocks, down to a 1x1 level
+        let dxt1 = texture(
+            D3DFormat::Standard(StandardFormat::DXT1),
+            8,
+            vec![0; 4 * 8 + 3 * 8],
+        );
+        assert_eq!(dxt1.mip_levels(), 4);
+
+        let dds = dxt1.encode_dds(true).unwrap();
+        assert_eq!(&dds[84..88], b"DXT1");
+        assert_eq!(u32::from_le_bytes(dds[28..32].try_into().unwrap()), 4);
+        assert_eq!(dds.len(), 128 + 4 * 8 + 3 * 8);
+        assert_eq!(dxt1.encode_dds(false).unwrap().len(), 128 + 4 * 8);
+    }
+
+    #[test]
+    fn blit_replaces_region() {
+        let data: Vec<u8> = (0..16u16)
+            .flat_map(|i| (0xf000 | i).to_le_bytes())
+            .collect();
+
+        let mut texture = Texture {
+            name: "aid_texture_blit".to_string(),
+            descriptor: TextureDescriptor::new(
+                D3DFormat::Linear(LinearColour::A4R4G4B4),
+                28,
+                4,
+                4,
+                0,
+                0,
+                2,
+                data.len() as u32,
+            ),
+            data: data.clone(),
+            palette: None,
+        };
+
+        let image = Image::from_rgba8(2, 1, [0xff, 0x00, 0x00, 0xff].repeat(2)).unwrap();
+        texture.blit(1, 2, &image).unwrap();
+
+        let mut expected = data;
+        expected[18..22].copy_from_slice(&[0x00, 0xff, 0x00, 0xff]);
+        assert_eq!(texture.data, expected);
+
+        assert!(texture.blit(3, 3, &image).is_err());
+
+        let mut raw_asset = RawAsset {
+            name: "aid_texture_blit".to_string(),
+            asset_type: AssetType::ResTexture,
+            descriptor_bytes: vec![],
+            data_slices: vec![vec![0; 10], vec![0; 24]],
+        };
+
+        texture.write_to(&mut raw_asset).unwrap();
+        assert_eq!(raw_asset.data_slices.concat()[2..], expected);
+
+        raw_asset.data_slices.pop();
+        assert!(texture.write_to(&mut raw_asset).is_err());
+    }
+
+    #[test]
+    fn p8_palette_follows_data() {
+        // A 4x4 P8 texture with 2 mip levels, followed by its palette
+        let data: Vec<u8> = (0..16 + 4).collect();
+        let palette = [0xab; PALETTE_SIZE];
+
+        let slices = [data.as_slice(), palette.as_slice()];
+        let virtual_res = VirtualResource::from_slices(&slices);
+
+        let descriptor = TextureDescriptor::new(
+            D3DFormat::Standard(StandardFormat::P8),
+            28,
+            4,
+            4,
+            0,
+            0,
+            0,
+            data.len() as u32,
+        );
+
+        let texture = Texture::new("aid_texture_p8", &descriptor, &virtual_res).unwrap();
+
+        assert_eq!(texture.palette(), Some(palette.as_slice()));
+        assert_eq!(texture.mip_levels(), 2);
+
+        // The indices are swizzled, so they can't be decoded as if they were linear
+        let error = texture.to_rgba_image().err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+
+        let raw_asset = texture.to_raw_asset().unwrap();
+        assert_eq!(raw_asset.data_slices[0], [data, palette.to_vec()].concat());
+    }
+
+    #[test]
+    fn import_checks_dxt_alignment() {
+        let dxt1 = D3DFormat::Standard(StandardFormat::DXT1);
+        let image = Image::from_rgba8(6, 4, [0xff; 4].repeat(24)).unwrap();
+
+        assert!(matches!(
+            Texture::from_image("aid_texture_import", &image, dxt1, &Default::default()),
+            Err(TextureImportError::UnalignedDimensions { width: 6, .. })
+        ));
+
+        let mut options = TextureImportOptions {
+            dxt_alignment: DxtAlignment::Pad,
+            ..Default::default()
+        };
+
+        let texture = Texture::from_image("aid_texture_import", &image, dxt1, &options).unwrap();
+        assert_eq!(
+            (texture.descriptor().width(), texture.descriptor().height()),
+            (8, 4)
+        );
+        assert_eq!(texture.data.len(), 16);
+
+        // The padded half of the second block is transparent, so it uses 3 colour mode
+        assert_eq!(texture.data[0..8], [0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0]);
+        assert_eq!(texture.data[12] & 0b1111, 0b0000);
+        assert_eq!(texture.data[12] >> 4, 0b1111);
+
+        options.dxt_alignment = DxtAlignment::Resize;
+        let texture = Texture::from_image("aid_texture_import", &image, dxt1, &options).unwrap();
+        assert_eq!(texture.data[8..16], [0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0]);
+
+        let raw_asset = texture.to_raw_asset().unwrap();
+        let descriptor = TextureDescriptor::from_bytes(&raw_asset.descriptor_bytes).unwrap();
+        assert_eq!(descriptor.format(), dxt1);
+        assert_eq!(descriptor.texture_size, 16);
+        assert_eq!(raw_asset.data_slices, [texture.data]);
+
+        // Formats without blocks don't need aligning
+        let a4r4g4b4 = D3DFormat::Linear(LinearColour::A4R4G4B4);
+        let texture =
+            Texture::from_image("aid_texture_import", &image, a4r4g4b4, &Default::default())
+                .unwrap();
+        assert_eq!(texture.data.len(), 48);
+
+        // Swizzled formats need power of two dimensions
+        let b8g8r8a8 = D3DFormat::Swizzled(Swizzled::B8G8R8A8);
+        assert!(matches!(
+            Texture::from_image("aid_texture_import", &image, b8g8r8a8, &Default::default()),
+            Err(TextureImportError::Constraint(
+                ConstraintViolation::TextureNotPowerOfTwo { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn import_infers_format() {
+        let opaque = Image::from_rgba8(128, 128, [0xff; 4].repeat(128 * 128)).unwrap();
+
+        let mut pixels = [0xff; 4].repeat(128 * 128);
+        pixels[3] = 0x80;
+        let transparent = Image::from_rgba8(128, 128, pixels).unwrap();
+
+        let icon = Image::from_rgba8(16, 16, [0x00; 4].repeat(16 * 16)).unwrap();
+
+        let import = |image: &Image, options: &TextureImportOptions| {
+            Texture::import("aid_texture_import", image, options).unwrap()
+        };
+
+        let texture = import(&opaque, &Default::default());
+        assert_eq!(
+            texture.descriptor().format(),
+            D3DFormat::Standard(StandardFormat::DXT1)
+        );
+        assert_eq!(texture.descriptor().texture_size, 128 * 128 / 2);
+
+        let texture = import(&transparent, &Default::default());
+        assert_eq!(
+            texture.descriptor().format(),
+            D3DFormat::Standard(StandardFormat::DXT4Or5)
+        );
+        assert_eq!(texture.descriptor().texture_size, 128 * 128);
+
+        let texture = import(&icon, &Default::default());
+        assert_eq!(
+            texture.descriptor().format(),
+            D3DFormat::Linear(LinearColour::A4R4G4B4)
+        );
+
+        let options = TextureImportOptions {
+            format: Some(D3DFormat::Swizzled(Swizzled::B8G8R8A8)),
+            flags: Some(0x10),
+            ..Default::default()
+        };
+
+        let texture = import(&icon, &options);
+        assert_eq!(
+            texture.descriptor().format(),
+            D3DFormat::Swizzled(Swizzled::B8G8R8A8)
+        );
+        assert_eq!(texture.descriptor().flags, 0x10);
+    }
+
+    #[test]
+    fn template_round_trips() {
+        let template = TextureDescriptor::template();
+        let descriptor = TextureDescriptor::from_bytes(&template.to_bytes().unwrap()).unwrap();
+
+        assert_eq!(descriptor.format(), template.format());
+        assert_eq!(descriptor.header_size, TEXTURE_DESCRIPTOR_SIZE as u32);
+        assert_eq!(descriptor.texture_size as usize, descriptor.required_size());
+    }
+}
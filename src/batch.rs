@@ -0,0 +1,307 @@
+//! Runs the same work over every bundle in a directory, such as the game's whole bundles folder,
+//! opening and processing the bundles in parallel.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use rayon::prelude::*;
+
+use crate::{
+    BNLFile, asset::AssetDescription, filter::AssetFilter, format, glob_match,
+    workspace::WorkspaceError,
+};
+
+/// Options controlling which bundles [`process_dir`] opens, and how.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct BatchOptions {
+    /// Glob patterns for the file names of the bundles to open, where `*` matches any run of
+    /// characters and `?` matches any single character. When empty, every `.bnl` file is opened.
+    pub patterns: Vec<String>,
+    /// Also open the bundles in subdirectories.
+    pub recursive: bool,
+    /// The number of bundles to process at once. Zero uses one thread per CPU.
+    pub threads: usize,
+}
+
+impl BatchOptions {
+    pub fn pattern(mut self, pattern: &str) -> Self {
+        self.patterns.push(pattern.to_string());
+        self
+    }
+
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Returns true if a file called `file_name` passes these options.
+    fn matches(&self, file_name: &str) -> bool {
+        match self.patterns.is_empty() {
+            true => Path::new(file_name)
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("bnl")),
+            false => self
+                .patterns
+                .iter()
+                .any(|pattern| glob_match(pattern.as_bytes(), file_name.as_bytes())),
+        }
+    }
+}
+
+/// The path of a bundle processed by [`process_dir`], alongside the result of the callback.
+pub type BatchResult<R> = (PathBuf, Result<R, WorkspaceError>);
+
+/// Opens every bundle in `dir` that passes `options`, in any of the supported
+/// [`format::FORMATS`], and calls `callback` with each one. Bundles are processed in parallel,
+/// but the results are returned in path order, alongside the path of their bundle.
+///
+/// A bundle that can't be read or parsed gives an error in place of its result, without stopping
+/// the others.
+///
+/// # Errors
+/// Returns an error if `dir` can't be read, or the thread pool can't be started.
+///
+/// # Examples
+/// ```no_run
+/// use std::path::Path;
+///
+/// use bnl::batch::{BatchOptions, process_dir};
+///
+/// # fn main() -> Result<(), bnl::workspace::WorkspaceError> {
+/// let options = BatchOptions::default().recursive(true);
+///
+/// let results = process_dir(Path::new("./game/bundles"), &options, |path, bnl| {
+///     bnl.asset_descriptions().len()
+/// })?;
+///
+/// for (path, count) in results {
+///     match count {
+///         Ok(count) => println!("{}: {} assets", path.display(), count),
+///         Err(e) => eprintln!("{}", e),
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn process_dir<F, R>(
+    dir: &Path,
+    options: &BatchOptions,
+    callback: F,
+) -> Result<Vec<BatchResult<R>>, WorkspaceError>
+where
+    F: Fn(&Path, BNLFile) -> R + Sync,
+    R: Send,
+{
+    run(dir, options, |path| {
+        open_bundle(path).map(|bnl| callback(path, bnl))
+    })
+}
+
+/// Searches the asset tables of every BNL file in `dir` that passes `options`, returning the
+/// descriptions of the assets that pass `filter` alongside the path of their bundle. Only the
+/// header and asset table of each bundle are read, so this is much faster than opening them.
+///
+/// Bundles without a matching asset are left out, while bundles that can't be read give an error.
+/// Unlike [`process_dir`], only plain BNL files are searched, not the other [`format::FORMATS`].
+///
+/// # Errors
+/// Returns an error if `dir` can't be read, or the thread pool can't be started.
+///
+/// # Examples
+/// ```no_run
+/// use std::path::Path;
+///
+/// use bnl::{batch::{BatchOptions, find_assets}, filter::AssetFilter};
+///
+/// # fn main() -> Result<(), bnl::workspace::WorkspaceError> {
+/// let options = BatchOptions::default().recursive(true);
+/// let filter = AssetFilter::default().pattern("aid_model_gribble*");
+///
+/// for (path, found) in find_assets(Path::new("./game/bundles"), &options, &filter)? {
+///     for asset_desc in found? {
+///         println!("{}: {}", path.display(), asset_desc.name());
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn find_assets(
+    dir: &Path,
+    options: &BatchOptions,
+    filter: &AssetFilter,
+) -> Result<Vec<BatchResult<Vec<AssetDescription>>>, WorkspaceError> {
+    let mut results = run(dir, options, |path| {
+        let bytes = fs::read(path).map_err(|error| WorkspaceError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+
+        let asset_descs =
+            BNLFile::read_asset_descriptions(&bytes).map_err(|error| WorkspaceError::Bundle {
+                path: path.to_path_buf(),
+                error,
+            })?;
+
+        Ok(asset_descs
+            .into_iter()
+            .filter(|asset_desc| filter.matches(asset_desc))
+            .collect::<Vec<_>>())
+    })?;
+
+    results.retain(|(_, found)| !matches!(found, Ok(found) if found.is_empty()));
+
+    Ok(results)
+}
+
+/// Calls `work` with the path of every bundle in `dir` that passes `options`, in parallel, and
+/// returns the results in path order.
+fn run<F, R>(
+    dir: &Path,
+    options: &BatchOptions,
+    work: F,
+) -> Result<Vec<BatchResult<R>>, WorkspaceError>
+where
+    F: Fn(&Path) -> Result<R, WorkspaceError> + Sync,
+    R: Send,
+{
+    let mut paths = Vec::new();
+    let mut errors = Vec::new();
+
+    bundle_paths(dir, options, &mut paths, &mut errors).map_err(|error| WorkspaceError::Io {
+        path: dir.to_path_buf(),
+        error,
+    })?;
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(options.threads)
+        .build()
+        .map_err(|e| WorkspaceError::Io {
+            path: dir.to_path_buf(),
+            error: std::io::Error::other(e),
+        })?;
+
+    let mut results: Vec<BatchResult<R>> = pool.install(|| {
+        paths
+            .into_par_iter()
+            .map(|path| {
+                let result = work(&path);
+                (path, result)
+            })
+            .collect()
+    });
+
+    results.extend(errors.into_iter().map(|error| match &error {
+        WorkspaceError::Io { path, .. } | WorkspaceError::Bundle { path, .. } => {
+            (path.clone(), Err(error))
+        }
+    }));
+    results.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    Ok(results)
+}
+
+fn open_bundle(path: &Path) -> Result<BNLFile, WorkspaceError> {
+    let bytes = fs::read(path).map_err(|error| WorkspaceError::Io {
+        path: path.to_path_buf(),
+        error,
+    })?;
+
+    format::parse(&bytes)
+        .map(|(bnl, _)| bnl)
+        .map_err(|error| WorkspaceError::Bundle {
+            path: path.to_path_buf(),
+            error,
+        })
+}
+
+/// Collects the paths of the bundles in `dir` that pass `options`. Subdirectories that can't be
+/// read are recorded in `errors`, while an error reading `dir` itself is returned.
+fn bundle_paths(
+    dir: &Path,
+    options: &BatchOptions,
+    paths: &mut Vec<PathBuf>,
+    errors: &mut Vec<WorkspaceError>,
+) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            if options.recursive
+                && let Err(error) = bundle_paths(&path, options, paths, errors)
+            {
+                errors.push(WorkspaceError::Io { path, error });
+            }
+        } else if path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| options.matches(name))
+        {
+            paths.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::make_bnl;
+
+    #[test]
+    fn processes_every_bundle() {
+        let dir = std::env::temp_dir().join(format!("bnl_batch_{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+
+        fs::write(dir.join("a.bnl"), make_bnl()).unwrap();
+        fs::write(dir.join("b.BNL"), make_bnl()).unwrap();
+        fs::write(dir.join("broken.bnl"), b"not a bundle").unwrap();
+        fs::write(dir.join("notes.txt"), b"").unwrap();
+        fs::write(dir.join("sub/c.bnl"), make_bnl()).unwrap();
+
+        let count_assets = |_: &Path, bnl: BNLFile| bnl.asset_descriptions().len();
+
+        let results = process_dir(&dir, &BatchOptions::default(), count_assets).unwrap();
+        let names: Vec<_> = results
+            .iter()
+            .map(|(path, _)| path.file_name().unwrap().to_str().unwrap())
+            .collect();
+
+        assert_eq!(names, ["a.bnl", "b.BNL", "broken.bnl"]);
+        assert!(matches!(results[0].1, Ok(1)));
+        assert!(matches!(results[2].1, Err(WorkspaceError::Bundle { .. })));
+
+        let options = BatchOptions::default()
+            .recursive(true)
+            .pattern("?.bnl")
+            .threads(1);
+
+        let results = process_dir(&dir, &options, count_assets).unwrap();
+        let paths: Vec<_> = results.into_iter().map(|(path, _)| path).collect();
+
+        assert_eq!(paths, [dir.join("a.bnl"), dir.join("sub/c.bnl")]);
+
+        let filter = AssetFilter::default().pattern("aid_texture_*");
+        let found = find_assets(&dir, &BatchOptions::default(), &filter).unwrap();
+
+        assert_eq!(found.len(), 3);
+        assert_eq!(found[0].1.as_ref().unwrap()[0].name(), "aid_texture_test");
+        assert!(found[2].1.is_err());
+
+        let filter = AssetFilter::default().pattern("aid_model_*");
+        let found = find_assets(&dir, &BatchOptions::default(), &filter).unwrap();
+
+        assert_eq!(found.len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(process_dir(&dir, &BatchOptions::default(), count_assets).is_err());
+    }
+}
@@ -0,0 +1,170 @@
+//! A small read-only HTTP server for browsing a BNL file from a web browser.
+//!
+//! Routes:
+//! - `GET /` - A basic HTML page showing every texture in the BNL file
+//! - `GET /assets` - The asset list as JSON
+//! - `GET /textures/<name>.png` - A texture asset converted to PNG
+
+use std::io;
+
+use bnl::{BNLFile, asset::texture::Texture, game::AssetType};
+use tiny_http::{Header, Method, Response, Server};
+
+use crate::serve;
+
+pub fn serve(bnl: &BNLFile, addr: &str) -> io::Result<()> {
+    let server = Server::http(addr).map_err(io::Error::other)?;
+
+    eprintln!("Serving on http://{}", addr);
+
+    for request in server.incoming_requests() {
+        if *request.method() != Method::Get {
+            request.respond(Response::from_string("Method not allowed").with_status_code(405))?;
+            continue;
+        }
+
+        let url = request.url().to_string();
+
+        let (content_type, body): (&str, Result<Vec<u8>, (u16, String)>) = match url.as_str() {
+            "/" => ("text/html", Ok(index(bnl).into_bytes())),
+            "/assets" => (
+                "application/json",
+                Ok(serve::list(bnl).to_string().into_bytes()),
+            ),
+            _ => match url
+                .strip_prefix("/textures/")
+                .and_then(|name| name.strip_suffix(".png"))
+                .and_then(percent_decode)
+            {
+                Some(name) => ("image/png", texture_png(bnl, &name)),
+                None => ("text/plain", Err((404, "Not found".to_string()))),
+            },
+        };
+
+        let response = match body {
+            Ok(body) => Response::from_data(body).with_header(
+                Header::from_bytes("Content-Type", content_type).expect("Invalid header"),
+            ),
+            Err((status, message)) => {
+                Response::from_data(message.into_bytes()).with_status_code(status)
+            }
+        };
+
+        // A client hanging up shouldn't take down the whole server
+        if let Err(e) = request.respond(response) {
+            eprintln!("Unable to respond to request for {}.\nError: {}", url, e);
+        }
+    }
+
+    Ok(())
+}
+
+fn texture_png(bnl: &BNLFile, name: &str) -> Result<Vec<u8>, (u16, String)> {
+    let texture = bnl
+        .get_asset::<Texture>(name)
+        .map_err(|e| (404, e.to_string()))?;
+
+    texture.encode_png().map_err(|e| (500, e.to_string()))
+}
+
+fn index(bnl: &BNLFile) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>BNL viewer</title></head><body>\n",
+    );
+
+    for desc in bnl.asset_descriptions() {
+        if desc.asset_type() != AssetType::ResTexture {
+            continue;
+        }
+
+        html.push_str(&format!(
+            "<figure><img src=\"/textures/{}.png\" loading=\"lazy\"><figcaption>{}</figcaption></figure>\n",
+            percent_encode(desc.name()),
+            escape_html(desc.name())
+        ));
+    }
+
+    html.push_str("</body></html>\n");
+
+    html
+}
+
+/// Escapes the characters of `text` that have a meaning in HTML, so that asset names can't inject
+/// markup into the page.
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Percent-encodes every byte of `text` other than the unreserved URL characters, for use as a
+/// path segment.
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            byte => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Decodes a percent-encoded path segment, the inverse of [`percent_encode`]. Returns None if an
+/// escape is malformed or the result isn't UTF-8.
+fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail
+                .get(..2)
+                .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))?;
+            bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_names() {
+        assert_eq!(
+            escape_html("<img src=x onerror='a&b'>\""),
+            "&lt;img src=x onerror=&#39;a&amp;b&#39;&gt;&quot;"
+        );
+
+        let name = "aid_texture_<b>\"hud\" 50%";
+        let encoded = percent_encode(name);
+
+        assert_eq!(encoded, "aid_texture_%3Cb%3E%22hud%22%2050%25");
+        assert_eq!(percent_decode(&encoded).as_deref(), Some(name));
+        assert_eq!(
+            percent_decode("aid_texture_x").as_deref(),
+            Some("aid_texture_x")
+        );
+        assert!(percent_decode("bad%4").is_none());
+        assert!(percent_decode("bad%zz").is_none());
+        assert!(percent_decode("bad%+1").is_none());
+    }
+}
@@ -1,48 +1,609 @@
+#[cfg(feature = "http")]
+mod http;
+mod serve;
+
 use std::{
-    env,
     ffi::OsStr,
     fs,
     path::{Path, PathBuf},
 };
 
-use bnl::BNLFile;
+use bnl::{
+    BNLFile,
+    analysis::{
+        call_graph::CallGraph,
+        dependency_graph::DependencyGraph,
+        field_survey::FieldSurvey,
+        string_index::{StringEncoding, StringIndex},
+    },
+    asset::{
+        Asset,
+        registry::AssetRegistry,
+        texture::{ExportFormat, Image, Texture, atlas::AtlasOptions},
+    },
+    batch::{self, BatchOptions},
+    contact_sheet::ContactSheetOptions,
+    extract::{self, ExtractJournal, ExtractOptions},
+    filter::AssetFilter,
+    game::AssetType,
+};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
+/// Inspect, extract and modify Grabbed by the Ghoulies BNL bundles.
+#[derive(Parser)]
+#[command(name = "bnltool", version, arg_required_else_help = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
 
-    // TODO: Refactor this to use a CLI args crate if this gets worked on more
-    if args.len() != 3 {
-        print_usage();
-        return;
+#[derive(Subcommand)]
+enum Command {
+    /// List every asset in a BNL file with its type and sizes
+    List {
+        bnl: PathBuf,
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+    /// Print the asset table of a BNL file, including pointers and unknown fields
+    Manifest {
+        bnl: PathBuf,
+        /// Print the manifest as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print the details and parsed descriptor of one asset
+    Info { bnl: PathBuf, name: String },
+    /// Print the asset table entry and descriptor of one asset as annotated hex
+    Hexdump { bnl: PathBuf, name: String },
+    /// Extract every asset's descriptor and resources to ./out/<bundle>_bnl
+    Extract {
+        bnl: PathBuf,
+        /// Skip assets already recorded as complete by a previous run
+        #[arg(long)]
+        resume: bool,
+        /// Also decode textures to texture.png
+        #[arg(long)]
+        png: bool,
+        /// The number of threads to extract with (defaults to one per CPU)
+        #[arg(long, default_value_t = 0)]
+        threads: usize,
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+    /// Rebuild a BNL file from an extracted directory
+    Pack {
+        /// The directory written by extract
+        dir: PathBuf,
+        /// The BNL file to write
+        output: PathBuf,
+        /// Write the assets back over the BNL file they were extracted from instead, keeping its
+        /// layout. Asset sizes can't change in this mode.
+        #[arg(long)]
+        base: Option<PathBuf>,
+    },
+    /// Convert a texture asset to an image
+    Convert {
+        bnl: PathBuf,
+        name: String,
+        output: PathBuf,
+        /// The image format to write (defaults to the output's extension, or PNG)
+        #[arg(long, value_enum)]
+        format: Option<ImageFormat>,
+    },
+    /// Export every texture in a BNL file as an image
+    Textures {
+        bnl: PathBuf,
+        #[arg(long, value_enum, default_value_t = ImageFormat::Png)]
+        format: ImageFormat,
+        /// The directory to write to (defaults to ./out/<bundle>_textures)
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// Also export mipmaps, into the DDS file or as <name>_mipN files
+        #[arg(long)]
+        mipmaps: bool,
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+    /// Check a BNL file for structural problems
+    Validate { bnl: PathBuf },
+    /// List resources that are wrapped in a known file format
+    Wrappers { bnl: PathBuf },
+    /// Write a copy of a BNL file without the given asset types
+    Strip {
+        bnl: PathBuf,
+        output: PathBuf,
+        #[arg(required = true)]
+        asset_types: Vec<String>,
+    },
+    /// Draw every texture into a single PNG
+    Contactsheet {
+        bnl: PathBuf,
+        output: PathBuf,
+        #[arg(long)]
+        columns: Option<usize>,
+    },
+    /// Pack textures into atlas PNGs, with their coordinates in atlas.json
+    Atlas {
+        bnl: PathBuf,
+        output: PathBuf,
+        /// The width and maximum height of each atlas page
+        #[arg(long)]
+        page_size: Option<usize>,
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+    /// Print the JSON schema of the asset types
+    Schema,
+    /// Serve requests for a BNL file over stdio or TCP
+    Serve {
+        bnl: PathBuf,
+        #[arg(long)]
+        tcp: Option<String>,
+        /// Where the save method writes the BNL file. Saving is disabled without it
+        #[arg(long)]
+        save_to: Option<PathBuf>,
+    },
+    /// Browse a BNL file over HTTP
+    #[cfg(feature = "http")]
+    Http {
+        bnl: PathBuf,
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        addr: String,
+    },
+    /// Build a call graph of scripts and cutscenes
+    Callgraph {
+        /// BNL files, or directories of them
+        paths: Vec<PathBuf>,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Build a dependency graph between assets
+    Depgraph {
+        /// BNL files, or directories of them
+        paths: Vec<PathBuf>,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Survey the values at each offset of a type's descriptors, to help decode unknown fields
+    Survey {
+        /// The asset type to survey, such as texture or ResTexture
+        asset_type: String,
+        /// BNL files, or directories of them
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Survey the asset table entries of the type instead of its descriptors
+        #[arg(long)]
+        table: bool,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Index the strings of many BNL files into a file for grep
+    Index {
+        index: PathBuf,
+        /// BNL files, or directories of them
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+    },
+    /// Search an index built by the index command
+    Grep { index: PathBuf, text: String },
+    /// Summarise the asset types, texture formats and largest assets of a BNL file
+    Stats { bnl: PathBuf },
+    /// Find the bundles holding assets with names matching a glob pattern
+    Find {
+        pattern: String,
+        /// The directory of BNL files to search, including its subdirectories
+        #[arg(long, default_value = ".")]
+        dir: PathBuf,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ImageFormat {
+    Png,
+    Dds,
+    Tga,
+    Bmp,
+}
+
+impl From<ImageFormat> for ExportFormat {
+    fn from(value: ImageFormat) -> Self {
+        match value {
+            ImageFormat::Png => ExportFormat::Png,
+            ImageFormat::Dds => ExportFormat::Dds,
+            ImageFormat::Tga => ExportFormat::Tga,
+            ImageFormat::Bmp => ExportFormat::Bmp,
+        }
     }
+}
+
+/// Limits a command to some of the assets in a BNL file.
+#[derive(Args)]
+struct FilterArgs {
+    /// Only include assets of this type, such as texture or ResTexture (repeatable)
+    #[arg(long = "type", value_name = "TYPE")]
+    asset_types: Vec<String>,
+    /// Only include assets with names matching this glob pattern (repeatable)
+    #[arg(long = "filter", value_name = "PATTERN")]
+    patterns: Vec<String>,
+}
+
+impl FilterArgs {
+    fn to_filter(&self) -> AssetFilter {
+        let mut filter = AssetFilter::default();
+
+        for name in &self.asset_types {
+            let Some(asset_type) = AssetType::from_name(name) else {
+                eprintln!("Unknown asset type {}.", name);
+                error_exit();
+            };
+
+            filter = filter.asset_type(asset_type);
+        }
+
+        for pattern in &self.patterns {
+            filter = filter.pattern(pattern);
+        }
 
-    if &args[1].to_lowercase() != "-x" {
-        eprintln!("Expected -x as second argument.");
-        error_exit(true);
+        filter
     }
+}
+
+fn main() {
+    match Cli::parse().command {
+        Command::List { bnl, filter } => list(&bnl, &filter.to_filter()),
+        Command::Manifest { bnl, json } => manifest(&bnl, json),
+        Command::Info { bnl, name } => info(&bnl, &name),
+        Command::Hexdump { bnl, name } => hexdump(&bnl, &name),
+        Command::Extract {
+            bnl,
+            resume,
+            png,
+            threads,
+            filter,
+        } => {
+            let options = ExtractOptions::default()
+                .threads(threads)
+                .png_textures(png)
+                .filter(filter.to_filter());
+
+            extract(&bnl, resume, &options);
+        }
+        Command::Pack { dir, output, base } => pack(&dir, &output, base.as_deref()),
+        Command::Convert {
+            bnl,
+            name,
+            output,
+            format,
+        } => {
+            let format = match format {
+                Some(format) => format.into(),
+                None => output
+                    .extension()
+                    .and_then(OsStr::to_str)
+                    .and_then(ExportFormat::from_extension)
+                    .unwrap_or_default(),
+            };
+
+            convert(&bnl, &name, &output, format)
+        }
+        Command::Textures {
+            bnl,
+            format,
+            output,
+            mipmaps,
+            filter,
+        } => textures(
+            &bnl,
+            output.as_deref(),
+            format.into(),
+            mipmaps,
+            &filter.to_filter(),
+        ),
+        Command::Validate { bnl } => {
+            let bnl = load_bnl(&bnl);
+            let report = bnl.validate();
+
+            for issue in &report.issues {
+                println!("{}", issue);
+            }
+
+            println!(
+                "{} errors, {} warnings",
+                report.errors().count(),
+                report.warnings().count()
+            );
+
+            if !report.is_ok() {
+                error_exit();
+            }
+        }
+        Command::Wrappers { bnl } => {
+            let bnl = load_bnl(&bnl);
+            let wrapped = bnl.find_wrapped_resources();
+
+            for resource in &wrapped {
+                println!(
+                    "{} resource {}: {} ({} bytes)",
+                    resource.asset, resource.slice_index, resource.signature.name, resource.size
+                );
+            }
+
+            println!("{} wrapped resources found.", wrapped.len());
+        }
+        Command::Strip {
+            bnl,
+            output,
+            asset_types,
+        } => strip(&bnl, &output, &asset_types),
+        Command::Contactsheet {
+            bnl,
+            output,
+            columns,
+        } => {
+            let mut options = ContactSheetOptions::default();
 
-    let bnl_path = PathBuf::from(&args[2]);
+            if let Some(columns) = columns {
+                options = options.columns(columns);
+            }
+
+            let bnl = load_bnl(&bnl);
+
+            match bnl.write_contact_sheet(&output, &options) {
+                Ok(count) => println!("Drew {} textures to {}", count, output.display()),
+                Err(e) => {
+                    eprintln!(
+                        "Unable to write contact sheet {}.\nError: {}",
+                        output.display(),
+                        e
+                    );
+                    error_exit();
+                }
+            }
+        }
+        Command::Atlas {
+            bnl,
+            output,
+            page_size,
+            filter,
+        } => {
+            let mut options = AtlasOptions::default();
+
+            if let Some(page_size) = page_size {
+                options = options.page_size(page_size);
+            }
+
+            let bnl = load_bnl(&bnl);
+
+            match bnl.write_texture_atlas(&output, &filter.to_filter(), &options) {
+                Ok(atlas) => {
+                    println!(
+                        "Packed {} textures into {} pages in {}",
+                        atlas.entries.len(),
+                        atlas.pages.len(),
+                        output.display()
+                    );
+
+                    for name in &atlas.skipped {
+                        eprintln!("Skipped {}, which couldn't be decoded", name);
+                    }
+                }
+                Err(e) => {
+                    eprintln!(
+                        "Unable to write atlas to {}.\nError: {}",
+                        output.display(),
+                        e
+                    );
+                    error_exit();
+                }
+            }
+        }
+        Command::Schema => println!("{:#}", bnl::schema::to_json()),
+        Command::Serve { bnl, tcp, save_to } => {
+            let mut bnl = load_bnl(&bnl);
 
-    println!("Opening BNL file {}", bnl_path.display());
+            let result = match tcp {
+                None => serve::serve_stdio(&mut bnl, save_to.as_deref()),
+                Some(addr) => serve::serve_tcp(&mut bnl, &addr, save_to.as_deref()),
+            };
+
+            if let Err(e) = result {
+                eprintln!("Server stopped unexpectedly.\nError: {}", e);
+                error_exit();
+            }
+        }
+        #[cfg(feature = "http")]
+        Command::Http { bnl, addr } => {
+            let bnl = load_bnl(&bnl);
+
+            if let Err(e) = http::serve(&bnl, &addr) {
+                eprintln!("HTTP server stopped unexpectedly.\nError: {}", e);
+                error_exit();
+            }
+        }
+        Command::Callgraph { paths, json } => {
+            let bundles: Vec<BNLFile> = bnl_paths(&paths)
+                .iter()
+                .map(|path| load_bnl(path))
+                .collect();
+
+            let graph = CallGraph::build(&bundles);
+
+            if json {
+                println!("{}", graph.to_json());
+            } else {
+                print!("{}", graph.to_dot());
+            }
+        }
+        Command::Depgraph { paths, json } => {
+            let bundles: Vec<BNLFile> = bnl_paths(&paths)
+                .iter()
+                .map(|path| load_bnl(path))
+                .collect();
+
+            let graph = DependencyGraph::build(&bundles);
+
+            if json {
+                println!("{}", graph.to_json());
+            } else {
+                print!("{}", graph.to_dot());
+            }
+        }
+        Command::Index { index, paths } => {
+            let paths = bnl_paths(&paths);
+            let bundles: Vec<BNLFile> = paths.iter().map(|path| load_bnl(path)).collect();
 
-    let bytes: Vec<u8> = match std::fs::read(&bnl_path) {
+            let labels: Vec<String> = paths
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect();
+
+            let string_index = StringIndex::build(labels.iter().map(String::as_str).zip(&bundles));
+
+            if let Err(e) = string_index.save(&index) {
+                eprintln!("Unable to save index {}: {}", index.display(), e);
+                error_exit();
+            }
+
+            println!(
+                "Indexed {} unique strings into {}",
+                string_index.len(),
+                index.display()
+            );
+        }
+        Command::Grep { index, text } => grep(&index, &text),
+        Command::Find { pattern, dir } => find(&pattern, &dir),
+        Command::Stats { bnl } => stats(&bnl),
+        Command::Survey {
+            asset_type,
+            paths,
+            table,
+            json,
+        } => survey(&asset_type, &paths, table, json),
+    }
+}
+
+fn load_bnl(bnl_path: &Path) -> BNLFile {
+    // Only log to stderr, as serve uses stdout for its messages
+    eprintln!("Opening BNL file {}", bnl_path.display());
+
+    let bytes: Vec<u8> = match std::fs::read(bnl_path) {
         Ok(f) => f,
         Err(e) => {
-            println!("Unable to open file {}. Error: {}", bnl_path.display(), e);
-            return;
+            eprintln!("Unable to open file {}. Error: {}", bnl_path.display(), e);
+            error_exit();
         }
     };
 
     let bnl = match BNLFile::from_bytes(&bytes) {
         Ok(b) => b,
         Err(e) => {
-            eprintln!("Unable to process BNL file: {:?}", e);
+            eprintln!("Unable to process BNL file: {}", e);
 
-            error_exit(false);
+            error_exit();
         }
     };
 
-    let raw_assets = bnl.get_raw_assets();
+    for warning in bnl.header_warnings() {
+        eprintln!("Warning: {}", warning);
+    }
+
+    bnl
+}
+
+/// Expands a list of paths into BNL file paths, where directories are replaced by the .bnl files
+/// inside of them.
+fn bnl_paths(args: &[PathBuf]) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+
+    for path in args {
+        if !path.is_dir() {
+            paths.push(path.clone());
+            continue;
+        }
+
+        match fs::read_dir(path) {
+            Ok(entries) => {
+                let mut dir_paths: Vec<PathBuf> = entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| {
+                        path.extension()
+                            .is_some_and(|ext| ext.eq_ignore_ascii_case("bnl"))
+                    })
+                    .collect();
+
+                dir_paths.sort();
+                paths.extend(dir_paths);
+            }
+            Err(e) => {
+                eprintln!("Unable to read directory {}.\nError: {}", path.display(), e);
+            }
+        }
+    }
+
+    paths
+}
+
+/// Encodes an RGBA8 image as a PNG in memory.
+fn encode_png(image: &Image) -> Result<Vec<u8>, png::EncodingError> {
+    let mut png_bytes = Vec::new();
+
+    let mut encoder =
+        png::Encoder::new(&mut png_bytes, image.width() as u32, image.height() as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(image.bytes()))?;
+
+    Ok(png_bytes)
+}
+
+fn strip(bnl_path: &Path, out_path: &Path, type_names: &[String]) {
+    let asset_types: Vec<AssetType> = type_names
+        .iter()
+        .map(|name| {
+            AssetType::from_name(name).unwrap_or_else(|| {
+                eprintln!("Unknown asset type {}.", name);
+                error_exit();
+            })
+        })
+        .collect();
+
+    let mut bnl = load_bnl(bnl_path);
+
+    let removed = match bnl.remove_asset_types(&asset_types) {
+        Ok(removed) => removed,
+        Err(e) => {
+            eprintln!("Unable to remove assets.\nError: {}", e);
+            error_exit();
+        }
+    };
+
+    let bytes = match bnl.to_bytes() {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("Unable to serialise BNL file.\nError: {}", e);
+            error_exit();
+        }
+    };
+
+    if let Err(e) = fs::write(out_path, &bytes) {
+        eprintln!("Unable to write {}.\nError: {}", out_path.display(), e);
+        error_exit();
+    }
+
+    println!(
+        "Removed {} assets, wrote {} ({} bytes).",
+        removed,
+        out_path.display(),
+        bytes.len()
+    );
+}
+
+fn extract(bnl_path: &Path, resume: bool, options: &ExtractOptions) {
+    let bnl = load_bnl(bnl_path);
 
     let out_filename = format!(
         "{}_bnl",
@@ -55,71 +616,492 @@ fn main() {
     // ./out/common_bnl
     let bnl_out_path = Path::new("./out").join(out_filename);
 
-    raw_assets.iter().for_each(|raw_asset| {
-        // ./out/common_bnl/aid_texture_xyz
-        let asset_path: PathBuf = bnl_out_path.join(&raw_asset.name);
+    if let Err(e) = fs::create_dir_all(&bnl_out_path) {
+        eprintln!(
+            "Unable to create directory {}.\nError: {}",
+            bnl_out_path.display(),
+            e
+        );
+        error_exit();
+    }
 
-        if asset_path.is_file() {
+    // The journal is always written, so that any run can be resumed later
+    let journal_path = bnl_out_path.join(".extract_journal");
+
+    let journal = if resume {
+        ExtractJournal::open(&journal_path)
+    } else {
+        ExtractJournal::create(&journal_path)
+    };
+
+    let mut journal = match journal {
+        Ok(journal) => journal,
+        Err(e) => {
             eprintln!(
-                "Unable to write to {} (A file already exists by that name)",
-                asset_path.display()
+                "Unable to open journal {}.\nError: {}",
+                journal_path.display(),
+                e
             );
-            return;
-        } else if !asset_path.exists() {
-            match fs::create_dir_all(&asset_path) {
-                Ok(_) => (),
-                Err(e) => {
-                    eprintln!(
-                        "Unable to create directory {}.\nError: {}",
-                        asset_path.display(),
-                        e
-                    );
-                    return;
-                }
+            error_exit();
+        }
+    };
+
+    match bnl.extract_all(&bnl_out_path, Some(&mut journal), options) {
+        Ok(summary) => {
+            for (name, e) in &summary.failed {
+                eprintln!("Unable to extract {}\nError: {}", name, e);
             }
+
+            println!(
+                "Extracted {} assets ({} already complete, {} failed)",
+                summary.extracted,
+                summary.skipped,
+                summary.failed.len()
+            );
         }
+        Err(e) => {
+            eprintln!("Unable to write extraction journal.\nError: {}", e);
+            error_exit();
+        }
+    }
+}
 
-        std::fs::write(asset_path.join("descriptor"), &raw_asset.descriptor_bytes).unwrap_or_else(
-            |e| {
-                eprintln!(
-                    "Unable to write descriptor for {}\nError: {}",
-                    &raw_asset.name, e
-                );
-            },
+fn list(bnl_path: &Path, filter: &AssetFilter) {
+    let bnl = load_bnl(bnl_path);
+    let asset_descs = bnl.filter_assets(filter);
+
+    println!(
+        "{:<48} {:<18} {:>10} {:>10}",
+        "name", "type", "descriptor", "resource"
+    );
+
+    for asset_desc in &asset_descs {
+        println!(
+            "{:<48} {:<18} {:>10} {:>10}",
+            asset_desc.name(),
+            format!("{:?}", asset_desc.asset_type()),
+            asset_desc.descriptor_size(),
+            asset_desc.resource_size()
         );
+    }
 
-        raw_asset
-            .data_slices
-            .iter()
-            .enumerate()
-            .for_each(|(i, slice)| {
-                std::fs::write(asset_path.join(format!("resource{}", i)), slice).unwrap_or_else(
-                    |e| {
-                        eprintln!(
-                            "Unable to write descriptor for {}\nError: {}",
-                            &raw_asset.name, e
-                        );
-                    },
-                );
+    println!("{} assets", asset_descs.len());
+}
+
+fn pack(dir: &Path, out_path: &Path, base_path: Option<&Path>) {
+    let bnl = match base_path {
+        Some(base_path) => pack_onto(dir, base_path),
+        None => match BNLFile::from_extracted_dir(dir) {
+            Ok(bnl) => {
+                println!("Packed {} assets.", bnl.asset_descriptions().len());
+                bnl
+            }
+            Err(e) => {
+                eprintln!("Unable to pack {}.\nError: {}", dir.display(), e);
+                error_exit();
+            }
+        },
+    };
+
+    let bytes = match bnl.to_bytes() {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("Unable to serialise BNL file.\nError: {}", e);
+            error_exit();
+        }
+    };
+
+    if let Err(e) = fs::write(out_path, &bytes) {
+        eprintln!("Unable to write {}.\nError: {}", out_path.display(), e);
+        error_exit();
+    }
+
+    println!("Wrote {} ({} bytes).", out_path.display(), bytes.len());
+}
+
+fn manifest(bnl_path: &Path, json: bool) {
+    let manifest = load_bnl(bnl_path).manifest();
+
+    if json {
+        println!("{:#}", manifest.to_json());
+        return;
+    }
+
+    println!(
+        "{:<48} {:<18} {:>10} {:>10} {:>10} {:>10} {:>6} {:>10} {:>10}",
+        "name", "type", "desc_ptr", "desc_size", "dvl_ptr", "res_size", "chunks", "unk_1", "unk_2"
+    );
+
+    for entry in &manifest.assets {
+        println!(
+            "{:<48} {:<18} {:>#10x} {:>#10x} {:>#10x} {:>#10x} {:>6} {:>#10x} {:>#10x}",
+            entry.name,
+            format!("{:?}", entry.asset_type),
+            entry.descriptor_ptr,
+            entry.descriptor_size,
+            entry.dataview_list_ptr,
+            entry.resource_size,
+            entry.chunk_count,
+            entry.unk_1,
+            entry.unk_2
+        );
+    }
+}
+
+/// Writes every asset found in `dir` back into the BNL file at `base_path`. Assets missing from
+/// `dir` keep their original data.
+fn pack_onto(dir: &Path, base_path: &Path) -> BNLFile {
+    let mut bnl = load_bnl(base_path);
+
+    let assets: Vec<(String, AssetType)> = bnl
+        .asset_descriptions()
+        .iter()
+        .map(|asset_desc| (asset_desc.name().to_string(), asset_desc.asset_type()))
+        .collect();
+
+    let mut packed = 0;
+    let mut failed = 0;
+
+    for (name, asset_type) in &assets {
+        if !dir.join(name).is_dir() {
+            continue;
+        }
+
+        let result = extract::read_raw_asset(dir, name, *asset_type)
+            .map_err(|e| e.to_string())
+            .and_then(|raw_asset| {
+                bnl.update_asset(name, &raw_asset)
+                    .map_err(|e| e.to_string())
             });
+
+        match result {
+            Ok(()) => packed += 1,
+            Err(e) => {
+                eprintln!("Unable to pack {}\nError: {}", name, e);
+                failed += 1;
+            }
+        }
+    }
+
+    println!(
+        "Packed {} assets ({} failed, {} unchanged).",
+        packed,
+        failed,
+        assets.len() - packed - failed
+    );
+
+    if failed > 0 {
+        error_exit();
+    }
+
+    bnl
+}
+
+fn textures(
+    bnl_path: &Path,
+    out_dir: Option<&Path>,
+    format: ExportFormat,
+    mipmaps: bool,
+    filter: &AssetFilter,
+) {
+    let bnl = load_bnl(bnl_path);
+
+    // ./out/common_textures
+    let out_dir = out_dir.map(Path::to_path_buf).unwrap_or_else(|| {
+        Path::new("./out").join(format!(
+            "{}_textures",
+            bnl_path
+                .file_stem()
+                .unwrap_or(OsStr::new("unknown"))
+                .display()
+        ))
     });
+
+    if let Err(e) = fs::create_dir_all(&out_dir) {
+        eprintln!(
+            "Unable to create directory {}.\nError: {}",
+            out_dir.display(),
+            e
+        );
+        error_exit();
+    }
+
+    let (textures, errors) = bnl.get_assets_filtered::<Texture>(filter);
+
+    for (name, e) in &errors {
+        eprintln!("Unable to read texture {}\nError: {}", name, e);
+    }
+
+    let mut exported = 0;
+    let mut failed = errors.len();
+
+    for texture in &textures {
+        match texture.export(&out_dir, format, mipmaps) {
+            Ok(_) => exported += 1,
+            Err(e) => {
+                eprintln!("Unable to export {}\nError: {}", texture.name(), e);
+                failed += 1;
+            }
+        }
+    }
+
+    println!(
+        "Exported {} textures to {} ({} failed)",
+        exported,
+        out_dir.display(),
+        failed
+    );
 }
 
-fn print_usage() {
+fn convert(bnl_path: &Path, name: &str, out_path: &Path, format: ExportFormat) {
+    let bnl = load_bnl(bnl_path);
+
+    let texture = match bnl.get_asset::<Texture>(name) {
+        Ok(texture) => texture,
+        Err(e) => {
+            eprintln!("Unable to read texture {}.\nError: {}", name, e);
+            error_exit();
+        }
+    };
+
+    if let Err(e) = texture.dump_as(out_path, format) {
+        eprintln!("Unable to convert {}.\nError: {}", name, e);
+        error_exit();
+    }
+
     println!(
-        r"Usage: bnltool -x [path to BNL file]
-Examples:
-    bnltool -x my_bnl.bnl
-    bnltool -x /home/username/game/bundles/common.bnl"
+        "Wrote {}x{} texture {} to {}",
+        texture.descriptor().width(),
+        texture.descriptor().height(),
+        name,
+        out_path.display()
     );
 }
 
-fn error_exit(show_usage: bool) -> ! {
-    eprintln!("\nUnable to continue.");
+fn grep(index_path: &Path, text: &str) {
+    let index = match StringIndex::load(index_path) {
+        Ok(index) => index,
+        Err(e) => {
+            eprintln!("{}", e);
+            error_exit();
+        }
+    };
+
+    for (string, locations) in index.grep(text) {
+        for location in locations {
+            let section = match location.slice {
+                Some(slice) => format!("resource {}", slice),
+                None => "descriptor".to_string(),
+            };
+
+            let encoding = match location.encoding {
+                StringEncoding::Ascii => "",
+                StringEncoding::Utf16 => " (UTF-16)",
+            };
+
+            println!(
+                "{}: {} {} +0x{:x}: {}{}",
+                index.bundle_label(location.bundle).unwrap_or_default(),
+                index.asset_name(location.asset).unwrap_or_default(),
+                section,
+                location.offset,
+                string,
+                encoding
+            );
+        }
+    }
+}
+
+fn find(pattern: &str, dir: &Path) {
+    let options = BatchOptions::default().recursive(true);
+    let filter = AssetFilter::default().pattern(pattern);
 
-    if show_usage {
-        print_usage();
+    let results = match batch::find_assets(dir, &options, &filter) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("{}", e);
+            error_exit();
+        }
+    };
+
+    let mut count = 0;
+
+    for (path, found) in results {
+        let asset_descs = match found {
+            Ok(asset_descs) => asset_descs,
+            Err(e) => {
+                eprintln!("{}", e);
+                continue;
+            }
+        };
+
+        println!("{}", path.display());
+
+        for asset_desc in &asset_descs {
+            println!(
+                "  {:<48} {:<18} {:>10} {:>10}",
+                asset_desc.name(),
+                format!("{:?}", asset_desc.asset_type()),
+                asset_desc.descriptor_size(),
+                asset_desc.resource_size()
+            );
+        }
+
+        count += asset_descs.len();
+    }
+
+    println!("{} matching assets", count);
+}
+
+fn survey(asset_type: &str, paths: &[PathBuf], table: bool, json: bool) {
+    let Some(asset_type) = AssetType::from_name(asset_type) else {
+        eprintln!("Unknown asset type {}.", asset_type);
+        error_exit();
+    };
+
+    let bundles: Vec<BNLFile> = bnl_paths(paths).iter().map(|path| load_bnl(path)).collect();
+
+    let survey = match table {
+        true => FieldSurvey::asset_descriptions(asset_type, &bundles),
+        false => FieldSurvey::descriptors(asset_type, &bundles),
+    };
+
+    if json {
+        println!("{}", survey.to_json());
+        return;
+    }
+
+    println!(
+        "{:>6} {:>7} {:>8} {:<14} common values",
+        "offset", "samples", "distinct", "kind"
+    );
+
+    for field in survey.fields() {
+        let common_values: Vec<String> = field
+            .common_values
+            .iter()
+            .map(|(value, count)| format!("{:#x} ({})", value, count))
+            .collect();
+
+        println!(
+            "{:>#6x} {:>7} {:>8} {:<14} {}",
+            field.offset,
+            field.samples,
+            field.distinct,
+            field.kind.name(),
+            common_values.join(", ")
+        );
+    }
+
+    println!("{} samples", survey.samples());
+}
+
+fn stats(bnl_path: &Path) {
+    let stats = load_bnl(bnl_path).stats();
+
+    println!(
+        "{:<18} {:>6} {:>12} {:>12}",
+        "type", "count", "descriptor", "resource"
+    );
+
+    for type_stats in &stats.types {
+        println!(
+            "{:<18} {:>6} {:>12} {:>12}",
+            format!("{:?}", type_stats.asset_type),
+            type_stats.count,
+            type_stats.descriptor_bytes,
+            type_stats.resource_bytes
+        );
     }
 
+    if !stats.texture_formats.is_empty() {
+        println!("\nTexture formats:");
+
+        for (format, count) in &stats.texture_formats {
+            println!("  {:<30} {:>6}", format!("{:?}", format), count);
+        }
+    }
+
+    println!("\nLargest assets:");
+
+    for asset in &stats.largest {
+        println!(
+            "  {:<48} {:<18} {:>10}",
+            asset.name,
+            format!("{:?}", asset.asset_type),
+            asset.size
+        );
+    }
+
+    println!("\n{} assets", stats.asset_count);
+    println!("Uncompressed size: {} bytes", stats.uncompressed_size);
+
+    if let (Some(file_size), Some(ratio)) = (stats.file_size, stats.ratio()) {
+        println!(
+            "File size: {} bytes ({:.1}% of uncompressed)",
+            file_size,
+            ratio * 100.0
+        );
+    }
+}
+
+fn info(bnl_path: &Path, name: &str) {
+    let bnl = load_bnl(bnl_path);
+
+    let Some(asset_desc) = bnl.asset_description(name) else {
+        eprintln!("No asset named {} in {}", name, bnl_path.display());
+        error_exit();
+    };
+
+    println!("name:            {}", asset_desc.name());
+    println!("type:            {:?}", asset_desc.asset_type());
+    println!("descriptor_ptr:  {:#x}", asset_desc.descriptor_ptr());
+    println!("descriptor_size: {:#x}", asset_desc.descriptor_size());
+    println!("resource_size:   {:#x}", asset_desc.resource_size());
+    println!("chunk_count:     {}", asset_desc.chunk_count());
+
+    let raw_asset = match bnl.get_raw_asset(name) {
+        Ok(raw_asset) => raw_asset,
+        Err(e) => {
+            eprintln!("Unable to read {}.\nError: {:?}", name, e);
+            error_exit();
+        }
+    };
+
+    println!("\ndescriptor:");
+
+    match AssetRegistry::default().describe_descriptor(&raw_asset) {
+        Some(Ok(description)) => print!("{}", description),
+        Some(Err(e)) => println!("Unable to parse descriptor: {}", e),
+        None => println!("No descriptor parser for {:?} yet", raw_asset.asset_type),
+    }
+}
+
+fn hexdump(bnl_path: &Path, name: &str) {
+    let bnl = load_bnl(bnl_path);
+
+    let Some(asset_desc) = bnl.asset_description(name) else {
+        eprintln!("No asset named {} in {}", name, bnl_path.display());
+        error_exit();
+    };
+
+    println!("asset description:");
+    print!("{}", asset_desc.annotated_hexdump());
+
+    match bnl.get_raw_asset(name) {
+        Ok(raw_asset) => {
+            println!("\ndescriptor:");
+            print!("{}", raw_asset.annotated_descriptor_hexdump());
+        }
+        Err(e) => {
+            eprintln!("Unable to read {}.\nError: {}", name, e);
+            error_exit();
+        }
+    }
+}
+
+fn error_exit() -> ! {
+    eprintln!("\nUnable to continue.");
+
     std::process::exit(1);
 }
@@ -0,0 +1,466 @@
+//! A small JSON-RPC 2.0 server exposing a loaded BNL file to external editors.
+//!
+//! Messages are framed the same way as the Language Server Protocol: a `Content-Length` header,
+//! a blank line, and then the JSON body. Binary data (descriptors, resources and PNG previews) is
+//! sent base64 encoded.
+//!
+//! Supported methods:
+//! - `list` - Lists every asset with its type and sizes
+//! - `get` `{ "name" }` - Returns the raw descriptor and resource data of an asset
+//! - `preview` `{ "name", "max_dimension"?, "time_budget_ms"? }` - Returns a texture as a base64
+//!   PNG, optionally downscaled
+//! - `update` `{ "name", "descriptor", "resources" }` - Replaces the data of an asset (same sizes)
+//! - `save` - Writes the (possibly updated) BNL file to the path given with `--save-to` when the
+//!   server was started, and fails if none was given
+//! - `shutdown` - Stops the server
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    path::Path,
+    time::Duration,
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use bnl::{
+    BNLFile,
+    asset::{RawAsset, texture::Texture},
+};
+use serde_json::{Value, json};
+
+use crate::encode_png;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+
+/// The largest message body accepted, so that a client can't make the server allocate an
+/// arbitrary amount of memory.
+const MAX_CONTENT_LENGTH: usize = 16 * 1024 * 1024;
+
+/// The longest header line accepted, for the same reason as [`MAX_CONTENT_LENGTH`].
+const MAX_HEADER_LENGTH: u64 = 1024;
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Serves requests over stdin/stdout until the client sends `shutdown` or closes the stream.
+/// `save` writes to `save_path`, and is disabled if it is None.
+pub fn serve_stdio(bnl: &mut BNLFile, save_path: Option<&Path>) -> io::Result<()> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+
+    serve(bnl, save_path, stdin.lock(), stdout.lock()).map(|_| ())
+}
+
+/// Serves requests over TCP, handling one client connection at a time. An error on one
+/// connection is logged and the server moves on to the next. `save` writes to `save_path`, and
+/// is disabled if it is None.
+pub fn serve_tcp(bnl: &mut BNLFile, addr: &str, save_path: Option<&Path>) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+
+    eprintln!("Listening on {}", listener.local_addr()?);
+
+    for stream in listener.incoming() {
+        let result = stream.and_then(|stream| {
+            let reader = stream.try_clone()?;
+            serve(bnl, save_path, BufReader::new(reader), stream)
+        });
+
+        match result {
+            Ok(true) => break,
+            Ok(false) => {}
+            Err(e) => eprintln!("Dropped a connection after an error.\nError: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles messages until the stream ends. Returns true if the client requested a shutdown.
+fn serve<R: BufRead, W: Write>(
+    bnl: &mut BNLFile,
+    save_path: Option<&Path>,
+    mut reader: R,
+    mut writer: W,
+) -> io::Result<bool> {
+    while let Some(body) = read_message(&mut reader)? {
+        let (response, shutdown) = match serde_json::from_slice::<Value>(&body) {
+            Ok(request) => handle_request(bnl, save_path, &request),
+            Err(e) => (
+                Some(error_response(
+                    Value::Null,
+                    RpcError::new(PARSE_ERROR, e.to_string()),
+                )),
+                false,
+            ),
+        };
+
+        if let Some(response) = response {
+            write_message(&mut writer, &response)?;
+        }
+
+        if shutdown {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Reads the body of the next message, or returns None once the stream ends.
+///
+/// # Errors
+/// Returns an error if the headers end without a `Content-Length`, or if it or a header line is
+/// too long.
+fn read_message<R: BufRead>(reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut content_length: Option<usize> = None;
+    let mut has_headers = false;
+
+    loop {
+        let mut line = String::new();
+
+        let read = reader
+            .by_ref()
+            .take(MAX_HEADER_LENGTH)
+            .read_line(&mut line)?;
+
+        if read == 0 {
+            return Ok(None);
+        }
+
+        if read as u64 == MAX_HEADER_LENGTH && !line.ends_with('\n') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Header line longer than {} bytes", MAX_HEADER_LENGTH),
+            ));
+        }
+
+        let line = line.trim_end();
+
+        if line.is_empty() {
+            match content_length {
+                Some(_) => break,
+                None if has_headers => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Missing Content-Length header",
+                    ));
+                }
+                // Blank lines between messages are skipped
+                None => continue,
+            }
+        }
+
+        has_headers = true;
+
+        if let Some((key, value)) = line.split_once(':')
+            && key.trim().eq_ignore_ascii_case("content-length")
+        {
+            content_length = Some(value.trim().parse().map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData, "Invalid Content-Length header")
+            })?);
+        }
+    }
+
+    let content_length = content_length.unwrap_or_default();
+
+    if content_length > MAX_CONTENT_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Content-Length {} is larger than the limit of {} bytes",
+                content_length, MAX_CONTENT_LENGTH
+            ),
+        ));
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    Ok(Some(body))
+}
+
+fn write_message<W: Write>(writer: &mut W, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+
+    write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    writer.flush()
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+/// Returns the response to send (if the request wasn't a notification) and whether to shut down.
+fn handle_request(
+    bnl: &mut BNLFile,
+    save_path: Option<&Path>,
+    request: &Value,
+) -> (Option<Value>, bool) {
+    let id = request.get("id").cloned();
+
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        return (
+            Some(error_response(
+                id.unwrap_or(Value::Null),
+                RpcError::new(INVALID_REQUEST, "Missing method"),
+            )),
+            false,
+        );
+    };
+
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+    let shutdown = method == "shutdown";
+
+    let result = match method {
+        "list" => Ok(list(bnl)),
+        "get" => get(bnl, &params),
+        "preview" => preview(bnl, &params),
+        "update" => update(bnl, &params),
+        "save" => save(bnl, save_path),
+        "shutdown" => Ok(Value::Null),
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Unknown method {}", method),
+        )),
+    };
+
+    // Requests without an id are notifications, and never get a response
+    let response = id.map(|id| match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => error_response(id, e),
+    });
+
+    (response, shutdown)
+}
+
+fn str_param<'a>(params: &'a Value, key: &str) -> Result<&'a str, RpcError> {
+    params
+        .get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Missing string param \"{}\"", key)))
+}
+
+fn decode_base64(value: &str) -> Result<Vec<u8>, RpcError> {
+    BASE64
+        .decode(value)
+        .map_err(|e| RpcError::new(INVALID_PARAMS, format!("Invalid base64 data: {}", e)))
+}
+
+pub fn list(bnl: &BNLFile) -> Value {
+    bnl.asset_descriptions()
+        .iter()
+        .map(|desc| {
+            json!({
+                "name": desc.name(),
+                "type": format!("{:?}", desc.asset_type()),
+                "descriptor_size": desc.descriptor_size(),
+                "resource_size": desc.resource_size(),
+                "chunk_count": desc.chunk_count(),
+            })
+        })
+        .collect()
+}
+
+fn get(bnl: &BNLFile, params: &Value) -> Result<Value, RpcError> {
+    let name = str_param(params, "name")?;
+
+    let raw_asset = bnl
+        .get_raw_asset(name)
+        .map_err(|e| RpcError::new(SERVER_ERROR, e.to_string()))?;
+
+    Ok(json!({
+        "name": raw_asset.name,
+        "type": format!("{:?}", raw_asset.asset_type),
+        "descriptor": BASE64.encode(&raw_asset.descriptor_bytes),
+        "resources": raw_asset
+            .data_slices
+            .iter()
+            .map(|slice| BASE64.encode(slice))
+            .collect::<Vec<_>>(),
+    }))
+}
+
+fn preview(bnl: &BNLFile, params: &Value) -> Result<Value, RpcError> {
+    let name = str_param(params, "name")?;
+
+    let texture = bnl
+        .get_asset::<Texture>(name)
+        .map_err(|e| RpcError::new(SERVER_ERROR, e.to_string()))?;
+
+    // Editors can ask for a cheaper, downscaled preview to keep their UI responsive
+    let image = match params.get("max_dimension").and_then(Value::as_u64) {
+        Some(max_dimension) => {
+            let time_budget = params
+                .get("time_budget_ms")
+                .and_then(Value::as_u64)
+                .map(Duration::from_millis);
+
+            texture.to_preview_image(max_dimension as usize, time_budget)
+        }
+        None => texture.to_rgba_image(),
+    }
+    .map_err(|e| RpcError::new(SERVER_ERROR, e.to_string()))?;
+
+    let png_bytes = encode_png(&image).map_err(|e| RpcError::new(SERVER_ERROR, e.to_string()))?;
+
+    Ok(json!({
+        "width": image.width(),
+        "height": image.height(),
+        "png": BASE64.encode(&png_bytes),
+    }))
+}
+
+fn update(bnl: &mut BNLFile, params: &Value) -> Result<Value, RpcError> {
+    let name = str_param(params, "name")?;
+
+    let mut raw_asset: RawAsset = bnl
+        .get_raw_asset(name)
+        .map_err(|e| RpcError::new(SERVER_ERROR, e.to_string()))?;
+
+    if let Some(descriptor) = params.get("descriptor").and_then(Value::as_str) {
+        raw_asset.descriptor_bytes = decode_base64(descriptor)?;
+    }
+
+    if let Some(resources) = params.get("resources").and_then(Value::as_array) {
+        raw_asset.data_slices = resources
+            .iter()
+            .map(|resource| {
+                resource
+                    .as_str()
+                    .ok_or_else(|| {
+                        RpcError::new(INVALID_PARAMS, "Resources must be base64 strings")
+                    })
+                    .and_then(decode_base64)
+            })
+            .collect::<Result<_, _>>()?;
+    }
+
+    bnl.update_asset(name, &raw_asset)
+        .map_err(|e| RpcError::new(SERVER_ERROR, e.to_string()))?;
+
+    Ok(Value::Null)
+}
+
+/// Writes the BNL file to the path fixed when the server was started. Clients can't choose the
+/// path, as anyone who can connect over TCP could otherwise write to any file.
+fn save(bnl: &BNLFile, save_path: Option<&Path>) -> Result<Value, RpcError> {
+    let path = save_path.ok_or_else(|| {
+        RpcError::new(
+            SERVER_ERROR,
+            "Saving is disabled, restart the server with --save-to to enable it",
+        )
+    })?;
+
+    let bytes = bnl
+        .to_bytes()
+        .map_err(|e| RpcError::new(SERVER_ERROR, e.to_string()))?;
+
+    std::fs::write(path, bytes).map_err(|e| RpcError::new(SERVER_ERROR, e.to_string()))?;
+
+    Ok(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(body: &str) -> Vec<u8> {
+        format!("Content-Length: {}\r\n\r\n{}", body.len(), body).into_bytes()
+    }
+
+    fn error_code(response: &Value) -> Option<i64> {
+        response["error"]["code"].as_i64()
+    }
+
+    #[test]
+    fn reads_messages() {
+        let mut data = message("{}");
+        data.extend(b"\r\n");
+        data.extend(message("[1]"));
+
+        let mut reader = data.as_slice();
+
+        assert_eq!(read_message(&mut reader).unwrap().unwrap(), b"{}");
+        assert_eq!(read_message(&mut reader).unwrap().unwrap(), b"[1]");
+        assert!(read_message(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_bad_headers() {
+        let mut reader: &[u8] = b"Content-Type: application/json\r\n\r\n{}";
+        assert!(read_message(&mut reader).is_err());
+
+        let header = format!("Content-Length: {}\r\n\r\n", MAX_CONTENT_LENGTH + 1);
+        let mut reader = header.as_bytes();
+        assert!(read_message(&mut reader).is_err());
+
+        let header = format!("X-Padding: {}\r\n", "a".repeat(MAX_HEADER_LENGTH as usize));
+        let mut reader = header.as_bytes();
+        assert!(read_message(&mut reader).is_err());
+    }
+
+    #[test]
+    fn answers_malformed_json() {
+        let mut bnl = BNLFile::new();
+        let mut output = vec![];
+
+        let data = message("{\"method\": ");
+        let shutdown = serve(&mut bnl, None, data.as_slice(), &mut output).unwrap();
+        assert!(!shutdown);
+
+        let body = read_message(&mut output.as_slice()).unwrap().unwrap();
+        let response: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error_code(&response), Some(PARSE_ERROR));
+    }
+
+    #[test]
+    fn handles_requests() {
+        let mut bnl = BNLFile::new();
+
+        let (response, _) = handle_request(&mut bnl, None, &json!({ "id": 1 }));
+        assert_eq!(error_code(&response.unwrap()), Some(INVALID_REQUEST));
+
+        let request = json!({ "id": 2, "method": "list" });
+        let (response, _) = handle_request(&mut bnl, None, &request);
+        assert_eq!(response.unwrap()["result"], json!([]));
+
+        // Notifications never get a response
+        let (response, shutdown) = handle_request(&mut bnl, None, &json!({ "method": "shutdown" }));
+        assert!(response.is_none());
+        assert!(shutdown);
+
+        // The client can't choose where to save
+        let path = std::env::temp_dir().join(format!("bnl_serve_{}.bnl", std::process::id()));
+        let request = json!({ "id": 3, "method": "save", "params": { "path": path } });
+
+        let (response, _) = handle_request(&mut bnl, None, &request);
+        assert_eq!(error_code(&response.unwrap()), Some(SERVER_ERROR));
+        assert!(!path.exists());
+
+        let (response, _) = handle_request(&mut bnl, Some(&path), &request);
+        assert!(response.unwrap().get("error").is_none());
+        assert!(BNLFile::from_bytes(&std::fs::read(&path).unwrap()).is_ok());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
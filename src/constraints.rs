@@ -0,0 +1,199 @@
+//! Known limits of the game engine and the BNL format. Breaking these usually doesn't fail until
+//! the game loads the bundle (often by crashing), so importers and validators check them up front.
+//!
+//! Only limits that are known are encoded here. Nothing is known yet about the maximum size of a
+//! script.
+
+use std::fmt::{self, Display};
+
+use crate::{asset::AID_SIZE, d3d::D3DFormat};
+
+/// The largest width or height of a texture supported by the Xbox GPU.
+pub const MAX_TEXTURE_DIMENSION: usize = 4096;
+
+/// The most assets a single bundle can hold, as the header stores the count in a u16.
+pub const MAX_ASSETS_PER_BUNDLE: usize = u16::MAX as usize;
+
+/// The longest asset name (AID), as names are stored NUL terminated in 128 byte fields.
+pub const MAX_AID_LENGTH: usize = AID_SIZE - 1;
+
+/// A broken engine or format limit.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ConstraintViolation {
+    /// A texture is wider or taller than [`MAX_TEXTURE_DIMENSION`].
+    TextureTooLarge { width: usize, height: usize },
+    /// A swizzled texture doesn't have power of two dimensions, which swizzling requires.
+    TextureNotPowerOfTwo {
+        width: usize,
+        height: usize,
+        format: D3DFormat,
+    },
+    /// A bundle holds more than [`MAX_ASSETS_PER_BUNDLE`] assets.
+    TooManyAssets(usize),
+    /// An asset name is longer than [`MAX_AID_LENGTH`].
+    AidTooLong(String),
+    /// The resource size stored in an asset description doesn't match the combined size of the
+    /// asset's data views. The game is suspected to reject assets like this while loading.
+    ResourceSizeMismatch {
+        aid: String,
+        stored: u32,
+        expected: u32,
+    },
+    /// The chunk count stored in an asset description doesn't match the number of views in the
+    /// asset's data view list.
+    ChunkCountMismatch {
+        aid: String,
+        stored: u32,
+        expected: u32,
+    },
+}
+
+impl Display for ConstraintViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstraintViolation::TextureTooLarge { width, height } => write!(
+                f,
+                "A {}x{} texture is too large, as textures are at most {}x{}",
+                width, height, MAX_TEXTURE_DIMENSION, MAX_TEXTURE_DIMENSION
+            ),
+            ConstraintViolation::TextureNotPowerOfTwo {
+                width,
+                height,
+                format,
+            } => write!(
+                f,
+                "A {}x{} texture can't be stored as {:?}, as swizzled textures need power of two \
+                 dimensions",
+                width, height, format
+            ),
+            ConstraintViolation::TooManyAssets(count) => write!(
+                f,
+                "A bundle can hold at most {} assets, but has {}",
+                MAX_ASSETS_PER_BUNDLE, count
+            ),
+            ConstraintViolation::AidTooLong(aid) => write!(
+                f,
+                "Asset name {} is {} bytes long, but can be at most {}",
+                aid,
+                aid.len(),
+                MAX_AID_LENGTH
+            ),
+            ConstraintViolation::ResourceSizeMismatch {
+                aid,
+                stored,
+                expected,
+            } => write!(
+                f,
+                "Asset {} has a resource size of {} bytes, but its data views hold {}",
+                aid, stored, expected
+            ),
+            ConstraintViolation::ChunkCountMismatch {
+                aid,
+                stored,
+                expected,
+            } => write!(
+                f,
+                "Asset {} has a chunk count of {}, but its data view list has {} views",
+                aid, stored, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConstraintViolation {}
+
+/// Checks that a texture of the given size and format can be loaded by the game.
+pub fn check_texture(
+    width: usize,
+    height: usize,
+    format: D3DFormat,
+) -> Result<(), ConstraintViolation> {
+    if width > MAX_TEXTURE_DIMENSION || height > MAX_TEXTURE_DIMENSION {
+        return Err(ConstraintViolation::TextureTooLarge { width, height });
+    }
+
+    if matches!(format, D3DFormat::Swizzled(_))
+        && !(width.is_power_of_two() && height.is_power_of_two())
+    {
+        return Err(ConstraintViolation::TextureNotPowerOfTwo {
+            width,
+            height,
+            format,
+        });
+    }
+
+    Ok(())
+}
+
+/// Checks that a bundle can hold the given number of assets.
+pub fn check_asset_count(count: usize) -> Result<(), ConstraintViolation> {
+    match count > MAX_ASSETS_PER_BUNDLE {
+        true => Err(ConstraintViolation::TooManyAssets(count)),
+        false => Ok(()),
+    }
+}
+
+/// Checks that an asset name fits in an AID field.
+pub fn check_aid(aid: &str) -> Result<(), ConstraintViolation> {
+    match aid.len() > MAX_AID_LENGTH {
+        true => Err(ConstraintViolation::AidTooLong(aid.to_string())),
+        false => Ok(()),
+    }
+}
+
+/// Checks that the resource size stored for an asset matches the combined size of its data views.
+pub fn check_resource_size(
+    aid: &str,
+    stored: u32,
+    expected: u32,
+) -> Result<(), ConstraintViolation> {
+    match stored == expected {
+        true => Ok(()),
+        false => Err(ConstraintViolation::ResourceSizeMismatch {
+            aid: aid.to_string(),
+            stored,
+            expected,
+        }),
+    }
+}
+
+/// Checks that the chunk count stored for an asset matches the number of its data views.
+pub fn check_chunk_count(aid: &str, stored: u32, expected: u32) -> Result<(), ConstraintViolation> {
+    match stored == expected {
+        true => Ok(()),
+        false => Err(ConstraintViolation::ChunkCountMismatch {
+            aid: aid.to_string(),
+            stored,
+            expected,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::d3d::{StandardFormat, Swizzled};
+
+    #[test]
+    fn texture_limits() {
+        let swizzled = D3DFormat::Swizzled(Swizzled::B8G8R8A8);
+        let dxt1 = D3DFormat::Standard(StandardFormat::DXT1);
+
+        assert!(check_texture(256, 64, swizzled).is_ok());
+        assert!(check_texture(8192, 64, dxt1).is_err());
+        assert_eq!(
+            check_texture(24, 64, swizzled),
+            Err(ConstraintViolation::TextureNotPowerOfTwo {
+                width: 24,
+                height: 64,
+                format: swizzled
+            })
+        );
+        assert!(check_texture(24, 64, dxt1).is_ok());
+
+        assert!(check_asset_count(70000).is_err());
+        assert!(check_aid(&"a".repeat(MAX_AID_LENGTH)).is_ok());
+        assert!(check_aid(&"a".repeat(AID_SIZE)).is_err());
+    }
+}
@@ -0,0 +1,362 @@
+//! Renders the textures of a bundle into a single labelled image, for eyeballing a bundle's art
+//! without exporting every texture.
+
+use std::{io, path::Path};
+
+use crate::{
+    BNLFile,
+    asset::{
+        Asset,
+        texture::{Image, Texture},
+    },
+    images,
+};
+
+/// Settings for [`render`] and [`BNLFile::write_contact_sheet`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ContactSheetOptions {
+    /// The number of textures per row.
+    pub columns: usize,
+    /// The size of the square that each texture is scaled down to fit in.
+    pub cell_size: usize,
+    /// Whether to draw the name of each texture underneath it.
+    pub labels: bool,
+}
+
+impl Default for ContactSheetOptions {
+    fn default() -> Self {
+        ContactSheetOptions {
+            columns: 8,
+            cell_size: 128,
+            labels: true,
+        }
+    }
+}
+
+impl ContactSheetOptions {
+    pub fn columns(mut self, columns: usize) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    pub fn cell_size(mut self, cell_size: usize) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+
+    pub fn labels(mut self, labels: bool) -> Self {
+        self.labels = labels;
+        self
+    }
+}
+
+/// The gap between cells, in pixels.
+const PADDING: usize = 4;
+
+/// The maximum number of lines a label is wrapped over before it is cut off.
+const LABEL_LINES: usize = 3;
+
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 7;
+
+const BACKGROUND: [u8; 4] = [0x20, 0x20, 0x20, 0xff];
+const LABEL_COLOUR: [u8; 4] = [0xff, 0xff, 0xff, 0xff];
+/// Drawn in place of textures that can't be decoded.
+const MISSING_COLOUR: [u8; 4] = [0xff, 0x00, 0xff, 0xff];
+
+/// Returns the rows of a 5x7 glyph, with the most significant of the low 5 bits as the leftmost
+/// pixel. Letters are drawn in upper case, and unsupported characters as a question mark.
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT] {
+    match c.to_ascii_uppercase() {
+        '0' => [0x0e, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0e],
+        '1' => [0x04, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e],
+        '2' => [0x0e, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1f],
+        '3' => [0x1f, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0e],
+        '4' => [0x02, 0x06, 0x0a, 0x12, 0x1f, 0x02, 0x02],
+        '5' => [0x1f, 0x10, 0x1e, 0x01, 0x01, 0x11, 0x0e],
+        '6' => [0x06, 0x08, 0x10, 0x1e, 0x11, 0x11, 0x0e],
+        '7' => [0x1f, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0e, 0x11, 0x11, 0x0e, 0x11, 0x11, 0x0e],
+        '9' => [0x0e, 0x11, 0x11, 0x0f, 0x01, 0x02, 0x0c],
+        'A' => [0x0e, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11],
+        'B' => [0x1e, 0x11, 0x11, 0x1e, 0x11, 0x11, 0x1e],
+        'C' => [0x0e, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0e],
+        'D' => [0x1c, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1c],
+        'E' => [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x1f],
+        'F' => [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x10],
+        'G' => [0x0e, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0f],
+        'H' => [0x11, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11],
+        'I' => [0x0e, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0e],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0c],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1f],
+        'M' => [0x11, 0x1b, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0e, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e],
+        'P' => [0x1e, 0x11, 0x11, 0x1e, 0x10, 0x10, 0x10],
+        'Q' => [0x0e, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0d],
+        'R' => [0x1e, 0x11, 0x11, 0x1e, 0x14, 0x12, 0x11],
+        'S' => [0x0f, 0x10, 0x10, 0x0e, 0x01, 0x01, 0x1e],
+        'T' => [0x1f, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0a, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0a],
+        'X' => [0x11, 0x11, 0x0a, 0x04, 0x0a, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0a, 0x04, 0x04, 0x04],
+        'Z' => [0x1f, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1f],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1f],
+        '-' => [0x00, 0x00, 0x00, 0x0e, 0x00, 0x00, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0c, 0x0c],
+        ' ' => [0x00; GLYPH_HEIGHT],
+        _ => [0x0e, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    }
+}
+
+/// An RGBA8 canvas that textures and text are drawn onto.
+struct Canvas {
+    width: usize,
+    height: usize,
+    bytes: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Canvas {
+        Canvas {
+            width,
+            height,
+            bytes: BACKGROUND.repeat(width * height),
+        }
+    }
+
+    fn set_pixel(&mut self, x: usize, y: usize, colour: &[u8]) {
+        if x < self.width && y < self.height {
+            let i = (y * self.width + x) * 4;
+            self.bytes[i..i + 4].copy_from_slice(colour);
+        }
+    }
+
+    fn draw_image(&mut self, x: usize, y: usize, image: &Image) {
+        for (row, pixels) in image.bytes().chunks_exact(image.width() * 4).enumerate() {
+            for (column, pixel) in pixels.chunks_exact(4).enumerate() {
+                self.set_pixel(x + column, y + row, pixel);
+            }
+        }
+    }
+
+    fn fill(&mut self, x: usize, y: usize, width: usize, height: usize, colour: &[u8]) {
+        for row in y..y + height {
+            for column in x..x + width {
+                self.set_pixel(column, row, colour);
+            }
+        }
+    }
+
+    /// Draws `text` wrapped to `width` pixels over at most [`LABEL_LINES`] lines.
+    fn draw_label(&mut self, x: usize, y: usize, width: usize, text: &str) {
+        let per_line = (width / (GLYPH_WIDTH + 1)).max(1);
+
+        let chars: Vec<char> = text.chars().collect();
+
+        for (line, chunk) in chars.chunks(per_line).take(LABEL_LINES).enumerate() {
+            for (i, &c) in chunk.iter().enumerate() {
+                let glyph_x = x + i * (GLYPH_WIDTH + 1);
+                let glyph_y = y + line * (GLYPH_HEIGHT + 1);
+
+                for (row, bits) in glyph(c).iter().enumerate() {
+                    for column in 0..GLYPH_WIDTH {
+                        if bits & (0x10 >> column) != 0 {
+                            self.set_pixel(glyph_x + column, glyph_y + row, &LABEL_COLOUR);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Renders `textures` into a grid, each scaled down to fit within
+/// [`ContactSheetOptions::cell_size`] and optionally labelled with its name. Textures that can't
+/// be decoded are drawn as a magenta square.
+///
+/// # Examples
+/// ```
+/// use bnl::{asset::texture::Texture, contact_sheet::{self, ContactSheetOptions}};
+///
+/// # let bnl = bnl::BNLFile::new();
+/// let textures: Vec<Texture> = bnl.get_assets();
+/// let sheet = contact_sheet::render(&textures, &ContactSheetOptions::default());
+/// ```
+pub fn render(textures: &[Texture], options: &ContactSheetOptions) -> Image {
+    let columns = options.columns.clamp(1, textures.len().max(1));
+    let rows = textures.len().div_ceil(columns);
+
+    let label_height = match options.labels {
+        true => LABEL_LINES * (GLYPH_HEIGHT + 1),
+        false => 0,
+    };
+
+    let cell_width = options.cell_size + PADDING;
+    let cell_height = options.cell_size + label_height + PADDING;
+
+    let mut canvas = Canvas::new(columns * cell_width + PADDING, rows * cell_height + PADDING);
+
+    for (i, texture) in textures.iter().enumerate() {
+        let x = PADDING + (i % columns) * cell_width;
+        let y = PADDING + (i / columns) * cell_height;
+
+        match texture.to_preview_image(options.cell_size, None) {
+            Ok(preview) => {
+                // Centre the preview within its cell
+                let preview_x =
+                    x + (options.cell_size - preview.width().min(options.cell_size)) / 2;
+                let preview_y =
+                    y + (options.cell_size - preview.height().min(options.cell_size)) / 2;
+
+                canvas.draw_image(preview_x, preview_y, &preview);
+            }
+            Err(_) => canvas.fill(x, y, options.cell_size, options.cell_size, &MISSING_COLOUR),
+        }
+
+        if options.labels {
+            canvas.draw_label(
+                x,
+                y + options.cell_size + 1,
+                options.cell_size,
+                texture.name(),
+            );
+        }
+    }
+
+    Image::from_rgba8(canvas.width, canvas.height, canvas.bytes)
+        .expect("Canvas size matches its dimensions")
+}
+
+impl BNLFile {
+    /// Writes a contact sheet of every texture with the default settings and the given number of
+    /// `columns`, see [`BNLFile::write_contact_sheet`]. Returns the number of textures drawn.
+    ///
+    /// # Errors
+    /// Returns an error if the bundle has no textures, or the PNG can't be written.
+    pub fn contact_sheet(&self, path: &Path, columns: usize) -> Result<usize, io::Error> {
+        self.write_contact_sheet(path, &ContactSheetOptions::default().columns(columns))
+    }
+
+    /// Renders every texture of this [`BNLFile`] into a contact sheet (see [`render`]) and writes
+    /// it to `path` as a PNG. Returns the number of textures drawn.
+    ///
+    /// # Errors
+    /// Returns an error if the bundle has no textures, or the PNG can't be written.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use std::path::Path;
+    /// # let bnl_file = bnl::BNLFile::new();
+    /// let count = bnl_file
+    ///     .write_contact_sheet(Path::new("./sheet.png"), &Default::default())
+    ///     .expect("Unable to write contact sheet.");
+    /// ```
+    pub fn write_contact_sheet(
+        &self,
+        path: &Path,
+        options: &ContactSheetOptions,
+    ) -> Result<usize, io::Error> {
+        let textures: Vec<Texture> = self.get_assets();
+
+        if textures.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "The bundle has no textures to draw.",
+            ));
+        }
+
+        let sheet = render(&textures, options);
+
+        std::fs::write(
+            path,
+            images::encode_png(sheet.width(), sheet.height(), sheet.bytes())?,
+        )?;
+
+        Ok(textures.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        d3d::{D3DFormat, LinearColour},
+        tests::bnl_from_raw_assets,
+    };
+
+    #[test]
+    fn renders_labelled_grid() {
+        let r5g6b5 = D3DFormat::Linear(LinearColour::R5G6B5);
+
+        let textures: Vec<Texture> = ["aid_texture_a", "aid_texture_b", "aid_texture_c"]
+            .iter()
+            .map(|name| {
+                let image = Image::from_rgba8(16, 8, [0x00, 0xff, 0x00, 0xff].repeat(128)).unwrap();
+                Texture::from_image(name, &image, r5g6b5, &Default::default()).unwrap()
+            })
+            .collect();
+
+        let options = ContactSheetOptions {
+            columns: 2,
+            cell_size: 32,
+            labels: true,
+        };
+
+        let sheet = render(&textures, &options);
+
+        let cell_height = 32 + LABEL_LINES * (GLYPH_HEIGHT + 1) + PADDING;
+        assert_eq!(sheet.width(), 2 * (32 + PADDING) + PADDING);
+        assert_eq!(sheet.height(), 2 * cell_height + PADDING);
+
+        let pixel = |x: usize, y: usize| {
+            let i = (y * sheet.width() + x) * 4;
+            &sheet.bytes()[i..i + 4]
+        };
+
+        // The 16x8 texture is centred in its 32x32 cell
+        assert_eq!(pixel(PADDING + 8, PADDING + 12), [0x00, 0xff, 0x00, 0xff]);
+        assert_eq!(pixel(PADDING, PADDING), BACKGROUND);
+
+        // The first letter of the label is an A, whose top row is the middle three pixels
+        let label_y = PADDING + 32 + 1;
+        assert_eq!(pixel(PADDING, label_y), BACKGROUND);
+        assert_eq!(pixel(PADDING + 1, label_y), LABEL_COLOUR);
+
+        let unlabelled = render(
+            &textures,
+            &ContactSheetOptions {
+                labels: false,
+                ..options
+            },
+        );
+        assert_eq!(unlabelled.height(), 2 * (32 + PADDING) + PADDING);
+    }
+
+    #[test]
+    fn writes_bundle_sheet() {
+        let image = Image::from_rgba8(4, 4, [0xff; 64].to_vec()).unwrap();
+        let texture = Texture::import("aid_texture_a", &image, &Default::default()).unwrap();
+
+        let bnl = bnl_from_raw_assets(&[texture.to_raw_asset().unwrap()]);
+        let path = std::env::temp_dir().join("bnl_contact_sheet_test.png");
+
+        assert_eq!(
+            bnl.write_contact_sheet(&path, &Default::default()).unwrap(),
+            1
+        );
+        assert!(std::fs::read(&path).unwrap().starts_with(b"\x89PNG"));
+        std::fs::remove_file(path).unwrap();
+
+        let empty = bnl_from_raw_assets(&[]);
+        assert!(
+            empty
+                .write_contact_sheet(Path::new("unused.png"), &Default::default())
+                .is_err()
+        );
+    }
+}
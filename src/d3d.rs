@@ -5,6 +5,7 @@ pub trait PixelBits {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LinearColour {
     A1R5G5B5 = 0x00000010,
     A4R4G4B4 = 0x0000001D,
@@ -51,6 +52,7 @@ impl PixelBits for LinearColour {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LinearLuminance {
     A8L8 = 0x00000020,
     AL8 = 0x0000001B,
@@ -95,6 +97,7 @@ impl PixelBits for LinearLuminance {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Swizzled {
     /* Swizzled formats */
     A8R8G8B8 = 0x00000006,
@@ -143,6 +146,7 @@ impl PixelBits for Swizzled {
 
 // TODO: Fix portability issue with enum
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum StandardFormat {
     Unknown = 0xFFFFFFFF,
 
@@ -211,6 +215,7 @@ impl PixelBits for StandardFormat {
 
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum D3DFormat {
     Swizzled(Swizzled),
     Luminance(LinearLuminance),
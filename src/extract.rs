@@ -0,0 +1,700 @@
+//! Writes the raw descriptor and resource data of assets out to disk.
+//!
+//! Each asset is written to its own directory, named after the asset, containing a `descriptor`
+//! file and one `resourceN` file per data view. An [`ASSET_INDEX_NAME`] file lists the extracted
+//! assets in bundle order along with the details their directories don't hold, so that
+//! [`BNLFile::from_extracted_dir`] can pack them back into a bundle. Large extractions can record
+//! their progress in an [`ExtractJournal`], so that an interrupted run can resume without
+//! rewriting finished assets.
+
+use std::{
+    collections::BTreeSet,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use rayon::prelude::*;
+
+use crate::{
+    BNLFile,
+    asset::{AssetDescription, RawAsset, RawAssetRef, texture::Texture},
+    filter::AssetFilter,
+    game::AssetType,
+    hooks::{AssetHooks, HookAction},
+};
+
+/// A record of the assets that have been completely extracted, stored as a text file with one
+/// asset name per line.
+///
+/// An asset is only recorded once all of its files have been written, so a run that is
+/// interrupted part way through an asset will extract that asset again when resumed.
+#[derive(Debug)]
+pub struct ExtractJournal {
+    path: PathBuf,
+    file: File,
+    completed: BTreeSet<String>,
+}
+
+impl ExtractJournal {
+    /// Opens an existing journal to resume from, or creates a new one if none exists at `path`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use std::path::Path;
+    ///
+    /// use bnl::extract::ExtractJournal;
+    ///
+    /// # let bnl = bnl::BNLFile::new();
+    /// let mut journal = ExtractJournal::open(Path::new("./out/common_bnl/.journal"))
+    ///     .expect("Unable to open journal.");
+    ///
+    /// bnl.extract_all(Path::new("./out/common_bnl"), Some(&mut journal), &Default::default())
+    ///     .expect("Unable to extract.");
+    /// ```
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+
+        // A line without a trailing newline was cut off mid-write, so it doesn't count
+        let complete_len = contents.rfind('\n').map_or(0, |end| end + 1);
+
+        let completed = contents[..complete_len]
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        // Drop the cut off entry so the next one starts on a fresh line
+        file.set_len(complete_len as u64)?;
+
+        Ok(ExtractJournal {
+            path: path.to_path_buf(),
+            file,
+            completed,
+        })
+    }
+
+    /// Creates a new, empty journal at `path`, discarding any previous progress.
+    pub fn create(path: &Path) -> io::Result<Self> {
+        File::create(path)?;
+
+        Self::open(path)
+    }
+
+    /// Returns true if the asset has already been completely extracted.
+    pub fn is_complete(&self, name: &str) -> bool {
+        self.completed.contains(name)
+    }
+
+    /// Records that an asset has been completely extracted, flushing the journal to disk.
+    pub fn mark_complete(&mut self, name: &str) -> io::Result<()> {
+        if self.completed.insert(name.to_string()) {
+            writeln!(self.file, "{}", name)?;
+            self.file.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of assets recorded as complete.
+    pub fn len(&self) -> usize {
+        self.completed.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.completed.is_empty()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// The outcome of [`BNLFile::extract_all`].
+#[derive(Debug, Default)]
+pub struct ExtractSummary {
+    /// The number of assets written during this run.
+    pub extracted: usize,
+    /// The number of assets skipped because the journal recorded them as complete.
+    pub skipped: usize,
+    /// The number of assets skipped by an [`AssetHooks`] callback.
+    pub vetoed: usize,
+    /// The assets that could not be extracted, along with the reason.
+    pub failed: Vec<(String, io::Error)>,
+}
+
+/// Writes a [`RawAsset`] into `out_dir/<asset name>/`.
+///
+/// # Errors
+/// Returns an error if a file already exists where the asset's directory should be, or if any of
+/// the asset's files can't be written.
+pub fn extract_raw_asset(raw_asset: &RawAsset, out_dir: &Path) -> io::Result<()> {
+    write_asset_files(
+        out_dir,
+        &raw_asset.name,
+        &raw_asset.descriptor_bytes,
+        &raw_asset.data_slices,
+    )
+}
+
+/// Writes a [`RawAssetRef`] into `out_dir/<asset name>/`, see [`extract_raw_asset`].
+///
+/// # Errors
+/// See [`extract_raw_asset`].
+pub fn extract_raw_asset_ref(raw_asset: &RawAssetRef, out_dir: &Path) -> io::Result<()> {
+    write_asset_files(
+        out_dir,
+        raw_asset.name,
+        raw_asset.descriptor_bytes,
+        &raw_asset.data_slices,
+    )
+}
+
+fn write_asset_files(
+    out_dir: &Path,
+    name: &str,
+    descriptor_bytes: &[u8],
+    data_slices: &[impl AsRef<[u8]>],
+) -> io::Result<()> {
+    // ./out/common_bnl/aid_texture_xyz
+    let asset_path = out_dir.join(name);
+
+    if asset_path.is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!(
+                "Unable to write to {} (A file already exists by that name)",
+                asset_path.display()
+            ),
+        ));
+    }
+
+    fs::create_dir_all(&asset_path)?;
+
+    fs::write(asset_path.join("descriptor"), descriptor_bytes)?;
+
+    for (i, slice) in data_slices.iter().enumerate() {
+        fs::write(asset_path.join(format!("resource{}", i)), slice)?;
+    }
+
+    Ok(())
+}
+
+/// Reads an asset back from `dir/<name>/`, as written by [`extract_raw_asset`]. Resource files
+/// are read in order from `resource0` until the next one is missing, so any other files in the
+/// directory (such as a `texture.png`) are ignored.
+///
+/// # Errors
+/// Returns an error if the asset's `descriptor` or any of its resource files can't be read.
+pub fn read_raw_asset(dir: &Path, name: &str, asset_type: AssetType) -> io::Result<RawAsset> {
+    let asset_path = dir.join(name);
+
+    let descriptor_bytes = fs::read(asset_path.join("descriptor"))?;
+
+    let mut data_slices = vec![];
+
+    loop {
+        let resource_path = asset_path.join(format!("resource{}", data_slices.len()));
+
+        match fs::read(&resource_path) {
+            Ok(bytes) => data_slices.push(bytes),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => break,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(RawAsset {
+        name: name.to_string(),
+        asset_type,
+        descriptor_bytes,
+        data_slices,
+    })
+}
+
+/// The name of the file listing the assets of an extraction, see [`write_asset_index`].
+pub const ASSET_INDEX_NAME: &str = "assets.txt";
+
+/// An asset listed in the index of an extraction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub name: String,
+    pub asset_type: AssetType,
+    /// The unknown fields of the asset's [`AssetDescription`], kept so that packing reproduces
+    /// them.
+    pub unk_1: u32,
+    pub unk_2: u32,
+}
+
+impl IndexEntry {
+    fn from_description(asset_desc: &AssetDescription) -> IndexEntry {
+        IndexEntry {
+            name: asset_desc.name().to_string(),
+            asset_type: asset_desc.asset_type(),
+            unk_1: asset_desc.unk_1,
+            unk_2: asset_desc.unk_2,
+        }
+    }
+}
+
+/// Writes an asset index to `path`, as one `<name> <type> <unk_1> <unk_2>` line per asset.
+/// Assets can be added to an extraction by adding a line here along with their directory.
+pub fn write_asset_index(path: &Path, entries: &[IndexEntry]) -> io::Result<()> {
+    let mut contents = String::new();
+
+    for entry in entries {
+        contents.push_str(&format!(
+            "{} {:?} {} {}\n",
+            entry.name, entry.asset_type, entry.unk_1, entry.unk_2
+        ));
+    }
+
+    fs::write(path, contents)
+}
+
+/// Reads an asset index written by [`write_asset_index`]. Blank lines are ignored, and the
+/// unknown fields default to zero when left out.
+///
+/// # Errors
+/// Returns an error if the file can't be read, or a line has an unknown asset type or a field
+/// that isn't a number.
+pub fn read_asset_index(path: &Path) -> io::Result<Vec<IndexEntry>> {
+    let invalid = |line_number: usize, message: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} line {}: {}", path.display(), line_number + 1, message),
+        )
+    };
+
+    let mut entries = vec![];
+
+    for (line_number, line) in fs::read_to_string(path)?.lines().enumerate() {
+        let mut fields = line.split_whitespace();
+
+        let Some(name) = fields.next() else {
+            continue;
+        };
+
+        let asset_type = fields
+            .next()
+            .and_then(AssetType::from_name)
+            .ok_or_else(|| invalid(line_number, "Missing or unknown asset type"))?;
+
+        let mut unknown = || {
+            fields
+                .next()
+                .map_or(Ok(0), str::parse)
+                .map_err(|_| invalid(line_number, "Unknown fields must be numbers"))
+        };
+
+        let (unk_1, unk_2) = (unknown()?, unknown()?);
+
+        entries.push(IndexEntry {
+            name: name.to_string(),
+            asset_type,
+            unk_1,
+            unk_2,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Options controlling how [`BNLFile::extract_all`] writes assets out.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ExtractOptions {
+    /// The number of threads to extract with. Zero uses one thread per CPU.
+    pub threads: usize,
+    /// Also decode every texture to a `texture.png` file inside of its asset directory.
+    pub png_textures: bool,
+    /// Only extract the assets that pass this filter.
+    pub filter: AssetFilter,
+}
+
+impl ExtractOptions {
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    pub fn png_textures(mut self, png_textures: bool) -> Self {
+        self.png_textures = png_textures;
+        self
+    }
+
+    pub fn filter(mut self, filter: AssetFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+}
+
+/// What happened to a single asset during [`BNLFile::extract_all_with_hooks`].
+enum Outcome {
+    Skipped,
+    Extracted,
+    Vetoed,
+    Failed(io::Error),
+}
+
+impl BNLFile {
+    /// Extracts the raw data of every asset in this [`BNLFile`] into `out_dir`, using the layout
+    /// described in [`crate::extract`]. Assets are extracted in parallel, and can be limited to a
+    /// subset with [`ExtractOptions::filter`].
+    ///
+    /// When a journal is given, assets it records as complete are skipped, and each newly
+    /// extracted asset is recorded in it. Assets that fail to extract are reported in the
+    /// [`ExtractSummary`] rather than stopping the extraction.
+    ///
+    /// # Errors
+    /// Returns an error if the thread pool can't be started, or if the journal can't be written
+    /// to, as progress could no longer be resumed correctly.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use std::path::Path;
+    ///
+    /// use bnl::{BNLFile, extract::ExtractOptions};
+    ///
+    /// let bytes = std::fs::read("./common.bnl").expect("Unable to read BNL.");
+    /// let bnl = BNLFile::from_bytes(&bytes).expect("Unable to parse BNL.");
+    ///
+    /// let options = ExtractOptions::default().png_textures(true);
+    ///
+    /// bnl.extract_all(Path::new("./out/common_bnl"), None, &options)
+    ///     .expect("Unable to extract.");
+    /// ```
+    pub fn extract_all(
+        &self,
+        out_dir: &Path,
+        journal: Option<&mut ExtractJournal>,
+        options: &ExtractOptions,
+    ) -> io::Result<ExtractSummary> {
+        self.extract_all_with_hooks(out_dir, journal, options, &AssetHooks::new())
+    }
+
+    /// Extracts every asset like [`BNLFile::extract_all`], running `hooks` on each asset before
+    /// it is written. Hooks may modify what gets written, or skip an asset entirely. An error from
+    /// a hook fails only that asset.
+    ///
+    /// # Errors
+    /// Returns an error if the thread pool can't be started, or if the journal can't be written
+    /// to.
+    pub fn extract_all_with_hooks(
+        &self,
+        out_dir: &Path,
+        journal: Option<&mut ExtractJournal>,
+        options: &ExtractOptions,
+        hooks: &AssetHooks,
+    ) -> io::Result<ExtractSummary> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(options.threads)
+            .build()
+            .map_err(io::Error::other)?;
+
+        let journal = Mutex::new(journal);
+
+        // The first journal write to fail, after which nothing more is extracted
+        let journal_error: Mutex<Option<io::Error>> = Mutex::new(None);
+
+        let outcomes: Vec<(&AssetDescription, Outcome)> = pool.install(|| {
+            self.asset_descriptions()
+                .par_iter()
+                .filter(|asset_desc| options.filter.matches(asset_desc))
+                .map(|asset_desc| {
+                    let name = asset_desc.name();
+
+                    let already_complete = journal
+                        .lock()
+                        .unwrap()
+                        .as_ref()
+                        .is_some_and(|journal| journal.is_complete(name));
+
+                    if already_complete || journal_error.lock().unwrap().is_some() {
+                        return (asset_desc, Outcome::Skipped);
+                    }
+
+                    let outcome = match self.extract_asset(name, out_dir, options, hooks) {
+                        Ok(true) => {
+                            if let Some(journal) = journal.lock().unwrap().as_mut()
+                                && let Err(e) = journal.mark_complete(name)
+                            {
+                                journal_error.lock().unwrap().get_or_insert(e);
+                            }
+
+                            Outcome::Extracted
+                        }
+                        Ok(false) => Outcome::Vetoed,
+                        Err(e) => Outcome::Failed(e),
+                    };
+
+                    (asset_desc, outcome)
+                })
+                .collect()
+        });
+
+        if let Some(e) = journal_error.into_inner().unwrap() {
+            return Err(e);
+        }
+
+        let mut summary = ExtractSummary::default();
+        let mut index = vec![];
+
+        for (asset_desc, outcome) in outcomes {
+            // Only the assets that are on disk are indexed, so the index can always be packed
+            if matches!(outcome, Outcome::Skipped | Outcome::Extracted) {
+                index.push(IndexEntry::from_description(asset_desc));
+            }
+
+            match outcome {
+                Outcome::Skipped => summary.skipped += 1,
+                Outcome::Extracted => summary.extracted += 1,
+                Outcome::Vetoed => summary.vetoed += 1,
+                Outcome::Failed(e) => summary.failed.push((asset_desc.name().to_string(), e)),
+            }
+        }
+
+        fs::create_dir_all(out_dir)?;
+        write_asset_index(&out_dir.join(ASSET_INDEX_NAME), &index)?;
+
+        Ok(summary)
+    }
+
+    /// Packs an extraction back into a new [`BNLFile`], adding the assets listed in its
+    /// [`ASSET_INDEX_NAME`] file in order, see [`read_raw_asset`]. The result is laid out from
+    /// scratch and zlib compressed, so it won't match the original bundle byte for byte.
+    ///
+    /// # Errors
+    /// Returns an error if the index or an asset can't be read, or if an asset can't be added,
+    /// see [`BNLFile::add_asset`].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use std::{fs, path::Path};
+    ///
+    /// use bnl::BNLFile;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let bnl = BNLFile::from_extracted_dir(Path::new("./out/common_bnl"))
+    ///     .expect("Unable to pack.");
+    ///
+    /// fs::write("./common.bnl", bnl.to_bytes()?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_extracted_dir(dir: &Path) -> io::Result<BNLFile> {
+        let mut bnl = BNLFile::new();
+
+        for entry in read_asset_index(&dir.join(ASSET_INDEX_NAME))? {
+            let raw_asset = read_raw_asset(dir, &entry.name, entry.asset_type).map_err(|e| {
+                io::Error::new(e.kind(), format!("Unable to read {}: {}", entry.name, e))
+            })?;
+
+            bnl.push_asset(&raw_asset, entry.unk_1, entry.unk_2)
+                .map_err(|e| io::Error::other(format!("Unable to pack {}: {}", entry.name, e)))?;
+        }
+
+        Ok(bnl)
+    }
+
+    /// Extracts a single asset, returning false if a hook skipped it.
+    fn extract_asset(
+        &self,
+        name: &str,
+        out_dir: &Path,
+        options: &ExtractOptions,
+        hooks: &AssetHooks,
+    ) -> io::Result<bool> {
+        // Without any hooks the asset never needs to be copied out of the BNL
+        let kept = match hooks.is_empty() {
+            true => self
+                .get_raw_asset_ref(name)
+                .map_err(io::Error::other)
+                .and_then(|raw_asset| extract_raw_asset_ref(&raw_asset, out_dir))
+                .map(|_| true),
+            false => {
+                self.get_raw_asset(name)
+                    .map_err(io::Error::other)
+                    .and_then(|mut raw_asset| match hooks.run(&mut raw_asset)? {
+                        HookAction::Keep => extract_raw_asset(&raw_asset, out_dir).map(|_| true),
+                        HookAction::Skip => Ok(false),
+                    })
+            }
+        }?;
+
+        let is_texture = self
+            .asset_description(name)
+            .is_some_and(|desc| desc.asset_type() == AssetType::ResTexture);
+
+        if kept && options.png_textures && is_texture {
+            let texture: Texture = self.get_asset(name).map_err(io::Error::other)?;
+
+            fs::write(
+                out_dir.join(name).join("texture.png"),
+                texture.encode_png()?,
+            )?;
+        }
+
+        Ok(kept)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn journal_ignores_partial_line() {
+        let dir = std::env::temp_dir().join(format!("bnl_journal_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("journal");
+        fs::write(&path, "aid_texture_a\naid_texture_b\naid_tex").unwrap();
+
+        let mut journal = ExtractJournal::open(&path).unwrap();
+
+        assert_eq!(journal.len(), 2);
+        assert!(journal.is_complete("aid_texture_b"));
+        assert!(!journal.is_complete("aid_tex"));
+
+        journal.mark_complete("aid_texture_c").unwrap();
+        drop(journal);
+
+        let journal = ExtractJournal::open(&path).unwrap();
+        assert!(journal.is_complete("aid_texture_c"));
+        assert_eq!(journal.len(), 3);
+
+        let journal = ExtractJournal::create(&path).unwrap();
+        assert!(journal.is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn hooks_transform_and_skip() {
+        use crate::{game::AssetType, tests::bnl_from_raw_assets};
+
+        let raw_asset = |name: &str| RawAsset {
+            name: name.to_string(),
+            asset_type: AssetType::ResScript,
+            descriptor_bytes: vec![],
+            data_slices: vec![vec![1, 2]],
+        };
+
+        let bnl = bnl_from_raw_assets(&[
+            raw_asset("aid_script_a"),
+            raw_asset("aid_script_skip"),
+            raw_asset("aid_script_fail"),
+        ]);
+
+        let mut hooks = AssetHooks::new();
+        hooks.add(|raw_asset| match raw_asset.name.as_str() {
+            "aid_script_skip" => Ok(HookAction::Skip),
+            "aid_script_fail" => Err(io::Error::other("Vetoed")),
+            _ => {
+                raw_asset.data_slices[0].reverse();
+                Ok(HookAction::Keep)
+            }
+        });
+
+        let dir = std::env::temp_dir().join(format!("bnl_hooks_{}", std::process::id()));
+
+        let summary = bnl
+            .extract_all_with_hooks(&dir, None, &ExtractOptions::default(), &hooks)
+            .unwrap();
+
+        assert_eq!((summary.extracted, summary.vetoed), (1, 1));
+        assert_eq!(summary.failed[0].0, "aid_script_fail");
+        assert_eq!(
+            fs::read(dir.join("aid_script_a/resource0")).unwrap(),
+            [2, 1]
+        );
+        assert!(!dir.join("aid_script_skip").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn packs_extracted_dir() {
+        use crate::tests::bnl_from_raw_assets;
+
+        let raw_asset = |name: &str, asset_type: AssetType, data: &[u8]| RawAsset {
+            name: name.to_string(),
+            asset_type,
+            descriptor_bytes: vec![7; 12],
+            data_slices: vec![data.to_vec(), vec![9; 3]],
+        };
+
+        let bnl = bnl_from_raw_assets(&[
+            raw_asset("aid_script_b", AssetType::ResScript, &[1, 2, 3]),
+            raw_asset("aid_marker_a", AssetType::ResMarker, &[4]),
+        ]);
+
+        let dir = std::env::temp_dir().join(format!("bnl_pack_{}", std::process::id()));
+
+        bnl.extract_all(&dir, None, &ExtractOptions::default())
+            .unwrap();
+        fs::write(dir.join("aid_marker_a/resource0"), [5, 6]).unwrap();
+
+        let packed = BNLFile::from_extracted_dir(&dir).unwrap();
+        let packed = BNLFile::from_bytes(&packed.to_bytes().unwrap()).unwrap();
+
+        let names: Vec<&str> = packed
+            .asset_descriptions()
+            .iter()
+            .map(|asset_desc| asset_desc.name())
+            .collect();
+        assert_eq!(names, ["aid_script_b", "aid_marker_a"]);
+
+        let marker = packed.get_raw_asset("aid_marker_a").unwrap();
+        assert_eq!(marker.asset_type, AssetType::ResMarker);
+        assert_eq!(marker.data_slices, [vec![5, 6], vec![9; 3]]);
+        assert!(packed.validate().is_ok());
+
+        // Unknown fields may be left out of hand written entries
+        fs::write(dir.join(ASSET_INDEX_NAME), "aid_script_b script\n\n").unwrap();
+        let entries = read_asset_index(&dir.join(ASSET_INDEX_NAME)).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].asset_type, AssetType::ResScript);
+
+        fs::write(dir.join(ASSET_INDEX_NAME), "aid_script_b notatype\n").unwrap();
+        assert!(BNLFile::from_extracted_dir(&dir).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn parallel_extract_with_pngs() {
+        let bnl = BNLFile::from_bytes(&crate::tests::make_bnl()).unwrap();
+
+        let dir = std::env::temp_dir().join(format!("bnl_parallel_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut journal = ExtractJournal::create(&dir.join("journal")).unwrap();
+
+        let options = ExtractOptions::default().threads(2).png_textures(true);
+
+        let summary = bnl.extract_all(&dir, Some(&mut journal), &options).unwrap();
+        assert_eq!(summary.extracted, 1);
+        assert!(summary.failed.is_empty());
+        assert!(journal.is_complete("aid_texture_test"));
+        assert!(dir.join("aid_texture_test/texture.png").is_file());
+
+        // The PNG sits alongside the resources, but isn't read back as one
+        let read_back = read_raw_asset(&dir, "aid_texture_test", AssetType::ResTexture).unwrap();
+        let raw_asset = bnl.get_raw_asset("aid_texture_test").unwrap();
+        assert_eq!(read_back.descriptor_bytes, raw_asset.descriptor_bytes);
+        assert_eq!(read_back.data_slices, raw_asset.data_slices);
+
+        let summary = bnl.extract_all(&dir, Some(&mut journal), &options).unwrap();
+        assert_eq!((summary.extracted, summary.skipped), (0, 1));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
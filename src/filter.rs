@@ -0,0 +1,178 @@
+//! Selects a subset of the assets in a [`BNLFile`] by type and name, so that work can be limited
+//! to a handful of assets instead of the whole bundle.
+
+use crate::{
+    BNLFile,
+    asset::{Asset, AssetDescription, AssetError, RawAsset},
+    game::AssetType,
+    glob_match,
+};
+
+/// A filter over the assets of a [`BNLFile`]. An asset matches when its type is one of the
+/// filter's types and its name matches one of the filter's glob patterns, and an empty list of
+/// either matches everything. The default filter matches every asset.
+///
+/// # Examples
+/// ```
+/// use bnl::{filter::AssetFilter, game::AssetType};
+///
+/// let filter = AssetFilter::default()
+///     .asset_type(AssetType::ResTexture)
+///     .pattern("aid_texture_gribble_*");
+///
+/// # let bnl_file = bnl::BNLFile::new();
+/// let (textures, errors) = bnl_file.get_raw_assets_filtered(&filter);
+/// ```
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct AssetFilter {
+    /// The asset types to keep.
+    pub asset_types: Vec<AssetType>,
+    /// Glob patterns for the asset names to keep, where `*` matches any run of characters and
+    /// `?` matches any single character.
+    pub patterns: Vec<String>,
+}
+
+impl AssetFilter {
+    pub fn asset_type(mut self, asset_type: AssetType) -> Self {
+        self.asset_types.push(asset_type);
+        self
+    }
+
+    pub fn pattern(mut self, pattern: &str) -> Self {
+        self.patterns.push(pattern.to_string());
+        self
+    }
+
+    /// Returns true if the asset described by `asset_desc` passes this filter.
+    pub fn matches(&self, asset_desc: &AssetDescription) -> bool {
+        let type_matches =
+            self.asset_types.is_empty() || self.asset_types.contains(&asset_desc.asset_type());
+
+        let name_matches = self.patterns.is_empty()
+            || self
+                .patterns
+                .iter()
+                .any(|pattern| glob_match(pattern.as_bytes(), asset_desc.name().as_bytes()));
+
+        type_matches && name_matches
+    }
+}
+
+impl BNLFile {
+    /// Returns the description of every asset that passes `filter`.
+    pub fn filter_assets(&self, filter: &AssetFilter) -> Vec<&AssetDescription> {
+        self.asset_descriptions()
+            .iter()
+            .filter(|asset_desc| filter.matches(asset_desc))
+            .collect()
+    }
+
+    /// Retrieves the [`RawAsset`] of every asset that passes `filter`, along with the name and
+    /// error of every matching asset that couldn't be retrieved.
+    pub fn get_raw_assets_filtered(
+        &self,
+        filter: &AssetFilter,
+    ) -> (Vec<RawAsset>, Vec<(String, AssetError)>) {
+        let mut assets = Vec::new();
+        let mut errors = Vec::new();
+
+        for asset_desc in self.filter_assets(filter) {
+            match self.load_raw_asset_ref(asset_desc) {
+                Ok(raw_asset) => assets.push(raw_asset.to_raw_asset()),
+                Err(e) => errors.push((asset_desc.name().to_string(), e)),
+            }
+        }
+
+        (assets, errors)
+    }
+
+    /// Returns every asset of type `A` that passes `filter`, along with the name and error of
+    /// every matching asset that couldn't be parsed. Types in the filter other than `A` are
+    /// ignored.
+    pub fn get_assets_filtered<A: Asset>(
+        &self,
+        filter: &AssetFilter,
+    ) -> (Vec<A>, Vec<(String, AssetError)>) {
+        let mut assets = Vec::new();
+        let mut errors = Vec::new();
+
+        for asset_desc in self.filter_assets(filter) {
+            if asset_desc.asset_type() != A::asset_type() {
+                continue;
+            }
+
+            match self.load_asset(asset_desc) {
+                Ok(asset) => assets.push(asset),
+                Err(e) => errors.push((asset_desc.name().to_string(), e)),
+            }
+        }
+
+        (assets, errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{extract::ExtractOptions, tests::bnl_from_raw_assets};
+
+    #[test]
+    fn filters_by_type_and_pattern() {
+        let raw_asset = |name: &str, asset_type: AssetType| RawAsset {
+            name: name.to_string(),
+            asset_type,
+            descriptor_bytes: vec![],
+            data_slices: vec![vec![1, 2]],
+        };
+
+        let bnl = bnl_from_raw_assets(&[
+            raw_asset("aid_script_gribble_a", AssetType::ResScript),
+            raw_asset("aid_script_gribble_b", AssetType::ResScript),
+            raw_asset("aid_script_imp", AssetType::ResScript),
+            raw_asset("aid_marker_gribble", AssetType::ResMarker),
+        ]);
+
+        let names = |filter: &AssetFilter| -> Vec<String> {
+            let (assets, errors) = bnl.get_raw_assets_filtered(filter);
+            assert!(errors.is_empty());
+
+            assets.into_iter().map(|raw_asset| raw_asset.name).collect()
+        };
+
+        assert_eq!(names(&AssetFilter::default()).len(), 4);
+        assert_eq!(
+            names(&AssetFilter::default().pattern("*gribble*")),
+            [
+                "aid_script_gribble_a",
+                "aid_script_gribble_b",
+                "aid_marker_gribble"
+            ]
+        );
+        assert_eq!(
+            names(
+                &AssetFilter::default()
+                    .asset_type(AssetType::ResScript)
+                    .pattern("*gribble*")
+                    .pattern("*imp")
+            ),
+            [
+                "aid_script_gribble_a",
+                "aid_script_gribble_b",
+                "aid_script_imp"
+            ]
+        );
+        assert!(names(&AssetFilter::default().asset_type(AssetType::ResTexture)).is_empty());
+
+        let dir = std::env::temp_dir().join(format!("bnl_filter_{}", std::process::id()));
+
+        let options = ExtractOptions::default().filter(AssetFilter::default().pattern("*imp"));
+        let summary = bnl.extract_all(&dir, None, &options).unwrap();
+
+        assert_eq!(summary.extracted, 1);
+        assert!(dir.join("aid_script_imp").is_dir());
+        assert!(!dir.join("aid_marker_gribble").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,110 @@
+//! The file formats that a [`BNLFile`] can be read from and written to.
+//!
+//! Other Xbox-era Rare titles pack their assets into bundles closely related to the BNL files of
+//! Grabbed by the Ghoulies. Each of these formats is a [`BundleFormat`] implementation that
+//! converts between its own file layout and a [`BNLFile`], so the asset and descriptor parsers
+//! are shared between them.
+
+use crate::{BNLError, BNLFile, BNLHeader, asset::AssetDescription};
+
+/// A bundle file layout that can be converted to and from a [`BNLFile`].
+pub trait BundleFormat: Sync {
+    /// A human readable name for this format.
+    fn name(&self) -> &'static str;
+
+    /// Returns true if `bytes` look like a bundle of this format. This should be cheap, as it is
+    /// checked against every known format by [`detect`].
+    fn detect(&self, bytes: &[u8]) -> bool;
+
+    /// Parses a bundle of this format.
+    ///
+    /// # Errors
+    /// Returns a [`BNLError`] if the bundle can't be parsed.
+    fn parse(&self, bytes: &[u8]) -> Result<BNLFile, BNLError>;
+
+    /// Writes a [`BNLFile`] out in this format.
+    ///
+    /// # Errors
+    /// Returns a [`BNLError`] if the bundle can't be represented in this format.
+    fn write(&self, bnl: &BNLFile) -> Result<Vec<u8>, BNLError>;
+}
+
+/// The BNL format used by Grabbed by the Ghoulies: an uncompressed 40 byte header followed by a
+/// zlib stream holding the asset description, data view, buffer and descriptor sections. The
+/// uncompressed variant, with the sections stored directly after the header, is also supported.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GhouliesBnl;
+
+impl BundleFormat for GhouliesBnl {
+    fn name(&self) -> &'static str {
+        "Grabbed by the Ghoulies BNL"
+    }
+
+    fn detect(&self, bytes: &[u8]) -> bool {
+        BNLHeader::from_bytes(bytes).is_ok_and(|header| {
+            header.compression(bytes).is_some()
+                && header.asset_desc_loc.size as usize
+                    == header.file_count as usize * size_of::<AssetDescription>()
+        })
+    }
+
+    fn parse(&self, bytes: &[u8]) -> Result<BNLFile, BNLError> {
+        BNLFile::from_bytes(bytes)
+    }
+
+    fn write(&self, bnl: &BNLFile) -> Result<Vec<u8>, BNLError> {
+        bnl.to_bytes()
+    }
+}
+
+/// Every supported bundle format, in the order they are checked by [`detect`].
+pub const FORMATS: &[&dyn BundleFormat] = &[&GhouliesBnl];
+
+/// Returns the first of [`FORMATS`] that recognises `bytes`.
+pub fn detect(bytes: &[u8]) -> Option<&'static dyn BundleFormat> {
+    FORMATS.iter().copied().find(|format| format.detect(bytes))
+}
+
+/// Detects the format of a bundle and parses it, returning the format alongside the bundle so
+/// that it can be written back out in the same format.
+///
+/// # Errors
+/// Returns [`BNLError::DataReadError`] if no format recognises the bundle, or any error from
+/// parsing it.
+///
+/// # Examples
+/// ```no_run
+/// let bytes = std::fs::read("./my_bundle.bnl").expect("Unable to read bundle.");
+/// let (bnl, format) = bnl::format::parse(&bytes).expect("Unable to parse bundle.");
+///
+/// println!("Loaded a {} with {} assets", format.name(), bnl.asset_descriptions().len());
+/// ```
+pub fn parse(bytes: &[u8]) -> Result<(BNLFile, &'static dyn BundleFormat), BNLError> {
+    let format = detect(bytes)
+        .ok_or_else(|| BNLError::DataReadError("Unrecognised bundle format.".to_string()))?;
+
+    Ok((format.parse(bytes)?, format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BNL_HEADER_SIZE, tests::make_bnl};
+
+    #[test]
+    fn detects_ghoulies_bnl() {
+        let bytes = make_bnl();
+
+        let (bnl, format) = parse(&bytes).unwrap();
+        assert_eq!(format.name(), GhouliesBnl.name());
+        assert_eq!(bnl.asset_descriptions().len(), 1);
+
+        // A header claiming a different number of assets than its description section holds
+        let mut wrong_count = bytes.clone();
+        wrong_count[0] = 2;
+        assert!(detect(&wrong_count).is_none());
+
+        assert!(detect(&bytes[..BNL_HEADER_SIZE]).is_none());
+        assert!(matches!(parse(b"RIFF"), Err(BNLError::DataReadError(_))));
+    }
+}
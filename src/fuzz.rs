@@ -0,0 +1,91 @@
+//! Entry points for the cargo-fuzz targets in `fuzz/`, only built when fuzzing. Each one feeds
+//! arbitrary bytes through a parser and then through everything that consumes its result, so
+//! that length fields trusted by later stages are exercised too. Errors are expected and ignored;
+//! only panics, hangs and runaway allocations are bugs.
+
+use crate::{
+    BNLFile,
+    asset::{
+        AssetDescriptor, RawAsset,
+        model::{Model, ModelDescriptor},
+        registry::AssetRegistry,
+        texture::{Texture, TextureDescriptor},
+    },
+    game::AssetType,
+};
+
+/// Parses `data` as a BNL file, then parses, decodes and validates every asset in it.
+pub fn bnl_from_bytes(data: &[u8]) {
+    let Ok(bnl) = BNLFile::from_bytes(data) else {
+        return;
+    };
+
+    let registry = AssetRegistry::default();
+
+    for asset_desc in bnl.asset_descriptions() {
+        if let Ok(raw_asset) = bnl.get_raw_asset(asset_desc.name()) {
+            exercise_asset(&registry, &raw_asset);
+        }
+    }
+
+    let _ = bnl.validate();
+    let _ = bnl.stats();
+    let _ = bnl.to_bytes();
+}
+
+/// Parses `data` as a [`TextureDescriptor`], and decodes the rest of the input as a texture
+/// using it.
+pub fn texture_descriptor(data: &[u8]) {
+    let Ok(descriptor) = TextureDescriptor::from_bytes(data) else {
+        return;
+    };
+
+    let Ok(bytes) = descriptor.to_bytes() else {
+        return;
+    };
+
+    let raw_asset = RawAsset {
+        name: "aid_texture_fuzz".to_string(),
+        asset_type: AssetType::ResTexture,
+        descriptor_bytes: bytes,
+        data_slices: vec![data.to_vec()],
+    };
+
+    if let Ok(texture) = raw_asset.parse::<Texture>() {
+        let _ = texture.to_rgba_image();
+        let _ = texture.thumbnail(16);
+    }
+}
+
+/// Parses `data` as a [`ModelDescriptor`], serialises it again, and reads the model's textures
+/// and meshes from the same bytes.
+pub fn model_descriptor(data: &[u8]) {
+    let Ok(descriptor) = ModelDescriptor::from_bytes(data) else {
+        return;
+    };
+
+    let _ = descriptor.to_bytes();
+
+    let raw_asset = RawAsset {
+        name: "aid_model_fuzz".to_string(),
+        asset_type: AssetType::ResModel,
+        descriptor_bytes: data.to_vec(),
+        data_slices: vec![data.to_vec()],
+    };
+
+    if let Ok(model) = raw_asset.parse::<Model>() {
+        let _ = model.to_glb();
+    }
+}
+
+fn exercise_asset(registry: &AssetRegistry, raw_asset: &RawAsset) {
+    let Ok(asset) = registry.parse(raw_asset) else {
+        return;
+    };
+
+    if let Some(texture) = asset.downcast_ref::<Texture>() {
+        let _ = texture.to_rgba_image();
+    } else if let Some(model) = asset.downcast_ref::<Model>() {
+        let _ = model.to_glb();
+    }
+}
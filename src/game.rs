@@ -2,7 +2,8 @@ use num_enum::{IntoPrimitive, TryFromPrimitive};
 
 // Taken from project_grabbed
 // https://github.com/x1nixmzeng/project-grabbed
-#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive, IntoPrimitive)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, TryFromPrimitive, IntoPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum AssetType {
     ResTexture = 1,
@@ -37,3 +38,17 @@ pub enum AssetType {
 
     ResCount, // This will automatically take the next value (30)
 }
+
+impl AssetType {
+    /// Finds an asset type by name, such as `ResCutscene` or `cutscene`, ignoring case.
+    pub fn from_name(name: &str) -> Option<AssetType> {
+        let name = name.to_lowercase();
+
+        (0..AssetType::ResCount as u32)
+            .filter_map(|id| AssetType::try_from(id).ok())
+            .find(|asset_type| {
+                let type_name = format!("{:?}", asset_type).to_lowercase();
+                type_name == name || type_name.strip_prefix("res") == Some(name.as_str())
+            })
+    }
+}
@@ -0,0 +1,251 @@
+//! Hexdumps of asset descriptions and descriptors, with each field labelled by name and decoded
+//! value using the layouts from [`crate::schema`]. Bytes that no known field covers, and fields
+//! that haven't been identified yet, are marked as unknown so they stand out when decoding the
+//! rest of the format.
+
+use std::fmt::Write;
+
+use crate::{
+    asset::{AssetDescription, RawAsset},
+    schema::{self, Layout},
+};
+
+/// The number of bytes shown on each line.
+const BYTES_PER_LINE: usize = 16;
+
+/// Marks bytes that no known field covers, or that belong to a field that hasn't been identified.
+const UNKNOWN_MARKER: &str = "??";
+
+/// Returns a plain hexdump of `bytes`, 16 bytes per line with their offsets and ASCII.
+///
+/// # Examples
+/// ```
+/// # let raw_asset = bnl::asset::RawAsset {
+/// #     name: "aid_script_test".to_string(),
+/// #     asset_type: bnl::game::AssetType::ResScript,
+/// #     descriptor_bytes: b"aid_script_test\0".to_vec(),
+/// #     data_slices: vec![],
+/// # };
+/// print!("{}", bnl::hexdump::hexdump(&raw_asset.descriptor_bytes));
+/// ```
+pub fn hexdump(bytes: &[u8]) -> String {
+    let mut out = String::new();
+
+    for (i, line) in bytes.chunks(BYTES_PER_LINE).enumerate() {
+        let ascii: String = line
+            .iter()
+            .map(|&b| match b.is_ascii_graphic() || b == b' ' {
+                true => b as char,
+                false => '.',
+            })
+            .collect();
+
+        let _ = writeln!(
+            out,
+            "{:#06x}  {:<width$}  {}",
+            i * BYTES_PER_LINE,
+            hex_bytes(line),
+            ascii,
+            width = BYTES_PER_LINE * 3 - 1
+        );
+    }
+
+    out
+}
+
+/// Returns a hexdump of `bytes` read as the structure `layout_name` from [`crate::schema`], with
+/// one line per field giving its name, type and decoded value. Bytes after the end of the layout
+/// are dumped as unknown, as is everything when the layout isn't known.
+///
+/// # Examples
+/// ```
+/// # let bytes = [0u8; 40];
+/// print!("{}", bnl::hexdump::annotated_hexdump(&bytes, "TextureDescriptor"));
+/// ```
+pub fn annotated_hexdump(bytes: &[u8], layout_name: &str) -> String {
+    match schema::find_layout(layout_name) {
+        Some(layout) => annotate(bytes, layout),
+        None => annotate_unknown(bytes, 0),
+    }
+}
+
+fn annotate(bytes: &[u8], layout: &Layout) -> String {
+    let mut out = String::new();
+    let mut offset = 0;
+
+    for (name, ty) in layout.fields {
+        let size = schema::type_size(ty);
+
+        let Some(field) = bytes.get(offset..offset + size) else {
+            let _ = writeln!(
+                out,
+                "{:#06x}  {:<width$}  {}: {} (missing, the input ends at {:#x})",
+                offset,
+                "",
+                name,
+                ty,
+                bytes.len(),
+                width = BYTES_PER_LINE * 3 - 1
+            );
+            break;
+        };
+
+        let marker = match is_unknown_field(name) {
+            true => format!(" {}", UNKNOWN_MARKER),
+            false => String::new(),
+        };
+
+        // Long fields only show the bytes that fit on one line, since the decoded value says the
+        // rest
+        let (shown, ellipsis) = match size > BYTES_PER_LINE {
+            true => (&field[..BYTES_PER_LINE - 1], " .."),
+            false => (field, ""),
+        };
+
+        let _ = writeln!(
+            out,
+            "{:#06x}  {:<width$}  {}: {} = {}{}",
+            offset,
+            format!("{}{}", hex_bytes(shown), ellipsis),
+            name,
+            ty,
+            decode(field, ty),
+            marker,
+            width = BYTES_PER_LINE * 3 - 1
+        );
+
+        offset += size;
+    }
+
+    if offset < bytes.len() {
+        out.push_str(&annotate_unknown(&bytes[offset..], offset));
+    }
+
+    out
+}
+
+/// Dumps `bytes`, which start at `offset` within their structure, as unknown.
+fn annotate_unknown(bytes: &[u8], offset: usize) -> String {
+    let mut out = String::new();
+
+    for (i, line) in bytes.chunks(BYTES_PER_LINE).enumerate() {
+        let _ = writeln!(
+            out,
+            "{:#06x}  {:<width$}  {}",
+            offset + i * BYTES_PER_LINE,
+            hex_bytes(line),
+            UNKNOWN_MARKER,
+            width = BYTES_PER_LINE * 3 - 1
+        );
+    }
+
+    out
+}
+
+/// Returns true if a field is named as one whose meaning hasn't been worked out yet.
+fn is_unknown_field(name: &str) -> bool {
+    name.starts_with("unk") || name.starts_with("padding")
+}
+
+fn hex_bytes(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Decodes a little endian field of type `ty` for display. Byte arrays are shown as the NUL
+/// terminated string they hold.
+fn decode(field: &[u8], ty: &str) -> String {
+    let (base, count) = schema::split_type(ty);
+
+    if base == "u8" && count > 1 {
+        return format!("{:?}", crate::asset::aid_from_bytes(field));
+    }
+
+    let element_size = field.len() / count.max(1);
+
+    let values: Vec<String> = field
+        .chunks_exact(element_size.max(1))
+        .map(|bytes| match (base, bytes) {
+            ("u8", [b]) => format!("{} ({:#x})", b, b),
+            ("i8", [b]) => (*b as i8).to_string(),
+            ("u16", &[a, b]) => {
+                let value = u16::from_le_bytes([a, b]);
+                format!("{} ({:#x})", value, value)
+            }
+            ("i16", &[a, b]) => i16::from_le_bytes([a, b]).to_string(),
+            ("i32", &[a, b, c, d]) => i32::from_le_bytes([a, b, c, d]).to_string(),
+            ("f32", &[a, b, c, d]) => f32::from_le_bytes([a, b, c, d]).to_string(),
+            (_, &[a, b, c, d]) => {
+                let value = u32::from_le_bytes([a, b, c, d]);
+                format!("{} ({:#x})", value, value)
+            }
+            _ => hex_bytes(bytes),
+        })
+        .collect();
+
+    match count {
+        1 => values.join(""),
+        _ => format!("[{}]", values.join(", ")),
+    }
+}
+
+impl AssetDescription {
+    /// Returns an annotated hexdump of this description as it is stored in the asset table, see
+    /// [`annotated_hexdump`].
+    pub fn annotated_hexdump(&self) -> String {
+        annotated_hexdump(&self.to_bytes(), "AssetDescription")
+    }
+}
+
+impl RawAsset {
+    /// Returns an annotated hexdump of the descriptor of this asset, see [`annotated_hexdump`].
+    /// Only the fixed part at the start of the descriptor is annotated, and anything after it
+    /// (such as tables the descriptor points to) is shown as unknown.
+    pub fn annotated_descriptor_hexdump(&self) -> String {
+        match schema::descriptor_layout(self.asset_type) {
+            Some(layout_name) => annotated_hexdump(&self.descriptor_bytes, layout_name),
+            None => annotate_unknown(&self.descriptor_bytes, 0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BNLFile, tests::make_bnl};
+
+    #[test]
+    fn annotates_fields_and_unknowns() {
+        let bnl = BNLFile::from_bytes(&make_bnl()).unwrap();
+
+        let dump = bnl.asset_descriptions()[0].annotated_hexdump();
+        let lines: Vec<&str> = dump.lines().collect();
+
+        assert_eq!(lines.len(), 9);
+        assert!(lines[0].starts_with("0x0000  61 69 64 5f"));
+        assert!(lines[0].ends_with("name: u8[128] = \"aid_texture_test\""));
+        assert!(lines[1].starts_with("0x0080  01 00 00 00"));
+        assert!(lines[1].ends_with("asset_type: u32 = 1 (0x1)"));
+        assert!(lines[2].ends_with(UNKNOWN_MARKER));
+
+        // Bytes past the end of a layout are dumped as unknown
+        let mut raw_asset = bnl.get_raw_asset("aid_texture_test").unwrap();
+        raw_asset.descriptor_bytes.extend_from_slice(&[0xab; 4]);
+
+        let dump = raw_asset.annotated_descriptor_hexdump();
+        assert!(dump.contains("width: u16 = 1 (0x1)"));
+        assert!(dump.ends_with(&format!("0x001c  ab ab ab ab{}  ??\n", " ".repeat(36))));
+
+        assert_eq!(
+            annotated_hexdump(&[1, 2], "NotALayout"),
+            annotate_unknown(&[1, 2], 0)
+        );
+        assert_eq!(
+            hexdump(b"aid\0"),
+            format!("0x0000  61 69 64 00{}  aid.\n", " ".repeat(36))
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! User callbacks run on every asset as it passes through a pipeline such as
+//! [`BNLFile::extract_all_with_hooks`](crate::BNLFile::extract_all_with_hooks), so that custom
+//! processing (like upscaling textures as they are extracted) doesn't need a fork of the tools.
+
+use std::{fmt, io};
+
+use crate::asset::RawAsset;
+
+/// What should happen to an asset after an [`AssetHooks`] callback has seen it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookAction {
+    /// Carry on processing the (possibly modified) asset.
+    Keep,
+    /// Drop the asset from the pipeline without treating it as a failure.
+    Skip,
+}
+
+type Hook<'a> = Box<dyn Fn(&mut RawAsset) -> io::Result<HookAction> + Send + Sync + 'a>;
+
+/// An ordered list of callbacks run on each asset. Each callback may modify the asset in place,
+/// skip it with [`HookAction::Skip`], or fail it by returning an error. Once an asset is skipped
+/// or failed, later callbacks don't see it.
+///
+/// # Examples
+/// ```no_run
+/// use std::path::Path;
+///
+/// use bnl::{game::AssetType, hooks::{AssetHooks, HookAction}};
+///
+/// # fn main() -> std::io::Result<()> {
+/// # let bnl = bnl::BNLFile::new();
+/// let mut hooks = AssetHooks::new();
+///
+/// // Only extract textures
+/// hooks.add(|raw_asset| match raw_asset.asset_type {
+///     AssetType::ResTexture => Ok(HookAction::Keep),
+///     _ => Ok(HookAction::Skip),
+/// });
+///
+/// bnl.extract_all_with_hooks(Path::new("./out"), None, &Default::default(), &hooks)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct AssetHooks<'a> {
+    hooks: Vec<Hook<'a>>,
+}
+
+impl<'a> AssetHooks<'a> {
+    pub fn new() -> AssetHooks<'a> {
+        AssetHooks::default()
+    }
+
+    /// Adds a callback, to run after every callback added before it.
+    pub fn add(
+        &mut self,
+        hook: impl Fn(&mut RawAsset) -> io::Result<HookAction> + Send + Sync + 'a,
+    ) -> &mut Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.hooks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Runs every callback on `raw_asset` in order, stopping early if one skips or fails it.
+    ///
+    /// # Errors
+    /// Returns the first error returned by a callback.
+    pub fn run(&self, raw_asset: &mut RawAsset) -> io::Result<HookAction> {
+        for hook in &self.hooks {
+            if hook(raw_asset)? == HookAction::Skip {
+                return Ok(HookAction::Skip);
+            }
+        }
+
+        Ok(HookAction::Keep)
+    }
+}
+
+impl fmt::Debug for AssetHooks<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssetHooks")
+            .field("hooks", &self.hooks.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::AssetType;
+
+    #[test]
+    fn runs_hooks_in_order() {
+        let mut hooks = AssetHooks::new();
+
+        hooks
+            .add(|raw_asset| {
+                raw_asset.data_slices[0].push(1);
+                Ok(HookAction::Keep)
+            })
+            .add(|raw_asset| match raw_asset.name.ends_with("_skip") {
+                true => Ok(HookAction::Skip),
+                false => Ok(HookAction::Keep),
+            })
+            .add(|raw_asset| {
+                raw_asset.data_slices[0].push(2);
+                Ok(HookAction::Keep)
+            });
+
+        let mut raw_asset = RawAsset {
+            name: "aid_script_a".to_string(),
+            asset_type: AssetType::ResScript,
+            descriptor_bytes: vec![],
+            data_slices: vec![vec![]],
+        };
+
+        assert_eq!(hooks.run(&mut raw_asset).unwrap(), HookAction::Keep);
+        assert_eq!(raw_asset.data_slices[0], [1, 2]);
+
+        raw_asset.name = "aid_script_skip".to_string();
+        assert_eq!(hooks.run(&mut raw_asset).unwrap(), HookAction::Skip);
+        assert_eq!(raw_asset.data_slices[0], [1, 2, 1]);
+    }
+}
@@ -1,4 +1,7 @@
-use crate::d3d::{D3DFormat, LinearColour, StandardFormat, Swizzled};
+use crate::{
+    asset::texture::{DumpOptions, PngBitDepth, PngColour},
+    d3d::{D3DFormat, LinearColour, StandardFormat, Swizzled},
+};
 
 pub fn transcode(
     width: usize,
@@ -48,11 +51,29 @@ pub fn transcode(
             )),
         },
 
+        D3DFormat::Standard(StandardFormat::DXT4Or5) => match dst_format {
+            D3DFormat::Linear(LinearColour::R8G8B8A8) => {
+                let buf = bcndecode::decode(
+                    bytes,
+                    width,
+                    height,
+                    bcndecode::BcnEncoding::Bc3, // BC3 = DXT5, DXT4 treated the same
+                    bcndecode::BcnDecoderFormat::RGBA,
+                )
+                .map_err(std::io::Error::other)?;
+
+                Ok(buf)
+            }
+            _ => Err(std::io::Error::other(
+                "Unsupported destination format for transcoding.",
+            )),
+        },
+
         D3DFormat::Swizzled(Swizzled::A8B8G8R8) => match dst_format {
             D3DFormat::Linear(LinearColour::R8G8B8A8) => {
                 let mut ret_bytes = bytes.to_vec();
 
-                ret_bytes.chunks_mut(4).for_each(|chunk| {
+                ret_bytes.chunks_exact_mut(4).for_each(|chunk| {
                     chunk.reverse();
                 });
 
@@ -67,7 +88,7 @@ pub fn transcode(
             D3DFormat::Linear(LinearColour::R8G8B8A8) => {
                 let mut ret_bytes = bytes.to_vec();
 
-                ret_bytes.chunks_mut(4).for_each(|chunk| {
+                ret_bytes.chunks_exact_mut(4).for_each(|chunk| {
                     let b = chunk[0];
                     let r = chunk[2];
 
@@ -86,7 +107,7 @@ pub fn transcode(
             D3DFormat::Linear(LinearColour::R8G8B8A8) => {
                 let mut ret_bytes = bytes.to_vec();
 
-                ret_bytes.chunks_mut(4).for_each(|chunk| {
+                ret_bytes.chunks_exact_mut(4).for_each(|chunk| {
                     chunk.rotate_left(1);
                 });
 
@@ -96,8 +117,979 @@ pub fn transcode(
                 "Unsupported destination format for transcoding.",
             )),
         },
+
+        D3DFormat::Swizzled(
+            Swizzled::R5G6B5 | Swizzled::A1R5G5B5 | Swizzled::X1R5G5B5 | Swizzled::A4R4G4B4,
+        )
+        | D3DFormat::Standard(StandardFormat::P8) => Err(unsupported_swizzle(src_format)),
+
+        D3DFormat::Linear(LinearColour::R5G6B5) => match dst_format {
+            D3DFormat::Linear(LinearColour::R8G8B8A8) => Ok(unpack_16bit(bytes, |pixel| {
+                [
+                    expand_bits(pixel >> 11, 5),
+                    expand_bits(pixel >> 5, 6),
+                    expand_bits(pixel, 5),
+                    0xff,
+                ]
+            })),
+            _ => Err(std::io::Error::other(
+                "Unsupported destination format for transcoding.",
+            )),
+        },
+
+        D3DFormat::Linear(LinearColour::A1R5G5B5) => match dst_format {
+            D3DFormat::Linear(LinearColour::R8G8B8A8) => Ok(unpack_16bit(bytes, |pixel| {
+                [
+                    expand_bits(pixel >> 10, 5),
+                    expand_bits(pixel >> 5, 5),
+                    expand_bits(pixel, 5),
+                    expand_bits(pixel >> 15, 1),
+                ]
+            })),
+            _ => Err(std::io::Error::other(
+                "Unsupported destination format for transcoding.",
+            )),
+        },
+
+        D3DFormat::Linear(LinearColour::X1R5G5B5) => match dst_format {
+            D3DFormat::Linear(LinearColour::R8G8B8A8) => Ok(unpack_16bit(bytes, |pixel| {
+                [
+                    expand_bits(pixel >> 10, 5),
+                    expand_bits(pixel >> 5, 5),
+                    expand_bits(pixel, 5),
+                    0xff,
+                ]
+            })),
+            _ => Err(std::io::Error::other(
+                "Unsupported destination format for transcoding.",
+            )),
+        },
+
+        D3DFormat::Linear(LinearColour::A4R4G4B4) => match dst_format {
+            D3DFormat::Linear(LinearColour::R8G8B8A8) => Ok(unpack_16bit(bytes, |pixel| {
+                [
+                    expand_bits(pixel >> 8, 4),
+                    expand_bits(pixel >> 4, 4),
+                    expand_bits(pixel, 4),
+                    expand_bits(pixel >> 12, 4),
+                ]
+            })),
+            _ => Err(std::io::Error::other(
+                "Unsupported destination format for transcoding.",
+            )),
+        },
+
         _ => Err(std::io::Error::other(
             "Unsupported source format for transcoding.",
         )),
     }
 }
+
+/// Encodes RGBA8 pixels as a PNG in memory.
+pub(crate) fn encode_png(
+    width: usize,
+    height: usize,
+    rgba: &[u8],
+) -> Result<Vec<u8>, std::io::Error> {
+    encode_png_with(width, height, rgba, &DumpOptions::default())
+}
+
+/// Encodes RGBA8 pixels as a PNG in memory, converted as set out by `options`. The alpha
+/// settings of `options` are applied by [`crate::asset::texture::Texture`] before this point.
+pub(crate) fn encode_png_with(
+    width: usize,
+    height: usize,
+    rgba: &[u8],
+    options: &DumpOptions,
+) -> Result<Vec<u8>, std::io::Error> {
+    match options.colour {
+        PngColour::Rgba => encode_png_channels(
+            width,
+            height,
+            rgba,
+            options,
+            png::ColorType::Rgba,
+            &[0, 1, 2, 3],
+        ),
+        PngColour::Rgb => encode_png_channels(
+            width,
+            height,
+            rgba,
+            options,
+            png::ColorType::Rgb,
+            &[0, 1, 2],
+        ),
+    }
+}
+
+/// Encodes the alpha channel of RGBA8 pixels as a greyscale PNG in memory, using the bit depth,
+/// gamma and orientation settings of `options`.
+pub(crate) fn encode_alpha_mask(
+    width: usize,
+    height: usize,
+    rgba: &[u8],
+    options: &DumpOptions,
+) -> Result<Vec<u8>, std::io::Error> {
+    encode_png_channels(
+        width,
+        height,
+        rgba,
+        options,
+        png::ColorType::Grayscale,
+        &[3],
+    )
+}
+
+/// Encodes the given channels of RGBA8 pixels as a PNG of the given colour type.
+fn encode_png_channels(
+    width: usize,
+    height: usize,
+    rgba: &[u8],
+    options: &DumpOptions,
+    colour_type: png::ColorType,
+    channels: &[usize],
+) -> Result<Vec<u8>, std::io::Error> {
+    check_rgba_size(width, height, rgba)?;
+
+    let mut rows: Vec<&[u8]> = rgba.chunks_exact(width.max(1) * 4).collect();
+
+    if options.flip_vertical {
+        rows.reverse();
+    }
+
+    let mut data = Vec::with_capacity(width * height * channels.len() * 2);
+
+    for pixel in rows.iter().flat_map(|row| row.chunks_exact(4)) {
+        for &channel in channels {
+            match options.bit_depth {
+                PngBitDepth::Eight => data.push(pixel[channel]),
+                // Repeating the byte maps 0xFF to 0xFFFF, keeping white white
+                PngBitDepth::Sixteen => data.extend_from_slice(&[pixel[channel]; 2]),
+            }
+        }
+    }
+
+    let mut png_bytes = Vec::new();
+
+    let mut encoder = png::Encoder::new(&mut png_bytes, width as u32, height as u32);
+
+    encoder.set_color(colour_type);
+    encoder.set_depth(match options.bit_depth {
+        PngBitDepth::Eight => png::BitDepth::Eight,
+        PngBitDepth::Sixteen => png::BitDepth::Sixteen,
+    });
+
+    if options.srgb {
+        encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+        // The gAMA value the PNG spec pairs with sRGB, for readers that ignore the sRGB chunk
+        encoder.set_source_gamma(png::ScaledFloat::from_scaled(45455));
+    }
+
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&data))
+        .map_err(std::io::Error::other)?;
+
+    Ok(png_bytes)
+}
+
+/// Multiplies the colour channels of RGBA8 pixels by their alpha, rounding to the nearest value.
+pub(crate) fn premultiply_alpha(rgba: &mut [u8]) {
+    for pixel in rgba.chunks_exact_mut(4) {
+        let alpha = pixel[3] as u32;
+
+        for channel in &mut pixel[..3] {
+            *channel = ((*channel as u32 * alpha + 127) / 255) as u8;
+        }
+    }
+}
+
+/// Divides the colour channels of RGBA8 pixels by their alpha, the reverse of
+/// [`premultiply_alpha`]. Fully transparent pixels become black, since their colour is lost.
+pub(crate) fn unpremultiply_alpha(rgba: &mut [u8]) {
+    for pixel in rgba.chunks_exact_mut(4) {
+        let alpha = pixel[3] as u32;
+
+        for channel in &mut pixel[..3] {
+            *channel = match alpha {
+                0 => 0,
+                alpha => ((*channel as u32 * 255 + alpha / 2) / alpha).min(255) as u8,
+            };
+        }
+    }
+}
+
+/// Encodes RGBA8 pixels as an uncompressed 32-bit TGA in memory, stored top row first.
+pub(crate) fn encode_tga(
+    width: usize,
+    height: usize,
+    rgba: &[u8],
+) -> Result<Vec<u8>, std::io::Error> {
+    let (Ok(tga_width), Ok(tga_height)) = (u16::try_from(width), u16::try_from(height)) else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{}x{} is too large for a TGA", width, height),
+        ));
+    };
+
+    check_rgba_size(width, height, rgba)?;
+
+    let mut bytes = Vec::with_capacity(18 + rgba.len());
+
+    // No ID or colour map, uncompressed true colour
+    bytes.extend_from_slice(&[0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    bytes.extend_from_slice(&tga_width.to_le_bytes());
+    bytes.extend_from_slice(&tga_height.to_le_bytes());
+    // 32 bits per pixel, with 8 alpha bits and the origin at the top left
+    bytes.extend_from_slice(&[32, 0x28]);
+
+    for pixel in rgba.chunks_exact(4) {
+        bytes.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]);
+    }
+
+    Ok(bytes)
+}
+
+/// Encodes RGBA8 pixels as an uncompressed 32-bit BMP in memory. Rows are stored bottom up with
+/// a plain BITMAPINFOHEADER, which older tools expect, and the alpha channel is kept in the
+/// fourth byte of each pixel.
+pub(crate) fn encode_bmp(
+    width: usize,
+    height: usize,
+    rgba: &[u8],
+) -> Result<Vec<u8>, std::io::Error> {
+    const HEADER_SIZE: usize = 14 + 40;
+
+    let file_size = (HEADER_SIZE + rgba.len()) as u64;
+
+    let (Ok(bmp_width), Ok(bmp_height), Ok(file_size)) = (
+        i32::try_from(width),
+        i32::try_from(height),
+        u32::try_from(file_size),
+    ) else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{}x{} is too large for a BMP", width, height),
+        ));
+    };
+
+    check_rgba_size(width, height, rgba)?;
+
+    let mut bytes = Vec::with_capacity(file_size as usize);
+
+    // File header, with the pixels straight after the info header
+    bytes.extend_from_slice(b"BM");
+    bytes.extend_from_slice(&file_size.to_le_bytes());
+    bytes.extend_from_slice(&[0; 4]);
+    bytes.extend_from_slice(&(HEADER_SIZE as u32).to_le_bytes());
+
+    // Info header: one plane of 32 bits per pixel, uncompressed
+    bytes.extend_from_slice(&40u32.to_le_bytes());
+    bytes.extend_from_slice(&bmp_width.to_le_bytes());
+    bytes.extend_from_slice(&bmp_height.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&32u16.to_le_bytes());
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    bytes.extend_from_slice(&(rgba.len() as u32).to_le_bytes());
+    // 72 DPI, and no palette
+    bytes.extend_from_slice(&2835u32.to_le_bytes());
+    bytes.extend_from_slice(&2835u32.to_le_bytes());
+    bytes.extend_from_slice(&[0; 8]);
+
+    // 32-bit rows never need padding
+    for row in rgba.chunks_exact(width.max(1) * 4).rev() {
+        for pixel in row.chunks_exact(4) {
+            bytes.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]);
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// The pixel data of every level of a DDS file, largest first.
+pub(crate) enum DdsLevels<'a> {
+    /// Block compressed data, stored as is under the given FourCC such as `DXT1`.
+    Compressed {
+        four_cc: [u8; 4],
+        levels: Vec<&'a [u8]>,
+    },
+    /// RGBA8 pixels, stored as 32-bit A8R8G8B8.
+    Rgba(Vec<Vec<u8>>),
+}
+
+/// Encodes a DDS file in memory. More than one level makes the file a mipmapped texture, with
+/// each level half the size of the one before.
+pub(crate) fn encode_dds(width: usize, height: usize, levels: &DdsLevels) -> Vec<u8> {
+    const DDSD_CAPS: u32 = 0x1;
+    const DDSD_HEIGHT: u32 = 0x2;
+    const DDSD_WIDTH: u32 = 0x4;
+    const DDSD_PITCH: u32 = 0x8;
+    const DDSD_PIXELFORMAT: u32 = 0x1000;
+    const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+    const DDSD_LINEARSIZE: u32 = 0x80000;
+
+    const DDPF_ALPHAPIXELS: u32 = 0x1;
+    const DDPF_FOURCC: u32 = 0x4;
+    const DDPF_RGB: u32 = 0x40;
+
+    const DDSCAPS_COMPLEX: u32 = 0x8;
+    const DDSCAPS_TEXTURE: u32 = 0x1000;
+    const DDSCAPS_MIPMAP: u32 = 0x400000;
+
+    let level_count = match levels {
+        DdsLevels::Compressed { levels, .. } => levels.len(),
+        DdsLevels::Rgba(levels) => levels.len(),
+    };
+
+    let mut flags = DDSD_CAPS | DDSD_HEIGHT | DDSD_WIDTH | DDSD_PIXELFORMAT;
+    let mut caps = DDSCAPS_TEXTURE;
+
+    if level_count > 1 {
+        flags |= DDSD_MIPMAPCOUNT;
+        caps |= DDSCAPS_COMPLEX | DDSCAPS_MIPMAP;
+    }
+
+    let pitch_or_linear_size = match levels {
+        DdsLevels::Compressed { levels, .. } => {
+            flags |= DDSD_LINEARSIZE;
+            levels.first().map_or(0, |level| level.len())
+        }
+        DdsLevels::Rgba(_) => {
+            flags |= DDSD_PITCH;
+            width * 4
+        }
+    };
+
+    let (pf_flags, four_cc, bit_count, masks) = match levels {
+        DdsLevels::Compressed { four_cc, .. } => (DDPF_FOURCC, *four_cc, 0u32, [0u32; 4]),
+        DdsLevels::Rgba(_) => (
+            DDPF_RGB | DDPF_ALPHAPIXELS,
+            [0; 4],
+            32,
+            [0x00ff0000, 0x0000ff00, 0x000000ff, 0xff000000],
+        ),
+    };
+
+    let mut bytes = b"DDS ".to_vec();
+
+    for value in [
+        124,
+        flags,
+        height as u32,
+        width as u32,
+        pitch_or_linear_size as u32,
+        0,
+        level_count as u32,
+    ] {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    bytes.extend_from_slice(&[0; 11 * 4]);
+
+    // DDS_PIXELFORMAT
+    bytes.extend_from_slice(&32u32.to_le_bytes());
+    bytes.extend_from_slice(&pf_flags.to_le_bytes());
+    bytes.extend_from_slice(&four_cc);
+    bytes.extend_from_slice(&bit_count.to_le_bytes());
+
+    for mask in masks {
+        bytes.extend_from_slice(&mask.to_le_bytes());
+    }
+
+    for value in [caps, 0, 0, 0, 0] {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    match levels {
+        DdsLevels::Compressed { levels, .. } => {
+            for level in levels {
+                bytes.extend_from_slice(level);
+            }
+        }
+        DdsLevels::Rgba(levels) => {
+            for level in levels {
+                for pixel in level.chunks_exact(4) {
+                    bytes.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]);
+                }
+            }
+        }
+    }
+
+    bytes
+}
+
+/// Shrinks RGBA8 pixels so that neither side is larger than `max_dimension`, keeping the aspect
+/// ratio. Each output pixel is the average of the source pixels it covers. Returns the new width,
+/// height and pixels, which are the originals if the image already fits.
+pub(crate) fn downscale(
+    width: usize,
+    height: usize,
+    rgba: &[u8],
+    max_dimension: usize,
+) -> Result<(usize, usize, Vec<u8>), std::io::Error> {
+    check_rgba_size(width, height, rgba)?;
+
+    let max_dimension = max_dimension.max(1);
+    let largest = width.max(height);
+
+    // Images with no pixels have nothing to average, so are returned as they are
+    if largest <= max_dimension || width == 0 || height == 0 {
+        return Ok((width, height, rgba.to_vec()));
+    }
+
+    let new_width = (width * max_dimension / largest).max(1);
+    let new_height = (height * max_dimension / largest).max(1);
+
+    let mut bytes = Vec::with_capacity(new_width * new_height * 4);
+
+    for y in 0..new_height {
+        let (top, bottom) = (y * height / new_height, (y + 1) * height / new_height);
+
+        for x in 0..new_width {
+            let (left, right) = (x * width / new_width, (x + 1) * width / new_width);
+
+            let mut sum = [0usize; 4];
+
+            for src_y in top..bottom {
+                for src_x in left..right {
+                    let i = (src_y * width + src_x) * 4;
+
+                    for (total, &channel) in sum.iter_mut().zip(&rgba[i..i + 4]) {
+                        *total += channel as usize;
+                    }
+                }
+            }
+
+            let count = (bottom - top) * (right - left);
+            bytes.extend(sum.map(|total| ((total + count / 2) / count) as u8));
+        }
+    }
+
+    Ok((new_width, new_height, bytes))
+}
+
+/// Checks that `rgba` holds exactly `width` x `height` RGBA8 pixels.
+fn check_rgba_size(width: usize, height: usize, rgba: &[u8]) -> Result<(), std::io::Error> {
+    if width * height * 4 != rgba.len() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "{} bytes is the wrong size for {}x{} RGBA8 pixels",
+                rgba.len(),
+                width,
+                height
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Encodes RGBA8 pixels into `dst_format`, the inverse of transcoding that format into R8G8B8A8.
+/// DXT formats are compressed block by block, with partial blocks at the right and bottom edges
+/// padded by repeating the edge pixels.
+pub fn encode(
+    width: usize,
+    height: usize,
+    dst_format: D3DFormat,
+    rgba: &[u8],
+) -> Result<Vec<u8>, std::io::Error> {
+    if rgba.len() != width * height * 4 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "Expected {} bytes of RGBA8 pixels for a {}x{} image, but received {}",
+                width * height * 4,
+                width,
+                height,
+                rgba.len()
+            ),
+        ));
+    }
+
+    match dst_format {
+        D3DFormat::Linear(LinearColour::R8G8B8A8) => Ok(rgba.to_vec()),
+
+        D3DFormat::Swizzled(Swizzled::A8B8G8R8) => {
+            let mut bytes = rgba.to_vec();
+            bytes.chunks_mut(4).for_each(|chunk| chunk.reverse());
+            Ok(bytes)
+        }
+
+        D3DFormat::Swizzled(Swizzled::B8G8R8A8) => {
+            let mut bytes = rgba.to_vec();
+            bytes.chunks_mut(4).for_each(|chunk| chunk.swap(0, 2));
+            Ok(bytes)
+        }
+
+        D3DFormat::Swizzled(Swizzled::A8R8G8B8) => {
+            let mut bytes = rgba.to_vec();
+            bytes.chunks_mut(4).for_each(|chunk| chunk.rotate_right(1));
+            Ok(bytes)
+        }
+
+        D3DFormat::Swizzled(
+            Swizzled::R5G6B5 | Swizzled::A1R5G5B5 | Swizzled::X1R5G5B5 | Swizzled::A4R4G4B4,
+        )
+        | D3DFormat::Standard(StandardFormat::P8) => Err(unsupported_swizzle(dst_format)),
+
+        D3DFormat::Linear(LinearColour::R5G6B5) => Ok(pack_16bit(rgba, |[r, g, b, _]| {
+            reduce_bits(r, 5) << 11 | reduce_bits(g, 6) << 5 | reduce_bits(b, 5)
+        })),
+
+        D3DFormat::Linear(LinearColour::A1R5G5B5) => Ok(pack_16bit(rgba, |[r, g, b, a]| {
+            reduce_bits(a, 1) << 15
+                | reduce_bits(r, 5) << 10
+                | reduce_bits(g, 5) << 5
+                | reduce_bits(b, 5)
+        })),
+
+        D3DFormat::Linear(LinearColour::X1R5G5B5) => Ok(pack_16bit(rgba, |[r, g, b, _]| {
+            reduce_bits(r, 5) << 10 | reduce_bits(g, 5) << 5 | reduce_bits(b, 5)
+        })),
+
+        D3DFormat::Linear(LinearColour::A4R4G4B4) => Ok(pack_16bit(rgba, |[r, g, b, a]| {
+            reduce_bits(a, 4) << 12
+                | reduce_bits(r, 4) << 8
+                | reduce_bits(g, 4) << 4
+                | reduce_bits(b, 4)
+        })),
+
+        D3DFormat::Standard(StandardFormat::DXT1) => {
+            Ok(encode_blocks(width, height, rgba, |block| {
+                encode_dxt_colour_block(block, true).to_vec()
+            }))
+        }
+
+        D3DFormat::Standard(StandardFormat::DXT2Or3) => {
+            Ok(encode_blocks(width, height, rgba, |block| {
+                let mut alpha = 0u64;
+
+                for (i, pixel) in block.iter().enumerate() {
+                    alpha |= (reduce_bits(pixel[3], 4) as u64) << (i * 4);
+                }
+
+                let mut bytes = alpha.to_le_bytes().to_vec();
+                bytes.extend_from_slice(&encode_dxt_colour_block(block, false));
+                bytes
+            }))
+        }
+
+        D3DFormat::Standard(StandardFormat::DXT4Or5) => {
+            Ok(encode_blocks(width, height, rgba, |block| {
+                let mut bytes = encode_dxt5_alpha_block(block).to_vec();
+                bytes.extend_from_slice(&encode_dxt_colour_block(block, false));
+                bytes
+            }))
+        }
+
+        _ => Err(std::io::Error::other(
+            "Unsupported destination format for encoding.",
+        )),
+    }
+}
+
+/// Splits RGBA8 pixels into 4x4 blocks (row by row) and concatenates the output of `encode_block`
+/// for each of them.
+fn encode_blocks(
+    width: usize,
+    height: usize,
+    rgba: &[u8],
+    encode_block: impl Fn(&[[u8; 4]; 16]) -> Vec<u8>,
+) -> Vec<u8> {
+    let mut bytes = Vec::new();
+
+    for block_y in 0..height.div_ceil(4) {
+        for block_x in 0..width.div_ceil(4) {
+            let mut block = [[0u8; 4]; 16];
+
+            for (i, pixel) in block.iter_mut().enumerate() {
+                let x = (block_x * 4 + i % 4).min(width - 1);
+                let y = (block_y * 4 + i / 4).min(height - 1);
+                let start = (y * width + x) * 4;
+
+                pixel.copy_from_slice(&rgba[start..start + 4]);
+            }
+
+            bytes.extend(encode_block(&block));
+        }
+    }
+
+    bytes
+}
+
+/// Compresses the alpha of a 4x4 block into an interpolated DXT5 alpha block, using the minimum
+/// and maximum alpha of the block as endpoints.
+fn encode_dxt5_alpha_block(block: &[[u8; 4]; 16]) -> [u8; 8] {
+    let alpha0 = block.iter().map(|pixel| pixel[3]).max().unwrap_or_default();
+    let alpha1 = block.iter().map(|pixel| pixel[3]).min().unwrap_or_default();
+
+    // alpha0 > alpha1 selects the 8 alpha mode, where index 0 is alpha0, 1 is alpha1 and 2-7 are
+    // evenly spaced between them
+    let palette: Vec<i32> = if alpha0 > alpha1 {
+        [alpha0 as i32, alpha1 as i32]
+            .into_iter()
+            .chain((1..7).map(|i| ((7 - i) * alpha0 as i32 + i * alpha1 as i32) / 7))
+            .collect()
+    } else {
+        vec![alpha0 as i32]
+    };
+
+    let mut indices = 0u64;
+
+    for (i, pixel) in block.iter().enumerate() {
+        let index = (0..palette.len())
+            .min_by_key(|&entry| (palette[entry] - pixel[3] as i32).abs())
+            .unwrap_or_default() as u64;
+
+        indices |= index << (i * 3);
+    }
+
+    let mut bytes = [0u8; 8];
+    bytes[0] = alpha0;
+    bytes[1] = alpha1;
+    bytes[2..8].copy_from_slice(&indices.to_le_bytes()[..6]);
+
+    bytes
+}
+
+/// Compresses the colours of a 4x4 block into a DXT colour block, using the corners of the
+/// block's bounding box as endpoints. If `punch_through` is set and any pixel is mostly
+/// transparent, the 3 colour + transparent mode of DXT1 is used.
+fn encode_dxt_colour_block(block: &[[u8; 4]; 16], punch_through: bool) -> [u8; 8] {
+    let to_565 = |colour: [u8; 3]| -> u16 {
+        (reduce_bits(colour[0], 5) << 11)
+            | (reduce_bits(colour[1], 6) << 5)
+            | reduce_bits(colour[2], 5)
+    };
+    let from_565 = |colour: u16| -> [i32; 3] {
+        [
+            expand_bits(colour >> 11, 5) as i32,
+            expand_bits(colour >> 5, 6) as i32,
+            expand_bits(colour, 5) as i32,
+        ]
+    };
+
+    let transparent = punch_through && block.iter().any(|pixel| pixel[3] < 0x80);
+
+    let mut min = [0xffu8; 3];
+    let mut max = [0u8; 3];
+
+    for pixel in block
+        .iter()
+        .filter(|pixel| !transparent || pixel[3] >= 0x80)
+    {
+        for channel in 0..3 {
+            min[channel] = min[channel].min(pixel[channel]);
+            max[channel] = max[channel].max(pixel[channel]);
+        }
+    }
+
+    let (mut colour0, mut colour1) = (to_565(max), to_565(min));
+
+    // The order of the endpoints selects the block mode: colour0 > colour1 gives 4 colours, and
+    // colour0 <= colour1 gives 3 colours and transparency
+    if transparent == (colour0 > colour1) {
+        std::mem::swap(&mut colour0, &mut colour1);
+    }
+
+    let (c0, c1) = (from_565(colour0), from_565(colour1));
+    let mix = |a: i32, b: i32, wa: i32, wb: i32| (a * wa + b * wb) / (wa + wb);
+
+    let mut palette = vec![c0, c1];
+
+    if colour0 > colour1 {
+        palette.push([0, 1, 2].map(|i| mix(c0[i], c1[i], 2, 1)));
+        palette.push([0, 1, 2].map(|i| mix(c0[i], c1[i], 1, 2)));
+    } else {
+        palette.push([0, 1, 2].map(|i| mix(c0[i], c1[i], 1, 1)));
+    }
+
+    let mut indices = 0u32;
+
+    for (i, pixel) in block.iter().enumerate() {
+        let index = if transparent && pixel[3] < 0x80 {
+            3
+        } else {
+            (0..palette.len())
+                .min_by_key(|&entry| {
+                    (0..3)
+                        .map(|channel| (palette[entry][channel] - pixel[channel] as i32).pow(2))
+                        .sum::<i32>()
+                })
+                .unwrap_or_default() as u32
+        };
+
+        indices |= index << (i * 2);
+    }
+
+    let mut bytes = [0u8; 8];
+    bytes[0..2].copy_from_slice(&colour0.to_le_bytes());
+    bytes[2..4].copy_from_slice(&colour1.to_le_bytes());
+    bytes[4..8].copy_from_slice(&indices.to_le_bytes());
+
+    bytes
+}
+
+/// The error for the swizzled 16-bit formats and P8, whose palette indices are swizzled too. Their
+/// pixels are stored in Morton order, which isn't unswizzled yet, so converting them as if they
+/// were linear would scramble the image.
+fn unsupported_swizzle(format: D3DFormat) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        format!(
+            "The swizzled format {:?} can't be converted, as unswizzling isn't supported",
+            format
+        ),
+    )
+}
+
+/// Converts RGBA8 pixels into little endian 16-bit pixels using `pack`.
+fn pack_16bit(rgba: &[u8], pack: impl Fn([u8; 4]) -> u16) -> Vec<u8> {
+    rgba.chunks_exact(4)
+        .flat_map(|pixel| pack([pixel[0], pixel[1], pixel[2], pixel[3]]).to_le_bytes())
+        .collect()
+}
+
+/// Reduces an 8-bit value to `bits` bits, rounding to the nearest value. The inverse of
+/// [`expand_bits`].
+fn reduce_bits(value: u8, bits: u32) -> u16 {
+    let max = (1u32 << bits) - 1;
+
+    ((value as u32 * max + 0x7f) / 0xff) as u16
+}
+
+/// Converts little endian 16-bit pixels into RGBA8 pixels using `unpack`. Any trailing odd byte is
+/// ignored.
+fn unpack_16bit(bytes: &[u8], unpack: impl Fn(u16) -> [u8; 4]) -> Vec<u8> {
+    bytes
+        .chunks_exact(2)
+        .flat_map(|chunk| unpack(u16::from_le_bytes([chunk[0], chunk[1]])))
+        .collect()
+}
+
+/// Expands the lowest `bits` bits of `value` to the full 0-255 range, replicating the high bits
+/// into the low bits so that the maximum value maps to 0xff.
+fn expand_bits(value: u16, bits: u32) -> u8 {
+    let max = (1u16 << bits) - 1;
+    let value = value & max;
+
+    ((value as u32 * 0xff + max as u32 / 2) / max as u32) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_rgba(format: D3DFormat, pixel: u16) -> Vec<u8> {
+        transcode(
+            1,
+            1,
+            format,
+            D3DFormat::Linear(LinearColour::R8G8B8A8),
+            &pixel.to_le_bytes(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn encodes_png_with_options() {
+        let rgba = [1, 2, 3, 4, 5, 6, 7, 8];
+
+        let options = DumpOptions::default()
+            .colour(PngColour::Rgb)
+            .bit_depth(PngBitDepth::Sixteen)
+            .srgb(true)
+            .flip_vertical(true);
+        let png_bytes = encode_png_with(1, 2, &rgba, &options).unwrap();
+
+        let mut reader = png::Decoder::new(png_bytes.as_slice()).read_info().unwrap();
+        let info = reader.info();
+
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        assert_eq!(info.bit_depth, png::BitDepth::Sixteen);
+        assert!(info.srgb.is_some());
+        assert!(info.source_gamma.is_some());
+
+        let mut data = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut data).unwrap();
+        assert_eq!(data, [5, 5, 6, 6, 7, 7, 1, 1, 2, 2, 3, 3]);
+
+        let default = encode_png_with(1, 2, &rgba, &DumpOptions::default()).unwrap();
+        assert_eq!(default, encode_png(1, 2, &rgba).unwrap());
+    }
+
+    #[test]
+    fn converts_alpha() {
+        let mut rgba = [200, 100, 0, 128, 50, 60, 70, 0, 10, 20, 30, 255];
+
+        premultiply_alpha(&mut rgba);
+        assert_eq!(rgba, [100, 50, 0, 128, 0, 0, 0, 0, 10, 20, 30, 255]);
+
+        unpremultiply_alpha(&mut rgba);
+        assert_eq!(rgba, [199, 100, 0, 128, 0, 0, 0, 0, 10, 20, 30, 255]);
+
+        let options = DumpOptions::default().flip_vertical(true);
+        let mask = encode_alpha_mask(1, 3, &rgba, &options).unwrap();
+
+        let mut reader = png::Decoder::new(mask.as_slice()).read_info().unwrap();
+        assert_eq!(reader.info().color_type, png::ColorType::Grayscale);
+
+        let mut data = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut data).unwrap();
+        assert_eq!(data, [255, 0, 128]);
+    }
+
+    #[test]
+    fn encodes_bmp_bottom_up() {
+        let rgba = [1, 2, 3, 4, 5, 6, 7, 8];
+        let bmp = encode_bmp(1, 2, &rgba).unwrap();
+
+        assert_eq!(&bmp[..2], b"BM");
+        assert_eq!(bmp.len(), 54 + 8);
+        assert_eq!(u32::from_le_bytes(bmp[2..6].try_into().unwrap()), 62);
+        assert_eq!(i32::from_le_bytes(bmp[22..26].try_into().unwrap()), 2);
+        assert_eq!(bmp[54..], [7, 6, 5, 8, 3, 2, 1, 4]);
+        assert!(encode_bmp(2, 2, &rgba).is_err());
+    }
+
+    #[test]
+    fn tolerates_truncated_and_empty_images() {
+        let format = D3DFormat::Swizzled(Swizzled::B8G8R8A8);
+        let rgba = D3DFormat::Linear(LinearColour::R8G8B8A8);
+
+        assert_eq!(
+            transcode(1, 1, format, rgba, &[1, 2, 3, 4, 5]).unwrap(),
+            [3, 2, 1, 4, 5]
+        );
+        assert_eq!(downscale(0, 100, &[], 10).unwrap(), (0, 100, vec![]));
+    }
+
+    #[test]
+    fn r5g6b5() {
+        let format = D3DFormat::Linear(LinearColour::R5G6B5);
+
+        assert_eq!(to_rgba(format, 0xf800), [0xff, 0x00, 0x00, 0xff]);
+        assert_eq!(to_rgba(format, 0x07e0), [0x00, 0xff, 0x00, 0xff]);
+        assert_eq!(to_rgba(format, 0x001f), [0x00, 0x00, 0xff, 0xff]);
+        assert_eq!(to_rgba(format, 0x8410), [0x84, 0x82, 0x84, 0xff]);
+    }
+
+    #[test]
+    fn a1r5g5b5() {
+        let format = D3DFormat::Linear(LinearColour::A1R5G5B5);
+
+        assert_eq!(to_rgba(format, 0xfc00), [0xff, 0x00, 0x00, 0xff]);
+        assert_eq!(to_rgba(format, 0x03e0), [0x00, 0xff, 0x00, 0x00]);
+        assert_eq!(to_rgba(format, 0x801f), [0x00, 0x00, 0xff, 0xff]);
+    }
+
+    #[test]
+    fn a4r4g4b4() {
+        let format = D3DFormat::Linear(LinearColour::A4R4G4B4);
+
+        assert_eq!(to_rgba(format, 0xf000), [0x00, 0x00, 0x00, 0xff]);
+        assert_eq!(to_rgba(format, 0x0f00), [0xff, 0x00, 0x00, 0x00]);
+        assert_eq!(to_rgba(format, 0x1234), [0x22, 0x33, 0x44, 0x11]);
+    }
+
+    #[test]
+    fn encode_16bit_round_trips() {
+        for format in [
+            D3DFormat::Linear(LinearColour::R5G6B5),
+            D3DFormat::Linear(LinearColour::A4R4G4B4),
+            D3DFormat::Linear(LinearColour::A1R5G5B5),
+        ] {
+            for pixel in [0x0000u16, 0xffff, 0x8410, 0x1234, 0x7bef] {
+                let rgba = to_rgba(format, pixel);
+
+                assert_eq!(
+                    transcode(
+                        1,
+                        1,
+                        format,
+                        D3DFormat::Linear(LinearColour::R8G8B8A8),
+                        &encode(1, 1, format, &rgba).unwrap()
+                    )
+                    .unwrap(),
+                    rgba
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_swizzled() {
+        let rgba = D3DFormat::Linear(LinearColour::R8G8B8A8);
+
+        for format in [
+            D3DFormat::Swizzled(Swizzled::R5G6B5),
+            D3DFormat::Swizzled(Swizzled::A1R5G5B5),
+            D3DFormat::Swizzled(Swizzled::A4R4G4B4),
+            D3DFormat::Standard(StandardFormat::P8),
+        ] {
+            let error = transcode(2, 2, format, rgba, &[0; 8]).unwrap_err();
+            assert_eq!(error.kind(), std::io::ErrorKind::Unsupported);
+            assert!(encode(2, 2, format, &[0; 16]).is_err());
+        }
+    }
+
+    #[test]
+    fn encode_dxt1_blocks() {
+        let dxt1 = D3DFormat::Standard(StandardFormat::DXT1);
+
+        // A solid red 2x2 image is padded out to a single block using only colour0
+        let red = [0xff, 0x00, 0x00, 0xff].repeat(4);
+        assert_eq!(
+            encode(2, 2, dxt1, &red).unwrap(),
+            [0x00, 0xf8, 0x00, 0xf8, 0, 0, 0, 0]
+        );
+
+        // Black and white halves use both endpoints, in 4 colour mode (colour0 > colour1)
+        let mut halves = [0xff; 4].repeat(8);
+        halves.extend([0x00, 0x00, 0x00, 0xff].repeat(8));
+        assert_eq!(
+            encode(4, 4, dxt1, &halves).unwrap(),
+            [0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0x55, 0x55]
+        );
+
+        // A transparent pixel switches to 3 colour mode, using index 3
+        let mut transparent = [0xff; 4].repeat(16);
+        transparent[3] = 0;
+        let block = encode(4, 4, dxt1, &transparent).unwrap();
+        assert!(
+            u16::from_le_bytes([block[0], block[1]]) <= u16::from_le_bytes([block[2], block[3]])
+        );
+        assert_eq!(block[4] & 0b11, 3);
+    }
+
+    #[test]
+    fn encode_dxt5_alpha() {
+        let dxt5 = D3DFormat::Standard(StandardFormat::DXT4Or5);
+
+        // A single transparent pixel uses the minimum alpha endpoint, everything else the maximum
+        let mut pixels = [0xff; 4].repeat(16);
+        pixels[3] = 0;
+
+        let block = encode(4, 4, dxt5, &pixels).unwrap();
+        assert_eq!(block.len(), 16);
+        assert_eq!(block[..8], [0xff, 0x00, 0x01, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn downscale_averages_pixels() {
+        // A 4x2 image of black and white columns halves into 2x1 mid grey pixels
+        let row = [[0x00, 0x00, 0x00, 0xff], [0xff, 0xff, 0xff, 0xff]].concat();
+        let rgba = row.repeat(4);
+
+        let (width, height, bytes) = downscale(4, 2, &rgba, 2).unwrap();
+        assert_eq!((width, height), (2, 1));
+        assert_eq!(bytes, [0x80, 0x80, 0x80, 0xff].repeat(2));
+
+        let (width, height, bytes) = downscale(4, 2, &rgba, 8).unwrap();
+        assert_eq!((width, height), (4, 2));
+        assert_eq!(bytes, rgba);
+
+        assert!(downscale(4, 4, &rgba, 2).is_err());
+    }
+}
@@ -0,0 +1,216 @@
+use std::ops::Range;
+
+use miniz_oxide::{
+    DataFormat, MZFlush, MZStatus,
+    inflate::stream::{InflateState, inflate},
+};
+
+use crate::BNLError;
+
+/// The number of bytes inflated per step while streaming.
+const INFLATE_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Returns true if `bytes` start with a zlib stream header using the deflate method.
+pub(crate) fn has_zlib_header(bytes: &[u8]) -> bool {
+    let Some(&[cmf, flg]) = bytes.get(..2) else {
+        return false;
+    };
+
+    // The header checksum makes the two bytes a multiple of 31
+    cmf & 0x0f == 8 && (u16::from(cmf) << 8 | u16::from(flg)).is_multiple_of(31)
+}
+
+/// Incrementally inflates the zlib body of a BNL file, so that the sections near the start of
+/// the file can be read before the rest has been decompressed.
+///
+/// The output starts with the uncompressed header, so offsets from the header can be used as
+/// indices into [`ZlibStream::bytes`] directly, until [`ZlibStream::take_range`] discards the
+/// start of it.
+pub(crate) struct ZlibStream<'a> {
+    state: Box<InflateState>,
+    input: &'a [u8],
+    output: Vec<u8>,
+    finished: bool,
+    /// Whether the input is stored without compression, and is copied rather than inflated.
+    stored: bool,
+    /// The offset of the first byte of `output` within the whole decompressed file.
+    base: usize,
+}
+
+impl<'a> ZlibStream<'a> {
+    pub(crate) fn new(header: &[u8], input: &'a [u8]) -> Self {
+        ZlibStream {
+            state: InflateState::new_boxed(DataFormat::Zlib),
+            input,
+            output: header.to_vec(),
+            finished: false,
+            stored: false,
+            base: 0,
+        }
+    }
+
+    /// Creates a stream over a body that isn't compressed, which passes it through unchanged.
+    pub(crate) fn stored(header: &[u8], input: &'a [u8]) -> Self {
+        ZlibStream {
+            state: InflateState::new_boxed(DataFormat::Zlib),
+            input,
+            output: header.to_vec(),
+            finished: input.is_empty(),
+            stored: true,
+            base: 0,
+        }
+    }
+
+    /// Inflates until at least `len` bytes (including the header) are available, or the end of
+    /// the stream is reached.
+    ///
+    /// # Errors
+    /// Returns [`BNLError::DecompressionFailure`] if the stream is invalid or truncated.
+    pub(crate) fn inflate_to(&mut self, len: usize) -> Result<(), BNLError> {
+        while self.base + self.output.len() < len && !self.finished {
+            if self.stored {
+                let take = self.input.len().min(len - self.base - self.output.len());
+
+                self.output.extend_from_slice(&self.input[..take]);
+                self.input = &self.input[take..];
+                self.finished = self.input.is_empty();
+
+                continue;
+            }
+
+            let start = self.output.len();
+            self.output.resize(start + INFLATE_CHUNK_SIZE, 0);
+
+            let result = inflate(
+                &mut self.state,
+                self.input,
+                &mut self.output[start..],
+                MZFlush::None,
+            );
+
+            self.input = &self.input[result.bytes_consumed..];
+            self.output.truncate(start + result.bytes_written);
+
+            match result.status {
+                Ok(MZStatus::StreamEnd) => self.finished = true,
+                Ok(_) => {}
+                // A buffer error means no progress could be made, so the input ended before the
+                // stream did
+                Err(_) => return Err(BNLError::DecompressionFailure),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Inflates the rest of the stream, returning the header followed by all of the decompressed
+    /// bytes. Must not be called after [`ZlibStream::take_range`].
+    pub(crate) fn finish(mut self) -> Result<Vec<u8>, BNLError> {
+        debug_assert_eq!(self.base, 0, "the start of the output was discarded");
+
+        self.inflate_to(usize::MAX)?;
+
+        Ok(self.output)
+    }
+
+    /// Inflates the rest of the stream, and returns the size of the whole decompressed file.
+    pub(crate) fn total_len(&mut self) -> Result<usize, BNLError> {
+        self.inflate_to(usize::MAX)?;
+
+        Ok(self.base + self.output.len())
+    }
+
+    /// Inflates up to the end of `range` and returns the bytes within it, discarding everything
+    /// inflated before its end. Reading the sections of a file in order this way means the whole
+    /// decompressed file is never held in memory at once.
+    ///
+    /// Returns None if the range starts before bytes that have already been discarded, or ends
+    /// past the end of the stream.
+    pub(crate) fn take_range(&mut self, range: Range<usize>) -> Result<Option<Vec<u8>>, BNLError> {
+        self.inflate_to(range.end)?;
+
+        if range.start < self.base || range.end > self.base + self.output.len() {
+            return Ok(None);
+        }
+
+        let bytes = self.output[range.start - self.base..range.end - self.base].to_vec();
+
+        self.output.drain(..range.end - self.base);
+        self.base = range.end;
+
+        Ok(Some(bytes))
+    }
+
+    /// Returns the bytes that have been inflated so far, including the header.
+    pub(crate) fn bytes(&self) -> &[u8] {
+        &self.output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inflates_in_steps() {
+        let body: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        let compressed = miniz_oxide::deflate::compress_to_vec_zlib(&body, 6);
+
+        let mut stream = ZlibStream::new(&[0xaa; 4], &compressed);
+
+        stream.inflate_to(10).unwrap();
+        assert!(stream.bytes().len() >= 10);
+        assert!(stream.bytes().len() < body.len());
+        assert_eq!(stream.bytes()[..4], [0xaa; 4]);
+
+        let bytes = stream.finish().unwrap();
+        assert_eq!(bytes[4..], body[..]);
+    }
+
+    #[test]
+    fn takes_ranges_in_order() {
+        let body: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        let compressed = miniz_oxide::deflate::compress_to_vec_zlib(&body, 6);
+
+        for mut stream in [
+            ZlibStream::new(&[0xaa; 4], &compressed),
+            ZlibStream::stored(&[0xaa; 4], &body),
+        ] {
+            assert_eq!(
+                stream.take_range(2..6).unwrap().unwrap(),
+                [0xaa, 0xaa, 0, 1]
+            );
+            assert_eq!(
+                stream.take_range(100_004..150_004).unwrap().unwrap(),
+                body[100_000..150_000]
+            );
+
+            // Discarded and missing bytes can't be taken
+            assert_eq!(stream.take_range(0..8).unwrap(), None);
+            assert_eq!(stream.take_range(150_004..300_000).unwrap(), None);
+            assert_eq!(stream.total_len().unwrap(), 200_004);
+        }
+    }
+
+    #[test]
+    fn sniffs_zlib_headers() {
+        let compressed = miniz_oxide::deflate::compress_to_vec_zlib(b"body", 1);
+
+        assert!(has_zlib_header(&compressed));
+        assert!(!has_zlib_header(b"aid_"));
+        assert!(!has_zlib_header(&[0x78]));
+
+        let stream = ZlibStream::stored(&[1], &[2, 3]);
+        assert_eq!(stream.finish().unwrap(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let body = vec![7u8; 100_000];
+        let compressed = miniz_oxide::deflate::compress_to_vec_zlib(&body, 6);
+
+        let stream = ZlibStream::new(&[], &compressed[..compressed.len() / 2]);
+
+        assert!(stream.finish().is_err());
+    }
+}
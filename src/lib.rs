@@ -2,34 +2,76 @@ pub(crate) mod d3d;
 
 pub(crate) mod images;
 
+mod inflate;
+
+pub mod analysis;
 pub mod asset;
+pub mod batch;
+pub mod constraints;
+pub mod contact_sheet;
+pub mod extract;
+pub mod filter;
+pub mod format;
+#[cfg(fuzzing)]
+pub mod fuzz;
+pub mod hexdump;
+pub mod hooks;
+pub mod manifest;
+pub mod prelude;
+pub mod schema;
+pub mod serialize;
+pub mod space;
+pub mod stable;
+pub mod stats;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testutil;
+pub mod validate;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod workspace;
+pub mod wrapper;
 
 use byteorder::{LittleEndian, ReadBytesExt};
 
 use std::{
     cmp,
+    collections::HashMap,
     error::Error,
     fmt::Display,
     io::{Cursor, Read, Seek, SeekFrom},
+    ops::Range,
 };
 
 use crate::{
     asset::{
         Asset, AssetDescription, AssetDescriptor, AssetError, AssetName, AssetParseError,
-        DataViewList, RawAsset,
+        DataViewList, RawAsset, RawAssetRef,
     },
+    constraints::ConstraintViolation,
     game::AssetType,
+    inflate::ZlibStream,
+    serialize::SerializeOptions,
+    space::{FreeSpace, SectionMap, SectionSpace, SpaceReport},
 };
 
 pub mod game;
 
-#[derive(Debug, Copy, Clone, Default)]
+/// The size of the uncompressed header at the start of every BNL file. Everything after it is
+/// zlib compressed.
+const BNL_HEADER_SIZE: usize = 40;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DataView {
     offset: u32,
     size: u32,
 }
 
 impl DataView {
+    pub fn new(offset: u32, size: u32) -> DataView {
+        DataView { offset, size }
+    }
+
     pub fn from_cursor<T>(cur: &mut Cursor<T>) -> Result<DataView, std::io::Error>
     where
         Cursor<T>: std::io::Read,
@@ -39,6 +81,14 @@ impl DataView {
 
         Ok(DataView { offset, size })
     }
+
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
 }
 
 macro_rules! read {
@@ -60,16 +110,58 @@ macro_rules! read {
 }
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum BNLError {
     /// The ZLIB portion of the BNL file could not be decompressed successfully.
     DecompressionFailure,
     /// An error occurred when parsing the [`AssetDescription`] data of the BNL file.
     DataReadError(String),
+    /// The in-memory BNL data could not be written back out as a BNL file.
+    DataWriteError(String),
+    /// A section given by the header lies outside of the decompressed data.
+    SectionOutOfBounds {
+        section: &'static str,
+        offset: u32,
+        size: u32,
+        available: usize,
+    },
+    /// An I/O error occurred while reading or writing the BNL data, such as the data ending early.
+    Io(std::io::Error),
+}
+
+impl Display for BNLError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BNLError::DecompressionFailure => write!(f, "Unable to decompress the BNL data"),
+            BNLError::DataReadError(message) => write!(f, "Unable to read BNL: {}", message),
+            BNLError::DataWriteError(message) => write!(f, "Unable to write BNL: {}", message),
+            BNLError::SectionOutOfBounds {
+                section,
+                offset,
+                size,
+                available,
+            } => write!(
+                f,
+                "The {} section ({} bytes at offset {:#x}) is out of bounds of the {} bytes of BNL data",
+                section, size, offset, available
+            ),
+            BNLError::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl Error for BNLError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BNLError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl From<std::io::Error> for BNLError {
     fn from(value: std::io::Error) -> Self {
-        BNLError::DataReadError(format!("File error: {}", value))
+        BNLError::Io(value)
     }
 }
 
@@ -79,10 +171,90 @@ impl From<miniz_oxide::inflate::DecompressError> for BNLError {
     }
 }
 
+/// The flags byte of a BNL header.
+///
+/// Every known retail bundle leaves all of the flags clear. Bits this crate doesn't understand are
+/// kept as they are, and reported through [`BNLFile::header_warnings`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BNLFlags(u8);
+
+impl BNLFlags {
+    /// Believed to mark bundles whose sections follow the header without zlib compression, as
+    /// reported on demo and debug builds. This hasn't been confirmed against a real bundle.
+    pub const UNCOMPRESSED: BNLFlags = BNLFlags(0x01);
+
+    /// Every flag this crate understands.
+    pub const KNOWN: BNLFlags = BNLFlags::UNCOMPRESSED;
+
+    pub fn from_bits(bits: u8) -> BNLFlags {
+        BNLFlags(bits)
+    }
+
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Returns true if every bit of `other` is set.
+    pub fn contains(self, other: BNLFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn set(&mut self, other: BNLFlags, value: bool) {
+        match value {
+            true => self.0 |= other.0,
+            false => self.0 &= !other.0,
+        }
+    }
+
+    pub fn is_compressed(self) -> bool {
+        !self.contains(BNLFlags::UNCOMPRESSED)
+    }
+
+    /// Returns the bits that aren't in [`BNLFlags::KNOWN`].
+    pub fn unknown_bits(self) -> u8 {
+        self.0 & !BNLFlags::KNOWN.0
+    }
+}
+
+/// Something in a BNL header that this crate doesn't understand. These don't stop the file from
+/// being parsed, but the file may not be read correctly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeaderWarning {
+    /// Flag bits outside of [`BNLFlags::KNOWN`] are set.
+    UnknownFlags(u8),
+    /// The reserved bytes after the flags aren't all zero.
+    NonZeroReserved([u8; 5]),
+}
+
+impl Display for HeaderWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HeaderWarning::UnknownFlags(bits) => {
+                write!(f, "Unknown header flags {:#04x} are set", bits)
+            }
+            HeaderWarning::NonZeroReserved(bytes) => {
+                write!(f, "Reserved header bytes are not zero: {:02x?}", bytes)
+            }
+        }
+    }
+}
+
+/// How the sections after the header of a BNL file are stored.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Compression {
+    /// A single zlib stream, as used by retail bundles.
+    #[default]
+    Zlib,
+    /// The sections are stored directly after the header, as reported on demo and debug builds.
+    Uncompressed,
+}
+
 #[derive(Debug, Default)]
 struct BNLHeader {
     file_count: u16,
-    flags: u8,
+    flags: BNLFlags,
     unknown_2: [u8; 5],
 
     asset_desc_loc: DataView,
@@ -91,6 +263,155 @@ struct BNLHeader {
     descriptor_loc: DataView,
 }
 
+impl BNLHeader {
+    fn from_bytes(bnl_bytes: &[u8]) -> Result<BNLHeader, BNLError> {
+        let mut cur = Cursor::new(bnl_bytes);
+
+        let mut header = BNLHeader {
+            file_count: read!(cur, u16),
+            flags: BNLFlags(read!(cur, u8)),
+            ..Default::default()
+        };
+
+        cur.read_exact(&mut header.unknown_2)?;
+
+        header.asset_desc_loc = DataView::from_cursor(&mut cur)?;
+        header.buffer_views_loc = DataView::from_cursor(&mut cur)?;
+        header.buffer_loc = DataView::from_cursor(&mut cur)?;
+        header.descriptor_loc = DataView::from_cursor(&mut cur)?;
+
+        Ok(header)
+    }
+
+    /// Works out how the body of `bnl_bytes` is stored. A zlib stream header is trusted first, as
+    /// the meaning of [`BNLFlags::UNCOMPRESSED`] is unconfirmed. Otherwise the body is taken to be
+    /// uncompressed if it is large enough to hold every section the header describes.
+    fn compression(&self, bnl_bytes: &[u8]) -> Option<Compression> {
+        let body = bnl_bytes.get(BNL_HEADER_SIZE..)?;
+
+        if inflate::has_zlib_header(body) {
+            return Some(Compression::Zlib);
+        }
+
+        let end = [
+            &self.asset_desc_loc,
+            &self.buffer_views_loc,
+            &self.buffer_loc,
+            &self.descriptor_loc,
+        ]
+        .iter()
+        .map(|loc| loc.offset as usize + loc.size as usize)
+        .max()
+        .unwrap_or(BNL_HEADER_SIZE);
+
+        (end <= bnl_bytes.len()).then_some(Compression::Uncompressed)
+    }
+
+    /// Returns a stream over the body of `bnl_bytes`, which inflates it when it is compressed.
+    fn body_stream<'a>(
+        &self,
+        bnl_bytes: &'a [u8],
+    ) -> Result<(ZlibStream<'a>, Compression), BNLError> {
+        let (header, body) = bnl_bytes.split_at(BNL_HEADER_SIZE);
+
+        match self.compression(bnl_bytes) {
+            Some(Compression::Zlib) => Ok((ZlibStream::new(header, body), Compression::Zlib)),
+            Some(Compression::Uncompressed) => {
+                Ok((ZlibStream::stored(header, body), Compression::Uncompressed))
+            }
+            None => Err(BNLError::DecompressionFailure),
+        }
+    }
+
+    fn warnings(&self) -> Vec<HeaderWarning> {
+        let mut warnings = Vec::new();
+
+        if self.flags.unknown_bits() != 0 {
+            warnings.push(HeaderWarning::UnknownFlags(self.flags.unknown_bits()));
+        }
+
+        if self.unknown_2 != [0; 5] {
+            warnings.push(HeaderWarning::NonZeroReserved(self.unknown_2));
+        }
+
+        warnings
+    }
+}
+
+/// Inflates the stream up to the end of the asset description section, and parses it.
+fn read_asset_descriptions(
+    header: &BNLHeader,
+    stream: &mut ZlibStream,
+) -> Result<Vec<AssetDescription>, BNLError> {
+    let loc = &header.asset_desc_loc;
+    stream.inflate_to(loc.offset as usize + loc.size as usize)?;
+
+    let mut cur = Cursor::new(stream.bytes());
+
+    assert_eq!(size_of::<AssetDescription>(), 160);
+
+    let num_descriptions = loc.size as usize / size_of::<AssetDescription>();
+
+    cur.seek(SeekFrom::Start(loc.offset as u64))?;
+
+    // The count comes from the header, so only reserve space for descriptions that were actually
+    // inflated
+    let available = stream.bytes().len().saturating_sub(loc.offset as usize);
+    let mut asset_descriptions =
+        Vec::with_capacity(num_descriptions.min(available / size_of::<AssetDescription>()));
+
+    for _ in 0..num_descriptions {
+        let mut asset_name: AssetName = [0x00; 128];
+
+        cur.read_exact(&mut asset_name)?;
+
+        // TODO: Rework this into an actual constructor
+        let asset_desc = AssetDescription {
+            name: asset_name,
+            asset_type: AssetType::try_from(read!(cur, u32)).map_err(|_| {
+                BNLError::DataReadError("Unable to parse asset type from BNL.".to_string())
+            })?,
+            unk_1: read!(cur, u32),
+            unk_2: read!(cur, u32),
+            chunk_count: read!(cur, u32),
+            descriptor_ptr: read!(cur, u32),
+            descriptor_size: read!(cur, u32),
+            dataview_list_ptr: read!(cur, u32),
+            resource_size: read!(cur, u32),
+        };
+
+        asset_descriptions.push(asset_desc);
+    }
+
+    Ok(asset_descriptions)
+}
+
+/// Pads `bytes` with zeros up to a multiple of `alignment`.
+fn pad_to(bytes: &mut Vec<u8>, alignment: usize) {
+    bytes.resize(bytes.len().next_multiple_of(alignment), 0);
+}
+
+/// Copies `data` into `bytes` at an offset chosen by `map` (see [`SectionMap::allocate`]), growing
+/// `bytes` if needed. Returns the offset the data was written to.
+fn write_allocated(bytes: &mut Vec<u8>, map: &mut SectionMap, data: &[u8], align: usize) -> usize {
+    let (offset, size) = map.allocate(data.len(), align);
+
+    bytes.resize(size.max(bytes.len()), 0);
+    bytes[offset..offset + data.len()].copy_from_slice(data);
+
+    offset
+}
+
+/// Matches `name` against a glob `pattern` made up of literal bytes, `*` and `?`.
+fn glob_match(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| glob_match(rest, &name[skip..])),
+        Some((b'?', rest)) => !name.is_empty() && glob_match(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && glob_match(rest, &name[1..]),
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct BNLFile {
     header: BNLHeader,
@@ -101,14 +422,33 @@ pub struct BNLFile {
     descriptor_bytes: Vec<u8>,
 
     asset_descriptions: Vec<AssetDescription>,
+    compression: Compression,
+    /// Maps every asset name to the index of its description. When a name appears more than once
+    /// the first description wins, matching a linear search.
+    name_index: HashMap<String, usize>,
+    /// The size of the file this was parsed from, or None if it was built in memory.
+    file_size: Option<usize>,
 }
 
 impl BNLFile {
+    /// Creates an empty, zlib compressed [`BNLFile`], ready for assets to be added with
+    /// [`BNLFile::add_asset`].
+    pub fn new() -> BNLFile {
+        let mut bnl = BNLFile::default();
+        bnl.relayout(true);
+
+        bnl
+    }
+
     /**
-    Parses a BNL file in memory, loading embedded [`AssetDescription`] data.
+    Parses a BNL file in memory, loading embedded [`AssetDescription`] data. Both the usual zlib
+    compressed files and the uncompressed variant are supported, see [`Compression`].
 
     # Errors
-    - [`BNLError::DecompressionFailure`] when the zlib compression section of the file could not be parsed
+    - [`BNLError::DecompressionFailure`] when the zlib compression section of the file could not be parsed,
+      or the file is too short to hold its sections uncompressed
+    - [`BNLError::SectionOutOfBounds`] when the header places a section outside of the decompressed data
+    - [`BNLError::Io`] when the file ends early
     - [`BNLError::DataReadError`] when any other part of the file could not be parsed
 
     # Examples
@@ -123,81 +463,146 @@ impl BNLFile {
     ```
     */
     pub fn from_bytes(bnl_bytes: &[u8]) -> Result<BNLFile, BNLError> {
-        let mut bytes = bnl_bytes[..40].to_vec();
+        let header = BNLHeader::from_bytes(bnl_bytes)?;
 
-        let mut cur = Cursor::new(bnl_bytes);
+        let (mut stream, compression) = header.body_stream(bnl_bytes)?;
 
-        let mut header = BNLHeader {
-            file_count: read!(cur, u16),
-            flags: read!(cur, u8),
-            ..Default::default()
-        };
+        // The asset descriptions can be parsed as soon as they have been inflated
+        let asset_descriptions = read_asset_descriptions(&header, &mut stream)?;
 
-        cur.read_exact(&mut header.unknown_2)?;
+        let sections = [
+            ("data view", header.buffer_views_loc),
+            ("buffer", header.buffer_loc),
+            ("descriptor", header.descriptor_loc),
+        ];
 
-        header.asset_desc_loc = DataView::from_cursor(&mut cur)?;
-        header.buffer_views_loc = DataView::from_cursor(&mut cur)?;
-        header.buffer_loc = DataView::from_cursor(&mut cur)?;
-        header.descriptor_loc = DataView::from_cursor(&mut cur)?;
+        let check_bounds = |available: usize| {
+            for (section, loc) in &sections {
+                if loc.offset as usize + loc.size as usize > available {
+                    return Err(BNLError::SectionOutOfBounds {
+                        section,
+                        offset: loc.offset,
+                        size: loc.size,
+                        available,
+                    });
+                }
+            }
+
+            Ok(())
+        };
+
+        let range = |loc: &DataView| loc.offset as usize..loc.offset as usize + loc.size as usize;
+
+        let mut by_offset = sections.map(|(_, loc)| range(&loc));
+        by_offset.sort_by_key(|range| range.start);
+
+        let [buffer_views_bytes, buffer_bytes, descriptor_bytes] = match by_offset
+            .windows(2)
+            .all(|pair| pair[0].end <= pair[1].start)
+        {
+            // Each section is inflated straight into its own buffer in the order they appear,
+            // so the whole decompressed file is never held in memory at once
+            true => {
+                let mut order = [0, 1, 2];
+                order.sort_by_key(|&i| sections[i].1.offset);
+
+                let mut section_bytes: [Vec<u8>; 3] = Default::default();
+
+                for i in order {
+                    match stream.take_range(range(&sections[i].1))? {
+                        Some(bytes) => section_bytes[i] = bytes,
+                        // Sections don't overlap, so this can only be one that ends past
+                        // the end of the file
+                        None => {
+                            check_bounds(stream.total_len()?)?;
+                            return Err(BNLError::DecompressionFailure);
+                        }
+                    }
+                }
 
-        let decompressed_bytes = miniz_oxide::inflate::decompress_to_vec_zlib(&bnl_bytes[40..])?;
-        bytes.extend_from_slice(&decompressed_bytes);
+                section_bytes
+            }
+            false => {
+                let bytes = stream.finish()?;
+                check_bounds(bytes.len())?;
 
-        // Need to to this so that bytes.extent_from_slice doesn't cause an immutable borrow error
-        cur = Cursor::new(&bytes);
+                sections.map(|(_, loc)| bytes[range(&loc)].to_vec())
+            }
+        };
 
         let mut new_bnl = BNLFile {
             header,
+            buffer_views_bytes,
+            buffer_bytes,
+            descriptor_bytes,
+            asset_descriptions,
+            compression,
+            file_size: Some(bnl_bytes.len()),
             ..Default::default()
         };
 
-        assert_eq!(size_of::<AssetDescription>(), 160);
+        new_bnl.rebuild_name_index();
 
-        let num_descriptions =
-            new_bnl.header.asset_desc_loc.size as usize / size_of::<AssetDescription>();
+        Ok(new_bnl)
+    }
 
-        cur.seek(SeekFrom::Start(new_bnl.header.asset_desc_loc.offset as u64))?;
+    /**
+    Memory maps the BNL file at `path` and parses it like [`BNLFile::from_bytes`], without first
+    reading the compressed file into memory. Each section is inflated straight into its own buffer
+    as the stream reaches it, so this keeps peak memory low when processing many bundles in one
+    run.
 
-        for _ in 0..num_descriptions {
-            let mut asset_name: AssetName = [0x00; 128];
+    # Errors
+    - [`BNLError::Io`] when the file can't be opened or mapped
+    - Any error returned by [`BNLFile::from_bytes`]
 
-            cur.read_exact(&mut asset_name)?;
+    # Examples
+    ```no_run
+    use std::path::Path;
 
-            // TODO: Rework this into an actual constructor
-            let asset_desc = AssetDescription {
-                name: asset_name,
-                asset_type: AssetType::try_from(read!(cur, u32)).map_err(|_| {
-                    BNLError::DataReadError("Unable to parse asset type from BNL.".to_string())
-                })?,
-                unk_1: read!(cur, u32),
-                unk_2: read!(cur, u32),
-                chunk_count: read!(cur, u32),
-                descriptor_ptr: read!(cur, u32),
-                descriptor_size: read!(cur, u32),
-                dataview_list_ptr: read!(cur, u32),
-                resource_size: read!(cur, u32),
-            };
+    use bnl::BNLFile;
 
-            // TODO: Resize this then push into it
-            new_bnl.asset_descriptions.push(asset_desc);
-        }
+    let bnl = BNLFile::open_mmap(Path::new("./my_bnl.bnl")).expect("Unable to parse BNL.");
+    ```
+    */
+    #[cfg(feature = "memmap2")]
+    pub fn open_mmap(path: &std::path::Path) -> Result<BNLFile, BNLError> {
+        let file = std::fs::File::open(path)?;
 
-        let loc = &new_bnl.header.buffer_views_loc;
-        cur.seek(SeekFrom::Start(loc.offset.into()))?;
-        new_bnl.buffer_views_bytes.resize(loc.size as usize, 0);
-        cur.read_exact(&mut new_bnl.buffer_views_bytes)?;
+        // SAFETY: The map is only read while parsing, and is dropped before returning. Like any
+        // memory map, it relies on the file not being truncated by another process meanwhile.
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
 
-        let loc = &new_bnl.header.buffer_loc;
-        cur.seek(SeekFrom::Start(loc.offset.into()))?;
-        new_bnl.buffer_bytes.resize(loc.size as usize, 0);
-        cur.read_exact(&mut new_bnl.buffer_bytes)?;
+        BNLFile::from_bytes(&mmap)
+    }
 
-        let loc = &new_bnl.header.descriptor_loc;
-        cur.seek(SeekFrom::Start(loc.offset.into()))?;
-        new_bnl.descriptor_bytes.resize(loc.size as usize, 0);
-        cur.read_exact(&mut new_bnl.descriptor_bytes)?;
+    /**
+    Parses only the [`AssetDescription`] table of a BNL file, inflating no more of the file than
+    is needed to reach it. This is much faster than [`BNLFile::from_bytes`] for operations that
+    only need to list assets, as the large buffer section is never decompressed.
 
-        Ok(new_bnl)
+    # Errors
+    - [`BNLError::DecompressionFailure`] when the zlib compression section of the file could not be parsed,
+      or the file is too short to hold its sections uncompressed
+    - [`BNLError::DataReadError`] when any other part of the file could not be parsed
+
+    # Examples
+    ```no_run
+    use bnl::BNLFile;
+
+    let bytes = std::fs::read("./my_bnl.bnl").expect("Unable to read BNL.");
+
+    for desc in BNLFile::read_asset_descriptions(&bytes).expect("Unable to parse BNL.") {
+        println!("{} ({:?})", desc.name(), desc.asset_type());
+    }
+    ```
+    */
+    pub fn read_asset_descriptions(bnl_bytes: &[u8]) -> Result<Vec<AssetDescription>, BNLError> {
+        let header = BNLHeader::from_bytes(bnl_bytes)?;
+
+        let (mut stream, _) = header.body_stream(bnl_bytes)?;
+
+        read_asset_descriptions(&header, &mut stream)
     }
 
     /// Retrieves an asset by name and type, creating it from the bytes of the BNL file.
@@ -217,43 +622,74 @@ impl BNLFile {
     ///                   .expect("Unable to get texture.");
     /// ```
     pub fn get_asset<A: Asset>(&self, name: &str) -> Result<A, AssetError> {
-        for asset_desc in &self.asset_descriptions {
-            if asset_desc.name() == name {
-                if asset_desc.asset_type() != A::asset_type() {
-                    return Err(AssetError::TypeMismatch);
-                }
+        let asset_desc = self.asset_description(name).ok_or(AssetError::NotFound)?;
+
+        if asset_desc.asset_type() != A::asset_type() {
+            return Err(AssetError::TypeMismatch {
+                name: name.to_string(),
+                expected: A::asset_type(),
+                found: asset_desc.asset_type(),
+            });
+        }
 
-                let descriptor_ptr: usize = asset_desc.descriptor_ptr() as usize;
-                let desc_slice = &self.descriptor_bytes[descriptor_ptr..];
+        self.load_asset(asset_desc)
+    }
 
-                let descriptor: A::Descriptor = A::Descriptor::from_bytes(desc_slice)?;
+    /// Returns an iterator over every asset of a given type in this [`BNLFile`], parsing each one
+    /// as it is reached. Assets that fail to parse are returned as errors alongside their name,
+    /// rather than being skipped.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use bnl::BNLFile;
+    /// use bnl::asset::{Asset, texture::Texture};
+    ///
+    /// let bytes = std::fs::read("./common.bnl").expect("Unable to read BNL.");
+    /// let bnl_file = BNLFile::from_bytes(&bytes).expect("Unable to parse BNL.");
+    ///
+    /// for (name, texture) in bnl_file.iter_assets::<Texture>() {
+    ///     match texture {
+    ///         Ok(texture) => println!("{}: {:?}", name, texture.descriptor().format()),
+    ///         Err(e) => eprintln!("Unable to load {}: {}", name, e),
+    ///     }
+    /// }
+    /// ```
+    pub fn iter_assets<A: Asset>(&self) -> impl Iterator<Item = (&str, Result<A, AssetError>)> {
+        self.asset_descriptions
+            .iter()
+            .filter(|asset_desc| asset_desc.asset_type() == A::asset_type())
+            .map(|asset_desc| (asset_desc.name(), self.load_asset(asset_desc)))
+    }
 
-                let dvl = self
-                    .get_dataview_list(asset_desc.dataview_list_ptr as usize)
-                    .map_err(|_| {
-                        AssetError::ParseError(AssetParseError::InvalidDataViews(
-                            "Unable to get data view list from BNL data.".to_string(),
-                        ))
-                    })?;
+    /// Creates an asset from its description, without checking its type.
+    fn load_asset<A: Asset>(&self, asset_desc: &AssetDescription) -> Result<A, AssetError> {
+        let desc_slice = self
+            .descriptor_bytes
+            .get(asset_desc.descriptor_ptr() as usize..)
+            .ok_or_else(|| {
+                AssetParseError::DescriptorOutOfBounds {
+                    offset: asset_desc.descriptor_ptr,
+                    size: asset_desc.descriptor_size,
+                }
+                .in_asset(asset_desc.name())
+            })?;
 
-                let virtual_res =
-                    VirtualResource::from_dvl(&dvl, &self.buffer_bytes).map_err(|e| {
-                        AssetError::ParseError(AssetParseError::InvalidDataViews(format!(
-                            "Unable to get data from data slices.\nError: {}",
-                            e
-                        )))
-                    })?;
+        let descriptor: A::Descriptor =
+            A::Descriptor::from_bytes(desc_slice).map_err(|e| e.in_asset(asset_desc.name()))?;
 
-                let asset = A::new(asset_desc.name(), &descriptor, &virtual_res)?;
+        let dvl = self.asset_dataview_list(asset_desc)?;
 
-                return Ok(asset);
-            }
-        }
+        let virtual_res = VirtualResource::from_dvl(&dvl, &self.buffer_bytes).map_err(|e| {
+            AssetParseError::InvalidDataViews(format!("Unable to get data from data slices: {}", e))
+                .in_asset(asset_desc.name())
+        })?;
 
-        Err(AssetError::NotFound)
+        A::new(asset_desc.name(), &descriptor, &virtual_res)
+            .map_err(|e| e.in_asset(asset_desc.name()))
     }
 
-    /// Returns all assets of a given type from this [`BNLFile`].
+    /// Returns all assets of a given type from this [`BNLFile`]. Assets that fail to parse are
+    /// skipped, see [`BNLFile::get_assets_with_errors`] to find out which.
     ///
     /// # Examples
     ///
@@ -267,53 +703,38 @@ impl BNLFile {
     /// // Dump all of the textures here
     /// ```
     pub fn get_assets<A: Asset>(&self) -> Vec<A> {
+        self.get_assets_with_errors().0
+    }
+
+    /// Returns all assets of a given type from this [`BNLFile`], along with the name and error of
+    /// every asset of that type that couldn't be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use bnl::BNLFile;
+    /// use bnl::asset::{Asset, texture::Texture};
+    ///
+    /// let bytes = std::fs::read("./common.bnl").expect("Unable to read BNL.");
+    /// let bnl_file = BNLFile::from_bytes(&bytes).expect("Unable to parse BNL.");
+    /// let (textures, errors) = bnl_file.get_assets_with_errors::<Texture>();
+    ///
+    /// for (name, e) in errors {
+    ///     eprintln!("Unable to load {}: {}", name, e);
+    /// }
+    /// ```
+    pub fn get_assets_with_errors<A: Asset>(&self) -> (Vec<A>, Vec<(String, AssetError)>) {
         let mut assets = Vec::new();
+        let mut errors = Vec::new();
 
-        for asset_desc in &self.asset_descriptions {
-            if asset_desc.asset_type() != A::asset_type() {
-                continue;
+        for (name, asset) in self.iter_assets() {
+            match asset {
+                Ok(asset) => assets.push(asset),
+                Err(e) => errors.push((name.to_string(), e)),
             }
-
-            let descriptor_ptr: usize = asset_desc.descriptor_ptr() as usize;
-            let desc_slice = &self.descriptor_bytes[descriptor_ptr..];
-
-            let descriptor: A::Descriptor = match A::Descriptor::from_bytes(desc_slice) {
-                Ok(d) => d,
-                Err(e) => {
-                    eprintln!(
-                        "Error getting asset descriptor for {}\nError: {}",
-                        asset_desc.name(),
-                        e
-                    );
-                    continue;
-                }
-            };
-
-            let dvl = match self.get_dataview_list(asset_desc.dataview_list_ptr as usize) {
-                Ok(dvl) => dvl,
-                Err(_) => {
-                    continue;
-                }
-            };
-
-            let virtual_res = match VirtualResource::from_dvl(&dvl, &self.buffer_bytes) {
-                Ok(res) => res,
-                Err(_) => {
-                    continue;
-                }
-            };
-
-            match A::new(asset_desc.name(), &descriptor, &virtual_res) {
-                Ok(a) => assets.push(a),
-                Err(e) => eprintln!(
-                    "Failed to load asset \"{}\"\n    Error: {}",
-                    asset_desc.name(),
-                    e
-                ),
-            };
         }
 
-        assets
+        (assets, errors)
     }
 
     /// Retrieves a [`RawAsset`] by name.
@@ -337,49 +758,70 @@ impl BNLFile {
     /// });
     /// ```
     pub fn get_raw_asset(&self, name: &str) -> Result<RawAsset, AssetError> {
-        for asset_desc in &self.asset_descriptions {
-            if asset_desc.name() == name {
-                let desc_ptr: usize = asset_desc.descriptor_ptr() as usize;
-                let desc_size: usize = asset_desc.descriptor_size as usize;
-
-                let desc_bytes: Vec<u8> =
-                    self.descriptor_bytes[desc_ptr..desc_ptr + desc_size].to_vec();
-
-                /*
-                    .map_err(|e| {
-                        AssetError::AssetParseError(AssetParseError::InvalidDataViews(
-                            "bruh".to_string(),
-                        ))
-                    })?;
-                */
+        let asset_desc = self.asset_description(name).ok_or(AssetError::NotFound)?;
 
-                let dvl = self
-                    .get_dataview_list(asset_desc.dataview_list_ptr as usize)
-                    .map_err(|_| {
-                        AssetError::ParseError(AssetParseError::InvalidDataViews(
-                            "Unable to get data view list from BNL data.".to_string(),
-                        ))
-                    })?;
-
-                let slices = dvl.slices(&self.buffer_bytes).map_err(|_| {
-                    AssetError::ParseError(AssetParseError::InvalidDataViews(
-                        "Unable to get data from data slices.".to_string(),
-                    ))
-                })?;
-
-                return Ok(RawAsset {
-                    name: asset_desc.name().to_string(),
-                    asset_type: asset_desc.asset_type,
-                    descriptor_bytes: desc_bytes,
-                    data_slices: slices.iter().map(|s| s.to_vec()).collect(),
-                });
-            }
-        }
+        self.load_raw_asset_ref(asset_desc)
+            .map(|raw_asset| raw_asset.to_raw_asset())
+    }
+
+    /// Retrieves a [`RawAssetRef`] by name, borrowing its descriptor and data slices from this
+    /// [`BNLFile`] rather than copying them like [`BNLFile::get_raw_asset`].
+    ///
+    /// # Errors
+    /// Returns an [`AssetError`] if the asset can not be parsed from the [`BNLFile`].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use bnl::BNLFile;
+    ///
+    /// let bytes = std::fs::read("./common.bnl").expect("Unable to read BNL.");
+    /// let bnl_file = BNLFile::from_bytes(&bytes).expect("Unable to parse BNL.");
+    /// let raw_asset = bnl_file.get_raw_asset_ref("aid_texture_mytexture_a_b")
+    ///                         .expect("Unable to get texture.");
+    ///
+    /// let size: usize = raw_asset.data_slices.iter().map(|slice| slice.len()).sum();
+    /// ```
+    pub fn get_raw_asset_ref(&self, name: &str) -> Result<RawAssetRef<'_>, AssetError> {
+        let asset_desc = self.asset_description(name).ok_or(AssetError::NotFound)?;
 
-        Err(AssetError::NotFound)
+        self.load_raw_asset_ref(asset_desc)
     }
 
-    /// Retrieves all [`RawAsset`] entries.
+    /// Returns an iterator over a [`RawAssetRef`] of every asset in this [`BNLFile`], alongside
+    /// its name. Nothing is copied, so this is the cheapest way to stream through every asset.
+    pub fn iter_raw_assets(
+        &self,
+    ) -> impl Iterator<Item = (&str, Result<RawAssetRef<'_>, AssetError>)> {
+        self.asset_descriptions
+            .iter()
+            .map(|asset_desc| (asset_desc.name(), self.load_raw_asset_ref(asset_desc)))
+    }
+
+    /// Borrows the descriptor and data slices of an asset from the BNL.
+    fn load_raw_asset_ref<'a>(
+        &'a self,
+        asset_desc: &'a AssetDescription,
+    ) -> Result<RawAssetRef<'a>, AssetError> {
+        let desc_bytes = self.asset_descriptor_bytes(asset_desc)?;
+
+        let dvl = self.asset_dataview_list(asset_desc)?;
+
+        let slices = dvl.slices(&self.buffer_bytes).map_err(|e| {
+            AssetParseError::InvalidDataViews(format!("Unable to get data from data slices: {}", e))
+                .in_asset(asset_desc.name())
+        })?;
+
+        Ok(RawAssetRef {
+            name: asset_desc.name(),
+            asset_type: asset_desc.asset_type,
+            descriptor_bytes: desc_bytes,
+            data_slices: slices,
+            dataview_list: dvl,
+        })
+    }
+
+    /// Retrieves all [`RawAsset`] entries. Assets that can't be retrieved are skipped, see
+    /// [`BNLFile::get_raw_assets_with_errors`] to find out which.
     ///
     /// # Examples
     /// ```
@@ -402,98 +844,868 @@ impl BNLFile {
     /// }
     /// ```
     pub fn get_raw_assets(&self) -> Vec<RawAsset> {
-        let mut assets = Vec::new();
-
-        let clo = |asset_desc: &AssetDescription| -> Result<RawAsset, AssetError> {
-            let desc_ptr: usize = asset_desc.descriptor_ptr() as usize;
-            let desc_size: usize = asset_desc.descriptor_size as usize;
-
-            let desc_bytes: Vec<u8> =
-                self.descriptor_bytes[desc_ptr..desc_ptr + desc_size].to_vec();
-
-            let dvl = self
-                .get_dataview_list(asset_desc.dataview_list_ptr as usize)
-                .map_err(|_| {
-                    AssetError::ParseError(AssetParseError::InvalidDataViews(
-                        "Unable to get data view list from BNL data.".to_string(),
-                    ))
-                })?;
-
-            let slices = dvl.slices(&self.buffer_bytes).map_err(|_| {
-                AssetError::ParseError(AssetParseError::InvalidDataViews(
-                    "Unable to get data from data slices.".to_string(),
-                ))
-            })?;
+        self.get_raw_assets_with_errors().0
+    }
 
-            Ok(RawAsset {
-                name: asset_desc.name().to_string(),
-                asset_type: asset_desc.asset_type,
-                descriptor_bytes: desc_bytes,
-                data_slices: slices.iter().map(|s| s.to_vec()).collect(),
-            })
-        };
+    /// Retrieves all [`RawAsset`] entries, along with the name and error of every asset that
+    /// couldn't be retrieved.
+    pub fn get_raw_assets_with_errors(&self) -> (Vec<RawAsset>, Vec<(String, AssetError)>) {
+        let mut assets = Vec::new();
+        let mut errors = Vec::new();
 
-        for asset_desc in &self.asset_descriptions {
-            match clo(asset_desc) {
-                Ok(asset) => {
-                    assets.push(asset);
-                }
-                Err(e) => {
-                    eprintln!(
-                        "Error retrieving RawAsset for {}.\nError: {}",
-                        asset_desc.name(),
-                        e
-                    );
-                }
+        for (name, asset) in self.iter_raw_assets() {
+            match asset {
+                Ok(asset) => assets.push(asset.to_raw_asset()),
+                Err(e) => errors.push((name.to_string(), e)),
             }
         }
 
-        assets
+        (assets, errors)
     }
 
-    /// Returns a reference to the asset descriptions of this [`BNLFile`].
-    pub fn asset_descriptions(&self) -> &[AssetDescription] {
-        &self.asset_descriptions
-    }
+    /// Overwrites the descriptor and resource data of an existing asset with the contents of a
+    /// [`RawAsset`]. The sizes of the descriptor and of the combined data slices must match the
+    /// existing asset, as nothing in the BNL is moved.
+    ///
+    /// # Errors
+    /// - [`AssetError::NotFound`] when the given name can't be found
+    /// - [`AssetError::TypeMismatch`] when the [`RawAsset`] type doesn't match the existing asset
+    /// - [`AssetError::SizeMismatch`] when the new data is a different size to the existing data,
+    ///   use [`BNLFile::replace_asset`] to resize it
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use bnl::BNLFile;
+    ///
+    /// let bytes = std::fs::read("./common.bnl").expect("Unable to read BNL.");
+    /// let mut bnl_file = BNLFile::from_bytes(&bytes).expect("Unable to parse BNL.");
+    /// let mut raw_asset = bnl_file.get_raw_asset("aid_texture_mytexture_a_b")
+    ///                             .expect("Unable to get texture.");
+    ///
+    /// raw_asset.data_slices[0].fill(0xff);
+    ///
+    /// bnl_file.update_asset("aid_texture_mytexture_a_b", &raw_asset)
+    ///         .expect("Unable to update texture.");
+    /// ```
+    pub fn update_asset(&mut self, name: &str, asset: &RawAsset) -> Result<(), AssetError> {
+        let asset_desc = self.asset_description(name).ok_or(AssetError::NotFound)?;
+
+        if asset_desc.asset_type() != asset.asset_type {
+            return Err(AssetError::TypeMismatch {
+                name: name.to_string(),
+                expected: asset_desc.asset_type(),
+                found: asset.asset_type,
+            });
+        }
 
-    fn get_dataview_list(&self, offset: usize) -> Result<DataViewList, Box<dyn Error>> {
-        Ok(DataViewList::from_bytes(
-            &self.buffer_views_bytes[offset..],
-        )?)
-    }
-}
+        let desc_ptr = asset_desc.descriptor_ptr() as usize;
+        let desc_size = asset_desc.descriptor_size() as usize;
 
-#[derive(Debug)]
-pub(crate) struct VirtualResource<'a> {
-    slices: Vec<&'a [u8]>,
-}
+        if asset.descriptor_bytes.len() != desc_size {
+            return Err(AssetError::SizeMismatch);
+        }
 
-#[derive(Debug)]
-enum VirtualResourceError {
-    OffsetOutOfBounds,
-    SizeOutOfBounds,
-}
+        // Checked up front, so that nothing is written if the descriptor is out of bounds
+        self.asset_descriptor_bytes(asset_desc)?;
 
-impl Display for VirtualResourceError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
-    }
-}
+        let dvl = self.asset_dataview_list(asset_desc)?;
 
-impl VirtualResource<'_> {
-    pub(crate) fn from_dvl<'a>(
-        dataview_list: &DataViewList,
-        bytes: &'a [u8],
-    ) -> Result<VirtualResource<'a>, VirtualResourceError> {
-        let views = dataview_list.views();
+        let resource_bytes = asset.data_slices.concat();
 
-        let mut slices = Vec::new();
+        dvl.write_bytes(&mut self.buffer_bytes, &resource_bytes)
+            .map_err(|_| AssetError::SizeMismatch)?;
 
-        for view in views {
-            let offset = view.offset as usize;
-            let size = view.size as usize;
+        self.descriptor_bytes[desc_ptr..desc_ptr + desc_size]
+            .copy_from_slice(&asset.descriptor_bytes);
 
-            if offset > bytes.len() {
+        Ok(())
+    }
+
+    /// Overwrites part of the resource data of the asset `name`, starting `offset` bytes into the
+    /// resource as returned by [`BNLFile::get_raw_asset`] (with its data slices joined together).
+    /// Unlike [`BNLFile::update_asset`], only the bytes given are written.
+    ///
+    /// # Errors
+    /// - [`AssetError::NotFound`] when the given name can't be found
+    /// - [`AssetError::SizeMismatch`] when the bytes don't fit in the resource at `offset`
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # fn main() -> Result<(), bnl::asset::AssetError> {
+    /// # let mut bnl_file = bnl::BNLFile::new();
+    /// # let (mip_offset, new_mip) = (0x4000, vec![0; 0x1000]);
+    /// // Replace one mip level of a texture, leaving the rest untouched
+    /// bnl_file.write_asset_bytes_at("aid_texture_gribble", mip_offset, &new_mip)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_asset_bytes_at(
+        &mut self,
+        name: &str,
+        offset: usize,
+        bytes: &[u8],
+    ) -> Result<(), AssetError> {
+        let asset_desc = self.asset_description(name).ok_or(AssetError::NotFound)?;
+        let dvl = self.asset_dataview_list(asset_desc)?;
+
+        dvl.write_bytes_at(&mut self.buffer_bytes, offset, bytes)
+            .map_err(|_| AssetError::SizeMismatch)
+    }
+
+    /// Duplicates the asset `src_name` as a new asset called `new_name`. The descriptor, data view
+    /// list and resource data are all copied wherever they fit in their sections (see
+    /// [`BNLFile::space_report`]), so the clone can be modified with [`BNLFile::update_asset`]
+    /// without affecting the original.
+    ///
+    /// # Errors
+    /// - [`AssetError::NotFound`] if `src_name` doesn't exist
+    /// - [`AssetError::AlreadyExists`] if `new_name` is already taken
+    /// - [`AssetError::InvalidName`] if `new_name` isn't a valid [`asset::id::AssetId`]
+    /// - [`AssetError::BundleFull`] if the file already holds the most assets it can
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use bnl::BNLFile;
+    ///
+    /// let bytes = std::fs::read("./common.bnl").expect("Unable to read BNL.");
+    /// let mut bnl_file = BNLFile::from_bytes(&bytes).expect("Unable to parse BNL.");
+    ///
+    /// bnl_file.clone_asset("aid_actorattribs_imp", "aid_actorattribs_imp_tough")
+    ///         .expect("Unable to clone asset.");
+    /// ```
+    pub fn clone_asset(&mut self, src_name: &str, new_name: &str) -> Result<(), AssetError> {
+        let mut raw_asset = self.get_raw_asset(src_name)?;
+        raw_asset.name = new_name.to_string();
+
+        let src_desc = self
+            .asset_description(src_name)
+            .ok_or(AssetError::NotFound)?;
+        let (unk_1, unk_2) = (src_desc.unk_1, src_desc.unk_2);
+
+        self.push_asset(&raw_asset, unk_1, unk_2)
+    }
+
+    /// Adds a brand new asset to the end of this [`BNLFile`], with its descriptor, data view list
+    /// and resource data placed wherever they fit in their sections (see
+    /// [`BNLFile::space_report`]). Each data slice of the [`RawAsset`] gets its own data view.
+    ///
+    /// # Errors
+    /// - [`AssetError::AlreadyExists`] if an asset already has the same name
+    /// - [`AssetError::InvalidName`] if the name isn't a valid [`asset::id::AssetId`]
+    /// - [`AssetError::BundleFull`] if the file already holds the most assets it can
+    ///
+    /// # Examples
+    /// ```
+    /// use bnl::BNLFile;
+    ///
+    /// # let raw_assets: Vec<bnl::asset::RawAsset> = Vec::new();
+    /// let mut bnl_file = BNLFile::new();
+    ///
+    /// for raw_asset in &raw_assets {
+    ///     bnl_file.add_asset(raw_asset).expect("Unable to add asset.");
+    /// }
+    ///
+    /// let bytes = bnl_file.to_bytes().expect("Unable to serialise BNL.");
+    /// ```
+    pub fn add_asset(&mut self, raw_asset: &RawAsset) -> Result<(), AssetError> {
+        self.push_asset(raw_asset, 0, 0)
+    }
+
+    /// Appends an asset, see [`BNLFile::add_asset`]. The unknown fields of its description are
+    /// given separately, as they aren't part of a [`RawAsset`].
+    pub(crate) fn push_asset(
+        &mut self,
+        raw_asset: &RawAsset,
+        unk_1: u32,
+        unk_2: u32,
+    ) -> Result<(), AssetError> {
+        if self.name_index.contains_key(&raw_asset.name) {
+            return Err(AssetError::AlreadyExists);
+        }
+
+        let name = raw_asset
+            .id()
+            .map_err(|_| AssetError::InvalidName)?
+            .to_bytes();
+
+        constraints::check_asset_count(self.asset_descriptions.len() + 1)
+            .map_err(|_| AssetError::BundleFull)?;
+
+        let mut space = self.space_report();
+        let (descriptor_ptr, dataview_list_ptr) = self.write_asset_data(&mut space, raw_asset);
+
+        let new_desc = AssetDescription {
+            name,
+            asset_type: raw_asset.asset_type,
+            unk_1,
+            unk_2,
+            chunk_count: raw_asset.data_slices.len() as u32,
+            descriptor_ptr,
+            descriptor_size: raw_asset.descriptor_bytes.len() as u32,
+            dataview_list_ptr,
+            resource_size: raw_asset.data_slices.iter().map(Vec::len).sum::<usize>() as u32,
+        };
+
+        self.name_index
+            .insert(raw_asset.name.clone(), self.asset_descriptions.len());
+        self.asset_descriptions.push(new_desc);
+        self.relayout(false);
+
+        Ok(())
+    }
+
+    /// Replaces the descriptor and resource data of the asset `name` with those of `asset`, which
+    /// may be any size. Unlike [`BNLFile::update_asset`], the new data is written wherever it
+    /// fits (see [`BNLFile::space_report`]), along with a new data view list, and the ranges the
+    /// asset used before are left free for later assets.
+    ///
+    /// # Errors
+    /// - [`AssetError::NotFound`] when the given name can't be found
+    /// - [`AssetError::TypeMismatch`] when the [`RawAsset`] type doesn't match the existing asset
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bnl::asset::texture::{Image, Texture};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut bnl_file = bnl::BNLFile::new();
+    /// # let pixels = vec![0; 512 * 512 * 4];
+    /// let image = Image::from_rgba8(512, 512, pixels)?;
+    /// let texture = Texture::import("aid_texture_gribble", &image, &Default::default())?;
+    ///
+    /// bnl_file.replace_asset("aid_texture_gribble", &texture.to_raw_asset()?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn replace_asset(&mut self, name: &str, asset: &RawAsset) -> Result<(), AssetError> {
+        let index = *self.name_index.get(name).ok_or(AssetError::NotFound)?;
+        let asset_desc = &self.asset_descriptions[index];
+
+        if asset_desc.asset_type() != asset.asset_type {
+            return Err(AssetError::TypeMismatch {
+                name: name.to_string(),
+                expected: asset_desc.asset_type(),
+                found: asset.asset_type,
+            });
+        }
+
+        // The asset's own ranges count as free, so the new data can reuse them
+        let [descriptor_ranges, dvl_ranges, buffer_ranges] = self.used_ranges(Some(index));
+
+        let mut space = SpaceReport {
+            descriptor: SectionMap::from_ranges(self.descriptor_bytes.len(), descriptor_ranges),
+            buffer_views: SectionMap::from_ranges(self.buffer_views_bytes.len(), dvl_ranges),
+            buffer: SectionMap::from_ranges(self.buffer_bytes.len(), buffer_ranges),
+        };
+
+        let (descriptor_ptr, dataview_list_ptr) = self.write_asset_data(&mut space, asset);
+
+        let asset_desc = &mut self.asset_descriptions[index];
+        asset_desc.chunk_count = asset.data_slices.len() as u32;
+        asset_desc.descriptor_ptr = descriptor_ptr;
+        asset_desc.descriptor_size = asset.descriptor_bytes.len() as u32;
+        asset_desc.dataview_list_ptr = dataview_list_ptr;
+        asset_desc.resource_size = asset.data_slices.iter().map(Vec::len).sum::<usize>() as u32;
+
+        self.relayout(false);
+
+        Ok(())
+    }
+
+    /// Replaces the resource data of the asset `name` with `bytes`, which may be a different size
+    /// to the existing data, keeping its descriptor. The data is stored as a single data view, see
+    /// [`BNLFile::replace_asset`].
+    ///
+    /// # Errors
+    /// - [`AssetError::NotFound`] when the given name can't be found
+    pub fn replace_asset_data(&mut self, name: &str, bytes: &[u8]) -> Result<(), AssetError> {
+        let asset_desc = self.asset_description(name).ok_or(AssetError::NotFound)?;
+
+        let asset = RawAsset {
+            name: name.to_string(),
+            asset_type: asset_desc.asset_type(),
+            descriptor_bytes: self.asset_descriptor_bytes(asset_desc)?.to_vec(),
+            data_slices: vec![bytes.to_vec()],
+        };
+
+        self.replace_asset(name, &asset)
+    }
+
+    /// Writes the descriptor, resource data and a new data view list for `raw_asset` into the
+    /// space given by `space`, returning the descriptor and data view list pointers.
+    ///
+    /// New data goes into the smallest unused gap that fits, and otherwise at the end of its
+    /// section. It is kept aligned, as the game may rely on the alignment of the originals.
+    fn write_asset_data(&mut self, space: &mut SpaceReport, raw_asset: &RawAsset) -> (u32, u32) {
+        let descriptor_ptr = write_allocated(
+            &mut self.descriptor_bytes,
+            &mut space.descriptor,
+            &raw_asset.descriptor_bytes,
+            4,
+        ) as u32;
+
+        let mut dvl = DataViewList::default();
+
+        for slice in &raw_asset.data_slices {
+            let offset = write_allocated(&mut self.buffer_bytes, &mut space.buffer, slice, 16);
+
+            dvl.push_view(DataView::new(offset as u32, slice.len() as u32));
+        }
+
+        let dataview_list_ptr = write_allocated(
+            &mut self.buffer_views_bytes,
+            &mut space.buffer_views,
+            &dvl.to_bytes(),
+            4,
+        ) as u32;
+
+        (descriptor_ptr, dataview_list_ptr)
+    }
+
+    /// Updates the header after sections have been resized, moving sections further into the
+    /// file where they would otherwise overlap. The order of the sections is kept. If `compact`
+    /// is set every section is moved directly after the previous one, otherwise sections that
+    /// still fit are left where they are.
+    fn relayout(&mut self, compact: bool) {
+        self.header.file_count = self.asset_descriptions.len() as u16;
+
+        self.header.asset_desc_loc.size =
+            (self.asset_descriptions.len() * size_of::<AssetDescription>()) as u32;
+        self.header.buffer_views_loc.size = self.buffer_views_bytes.len() as u32;
+        self.header.buffer_loc.size = self.buffer_bytes.len() as u32;
+        self.header.descriptor_loc.size = self.descriptor_bytes.len() as u32;
+
+        let mut locs = [
+            &mut self.header.asset_desc_loc,
+            &mut self.header.buffer_views_loc,
+            &mut self.header.buffer_loc,
+            &mut self.header.descriptor_loc,
+        ];
+
+        locs.sort_by_key(|loc| loc.offset);
+
+        let mut end = BNL_HEADER_SIZE as u32;
+
+        for loc in locs {
+            loc.offset = match compact {
+                true => end,
+                false => loc.offset.max(end),
+            };
+            end = loc.offset + loc.size;
+        }
+    }
+
+    /// Removes every asset for which `keep` returns false, and rebuilds the descriptor, data view
+    /// list and buffer sections so that none of the removed data is left behind. Data shared
+    /// between the remaining assets stays shared. Returns the number of assets removed.
+    ///
+    /// # Errors
+    /// - [`BNLError::DataReadError`] if the data of a remaining asset can't be read, in which case
+    ///   this file is left unchanged
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use bnl::BNLFile;
+    ///
+    /// let bytes = std::fs::read("./common.bnl").expect("Unable to read BNL.");
+    /// let mut bnl_file = BNLFile::from_bytes(&bytes).expect("Unable to parse BNL.");
+    ///
+    /// let removed = bnl_file.retain_assets(|desc| !desc.name().starts_with("aid_texture_debug"))
+    ///                       .expect("Unable to remove assets.");
+    /// ```
+    pub fn retain_assets(
+        &mut self,
+        mut keep: impl FnMut(&AssetDescription) -> bool,
+    ) -> Result<usize, BNLError> {
+        let mut kept: Vec<AssetDescription> = self
+            .asset_descriptions
+            .iter()
+            .filter(|desc| keep(desc))
+            .cloned()
+            .collect();
+
+        let removed = self.asset_descriptions.len() - kept.len();
+
+        let mut descriptor_bytes = Vec::new();
+        let mut buffer_views_bytes = Vec::new();
+        let mut buffer_bytes = Vec::new();
+
+        // Maps the old locations of data to their new locations, so shared data is copied once
+        let mut descriptors: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut dvls: HashMap<u32, u32> = HashMap::new();
+        let mut views: HashMap<(u32, u32), u32> = HashMap::new();
+
+        for desc in &mut kept {
+            let key = (desc.descriptor_ptr, desc.descriptor_size);
+
+            desc.descriptor_ptr = match descriptors.get(&key) {
+                Some(ptr) => *ptr,
+                None => {
+                    let start = desc.descriptor_ptr as usize;
+
+                    let bytes = self
+                        .descriptor_bytes
+                        .get(start..start + desc.descriptor_size as usize)
+                        .ok_or_else(|| {
+                            BNLError::DataReadError(format!(
+                                "Descriptor of {} is out of bounds",
+                                desc.name()
+                            ))
+                        })?;
+
+                    pad_to(&mut descriptor_bytes, 4);
+                    let ptr = descriptor_bytes.len() as u32;
+                    descriptor_bytes.extend_from_slice(bytes);
+
+                    descriptors.insert(key, ptr);
+                    ptr
+                }
+            };
+
+            desc.dataview_list_ptr = match dvls.get(&desc.dataview_list_ptr) {
+                Some(ptr) => *ptr,
+                None => {
+                    let dvl = self
+                        .get_dataview_list(desc.dataview_list_ptr as usize)
+                        .map_err(|e| {
+                            BNLError::DataReadError(format!(
+                                "Unable to read the data view list of {}: {}",
+                                desc.name(),
+                                e
+                            ))
+                        })?;
+
+                    pad_to(&mut buffer_views_bytes, 4);
+                    let ptr = buffer_views_bytes.len() as u32;
+
+                    let dvl_size = 8 + size_of_val(dvl.views());
+                    buffer_views_bytes.extend_from_slice(&(dvl_size as u32).to_le_bytes());
+                    buffer_views_bytes.extend_from_slice(&(dvl.views().len() as u32).to_le_bytes());
+
+                    for view in dvl.views() {
+                        let offset = match views.get(&(view.offset, view.size)) {
+                            Some(offset) => *offset,
+                            None => {
+                                let start = view.offset as usize;
+
+                                let bytes = self
+                                    .buffer_bytes
+                                    .get(start..start + view.size as usize)
+                                    .ok_or_else(|| {
+                                        BNLError::DataReadError(format!(
+                                            "Data view of {} is out of bounds",
+                                            desc.name()
+                                        ))
+                                    })?;
+
+                                pad_to(&mut buffer_bytes, 16);
+                                let offset = buffer_bytes.len() as u32;
+                                buffer_bytes.extend_from_slice(bytes);
+
+                                views.insert((view.offset, view.size), offset);
+                                offset
+                            }
+                        };
+
+                        buffer_views_bytes.extend_from_slice(&offset.to_le_bytes());
+                        buffer_views_bytes.extend_from_slice(&view.size.to_le_bytes());
+                    }
+
+                    dvls.insert(desc.dataview_list_ptr, ptr);
+                    ptr
+                }
+            };
+        }
+
+        self.asset_descriptions = kept;
+        self.rebuild_name_index();
+        self.descriptor_bytes = descriptor_bytes;
+        self.buffer_views_bytes = buffer_views_bytes;
+        self.buffer_bytes = buffer_bytes;
+
+        self.relayout(true);
+
+        Ok(removed)
+    }
+
+    /// Removes every asset of the given types, for example to strip the cutscenes and soundbanks
+    /// out of a bundle for faster testing. See [`BNLFile::retain_assets`].
+    pub fn remove_asset_types(&mut self, asset_types: &[AssetType]) -> Result<usize, BNLError> {
+        self.retain_assets(|desc| !asset_types.contains(&desc.asset_type()))
+    }
+
+    /**
+    Serialises this [`BNLFile`] back into the on-disk BNL format, compressing everything after
+    the header. See [`BNLFile::to_bytes_with_options`] to choose how it is compressed.
+
+    # Errors
+    - [`BNLError::DataWriteError`] when a section no longer fits in the location given by the header
+
+    # Examples
+    ```no_run
+    use bnl::BNLFile;
+
+    let bytes = std::fs::read("./my_bnl.bnl").expect("Unable to read BNL.");
+    let bnl = BNLFile::from_bytes(&bytes).expect("Unable to parse BNL.");
+    let bytes = bnl.to_bytes().expect("Unable to serialise BNL.");
+
+    std::fs::write("./my_bnl.bnl", &bytes).expect("Unable to write BNL.");
+    ```
+    */
+    pub fn to_bytes(&self) -> Result<Vec<u8>, BNLError> {
+        self.to_bytes_with_options(&SerializeOptions::default())
+            .map(|(bytes, _)| bytes)
+    }
+
+    /// Summarises the unused bytes of the descriptor, data view list and buffer sections of this
+    /// [`BNLFile`]. Bytes count as used if any asset references them, so tools can check whether
+    /// a larger replacement would fit in a section without rebuilding the file.
+    ///
+    /// Assets whose data view list can't be read only count their descriptor as used.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use bnl::BNLFile;
+    ///
+    /// let bytes = std::fs::read("./common.bnl").expect("Unable to read BNL.");
+    /// let bnl_file = BNLFile::from_bytes(&bytes).expect("Unable to parse BNL.");
+    /// let free_space = bnl_file.free_space();
+    ///
+    /// # let new_texture_size = 0x10000;
+    /// if !free_space.buffer.fits(new_texture_size) {
+    ///     println!("The new texture needs a rebuild of the BNL file.");
+    /// }
+    /// ```
+    pub fn free_space(&self) -> FreeSpace {
+        let report = self.space_report();
+
+        FreeSpace {
+            descriptor: SectionSpace::from_map(&report.descriptor),
+            buffer_views: SectionSpace::from_map(&report.buffer_views),
+            buffer: SectionSpace::from_map(&report.buffer),
+        }
+    }
+
+    /// Maps out the free byte ranges of the descriptor, data view list and buffer sections of
+    /// this [`BNLFile`], using the same rules as [`BNLFile::free_space`]. Bytes that no asset
+    /// references any more, such as the old data of a resized asset, show up as free.
+    ///
+    /// # Examples
+    /// ```
+    /// # let bnl_file = bnl::BNLFile::new();
+    /// let report = bnl_file.space_report();
+    ///
+    /// for range in report.buffer.free_ranges() {
+    ///     println!("{:#x}..{:#x} is unused", range.start, range.end);
+    /// }
+    /// ```
+    pub fn space_report(&self) -> SpaceReport {
+        let [descriptor_ranges, dvl_ranges, buffer_ranges] = self.used_ranges(None);
+
+        SpaceReport {
+            descriptor: SectionMap::from_ranges(self.descriptor_bytes.len(), descriptor_ranges),
+            buffer_views: SectionMap::from_ranges(self.buffer_views_bytes.len(), dvl_ranges),
+            buffer: SectionMap::from_ranges(self.buffer_bytes.len(), buffer_ranges),
+        }
+    }
+
+    /// Returns the byte ranges referenced by every asset in the descriptor, data view list and
+    /// buffer sections, in that order, leaving out the asset at index `skip` if given. Assets
+    /// whose data view list can't be read only count their descriptor.
+    fn used_ranges(&self, skip: Option<usize>) -> [Vec<Range<usize>>; 3] {
+        let mut descriptor_ranges = Vec::new();
+        let mut dvl_ranges = Vec::new();
+        let mut buffer_ranges = Vec::new();
+
+        for (i, asset_desc) in self.asset_descriptions.iter().enumerate() {
+            if skip == Some(i) {
+                continue;
+            }
+
+            let desc_ptr = asset_desc.descriptor_ptr as usize;
+            descriptor_ranges.push(desc_ptr..desc_ptr + asset_desc.descriptor_size as usize);
+
+            let Ok(dvl) = self.get_dataview_list(asset_desc.dataview_list_ptr as usize) else {
+                continue;
+            };
+
+            let dvl_ptr = asset_desc.dataview_list_ptr as usize;
+            dvl_ranges.push(dvl_ptr..dvl_ptr + dvl.size() as usize);
+
+            buffer_ranges.extend(dvl.views().iter().map(|view| {
+                let offset = view.offset as usize;
+                offset..offset + view.size as usize
+            }));
+        }
+
+        [descriptor_ranges, dvl_ranges, buffer_ranges]
+    }
+
+    /// Checks the resource size of every asset against the combined size of its data views,
+    /// returning a violation for each asset that doesn't match. Assets whose data view list can't
+    /// be read are skipped.
+    pub fn check_resource_sizes(&self) -> Vec<ConstraintViolation> {
+        self.asset_descriptions
+            .iter()
+            .filter_map(|desc| {
+                let dvl = self
+                    .get_dataview_list(desc.dataview_list_ptr as usize)
+                    .ok()?;
+
+                constraints::check_resource_size(desc.name(), desc.resource_size, dvl.total_size())
+                    .err()
+            })
+            .collect()
+    }
+
+    /// Sets the resource size of every asset to the combined size of its data views, fixing any
+    /// reported by [`BNLFile::check_resource_sizes`]. Returns the number of assets changed.
+    pub fn recompute_resource_sizes(&mut self) -> usize {
+        let mut changed = 0;
+
+        for index in 0..self.asset_descriptions.len() {
+            let dvl_ptr = self.asset_descriptions[index].dataview_list_ptr as usize;
+
+            let Ok(dvl) = self.get_dataview_list(dvl_ptr) else {
+                continue;
+            };
+
+            let desc = &mut self.asset_descriptions[index];
+
+            if desc.resource_size != dvl.total_size() {
+                desc.resource_size = dvl.total_size();
+                changed += 1;
+            }
+        }
+
+        changed
+    }
+
+    /// Checks the chunk count of every asset against the number of views in its data view list,
+    /// returning a violation for each asset that doesn't match. Assets whose data view list can't
+    /// be read are skipped.
+    pub fn check_chunk_counts(&self) -> Vec<ConstraintViolation> {
+        self.asset_descriptions
+            .iter()
+            .filter_map(|desc| {
+                let dvl = self
+                    .get_dataview_list(desc.dataview_list_ptr as usize)
+                    .ok()?;
+
+                constraints::check_chunk_count(desc.name(), desc.chunk_count, dvl.num_views()).err()
+            })
+            .collect()
+    }
+
+    /// Sets the chunk count of every asset to the number of views in its data view list, fixing
+    /// any reported by [`BNLFile::check_chunk_counts`]. Returns the number of assets changed.
+    pub fn recompute_chunk_counts(&mut self) -> usize {
+        let mut changed = 0;
+
+        for index in 0..self.asset_descriptions.len() {
+            let dvl_ptr = self.asset_descriptions[index].dataview_list_ptr as usize;
+
+            let Ok(dvl) = self.get_dataview_list(dvl_ptr) else {
+                continue;
+            };
+
+            let desc = &mut self.asset_descriptions[index];
+
+            if desc.chunk_count != dvl.num_views() {
+                desc.chunk_count = dvl.num_views();
+                changed += 1;
+            }
+        }
+
+        changed
+    }
+
+    /// Returns how the body of this [`BNLFile`] was stored when it was parsed, which
+    /// [`BNLFile::to_bytes`] keeps when writing it back out.
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    /// Returns the flags from the header of this [`BNLFile`].
+    pub fn flags(&self) -> BNLFlags {
+        self.header.flags
+    }
+
+    /// Returns every part of the header of this [`BNLFile`] that isn't understood, see
+    /// [`HeaderWarning`].
+    pub fn header_warnings(&self) -> Vec<HeaderWarning> {
+        self.header.warnings()
+    }
+
+    /// Returns a reference to the asset descriptions of this [`BNLFile`].
+    pub fn asset_descriptions(&self) -> &[AssetDescription] {
+        &self.asset_descriptions
+    }
+
+    /// Finds the description of an asset by its exact name.
+    pub fn asset_description(&self, name: &str) -> Option<&AssetDescription> {
+        self.name_index
+            .get(name)
+            .map(|&index| &self.asset_descriptions[index])
+    }
+
+    /// Rebuilds the name lookup of [`BNLFile::asset_description`] after the asset descriptions
+    /// have been replaced.
+    fn rebuild_name_index(&mut self) {
+        self.name_index.clear();
+
+        for (index, desc) in self.asset_descriptions.iter().enumerate() {
+            self.name_index
+                .entry(desc.name().to_string())
+                .or_insert(index);
+        }
+    }
+
+    /// Returns the description of every asset whose name matches a glob `pattern`, where `*`
+    /// matches any run of characters and `?` matches any single character.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bnl::BNLFile;
+    /// let bytes = std::fs::read("./common.bnl").expect("Unable to read BNL.");
+    /// let bnl_file = BNLFile::from_bytes(&bytes).expect("Unable to parse BNL.");
+    ///
+    /// for desc in bnl_file.find_assets("aid_texture_gribble_*") {
+    ///     println!("{}", desc.name());
+    /// }
+    /// ```
+    pub fn find_assets(&self, pattern: &str) -> Vec<&AssetDescription> {
+        self.asset_descriptions
+            .iter()
+            .filter(|desc| glob_match(pattern.as_bytes(), desc.name().as_bytes()))
+            .collect()
+    }
+
+    /// Returns the description of every asset whose name matches `regex`.
+    #[cfg(feature = "regex")]
+    pub fn find_assets_regex(&self, regex: &regex::Regex) -> Vec<&AssetDescription> {
+        self.asset_descriptions
+            .iter()
+            .filter(|desc| regex.is_match(desc.name()))
+            .collect()
+    }
+
+    /// Finds the description of an asset by name, ignoring ASCII case.
+    pub fn find_asset_ignore_case(&self, name: &str) -> Option<&AssetDescription> {
+        self.asset_descriptions
+            .iter()
+            .find(|desc| desc.name().eq_ignore_ascii_case(name))
+    }
+
+    fn get_dataview_list(&self, offset: usize) -> Result<DataViewList, std::io::Error> {
+        let bytes = self.buffer_views_bytes.get(offset..).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "Data view list is out of bounds of the data view section",
+            )
+        })?;
+
+        DataViewList::from_bytes(bytes).map_err(|e| *e)
+    }
+
+    /// Returns the descriptor of an asset, see [`AssetParseError::DescriptorOutOfBounds`].
+    fn asset_descriptor_bytes(&self, asset_desc: &AssetDescription) -> Result<&[u8], AssetError> {
+        let start = asset_desc.descriptor_ptr as usize;
+
+        self.descriptor_bytes
+            .get(start..start + asset_desc.descriptor_size as usize)
+            .ok_or_else(|| {
+                AssetParseError::DescriptorOutOfBounds {
+                    offset: asset_desc.descriptor_ptr,
+                    size: asset_desc.descriptor_size,
+                }
+                .in_asset(asset_desc.name())
+            })
+    }
+
+    /// Reads the data view list of an asset, see [`AssetError::InvalidDataViewList`].
+    fn asset_dataview_list(
+        &self,
+        asset_desc: &AssetDescription,
+    ) -> Result<DataViewList, AssetError> {
+        self.get_dataview_list(asset_desc.dataview_list_ptr as usize)
+            .map_err(|source| AssetError::InvalidDataViewList {
+                name: asset_desc.name().to_string(),
+                offset: asset_desc.dataview_list_ptr,
+                source,
+            })
+    }
+}
+
+/// The resource data of an asset, split across the data views that hold it. The views are read
+/// as one contiguous resource, either by copying ranges out with [`VirtualResource::get_bytes`],
+/// or through its [`Read`] and [`Seek`] implementations, which copy only what is read.
+///
+/// # Examples
+/// ```
+/// use std::io::{Read, Seek, SeekFrom};
+///
+/// use bnl::VirtualResource;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let mut resource = VirtualResource::from_slices(&[&[0, 1, 2], &[3, 4]]);
+/// resource.seek(SeekFrom::Start(2))?;
+///
+/// let mut bytes = [0; 2];
+/// resource.read_exact(&mut bytes)?;
+/// assert_eq!(bytes, [2, 3]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct VirtualResource<'a> {
+    slices: Vec<&'a [u8]>,
+    /// The position of the [`Read`] and [`Seek`] implementations within the whole resource.
+    position: u64,
+}
+
+/// The error returned when a [`VirtualResource`] is read out of bounds.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum VirtualResourceError {
+    /// The start of the range lies past the end of the resource.
+    OffsetOutOfBounds,
+    /// The range ends past the end of the resource.
+    SizeOutOfBounds,
+}
+
+impl Display for VirtualResourceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for VirtualResourceError {}
+
+impl<'a> VirtualResource<'a> {
+    pub fn new(slices: Vec<&'a [u8]>) -> VirtualResource<'a> {
+        VirtualResource {
+            slices,
+            position: 0,
+        }
+    }
+
+    /// Creates the resource described by `dataview_list` within the buffer section `bytes`.
+    ///
+    /// # Errors
+    /// Returns an error if a data view lies outside of `bytes`.
+    pub fn from_dvl(
+        dataview_list: &DataViewList,
+        bytes: &'a [u8],
+    ) -> Result<VirtualResource<'a>, VirtualResourceError> {
+        let views = dataview_list.views();
+
+        let mut slices = Vec::new();
+
+        for view in views {
+            let offset = view.offset as usize;
+            let size = view.size as usize;
+
+            if offset > bytes.len() {
                 return Err(VirtualResourceError::OffsetOutOfBounds);
             } else if bytes.len() - offset < size {
                 return Err(VirtualResourceError::SizeOutOfBounds);
@@ -502,9 +1714,20 @@ impl VirtualResource<'_> {
             slices.push(&bytes[offset..offset + size]);
         }
 
-        Ok(VirtualResource { slices })
+        Ok(VirtualResource::new(slices))
+    }
+
+    pub fn from_slices(slices: &[&'a [u8]]) -> VirtualResource<'a> {
+        VirtualResource::new(slices.to_vec())
+    }
+
+    /// The slices of this resource, one per data view.
+    pub fn slices(&self) -> &[&'a [u8]] {
+        &self.slices
     }
+}
 
+impl VirtualResource<'_> {
     pub fn get_bytes(
         &self,
         start_offset: usize,
@@ -558,12 +1781,6 @@ where {
         Ok(v)
     }
 
-    pub(crate) fn from_slices<'a>(slices: &'a [&[u8]]) -> VirtualResource<'a> {
-        VirtualResource {
-            slices: slices.to_vec(),
-        }
-    }
-
     pub fn len(&self) -> usize {
         self.slices
             .iter()
@@ -575,6 +1792,52 @@ where {
     }
 }
 
+impl Read for VirtualResource<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut slice_start = 0u64;
+
+        for slice in &self.slices {
+            let slice_end = slice_start + slice.len() as u64;
+
+            if self.position < slice_end {
+                let offset = (self.position - slice_start) as usize;
+                let len = cmp::min(buf.len(), slice.len() - offset);
+
+                buf[..len].copy_from_slice(&slice[offset..offset + len]);
+                self.position += len as u64;
+
+                return Ok(len);
+            }
+
+            slice_start = slice_end;
+        }
+
+        Ok(0)
+    }
+}
+
+impl Seek for VirtualResource<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(offset) => {
+                self.position = offset;
+                return Ok(offset);
+            }
+            SeekFrom::End(offset) => (self.len() as u64, offset),
+            SeekFrom::Current(offset) => (self.position, offset),
+        };
+
+        self.position = base.checked_add_signed(offset).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Invalid seek to a negative or overflowing position",
+            )
+        })?;
+
+        Ok(self.position)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -609,4 +1872,770 @@ mod tests {
         assert_eq!(bytes[20..120], DATA[400..500]);
         assert_eq!(bytes[120..200], DATA[600..680]);
     }
+
+    #[test]
+    fn reads_and_seeks_across_slices() {
+        let slices = [&DATA[0..100], &DATA[200..300], &DATA[400..500]];
+        let mut virtual_res = VirtualResource::from_slices(&slices);
+
+        virtual_res.seek(SeekFrom::Start(180)).unwrap();
+
+        let mut bytes = [0; 40];
+        virtual_res.read_exact(&mut bytes).unwrap();
+        assert_eq!(bytes[..20], DATA[280..300]);
+        assert_eq!(bytes[20..], DATA[400..420]);
+
+        assert_eq!(virtual_res.seek(SeekFrom::Current(-30)).unwrap(), 190);
+        assert_eq!(
+            virtual_res.read_u32::<LittleEndian>().unwrap(),
+            u32::from_le_bytes(DATA[290..294].try_into().unwrap())
+        );
+
+        assert_eq!(virtual_res.seek(SeekFrom::End(-10)).unwrap(), 290);
+        let mut rest = vec![];
+        assert_eq!(virtual_res.read_to_end(&mut rest).unwrap(), 10);
+        assert_eq!(rest, DATA[490..500]);
+
+        assert!(virtual_res.seek(SeekFrom::Current(-1000)).is_err());
+    }
+
+    /// Builds a BNL file holding the given assets. Each asset gets one data view per data slice
+    /// (or a single empty view if it has none).
+    pub(crate) fn bnl_from_raw_assets(assets: &[RawAsset]) -> BNLFile {
+        let mut asset_descs = vec![];
+        let mut views = vec![];
+        let mut buffer = vec![];
+        let mut descriptors = vec![];
+
+        for raw_asset in assets {
+            let slices = match raw_asset.data_slices.is_empty() {
+                true => vec![vec![]],
+                false => raw_asset.data_slices.clone(),
+            };
+
+            asset_descs.extend_from_slice(&asset::aid_to_bytes(&raw_asset.name).unwrap());
+
+            for value in [
+                u32::from(raw_asset.asset_type),
+                0,
+                0,
+                slices.len() as u32,
+                descriptors.len() as u32,
+                raw_asset.descriptor_bytes.len() as u32,
+                views.len() as u32,
+                slices.iter().map(Vec::len).sum::<usize>() as u32,
+            ] {
+                asset_descs.extend_from_slice(&value.to_le_bytes());
+            }
+
+            views.extend_from_slice(&(8 + slices.len() as u32 * 8).to_le_bytes());
+            views.extend_from_slice(&(slices.len() as u32).to_le_bytes());
+
+            for slice in slices {
+                views.extend_from_slice(&(buffer.len() as u32).to_le_bytes());
+                views.extend_from_slice(&(slice.len() as u32).to_le_bytes());
+                buffer.extend_from_slice(&slice);
+            }
+
+            descriptors.extend_from_slice(&raw_asset.descriptor_bytes);
+        }
+
+        let mut bytes = vec![];
+        bytes.extend_from_slice(&(assets.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&[0; 6]);
+
+        let mut offset = BNL_HEADER_SIZE as u32;
+
+        for section in [&asset_descs, &views, &buffer, &descriptors] {
+            bytes.extend_from_slice(&offset.to_le_bytes());
+            bytes.extend_from_slice(&(section.len() as u32).to_le_bytes());
+            offset += section.len() as u32;
+        }
+
+        let body = [asset_descs, views, buffer, descriptors].concat();
+        bytes.extend_from_slice(&miniz_oxide::deflate::compress_to_vec_zlib(&body, 1));
+
+        BNLFile::from_bytes(&bytes).unwrap()
+    }
+
+    /// Builds a tiny BNL file containing a single texture asset with a 4 byte resource.
+    pub(crate) fn make_bnl() -> Vec<u8> {
+        let mut bytes = vec![];
+
+        bytes.extend_from_slice(&1u16.to_le_bytes()); // File count
+        bytes.push(0); // Flags
+        bytes.extend_from_slice(&[0; 5]);
+
+        for (offset, size) in [(40u32, 160u32), (200, 16), (216, 4), (220, 28)] {
+            bytes.extend_from_slice(&offset.to_le_bytes());
+            bytes.extend_from_slice(&size.to_le_bytes());
+        }
+
+        let mut body = vec![];
+
+        let mut name = [0u8; 128];
+        name[..16].copy_from_slice(b"aid_texture_test");
+        body.extend_from_slice(&name);
+
+        for value in [1u32, 0, 0, 1, 0, 28, 0, 4] {
+            body.extend_from_slice(&value.to_le_bytes());
+        }
+
+        // Data view list with a single view
+        for value in [16u32, 1, 0, 4] {
+            body.extend_from_slice(&value.to_le_bytes());
+        }
+
+        body.extend_from_slice(&[1, 2, 3, 4]);
+
+        for value in [0x12u32, 28, 0x0001_0001, 0, 0, 0, 4] {
+            body.extend_from_slice(&value.to_le_bytes());
+        }
+
+        bytes.extend_from_slice(&miniz_oxide::deflate::compress_to_vec_zlib(&body, 1));
+
+        bytes
+    }
+
+    #[test]
+    fn update_and_round_trip() {
+        let mut bnl = BNLFile::from_bytes(&make_bnl()).unwrap();
+
+        let mut raw_asset = bnl.get_raw_asset("aid_texture_test").unwrap();
+        assert_eq!(raw_asset.data_slices, vec![vec![1, 2, 3, 4]]);
+
+        raw_asset.data_slices[0] = vec![5, 6, 7, 8];
+        bnl.update_asset("aid_texture_test", &raw_asset).unwrap();
+
+        let bnl = BNLFile::from_bytes(&bnl.to_bytes().unwrap()).unwrap();
+
+        let raw_asset = bnl.get_raw_asset("aid_texture_test").unwrap();
+        assert_eq!(raw_asset.data_slices, vec![vec![5, 6, 7, 8]]);
+        assert_eq!(raw_asset.descriptor_bytes.len(), 28);
+    }
+
+    #[test]
+    fn reads_descriptions_only() {
+        let descriptions = BNLFile::read_asset_descriptions(&make_bnl()).unwrap();
+
+        assert_eq!(descriptions.len(), 1);
+        assert_eq!(descriptions[0].name(), "aid_texture_test");
+        assert_eq!(descriptions[0].resource_size(), 4);
+    }
+
+    #[test]
+    fn clone_asset_copies_data() {
+        let mut bnl = BNLFile::from_bytes(&make_bnl()).unwrap();
+
+        bnl.clone_asset("aid_texture_test", "aid_texture_copy")
+            .unwrap();
+
+        let mut raw_asset = bnl.get_raw_asset("aid_texture_copy").unwrap();
+        raw_asset.data_slices[0] = vec![5, 6, 7, 8];
+        bnl.update_asset("aid_texture_copy", &raw_asset).unwrap();
+
+        let bnl = BNLFile::from_bytes(&bnl.to_bytes().unwrap()).unwrap();
+
+        assert_eq!(bnl.asset_descriptions().len(), 2);
+        assert_eq!(
+            bnl.get_raw_asset("aid_texture_test").unwrap().data_slices,
+            [vec![1, 2, 3, 4]]
+        );
+
+        let copy = bnl.get_raw_asset("aid_texture_copy").unwrap();
+        assert_eq!(copy.data_slices, [vec![5, 6, 7, 8]]);
+        assert_eq!(
+            copy.descriptor_bytes,
+            bnl.get_raw_asset("aid_texture_test")
+                .unwrap()
+                .descriptor_bytes
+        );
+
+        let mut bnl = bnl;
+        assert!(matches!(
+            bnl.clone_asset("aid_texture_test", "aid_texture_copy"),
+            Err(AssetError::AlreadyExists)
+        ));
+        assert!(matches!(
+            bnl.clone_asset("aid_texture_missing", "aid_texture_new"),
+            Err(AssetError::NotFound)
+        ));
+        assert!(matches!(
+            bnl.clone_asset("aid_texture_test", &"a".repeat(128)),
+            Err(AssetError::InvalidName)
+        ));
+    }
+
+    #[test]
+    fn remove_asset_types_compacts() {
+        let raw_asset = |name: &str, asset_type, data: Vec<u8>| RawAsset {
+            name: name.to_string(),
+            asset_type,
+            descriptor_bytes: vec![asset_type as u8; 4],
+            data_slices: vec![data],
+        };
+
+        let mut bnl = bnl_from_raw_assets(&[
+            raw_asset("aid_texture_a", AssetType::ResTexture, vec![1; 8]),
+            raw_asset("aid_cutscene_b", AssetType::ResCutscene, vec![2; 64]),
+            raw_asset("aid_texture_c", AssetType::ResTexture, vec![3; 8]),
+        ]);
+
+        let original_size = bnl.to_bytes().unwrap().len();
+
+        assert_eq!(
+            bnl.remove_asset_types(&[AssetType::ResCutscene]).unwrap(),
+            1
+        );
+
+        let bytes = bnl.to_bytes().unwrap();
+        assert!(bytes.len() < original_size);
+
+        let bnl = BNLFile::from_bytes(&bytes).unwrap();
+        assert_eq!(bnl.asset_descriptions().len(), 2);
+        assert!(bnl.get_raw_asset("aid_cutscene_b").is_err());
+        assert_eq!(bnl.free_space().buffer.free(), 8);
+
+        let texture = bnl.get_raw_asset("aid_texture_c").unwrap();
+        assert_eq!(texture.data_slices, [vec![3; 8]]);
+        assert_eq!(texture.descriptor_bytes, [1; 4]);
+    }
+
+    #[test]
+    fn name_index_follows_edits() {
+        let mut bnl = BNLFile::from_bytes(&make_bnl()).unwrap();
+
+        bnl.clone_asset("aid_texture_test", "aid_texture_copy")
+            .unwrap();
+        assert_eq!(
+            bnl.asset_description("aid_texture_copy").unwrap().name(),
+            "aid_texture_copy"
+        );
+
+        bnl.retain_assets(|desc| desc.name() != "aid_texture_test")
+            .unwrap();
+        assert!(bnl.asset_description("aid_texture_test").is_none());
+        assert_eq!(
+            bnl.get_raw_asset("aid_texture_copy").unwrap().data_slices,
+            [[1, 2, 3, 4]]
+        );
+    }
+
+    #[test]
+    fn recomputes_resource_sizes() {
+        let mut bnl = BNLFile::from_bytes(&make_bnl()).unwrap();
+        assert!(bnl.check_resource_sizes().is_empty());
+
+        bnl.asset_descriptions[0].resource_size = 12;
+
+        assert_eq!(
+            bnl.check_resource_sizes(),
+            [ConstraintViolation::ResourceSizeMismatch {
+                aid: "aid_texture_test".to_string(),
+                stored: 12,
+                expected: 4,
+            }]
+        );
+        assert_eq!(bnl.recompute_resource_sizes(), 1);
+        assert!(bnl.check_resource_sizes().is_empty());
+        assert_eq!(bnl.asset_descriptions()[0].resource_size(), 4);
+    }
+
+    #[test]
+    fn recomputes_chunk_counts() {
+        let mut bnl = BNLFile::from_bytes(&make_bnl()).unwrap();
+        assert_eq!(bnl.asset_descriptions()[0].chunk_count(), 1);
+        assert!(bnl.check_chunk_counts().is_empty());
+
+        bnl.asset_descriptions[0].chunk_count = 3;
+
+        assert_eq!(bnl.check_chunk_counts().len(), 1);
+        assert_eq!(bnl.recompute_chunk_counts(), 1);
+        assert!(bnl.check_chunk_counts().is_empty());
+
+        bnl.clone_asset("aid_texture_test", "aid_texture_copy")
+            .unwrap();
+        assert!(bnl.check_chunk_counts().is_empty());
+    }
+
+    #[test]
+    fn raw_asset_refs_borrow_data() {
+        let bnl = BNLFile::from_bytes(&make_bnl()).unwrap();
+
+        let raw_asset = bnl.get_raw_asset_ref("aid_texture_test").unwrap();
+        assert_eq!(raw_asset.data_slices, [[1, 2, 3, 4]]);
+        assert_eq!(raw_asset.dataview_list.num_views(), 1);
+        assert!(std::ptr::eq(
+            raw_asset.data_slices[0],
+            &bnl.buffer_bytes[..4]
+        ));
+
+        let owned = bnl.get_raw_asset("aid_texture_test").unwrap();
+        assert_eq!(owned.descriptor_bytes, raw_asset.descriptor_bytes);
+        assert_eq!(bnl.iter_raw_assets().count(), 1);
+    }
+
+    #[test]
+    fn warns_on_unknown_header_values() {
+        let bnl = BNLFile::from_bytes(&make_bnl()).unwrap();
+        assert!(bnl.flags().is_compressed());
+        assert!(bnl.header_warnings().is_empty());
+
+        let mut bytes = make_bnl();
+        bytes[2] = 0x80;
+        bytes[4] = 7;
+
+        let bnl = BNLFile::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            bnl.header_warnings(),
+            [
+                HeaderWarning::UnknownFlags(0x80),
+                HeaderWarning::NonZeroReserved([0, 7, 0, 0, 0])
+            ]
+        );
+        assert_eq!(bnl.to_bytes().unwrap()[..8], bytes[..8]);
+    }
+
+    #[test]
+    fn free_space_of_full_bnl() {
+        let bnl = BNLFile::from_bytes(&make_bnl()).unwrap();
+
+        let free_space = bnl.free_space();
+
+        assert_eq!(free_space.descriptor.free(), 0);
+        assert_eq!(free_space.buffer_views.used, 16);
+        assert_eq!(free_space.buffer.size, 4);
+        assert!(!free_space.buffer.fits(1));
+    }
+
+    #[test]
+    fn space_report_maps_free_ranges() {
+        let raw_asset = |name: &str, asset_type, size| RawAsset {
+            name: name.to_string(),
+            asset_type,
+            descriptor_bytes: vec![1; 4],
+            data_slices: vec![vec![2; size]],
+        };
+
+        let mut bnl = BNLFile::new();
+        bnl.add_asset(&raw_asset("aid_script_a", AssetType::ResScript, 4))
+            .unwrap();
+        bnl.add_asset(&raw_asset("aid_cutscene_b", AssetType::ResCutscene, 40))
+            .unwrap();
+        bnl.add_asset(&raw_asset("aid_script_c", AssetType::ResScript, 4))
+            .unwrap();
+
+        // Each buffer is aligned to 16 bytes, leaving gaps after the small ones
+        let report = bnl.space_report();
+        assert_eq!(report.buffer.size(), 68);
+        assert_eq!(report.buffer.free_ranges(), [4..16, 56..64]);
+        assert_eq!(report.buffer.used(), 48);
+        assert!(report.descriptor.free_ranges().is_empty());
+
+        // The smallest gap that fits is picked, and nothing fits 16 aligned bytes
+        assert_eq!(report.buffer.best_fit(8, 4), Some(56));
+        assert_eq!(report.buffer.best_fit(12, 4), Some(4));
+        assert_eq!(report.buffer.best_fit(4, 16), None);
+    }
+
+    #[test]
+    fn replace_asset_data_reuses_space() {
+        let raw_asset = |name: &str, size| RawAsset {
+            name: name.to_string(),
+            asset_type: AssetType::ResScript,
+            descriptor_bytes: vec![1; 4],
+            data_slices: vec![vec![2; size]],
+        };
+
+        let mut bnl = BNLFile::new();
+        bnl.add_asset(&raw_asset("aid_script_a", 4)).unwrap();
+        bnl.add_asset(&raw_asset("aid_script_b", 40)).unwrap();
+        bnl.add_asset(&raw_asset("aid_script_c", 4)).unwrap();
+
+        // Too big for the space the asset had, so it moves to the end and leaves that space free
+        bnl.replace_asset_data("aid_script_b", &[3; 60]).unwrap();
+
+        let report = bnl.space_report();
+        assert_eq!(report.buffer.size(), 140);
+        assert_eq!(report.buffer.free_ranges(), [4..64, 68..80]);
+
+        // The freed space is reused by later replacements
+        bnl.replace_asset_data("aid_script_c", &[4; 20]).unwrap();
+        assert_eq!(bnl.space_report().buffer.size(), 140);
+
+        let mut bnl = BNLFile::from_bytes(&bnl.to_bytes().unwrap()).unwrap();
+        assert_eq!(
+            bnl.get_raw_asset("aid_script_b").unwrap().data_slices,
+            [vec![3; 60]]
+        );
+
+        let asset_c = bnl.get_raw_asset("aid_script_c").unwrap();
+        assert_eq!(asset_c.descriptor_bytes, [1; 4]);
+        assert_eq!(asset_c.data_slices, [vec![4; 20]]);
+        assert_eq!(
+            bnl.asset_description("aid_script_c").unwrap().resource_size,
+            20
+        );
+
+        assert!(matches!(
+            bnl.replace_asset(
+                "aid_script_a",
+                &RawAsset {
+                    asset_type: AssetType::ResTexture,
+                    ..raw_asset("aid_script_a", 4)
+                }
+            ),
+            Err(AssetError::TypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn dataview_list_round_trips() {
+        let mut dvl = DataViewList::new(vec![DataView::new(0, 16)]);
+        dvl.push_view(DataView::new(32, 4));
+        dvl.set_view(0, DataView::new(16, 8)).unwrap();
+
+        assert!(dvl.set_view(2, DataView::default()).is_err());
+        assert!(dvl.validate().is_ok());
+        assert!(DataViewList::default().validate().is_err());
+
+        let bytes = dvl.to_bytes();
+        assert_eq!(bytes.len(), 24);
+
+        let read = DataViewList::from_bytes(&bytes).unwrap();
+        assert_eq!(read.size(), 24);
+        assert_eq!(read.views(), [DataView::new(16, 8), DataView::new(32, 4)]);
+    }
+
+    #[test]
+    fn writes_bytes_at_offset() {
+        let mut bnl = bnl_from_raw_assets(&[RawAsset {
+            name: "aid_script_a".to_string(),
+            asset_type: AssetType::ResScript,
+            descriptor_bytes: vec![0; 4],
+            data_slices: vec![vec![1; 4], vec![2; 4], vec![3; 4]],
+        }]);
+
+        bnl.write_asset_bytes_at("aid_script_a", 2, &[9; 8])
+            .unwrap();
+
+        assert_eq!(
+            bnl.get_raw_asset("aid_script_a").unwrap().data_slices,
+            [vec![1, 1, 9, 9], vec![9; 4], vec![9, 9, 3, 3]]
+        );
+        assert!(matches!(
+            bnl.write_asset_bytes_at("aid_script_a", 10, &[0; 3]),
+            Err(AssetError::SizeMismatch)
+        ));
+    }
+
+    #[test]
+    fn update_rejects_size_change() {
+        let mut bnl = BNLFile::from_bytes(&make_bnl()).unwrap();
+
+        let mut raw_asset = bnl.get_raw_asset("aid_texture_test").unwrap();
+        raw_asset.data_slices[0].push(0);
+
+        assert!(matches!(
+            bnl.update_asset("aid_texture_test", &raw_asset),
+            Err(AssetError::SizeMismatch)
+        ));
+    }
+
+    #[test]
+    fn find_assets_by_pattern() {
+        let bnl = BNLFile::from_bytes(&make_bnl()).unwrap();
+
+        assert_eq!(bnl.find_assets("aid_texture_*").len(), 1);
+        assert_eq!(bnl.find_assets("aid_*_test").len(), 1);
+        assert_eq!(bnl.find_assets("aid_texture_tes?").len(), 1);
+        assert!(bnl.find_assets("aid_texture_tes").is_empty());
+        assert!(bnl.find_assets("aid_script_*").is_empty());
+
+        assert!(bnl.find_asset_ignore_case("AID_Texture_Test").is_some());
+        assert!(bnl.find_asset_ignore_case("aid_texture_tes").is_none());
+
+        #[cfg(feature = "regex")]
+        assert_eq!(
+            bnl.find_assets_regex(&regex::Regex::new("^aid_tex.*st$").unwrap())
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn iter_assets_reports_errors() {
+        use crate::asset::{
+            DescriptorTemplate,
+            texture::{Texture, TextureDescriptor},
+        };
+
+        let texture = |name: &str, descriptor_bytes: Vec<u8>| RawAsset {
+            name: name.to_string(),
+            asset_type: AssetType::ResTexture,
+            descriptor_bytes,
+            data_slices: vec![vec![0; 64]],
+        };
+
+        let bnl = bnl_from_raw_assets(&[
+            texture(
+                "aid_texture_good",
+                TextureDescriptor::template().to_bytes().unwrap(),
+            ),
+            RawAsset {
+                name: "aid_script_skipped".to_string(),
+                asset_type: AssetType::ResScript,
+                descriptor_bytes: vec![],
+                data_slices: vec![],
+            },
+            texture("aid_texture_truncated", vec![0; 4]),
+        ]);
+
+        let results: Vec<_> = bnl.iter_assets::<Texture>().collect();
+
+        assert_eq!(results.len(), 2);
+        assert!(matches!(results[0], ("aid_texture_good", Ok(_))));
+        assert!(matches!(
+            results[1],
+            ("aid_texture_truncated", Err(AssetError::ParseError { .. }))
+        ));
+
+        assert_eq!(bnl.iter_assets::<Texture>().take(1).count(), 1);
+        assert_eq!(bnl.get_assets::<Texture>().len(), 1);
+
+        let (textures, errors) = bnl.get_assets_with_errors::<Texture>();
+        assert_eq!(textures.len(), 1);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, "aid_texture_truncated");
+    }
+
+    #[test]
+    fn errors_carry_context() {
+        let mut bytes = make_bnl();
+        bytes[36..40].copy_from_slice(&1000u32.to_le_bytes());
+
+        assert!(matches!(
+            BNLFile::from_bytes(&bytes),
+            Err(BNLError::SectionOutOfBounds {
+                section: "descriptor",
+                size: 1000,
+                ..
+            })
+        ));
+
+        let bnl = bnl_from_raw_assets(&[RawAsset {
+            name: "aid_texture_truncated".to_string(),
+            asset_type: AssetType::ResTexture,
+            descriptor_bytes: vec![0; 4],
+            data_slices: vec![],
+        }]);
+
+        let e = bnl
+            .get_asset::<crate::asset::texture::Texture>("aid_texture_truncated")
+            .unwrap_err();
+
+        assert!(e.to_string().contains("aid_texture_truncated"));
+        assert!(matches!(
+            e.source()
+                .and_then(|source| source.downcast_ref::<AssetParseError>()),
+            Some(AssetParseError::InputTooSmall)
+        ));
+
+        assert!(matches!(
+            bnl.get_asset::<crate::asset::model::Model>("aid_texture_truncated"),
+            Err(AssetError::TypeMismatch {
+                found: AssetType::ResTexture,
+                ..
+            })
+        ));
+    }
+
+    /// A xorshift generator, so that the fuzz tests are reproducible without a dependency.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        /// Returns bytes that are mostly small values, so that offsets and counts read from them
+        /// land in range often enough to reach deeper into the parsers.
+        fn bytes(&mut self, len: usize) -> Vec<u8> {
+            (0..len)
+                .map(|_| match self.next() % 4 {
+                    0 => self.next() as u8,
+                    _ => (self.next() % 8) as u8,
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn truncated_bnl_does_not_panic() {
+        let bytes = make_bnl();
+
+        for len in 0..bytes.len() {
+            let _ = BNLFile::from_bytes(&bytes[..len]);
+            let _ = BNLFile::read_asset_descriptions(&bytes[..len]);
+            let _ = format::detect(&bytes[..len]);
+        }
+    }
+
+    #[test]
+    fn corrupted_bnl_does_not_panic() {
+        let bnl = BNLFile::from_bytes(&make_bnl()).unwrap();
+
+        // Every byte of an uncompressed file is meaningful, unlike a zlib stream
+        let (bytes, _) = bnl
+            .to_bytes_with_options(
+                &serialize::SerializeOptions::default().compression(Compression::Uncompressed),
+            )
+            .unwrap();
+
+        for i in 0..bytes.len() {
+            for value in [0x00, 0x01, 0x7f, 0x80, 0xff] {
+                let mut corrupted = bytes.clone();
+                corrupted[i] = value;
+
+                let Ok(mut bnl) = BNLFile::from_bytes(&corrupted) else {
+                    continue;
+                };
+
+                let _ = bnl.get_raw_assets_with_errors();
+                let _ = bnl.get_assets_with_errors::<asset::texture::Texture>();
+                let _ = bnl.validate();
+                let _ = bnl.free_space();
+                let _ = bnl.to_bytes();
+                let _ = bnl.retain_assets(|_| true);
+            }
+        }
+    }
+
+    #[test]
+    fn huge_asset_desc_size_is_rejected() {
+        let bnl = BNLFile::from_bytes(&make_bnl()).unwrap();
+        let (mut bytes, _) = bnl
+            .to_bytes_with_options(
+                &serialize::SerializeOptions::default().compression(Compression::Uncompressed),
+            )
+            .unwrap();
+
+        // The size of the asset description section, which once sized the description list
+        // before any of it was read
+        assert_eq!(bytes[12..16], 160u32.to_le_bytes());
+        bytes[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        assert!(BNLFile::from_bytes(&bytes).is_err());
+        assert!(BNLFile::read_asset_descriptions(&bytes).is_err());
+    }
+
+    #[test]
+    fn malformed_assets_do_not_panic() {
+        const ITERATIONS: usize = 2000;
+
+        use asset::{
+            actor_attribs::ActorAttribs,
+            aid_list::AidList,
+            anim::Animation,
+            events::{AnimEvents, CutsceneEvents},
+            feedback::{Rumble, ShakeCam},
+            font::Font,
+            ghoulybox::Ghoulybox,
+            ghoulybox::Ghoulyspawn,
+            loctext::Loctext,
+            marker::Marker,
+            model::Model,
+            particle::Emitter,
+            particle::Particle,
+            soundbank::Soundbank,
+            texture::Texture,
+        };
+
+        fn parse_all<A: Asset>(rng: &mut Rng) {
+            for _ in 0..ITERATIONS {
+                let descriptor_len = (rng.next() % 160) as usize;
+                let descriptor_bytes = rng.bytes(descriptor_len);
+
+                let Ok(descriptor) = A::Descriptor::from_bytes(&descriptor_bytes) else {
+                    continue;
+                };
+
+                let slice_count = (rng.next() % 3) as usize;
+                let data_slices: Vec<Vec<u8>> = (0..slice_count)
+                    .map(|_| {
+                        let len = (rng.next() % 256) as usize;
+                        rng.bytes(len)
+                    })
+                    .collect();
+
+                let virtual_res =
+                    VirtualResource::new(data_slices.iter().map(Vec::as_slice).collect());
+                let _ = A::new("aid_fuzz", &descriptor, &virtual_res);
+            }
+        }
+
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+        parse_all::<ActorAttribs>(&mut rng);
+        parse_all::<AidList>(&mut rng);
+        parse_all::<Animation>(&mut rng);
+        parse_all::<AnimEvents>(&mut rng);
+        parse_all::<CutsceneEvents>(&mut rng);
+        parse_all::<Font>(&mut rng);
+        parse_all::<Ghoulybox>(&mut rng);
+        parse_all::<Ghoulyspawn>(&mut rng);
+        parse_all::<Loctext>(&mut rng);
+        parse_all::<Marker>(&mut rng);
+        parse_all::<Model>(&mut rng);
+        parse_all::<Emitter>(&mut rng);
+        parse_all::<Particle>(&mut rng);
+        parse_all::<Rumble>(&mut rng);
+        parse_all::<ShakeCam>(&mut rng);
+        parse_all::<Soundbank>(&mut rng);
+        parse_all::<Texture>(&mut rng);
+    }
+
+    #[cfg(feature = "memmap2")]
+    #[test]
+    fn opens_memory_mapped_file() {
+        let path = std::env::temp_dir().join(format!("bnl_mmap_{}.bnl", std::process::id()));
+        std::fs::write(&path, make_bnl()).unwrap();
+
+        let bnl = BNLFile::open_mmap(&path).unwrap();
+        assert_eq!(
+            bnl.get_raw_asset("aid_texture_test").unwrap().data_slices,
+            BNLFile::from_bytes(&make_bnl())
+                .unwrap()
+                .get_raw_asset("aid_texture_test")
+                .unwrap()
+                .data_slices
+        );
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(BNLFile::open_mmap(&path), Err(BNLError::Io(_))));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trips() {
+        let bnl = BNLFile::from_bytes(&make_bnl()).unwrap();
+
+        let asset_desc = &bnl.asset_descriptions()[0];
+        let json = serde_json::to_value(asset_desc).unwrap();
+        assert_eq!(json["name"], "aid_texture_test");
+
+        let parsed: AssetDescription = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.name(), asset_desc.name());
+        assert_eq!(parsed.asset_type(), asset_desc.asset_type());
+        assert_eq!(parsed.resource_size(), asset_desc.resource_size());
+
+        let raw_asset = bnl.get_raw_asset("aid_texture_test").unwrap();
+        let json = serde_json::to_string(&raw_asset).unwrap();
+        let parsed: RawAsset = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.descriptor_bytes, raw_asset.descriptor_bytes);
+        assert_eq!(parsed.data_slices, raw_asset.data_slices);
+    }
 }
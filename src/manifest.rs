@@ -0,0 +1,126 @@
+//! A summary of the asset table of a [`BNLFile`], for external tools and web viewers that want to
+//! index bundles without linking this crate.
+
+use serde_json::{Value, json};
+
+use crate::{BNLFile, Compression, asset::AssetDescription, game::AssetType};
+
+/// Everything in the asset table of a [`BNLFile`], see [`BNLFile::manifest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Manifest {
+    pub compression: Compression,
+    /// The raw header flags, see [`crate::BNLFlags`].
+    pub flags: u8,
+    pub assets: Vec<ManifestEntry>,
+}
+
+/// The description of a single asset in a [`Manifest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManifestEntry {
+    pub name: String,
+    pub asset_type: AssetType,
+    pub descriptor_ptr: u32,
+    pub descriptor_size: u32,
+    pub dataview_list_ptr: u32,
+    pub resource_size: u32,
+    pub chunk_count: u32,
+    pub unk_1: u32,
+    pub unk_2: u32,
+}
+
+impl From<&AssetDescription> for ManifestEntry {
+    fn from(desc: &AssetDescription) -> Self {
+        ManifestEntry {
+            name: desc.name().to_string(),
+            asset_type: desc.asset_type(),
+            descriptor_ptr: desc.descriptor_ptr(),
+            descriptor_size: desc.descriptor_size(),
+            dataview_list_ptr: desc.bufferview_list_ptr(),
+            resource_size: desc.resource_size(),
+            chunk_count: desc.chunk_count(),
+            unk_1: desc.unk_1,
+            unk_2: desc.unk_2,
+        }
+    }
+}
+
+impl Manifest {
+    /// Returns this manifest as JSON, with asset types given by name.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let bnl_file = bnl::BNLFile::new();
+    /// let manifest = bnl_file.manifest();
+    ///
+    /// std::fs::write("common.json", manifest.to_json().to_string())?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_json(&self) -> Value {
+        let assets: Vec<Value> = self
+            .assets
+            .iter()
+            .map(|entry| {
+                json!({
+                    "name": entry.name,
+                    "type": format!("{:?}", entry.asset_type),
+                    "type_id": u32::from(entry.asset_type),
+                    "descriptor_ptr": entry.descriptor_ptr,
+                    "descriptor_size": entry.descriptor_size,
+                    "dataview_list_ptr": entry.dataview_list_ptr,
+                    "resource_size": entry.resource_size,
+                    "chunk_count": entry.chunk_count,
+                    "unk_1": entry.unk_1,
+                    "unk_2": entry.unk_2,
+                })
+            })
+            .collect();
+
+        json!({
+            "compression": format!("{:?}", self.compression),
+            "flags": self.flags,
+            "assets": assets,
+        })
+    }
+}
+
+impl BNLFile {
+    /// Returns a [`Manifest`] of every asset in this file, in the order of the asset table.
+    pub fn manifest(&self) -> Manifest {
+        Manifest {
+            compression: self.compression(),
+            flags: self.flags().bits(),
+            assets: self
+                .asset_descriptions()
+                .iter()
+                .map(ManifestEntry::from)
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::make_bnl;
+
+    #[test]
+    fn manifest_lists_assets() {
+        let bnl = BNLFile::from_bytes(&make_bnl()).unwrap();
+        let manifest = bnl.manifest();
+
+        assert_eq!(manifest.assets.len(), 1);
+        assert_eq!(manifest.assets[0].asset_type, AssetType::ResTexture);
+
+        let json = manifest.to_json();
+        assert_eq!(json["compression"], "Zlib");
+        assert_eq!(json["assets"][0]["name"], "aid_texture_test");
+        assert_eq!(json["assets"][0]["type"], "ResTexture");
+        assert_eq!(json["assets"][0]["type_id"], 1);
+        assert_eq!(json["assets"][0]["resource_size"], 4);
+        assert_eq!(json["assets"][0]["chunk_count"], 1);
+    }
+}
@@ -0,0 +1,38 @@
+//! Re-exports the types most programs using this crate need, so that they don't depend on the
+//! crate's module layout.
+//!
+//! # Examples
+//! ```no_run
+//! use bnl::prelude::*;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let bytes = std::fs::read("./common.bnl")?;
+//! let bnl = BNLFile::from_bytes(&bytes)?;
+//! let texture = bnl.get_asset::<Texture>("aid_texture_mytexture_a_b")?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub use crate::{
+    BNLError, BNLFile,
+    asset::{
+        Asset, AssetDescription, AssetDescriptor, AssetError, AssetParseError, DescriptorTemplate,
+        RawAsset, RawAssetRef, WritableAsset,
+        actor_attribs::ActorAttribs,
+        aid_list::AidList,
+        anim::Animation,
+        events::{AnimEvents, CutsceneEvents},
+        feedback::{Rumble, ShakeCam},
+        font::Font,
+        ghoulybox::{Ghoulybox, Ghoulyspawn},
+        id::AssetId,
+        loctext::{Loctext, LoctextError},
+        marker::Marker,
+        model::Model,
+        particle::{Emitter, Particle},
+        soundbank::Soundbank,
+        texture::{Texture, TextureDumpError, TextureImportError},
+    },
+    game::AssetType,
+    workspace::{Workspace, WorkspaceError},
+};
@@ -0,0 +1,500 @@
+//! A machine-readable description of everything this crate currently knows about the BNL
+//! format: asset type IDs, texture format IDs, model subresource types and descriptor layouts.
+//!
+//! The ID tables are read from the same tables the parsers use. The descriptor layouts are
+//! written out by hand, so they must be updated alongside the parser they describe.
+
+use serde_json::{Value, json};
+
+use crate::{
+    asset::{model::ModelSubresType, texture::TEXTURE_FORMATS},
+    d3d::PixelBits,
+    game::AssetType,
+};
+
+/// A structure with a fixed layout, made up of (field name, field type) pairs.
+pub(crate) struct Layout {
+    pub(crate) name: &'static str,
+    pub(crate) fields: &'static [(&'static str, &'static str)],
+}
+
+const LAYOUTS: &[Layout] = &[
+    Layout {
+        name: "BNLHeader",
+        fields: &[
+            ("file_count", "u16"),
+            ("flags", "u8"),
+            ("unknown_2", "u8[5]"),
+            ("asset_desc_offset", "u32"),
+            ("asset_desc_size", "u32"),
+            ("buffer_views_offset", "u32"),
+            ("buffer_views_size", "u32"),
+            ("buffer_offset", "u32"),
+            ("buffer_size", "u32"),
+            ("descriptor_offset", "u32"),
+            ("descriptor_size", "u32"),
+        ],
+    },
+    Layout {
+        name: "AssetDescription",
+        fields: &[
+            ("name", "u8[128]"),
+            ("asset_type", "u32"),
+            ("unk_1", "u32"),
+            ("unk_2", "u32"),
+            ("chunk_count", "u32"),
+            ("descriptor_ptr", "u32"),
+            ("descriptor_size", "u32"),
+            ("dataview_list_ptr", "u32"),
+            ("resource_size", "u32"),
+        ],
+    },
+    Layout {
+        name: "TextureDescriptor",
+        fields: &[
+            ("format", "u32"),
+            ("header_size", "u32"),
+            ("width", "u16"),
+            ("height", "u16"),
+            ("flags", "u32"),
+            ("unknown_3a", "u32"),
+            ("texture_offset", "u32"),
+            ("texture_size", "u32"),
+        ],
+    },
+    Layout {
+        name: "ModelDescriptor",
+        fields: &[("subresources_offset", "u32"), ("subresource_count", "u32")],
+    },
+    Layout {
+        name: "ModelSubresource",
+        fields: &[("subres_type", "u32"), ("subres_param", "u32")],
+    },
+    Layout {
+        name: "MeshDescriptor",
+        fields: &[
+            ("vertex_count", "u32"),
+            ("vertex_stride", "u32"),
+            ("vertex_offset", "u32"),
+            ("index_count", "u32"),
+            ("index_offset", "u32"),
+            ("fvf", "u32"),
+        ],
+    },
+    Layout {
+        name: "Material",
+        fields: &[
+            ("texture_index", "i32"),
+            ("diffuse", "f32[4]"),
+            ("specular", "f32[4]"),
+            ("specular_power", "f32"),
+            ("flags", "u32"),
+        ],
+    },
+    Layout {
+        name: "VertexBufferDescriptor",
+        fields: &[
+            ("offset", "u32"),
+            ("size", "u32"),
+            ("stride", "u32"),
+            ("fvf", "u32"),
+        ],
+    },
+    Layout {
+        name: "IndexBufferDescriptor",
+        fields: &[
+            ("offset", "u32"),
+            ("index_count", "u32"),
+            ("primitive_type", "u32"),
+        ],
+    },
+    Layout {
+        name: "Bone",
+        fields: &[
+            ("name", "u8[32]"),
+            ("parent", "i32"),
+            ("bind_pose", "f32[16]"),
+        ],
+    },
+    Layout {
+        name: "AnimationDescriptor",
+        fields: &[
+            ("duration", "f32"),
+            ("track_count", "u32"),
+            ("track_list_offset", "u32"),
+        ],
+    },
+    Layout {
+        name: "TrackDescriptor",
+        fields: &[
+            ("target", "u32"),
+            ("channel_type", "u32"),
+            ("keyframe_count", "u32"),
+            ("data_offset", "u32"),
+            ("data_size", "u32"),
+        ],
+    },
+    Layout {
+        name: "LoctextDescriptor",
+        fields: &[
+            ("string_count", "u32"),
+            ("language_count", "u32"),
+            ("string_ids_offset", "u32"),
+            ("languages_offset", "u32"),
+        ],
+    },
+    Layout {
+        name: "LanguageEntry",
+        fields: &[
+            ("language", "u32"),
+            ("encoding", "u32"),
+            ("strings_offset", "u32"),
+        ],
+    },
+    Layout {
+        name: "FontDescriptor",
+        fields: &[
+            ("glyph_count", "u32"),
+            ("glyph_list_offset", "u32"),
+            ("line_height", "u16"),
+            ("baseline", "u16"),
+            ("texture_descriptor_offset", "u32"),
+        ],
+    },
+    Layout {
+        name: "Glyph",
+        fields: &[
+            ("character", "u16"),
+            ("x", "u16"),
+            ("y", "u16"),
+            ("width", "u16"),
+            ("height", "u16"),
+            ("x_offset", "i16"),
+            ("y_offset", "i16"),
+            ("advance", "i16"),
+        ],
+    },
+    Layout {
+        name: "SoundbankDescriptor",
+        fields: &[
+            ("cue_count", "u32"),
+            ("cue_names_offset", "u32"),
+            ("wave_bank_offset", "u32"),
+            ("wave_bank_size", "u32"),
+        ],
+    },
+    Layout {
+        name: "GhoulyboxDescriptor",
+        fields: &[("entry_count", "u32"), ("entries_offset", "u32")],
+    },
+    Layout {
+        name: "SpawnEntry",
+        fields: &[
+            ("actor_attribs", "u8[128]"),
+            ("count", "u32"),
+            ("max_alive", "u32"),
+            ("spawn_delay", "f32"),
+            ("spawn_radius", "f32"),
+        ],
+    },
+    Layout {
+        name: "GhoulyspawnDescriptor",
+        fields: &[
+            ("ghoulybox", "u8[128]"),
+            ("position", "f32[3]"),
+            ("rotation", "f32"),
+            ("trigger_delay", "f32"),
+            ("flags", "u32"),
+        ],
+    },
+    Layout {
+        name: "ActorAttribsDescriptor",
+        fields: &[
+            ("health", "f32"),
+            ("walk_speed", "f32"),
+            ("run_speed", "f32"),
+            ("turn_speed", "f32"),
+            ("attack_damage", "f32"),
+            ("attack_range", "f32"),
+            ("sight_range", "f32"),
+            ("behavior_flags", "u32"),
+            ("team", "u32"),
+            ("unknown_0x24", "u32"),
+            ("unknown_0x28", "u32"),
+            ("unknown_0x2c", "u32"),
+        ],
+    },
+    Layout {
+        name: "EmitterDescriptor",
+        fields: &[
+            ("particle", "u8[128]"),
+            ("emission_rate", "f32"),
+            ("max_particles", "u32"),
+            ("duration", "f32"),
+            ("offset", "f32[3]"),
+            ("flags", "u32"),
+        ],
+    },
+    Layout {
+        name: "ParticleDescriptor",
+        fields: &[
+            ("texture", "u8[128]"),
+            ("lifetime", "f32[2]"),
+            ("size", "f32[2]"),
+            ("colour", "u32[2]"),
+            ("velocity", "f32[3]"),
+            ("gravity", "f32"),
+            ("flags", "u32"),
+        ],
+    },
+    Layout {
+        name: "MarkerDescriptor",
+        fields: &[("point_count", "u32"), ("points_offset", "u32")],
+    },
+    Layout {
+        name: "MarkerPoint",
+        fields: &[
+            ("name", "u8[32]"),
+            ("position", "f32[3]"),
+            ("rotation", "f32[3]"),
+            ("marker_type", "u32"),
+        ],
+    },
+    Layout {
+        name: "EventsDescriptor",
+        fields: &[("event_count", "u32"), ("events_offset", "u32")],
+    },
+    Layout {
+        name: "Event",
+        fields: &[("time", "f32"), ("opcode", "u32"), ("params", "u32[4]")],
+    },
+    Layout {
+        name: "RumbleDescriptor",
+        fields: &[
+            ("duration", "f32"),
+            ("flags", "u32"),
+            ("keyframe_count", "u32"),
+            ("keyframes_offset", "u32"),
+        ],
+    },
+    Layout {
+        name: "RumbleKeyframe",
+        fields: &[
+            ("time", "f32"),
+            ("low_frequency", "f32"),
+            ("high_frequency", "f32"),
+        ],
+    },
+    Layout {
+        name: "ShakeCamDescriptor",
+        fields: &[
+            ("duration", "f32"),
+            ("frequency", "f32"),
+            ("radius", "f32"),
+            ("keyframe_count", "u32"),
+            ("keyframes_offset", "u32"),
+        ],
+    },
+    Layout {
+        name: "ShakeKeyframe",
+        fields: &[
+            ("time", "f32"),
+            ("translation", "f32[3]"),
+            ("rotation", "f32"),
+        ],
+    },
+    Layout {
+        name: "AidListDescriptor",
+        fields: &[("aid_count", "u32"), ("aids_offset", "u32")],
+    },
+];
+
+/// Returns the layout with the given name, such as `TextureDescriptor`.
+pub(crate) fn find_layout(name: &str) -> Option<&'static Layout> {
+    LAYOUTS.iter().find(|layout| layout.name == name)
+}
+
+/// Returns the name of the layout of the descriptors of `asset_type`, if it is known.
+pub(crate) fn descriptor_layout(asset_type: AssetType) -> Option<&'static str> {
+    Some(match asset_type {
+        AssetType::ResTexture => "TextureDescriptor",
+        AssetType::ResModel => "ModelDescriptor",
+        AssetType::ResAnim => "AnimationDescriptor",
+        AssetType::ResLoctext => "LoctextDescriptor",
+        AssetType::ResFont => "FontDescriptor",
+        AssetType::ResXSoundbank => "SoundbankDescriptor",
+        AssetType::ResGhoulybox => "GhoulyboxDescriptor",
+        AssetType::ResGhoulyspawn => "GhoulyspawnDescriptor",
+        AssetType::ResActorAttribs => "ActorAttribsDescriptor",
+        AssetType::ResEmitter => "EmitterDescriptor",
+        AssetType::ResParticle => "ParticleDescriptor",
+        AssetType::ResMarker => "MarkerDescriptor",
+        AssetType::ResAidList => "AidListDescriptor",
+        AssetType::ResAnimEvents | AssetType::ResCutsceneEvents => "EventsDescriptor",
+        AssetType::ResRumble => "RumbleDescriptor",
+        AssetType::ResShakeCam => "ShakeCamDescriptor",
+        _ => return None,
+    })
+}
+
+/// Splits a field type such as `u8[128]` into its element type and count.
+pub(crate) fn split_type(ty: &str) -> (&str, usize) {
+    match ty.split_once('[') {
+        Some((base, count)) => (base, count.trim_end_matches(']').parse().unwrap_or(1)),
+        None => (ty, 1),
+    }
+}
+
+/// Returns the size in bytes of a field type, such as `u32` or `u8[128]`.
+pub(crate) fn type_size(ty: &str) -> usize {
+    let (base, count) = split_type(ty);
+
+    let size = match base {
+        "u8" | "i8" => 1,
+        "u16" | "i16" => 2,
+        _ => 4,
+    };
+
+    size * count
+}
+
+impl Layout {
+    fn to_json(&self) -> Value {
+        let mut offset = 0;
+
+        let fields: Vec<Value> = self
+            .fields
+            .iter()
+            .map(|(name, ty)| {
+                let field = json!({ "name": name, "type": ty, "offset": offset });
+                offset += type_size(ty);
+                field
+            })
+            .collect();
+
+        json!({ "name": self.name, "size": offset, "fields": fields })
+    }
+}
+
+/// Returns the schema of every known structure and ID table as JSON.
+///
+/// # Examples
+/// ```
+/// let schema = bnl::schema::to_json();
+/// println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+/// ```
+pub fn to_json() -> Value {
+    let asset_types: Vec<Value> = (0..AssetType::ResCount as u32)
+        .filter_map(|id| AssetType::try_from(id).ok())
+        .map(|asset_type| json!({ "id": u32::from(asset_type), "name": format!("{:?}", asset_type) }))
+        .collect();
+
+    let texture_formats: Vec<Value> = TEXTURE_FORMATS
+        .iter()
+        .map(|(id, format)| {
+            json!({
+                "id": id,
+                "format": format!("{:?}", format),
+                "bits_per_pixel": format.bits_per_pixel(),
+            })
+        })
+        .collect();
+
+    let model_subresource_types: Vec<Value> = (0..=u8::MAX as u32)
+        .filter_map(|id| ModelSubresType::try_from(id).ok())
+        .map(|subres_type| {
+            json!({ "id": u32::from(subres_type), "name": format!("{:?}", subres_type) })
+        })
+        .collect();
+
+    json!({
+        "asset_types": asset_types,
+        "texture_formats": texture_formats,
+        "model_subresource_types": model_subresource_types,
+        "layouts": LAYOUTS.iter().map(Layout::to_json).collect::<Vec<_>>(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_sizes_match_parsers() {
+        let schema = to_json();
+
+        let size = |name: &str| {
+            schema["layouts"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|layout| layout["name"] == name)
+                .unwrap()["size"]
+                .as_u64()
+                .unwrap() as usize
+        };
+
+        assert_eq!(size("BNLHeader"), crate::BNL_HEADER_SIZE);
+        assert_eq!(size("AssetDescription"), 160);
+        assert_eq!(size("TextureDescriptor"), 28);
+        assert_eq!(
+            size("MeshDescriptor"),
+            crate::asset::model::mesh::MESH_DESCRIPTOR_SIZE
+        );
+        assert_eq!(size("Bone"), crate::asset::model::skeleton::BONE_SIZE);
+        assert_eq!(
+            size("Material"),
+            crate::asset::model::subresource::MATERIAL_SIZE
+        );
+        assert_eq!(
+            size("VertexBufferDescriptor"),
+            crate::asset::model::subresource::VERTEX_BUFFER_SIZE
+        );
+        assert_eq!(
+            size("IndexBufferDescriptor"),
+            crate::asset::model::subresource::INDEX_BUFFER_SIZE
+        );
+        assert_eq!(size("Glyph"), 16);
+        assert_eq!(
+            size("ActorAttribsDescriptor"),
+            crate::asset::actor_attribs::ACTOR_ATTRIBS_DESCRIPTOR_SIZE
+        );
+        assert_eq!(
+            size("EmitterDescriptor"),
+            crate::asset::particle::EMITTER_DESCRIPTOR_SIZE
+        );
+        assert_eq!(
+            size("ParticleDescriptor"),
+            crate::asset::particle::PARTICLE_DESCRIPTOR_SIZE
+        );
+        assert_eq!(size("MarkerPoint"), crate::asset::marker::MARKER_POINT_SIZE);
+        assert_eq!(
+            size("EventsDescriptor"),
+            crate::asset::events::EVENTS_DESCRIPTOR_SIZE
+        );
+        assert_eq!(size("Event"), crate::asset::events::EVENT_SIZE);
+        assert_eq!(
+            size("RumbleDescriptor"),
+            crate::asset::feedback::RUMBLE_DESCRIPTOR_SIZE
+        );
+        assert_eq!(
+            size("RumbleKeyframe"),
+            crate::asset::feedback::RUMBLE_KEYFRAME_SIZE
+        );
+        assert_eq!(
+            size("ShakeCamDescriptor"),
+            crate::asset::feedback::SHAKE_CAM_DESCRIPTOR_SIZE
+        );
+        assert_eq!(
+            size("ShakeKeyframe"),
+            crate::asset::feedback::SHAKE_KEYFRAME_SIZE
+        );
+        assert_eq!(
+            size("AidListDescriptor"),
+            crate::asset::aid_list::AID_LIST_DESCRIPTOR_SIZE
+        );
+
+        assert_eq!(schema["asset_types"][0]["name"], "ResTexture");
+        assert_eq!(schema["texture_formats"].as_array().unwrap().len(), 15);
+    }
+}
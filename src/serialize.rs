@@ -0,0 +1,265 @@
+//! Controls how a [`BNLFile`] is compressed when it is written back out.
+
+use std::io::Write;
+
+use byteorder::{LittleEndian, WriteBytesExt};
+
+use crate::{BNL_HEADER_SIZE, BNLError, BNLFile, BNLFlags, Compression, DataView};
+
+/// The highest zlib compression level accepted by [`SerializeOptions`].
+pub const MAX_COMPRESSION_LEVEL: u8 = 9;
+
+/// Settings for [`BNLFile::to_bytes_with_options`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SerializeOptions {
+    /// The zlib compression level, from 0 (stored without compression) to
+    /// [`MAX_COMPRESSION_LEVEL`]. Level 0 still wraps the data in a zlib stream, so the game can
+    /// read it like any other bundle.
+    pub level: u8,
+    /// How to store the body of the file. When unset, the body is stored the same way as the
+    /// bundle that was parsed, see [`BNLFile::compression`].
+    pub compression: Option<Compression>,
+}
+
+impl Default for SerializeOptions {
+    fn default() -> Self {
+        SerializeOptions {
+            level: 1,
+            compression: None,
+        }
+    }
+}
+
+impl SerializeOptions {
+    pub fn level(mut self, level: u8) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Stores the data without compressing it, see [`SerializeOptions::level`].
+    pub fn stored(self) -> Self {
+        self.level(0)
+    }
+
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+}
+
+/// The sizes of a serialised [`BNLFile`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerializeReport {
+    /// The size of everything after the header before it was compressed.
+    pub uncompressed_size: usize,
+    /// The size of the whole file, including the header.
+    pub file_size: usize,
+    /// How the body of the file was stored.
+    pub compression: Compression,
+}
+
+impl SerializeReport {
+    /// The size of the compressed data relative to the uncompressed data.
+    pub fn ratio(&self) -> f64 {
+        match self.uncompressed_size {
+            0 => 1.0,
+            size => (self.file_size - BNL_HEADER_SIZE) as f64 / size as f64,
+        }
+    }
+}
+
+impl BNLFile {
+    /**
+    Serialises this [`BNLFile`] like [`BNLFile::to_bytes`], compressed with the given
+    [`SerializeOptions`]. The sizes of the result are returned alongside it, so tools can compare
+    a repacked bundle against the original.
+
+    # Errors
+    - [`BNLError::DataWriteError`] when a section no longer fits in the location given by the
+      header, or the compression level is above [`MAX_COMPRESSION_LEVEL`]
+
+    # Examples
+    ```
+    use bnl::{BNLFile, serialize::SerializeOptions};
+
+    let bnl = BNLFile::new();
+    let (bytes, report) = bnl
+        .to_bytes_with_options(&SerializeOptions::default().level(9))
+        .expect("Unable to serialise BNL.");
+
+    println!("Compressed to {:.1}%", report.ratio() * 100.0);
+    ```
+    */
+    pub fn to_bytes_with_options(
+        &self,
+        options: &SerializeOptions,
+    ) -> Result<(Vec<u8>, SerializeReport), BNLError> {
+        if options.level > MAX_COMPRESSION_LEVEL {
+            return Err(BNLError::DataWriteError(format!(
+                "Compression level {} is above the maximum of {}",
+                options.level, MAX_COMPRESSION_LEVEL
+            )));
+        }
+
+        let compression = options.compression.unwrap_or(self.compression);
+
+        // Keep the flags consistent with how the body is actually stored
+        let mut flags = self.header.flags;
+        flags.set(
+            BNLFlags::UNCOMPRESSED,
+            compression == Compression::Uncompressed,
+        );
+
+        let mut bytes: Vec<u8> = Vec::with_capacity(BNL_HEADER_SIZE);
+
+        bytes.write_u16::<LittleEndian>(self.header.file_count)?;
+        bytes.write_u8(flags.bits())?;
+        bytes.write_all(&self.header.unknown_2)?;
+
+        let asset_desc_bytes: Vec<u8> = self
+            .asset_descriptions
+            .iter()
+            .flat_map(|desc| desc.to_bytes())
+            .collect();
+
+        let sections: [(&DataView, &[u8]); 4] = [
+            (&self.header.asset_desc_loc, &asset_desc_bytes),
+            (&self.header.buffer_views_loc, &self.buffer_views_bytes),
+            (&self.header.buffer_loc, &self.buffer_bytes),
+            (&self.header.descriptor_loc, &self.descriptor_bytes),
+        ];
+
+        for (loc, _) in &sections {
+            bytes.write_u32::<LittleEndian>(loc.offset)?;
+            bytes.write_u32::<LittleEndian>(loc.size)?;
+        }
+
+        let end = sections
+            .iter()
+            .map(|(loc, _)| loc.offset as usize + loc.size as usize)
+            .max()
+            .unwrap_or(BNL_HEADER_SIZE)
+            .max(BNL_HEADER_SIZE);
+
+        let mut decompressed_bytes = vec![0u8; end - BNL_HEADER_SIZE];
+
+        for (loc, section) in &sections {
+            let offset = loc.offset as usize;
+
+            if offset < BNL_HEADER_SIZE || section.len() != loc.size as usize {
+                return Err(BNLError::DataWriteError(format!(
+                    "Section of size {} does not fit in the header location {:?}",
+                    section.len(),
+                    loc
+                )));
+            }
+
+            let start = offset - BNL_HEADER_SIZE;
+            decompressed_bytes[start..start + section.len()].copy_from_slice(section);
+        }
+
+        match compression {
+            Compression::Zlib => bytes.extend_from_slice(
+                &miniz_oxide::deflate::compress_to_vec_zlib(&decompressed_bytes, options.level),
+            ),
+            Compression::Uncompressed => bytes.extend_from_slice(&decompressed_bytes),
+        }
+
+        let report = SerializeReport {
+            uncompressed_size: decompressed_bytes.len(),
+            file_size: bytes.len(),
+            compression,
+        };
+
+        Ok((bytes, report))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::make_bnl;
+
+    #[test]
+    fn compression_levels_round_trip() {
+        let bnl = BNLFile::from_bytes(&make_bnl()).unwrap();
+
+        let (stored, stored_report) = bnl
+            .to_bytes_with_options(&SerializeOptions::default().stored())
+            .unwrap();
+        let (best, best_report) = bnl
+            .to_bytes_with_options(&SerializeOptions::default().level(9))
+            .unwrap();
+
+        assert_eq!(stored_report.file_size, stored.len());
+        assert!(stored_report.ratio() > 1.0);
+        assert!(best_report.file_size < stored_report.file_size);
+        assert_eq!(
+            best_report.uncompressed_size,
+            stored_report.uncompressed_size
+        );
+
+        for bytes in [stored, best] {
+            let reparsed = BNLFile::from_bytes(&bytes).unwrap();
+            assert_eq!(
+                reparsed
+                    .get_raw_asset("aid_texture_test")
+                    .unwrap()
+                    .data_slices,
+                [[1, 2, 3, 4]]
+            );
+        }
+
+        assert!(
+            bnl.to_bytes_with_options(&SerializeOptions::default().level(10))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn uncompressed_round_trip() {
+        let bnl = BNLFile::from_bytes(&make_bnl()).unwrap();
+        assert_eq!(bnl.compression(), Compression::Zlib);
+
+        let (bytes, report) = bnl
+            .to_bytes_with_options(
+                &SerializeOptions::default().compression(Compression::Uncompressed),
+            )
+            .unwrap();
+        assert_eq!(report.file_size, BNL_HEADER_SIZE + report.uncompressed_size);
+        assert!(BNLFlags::from_bits(bytes[2]).contains(BNLFlags::UNCOMPRESSED));
+
+        let uncompressed = BNLFile::from_bytes(&bytes).unwrap();
+        assert_eq!(uncompressed.compression(), Compression::Uncompressed);
+        assert_eq!(
+            uncompressed
+                .get_raw_asset("aid_texture_test")
+                .unwrap()
+                .data_slices,
+            [[1, 2, 3, 4]]
+        );
+        assert_eq!(
+            BNLFile::read_asset_descriptions(&bytes).unwrap()[0].name(),
+            "aid_texture_test"
+        );
+
+        // Written back out the same way it was read, until asked otherwise
+        assert_eq!(uncompressed.to_bytes().unwrap(), bytes);
+
+        let recompressed = uncompressed
+            .to_bytes_with_options(&SerializeOptions::default().compression(Compression::Zlib));
+        let (recompressed, _) = recompressed.unwrap();
+        assert_eq!(recompressed[2], 0);
+        assert_eq!(
+            BNLFile::from_bytes(&recompressed).unwrap().compression(),
+            Compression::Zlib
+        );
+
+        // Too short to hold its sections either way
+        assert!(matches!(
+            BNLFile::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(BNLError::DecompressionFailure)
+        ));
+    }
+}
@@ -0,0 +1,248 @@
+//! Reports on how much of each section of a [`crate::BNLFile`] is used by its assets.
+
+use std::ops::Range;
+
+/// The used and unused bytes of one section of a BNL file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SectionSpace {
+    /// The total size of the section in bytes.
+    pub size: usize,
+    /// The number of bytes referenced by at least one asset.
+    pub used: usize,
+    /// The size of the largest run of bytes that no asset references.
+    pub largest_free_block: usize,
+}
+
+impl SectionSpace {
+    /// Summarises the free ranges of a [`SectionMap`].
+    pub(crate) fn from_map(map: &SectionMap) -> SectionSpace {
+        SectionSpace {
+            size: map.size(),
+            used: map.used(),
+            largest_free_block: map
+                .free_ranges()
+                .iter()
+                .map(ExactSizeIterator::len)
+                .max()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns the number of bytes that no asset references.
+    pub fn free(&self) -> usize {
+        self.size - self.used
+    }
+
+    /// Returns true if `size` bytes could be placed in one contiguous unused block of this section.
+    pub fn fits(&self, size: usize) -> bool {
+        size <= self.largest_free_block
+    }
+}
+
+/// The unused bytes in each section of a BNL file, as returned by [`crate::BNLFile::free_space`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FreeSpace {
+    /// The section holding asset descriptors.
+    pub descriptor: SectionSpace,
+    /// The section holding the data view lists of each asset.
+    pub buffer_views: SectionSpace,
+    /// The section holding the resource data of each asset.
+    pub buffer: SectionSpace,
+}
+
+/// The free byte ranges of one section of a BNL file, as found by [`crate::BNLFile::space_report`].
+/// Within the crate it doubles as an allocator for placing new data in the section.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SectionMap {
+    size: usize,
+    /// Sorted, non-overlapping and non-adjacent ranges that no asset references.
+    free: Vec<Range<usize>>,
+}
+
+impl SectionMap {
+    /// Builds a [`SectionMap`] for a section of `size` bytes, given the byte ranges used by the
+    /// assets within it. Ranges may overlap, and anything past the end of the section is ignored.
+    pub(crate) fn from_ranges(size: usize, mut ranges: Vec<Range<usize>>) -> SectionMap {
+        ranges.sort_by_key(|range| range.start);
+
+        let mut free = Vec::new();
+        let mut cursor = 0;
+
+        for range in ranges {
+            let start = range.start.min(size);
+            let end = range.end.min(size);
+
+            if start > cursor {
+                free.push(cursor..start);
+            }
+
+            cursor = cursor.max(end);
+        }
+
+        if size > cursor {
+            free.push(cursor..size);
+        }
+
+        SectionMap { size, free }
+    }
+
+    /// Returns the total size of the section in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the byte ranges that no asset references, in order.
+    pub fn free_ranges(&self) -> &[Range<usize>] {
+        &self.free
+    }
+
+    /// Returns the number of bytes referenced by at least one asset.
+    pub fn used(&self) -> usize {
+        self.size - self.free.iter().map(ExactSizeIterator::len).sum::<usize>()
+    }
+
+    /// Returns the offset at which `size` bytes aligned to `align` would be placed by a best fit
+    /// allocation, which is the smallest free range that can hold them. Returns None if no free
+    /// range is large enough, in which case the section would have to grow.
+    pub fn best_fit(&self, size: usize, align: usize) -> Option<usize> {
+        self.free
+            .iter()
+            .filter_map(|range| {
+                let start = range.start.next_multiple_of(align.max(1));
+                (start + size <= range.end).then_some((range.len(), start))
+            })
+            .min()
+            .map(|(_, start)| start)
+    }
+
+    /// Reserves `size` bytes aligned to `align`, using the best fitting free range, or growing
+    /// the section past its end (reusing any free bytes at the end) if none fit. Returns the
+    /// offset of the reserved bytes, and the new size of the section, which is unchanged unless
+    /// it had to grow.
+    pub(crate) fn allocate(&mut self, size: usize, align: usize) -> (usize, usize) {
+        let start = match self.best_fit(size, align) {
+            Some(start) => start,
+            None => {
+                let end_of_data = match self.free.last() {
+                    Some(last) if last.end == self.size => last.start,
+                    _ => self.size,
+                };
+
+                let start = end_of_data.next_multiple_of(align.max(1));
+
+                // Any alignment padding left between the data and the new bytes stays free
+                if self.size < start + size {
+                    let old_size = self.size;
+
+                    self.size = start + size;
+                    self.release(old_size..self.size);
+                }
+
+                start
+            }
+        };
+
+        self.reserve(start..start + size);
+
+        (start, self.size)
+    }
+
+    /// Marks `range` as free.
+    pub(crate) fn release(&mut self, range: Range<usize>) {
+        let range = range.start..range.end.min(self.size);
+
+        if range.is_empty() {
+            return;
+        }
+
+        self.free.push(range);
+        self.free.sort_by_key(|range| range.start);
+
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(self.free.len());
+
+        for range in self.free.drain(..) {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+
+        self.free = merged;
+    }
+
+    /// Marks `range` as used.
+    fn reserve(&mut self, range: Range<usize>) {
+        self.free = self
+            .free
+            .iter()
+            .flat_map(|free| {
+                [
+                    free.start..free.end.min(range.start),
+                    free.start.max(range.end)..free.end,
+                ]
+            })
+            .filter(|piece| !piece.is_empty())
+            .collect();
+    }
+}
+
+/// The free byte ranges of each section of a BNL file, as returned by
+/// [`crate::BNLFile::space_report`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SpaceReport {
+    /// The section holding asset descriptors.
+    pub descriptor: SectionMap,
+    /// The section holding the data view lists of each asset.
+    pub buffer_views: SectionMap,
+    /// The section holding the resource data of each asset.
+    pub buffer: SectionMap,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping_ranges() {
+        let map = SectionMap::from_ranges(100, vec![40..60, 0..10, 50..70, 90..120]);
+        let space = SectionSpace::from_map(&map);
+
+        assert_eq!(map.free_ranges(), [10..40, 70..90]);
+
+        assert_eq!(space.used, 10 + 30 + 10);
+        assert_eq!(space.free(), 50);
+        assert_eq!(space.largest_free_block, 30);
+        assert!(space.fits(30));
+        assert!(!space.fits(31));
+    }
+
+    #[test]
+    fn allocates_best_fit_then_grows() {
+        let mut map = SectionMap::from_ranges(100, vec![10..20, 40..90]);
+
+        assert_eq!(map.free_ranges(), [0..10, 20..40, 90..100]);
+        assert_eq!(map.used(), 60);
+
+        // 8 bytes fit in 0..10 and 90..100, and the first of the two smallest is picked
+        assert_eq!(map.best_fit(8, 1), Some(0));
+        assert_eq!(map.allocate(8, 1), (0, 100));
+
+        // Alignment is applied within the free range
+        assert_eq!(map.allocate(8, 16), (32, 100));
+        assert_eq!(map.free_ranges(), [8..10, 20..32, 90..100]);
+
+        // Nothing fits 16 bytes, so the section grows, reusing the free bytes at its end
+        assert_eq!(map.best_fit(16, 1), None);
+        assert_eq!(map.allocate(16, 1), (90, 106));
+        assert_eq!(map.size(), 106);
+
+        // Growing past alignment padding leaves the padding free
+        assert_eq!(map.allocate(4, 16), (112, 116));
+        assert_eq!(map.free_ranges(), [8..10, 20..32, 106..112]);
+
+        map.release(100..112);
+        assert_eq!(map.free_ranges(), [8..10, 20..32, 100..112]);
+        map.release(0..8);
+        assert_eq!(map.free_ranges(), [0..10, 20..32, 100..112]);
+    }
+}
@@ -0,0 +1,73 @@
+//! The supported public API of this crate.
+//!
+//! Everything re-exported here follows semantic versioning: it won't be removed or changed
+//! incompatibly without a major version bump, no matter how the modules behind it are
+//! reorganised. Items that are only reachable through their own modules may change in any
+//! release.
+//!
+//! To leave room for growth, the error enums re-exported here are `#[non_exhaustive]`, so matches
+//! on them need a wildcard arm, and options structs are built from their `Default` through their
+//! builder methods rather than with struct literals.
+//!
+//! # Examples
+//! ```no_run
+//! use std::path::Path;
+//!
+//! use bnl::stable::{BNLFile, ExtractOptions};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let bytes = std::fs::read("./common.bnl")?;
+//! let bnl = BNLFile::from_bytes(&bytes)?;
+//!
+//! let options = ExtractOptions::default().threads(4).png_textures(true);
+//! bnl.extract_all(Path::new("./out/common_bnl"), None, &options)?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub use crate::{
+    BNLError, BNLFile, BNLFlags, Compression, DataView, HeaderWarning, VirtualResource,
+    VirtualResourceError,
+    asset::{
+        Asset, AssetDescription, AssetDescriptor, AssetError, AssetParseError, DataViewList,
+        DescriptorTemplate, RawAsset, RawAssetRef, WritableAsset,
+        actor_attribs::ActorAttribs,
+        aid_list::AidList,
+        anim::Animation,
+        events::{AnimEvents, CutsceneEvents, Event, EventsDescriptor},
+        export::{ExportError, ExportReport, Exporter, ExporterRegistry},
+        feedback::{
+            Rumble, RumbleDescriptor, RumbleKeyframe, ShakeCam, ShakeCamDescriptor, ShakeKeyframe,
+        },
+        font::Font,
+        ghoulybox::{Ghoulybox, Ghoulyspawn},
+        handle::AssetMut,
+        id::{AssetId, AssetIdError},
+        loctext::{Loctext, LoctextError},
+        marker::Marker,
+        model::Model,
+        particle::{Emitter, Particle},
+        registry::{AnyAsset, AnyAssetParser, AssetRegistry},
+        soundbank::Soundbank,
+        texture::{
+            AlphaMode, DumpOptions, DxtAlignment, ExportFormat, Image, PngBitDepth, PngColour,
+            Texture, TextureDescriptor, TextureDumpError, TextureImportError, TextureImportOptions,
+            atlas::{Atlas, AtlasEntry, AtlasOptions},
+        },
+    },
+    batch::{BatchOptions, BatchResult, find_assets, process_dir},
+    constraints::ConstraintViolation,
+    contact_sheet::ContactSheetOptions,
+    d3d::{D3DFormat, LinearColour, LinearLuminance, StandardFormat, Swizzled},
+    extract::{ExtractJournal, ExtractOptions, ExtractSummary},
+    filter::AssetFilter,
+    format::BundleFormat,
+    game::AssetType,
+    hooks::{AssetHooks, HookAction},
+    manifest::{Manifest, ManifestEntry},
+    serialize::{SerializeOptions, SerializeReport},
+    space::{FreeSpace, SectionMap, SectionSpace, SpaceReport},
+    stats::{AssetSize, BundleStats, TypeStats},
+    validate::{Issue, IssueKind, Severity, ValidationReport},
+    workspace::{Bundle, Workspace, WorkspaceError},
+};
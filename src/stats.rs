@@ -0,0 +1,193 @@
+//! Summaries of where the bytes of a [`BNLFile`] go, for deciding which assets are worth
+//! optimising.
+
+use crate::{
+    BNL_HEADER_SIZE, BNLFile,
+    asset::{AssetDescriptor, texture::TextureDescriptor},
+    d3d::D3DFormat,
+    game::AssetType,
+};
+
+/// The number of assets listed in [`BundleStats::largest`].
+pub const LARGEST_ASSET_COUNT: usize = 10;
+
+/// The assets of one type in a [`BNLFile`], see [`BundleStats::types`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypeStats {
+    pub asset_type: AssetType,
+    pub count: usize,
+    /// The combined size of the descriptors of these assets.
+    pub descriptor_bytes: usize,
+    /// The combined size of the resources of these assets.
+    pub resource_bytes: usize,
+}
+
+impl TypeStats {
+    pub fn total_bytes(&self) -> usize {
+        self.descriptor_bytes + self.resource_bytes
+    }
+}
+
+/// The size of one asset, as listed in [`BundleStats::largest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetSize {
+    pub name: String,
+    pub asset_type: AssetType,
+    /// The size of the asset's descriptor and resource together.
+    pub size: usize,
+}
+
+/// A summary of the assets in a [`BNLFile`] and the space they take up, see [`BNLFile::stats`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleStats {
+    pub asset_count: usize,
+    /// The assets of each type present, largest first.
+    pub types: Vec<TypeStats>,
+    /// The number of textures stored in each format, most common first. Textures whose
+    /// descriptors can't be parsed aren't counted.
+    pub texture_formats: Vec<(D3DFormat, usize)>,
+    /// The [`LARGEST_ASSET_COUNT`] largest assets, largest first.
+    pub largest: Vec<AssetSize>,
+    /// The size of the file before compression, including the header.
+    pub uncompressed_size: usize,
+    /// The size of the file the bundle was parsed from, or None if it was built in memory.
+    pub file_size: Option<usize>,
+}
+
+impl BundleStats {
+    /// The size of the parsed file relative to its uncompressed size, or None if the bundle wasn't
+    /// parsed from a file.
+    pub fn ratio(&self) -> Option<f64> {
+        match (self.file_size, self.uncompressed_size) {
+            (None, _) => None,
+            (Some(_), 0) => Some(1.0),
+            (Some(file_size), size) => Some(file_size as f64 / size as f64),
+        }
+    }
+}
+
+impl BNLFile {
+    /// Summarises the assets of this [`BNLFile`] by type and size, along with the formats of its
+    /// textures and how well the file was compressed.
+    ///
+    /// # Examples
+    /// ```
+    /// # let bnl_file = bnl::BNLFile::new();
+    /// let stats = bnl_file.stats();
+    ///
+    /// for type_stats in &stats.types {
+    ///     println!("{:?}: {} bytes", type_stats.asset_type, type_stats.total_bytes());
+    /// }
+    /// ```
+    pub fn stats(&self) -> BundleStats {
+        let mut types: Vec<TypeStats> = Vec::new();
+        let mut texture_formats: Vec<(D3DFormat, usize)> = Vec::new();
+        let mut largest = Vec::with_capacity(self.asset_descriptions.len());
+
+        for asset_desc in &self.asset_descriptions {
+            let asset_type = asset_desc.asset_type();
+            let descriptor_bytes = asset_desc.descriptor_size() as usize;
+            let resource_bytes = asset_desc.resource_size() as usize;
+
+            let index = match types.iter().position(|t| t.asset_type == asset_type) {
+                Some(index) => index,
+                None => {
+                    types.push(TypeStats {
+                        asset_type,
+                        count: 0,
+                        descriptor_bytes: 0,
+                        resource_bytes: 0,
+                    });
+                    types.len() - 1
+                }
+            };
+
+            types[index].count += 1;
+            types[index].descriptor_bytes += descriptor_bytes;
+            types[index].resource_bytes += resource_bytes;
+
+            largest.push(AssetSize {
+                name: asset_desc.name().to_string(),
+                asset_type,
+                size: descriptor_bytes + resource_bytes,
+            });
+
+            if asset_type != AssetType::ResTexture {
+                continue;
+            }
+
+            let Some(format) = self
+                .asset_descriptor_bytes(asset_desc)
+                .ok()
+                .and_then(|bytes| TextureDescriptor::from_bytes(bytes).ok())
+                .map(|descriptor| descriptor.format())
+            else {
+                continue;
+            };
+
+            match texture_formats.iter_mut().find(|(f, _)| *f == format) {
+                Some((_, count)) => *count += 1,
+                None => texture_formats.push((format, 1)),
+            }
+        }
+
+        types.sort_by_key(|t| std::cmp::Reverse(t.total_bytes()));
+        texture_formats.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        largest.sort_by_key(|asset| std::cmp::Reverse(asset.size));
+        largest.truncate(LARGEST_ASSET_COUNT);
+
+        let uncompressed_size = [
+            &self.header.asset_desc_loc,
+            &self.header.buffer_views_loc,
+            &self.header.buffer_loc,
+            &self.header.descriptor_loc,
+        ]
+        .iter()
+        .map(|loc| loc.offset as usize + loc.size as usize)
+        .max()
+        .unwrap_or(0)
+        .max(BNL_HEADER_SIZE);
+
+        BundleStats {
+            asset_count: self.asset_descriptions.len(),
+            types,
+            texture_formats,
+            largest,
+            uncompressed_size,
+            file_size: self.file_size,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{asset::RawAsset, tests::make_bnl};
+
+    #[test]
+    fn summarises_assets() {
+        let mut bnl = BNLFile::from_bytes(&make_bnl()).unwrap();
+
+        bnl.add_asset(&RawAsset {
+            name: "aid_script_a".to_string(),
+            asset_type: AssetType::ResScript,
+            descriptor_bytes: vec![0; 4],
+            data_slices: vec![vec![0; 100]],
+        })
+        .unwrap();
+
+        let stats = bnl.stats();
+
+        assert_eq!(stats.asset_count, 2);
+        assert_eq!(stats.types[0].asset_type, AssetType::ResScript);
+        assert_eq!(stats.types[0].total_bytes(), 104);
+        assert_eq!(stats.types[1].count, 1);
+        assert_eq!(stats.largest[0].name, "aid_script_a");
+        assert_eq!(stats.texture_formats.len(), 1);
+        assert_eq!(stats.texture_formats[0].1, 1);
+        assert_eq!(stats.file_size, Some(make_bnl().len()));
+        assert!(stats.uncompressed_size > BNL_HEADER_SIZE);
+
+        assert_eq!(BNLFile::new().stats().ratio(), None);
+    }
+}
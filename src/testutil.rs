@@ -0,0 +1,144 @@
+//! Tiny synthetic bundles for tests, so the format can be exercised without shipping the game's
+//! own copyrighted data. Available outside this crate with the `test-util` feature.
+//!
+//! Everything here is deterministic: the same calls always give the same bytes, so the results
+//! can be compared against golden files.
+//!
+//! # Examples
+//! ```
+//! use bnl::testutil;
+//!
+//! let bnl = testutil::sample_bnl();
+//! assert!(bnl.asset_description(testutil::SAMPLE_SCRIPT).is_some());
+//! ```
+
+use crate::{
+    BNLFile,
+    asset::{
+        RawAsset,
+        texture::{Image, Texture, TextureImportOptions},
+    },
+    d3d::{D3DFormat, StandardFormat, Swizzled},
+    game::AssetType,
+};
+
+/// The names of the textures in [`sample_bnl`], a 4x4 swizzled B8G8R8A8 texture and an 8x8 DXT1
+/// texture.
+pub const SAMPLE_TEXTURES: [&str; 2] = ["aid_texture_sample_a", "aid_texture_sample_b"];
+
+/// The name of the script in [`sample_bnl`].
+pub const SAMPLE_SCRIPT: &str = "aid_script_sample";
+
+/// Builds a `width` by `height` checkerboard image, alternating between `a` and `b` every pixel.
+pub fn checkerboard(width: usize, height: usize, a: [u8; 4], b: [u8; 4]) -> Image {
+    let bytes = (0..width * height)
+        .flat_map(|i| match (i % width + i / width) % 2 {
+            0 => a,
+            _ => b,
+        })
+        .collect();
+
+    Image::from_rgba8(width, height, bytes).expect("The image size matches its bytes")
+}
+
+/// Builds a texture asset holding a checkerboard image in the given format.
+///
+/// # Panics
+/// Panics if the texture can't be encoded in `format`, such as DXT formats with dimensions that
+/// aren't multiples of 4.
+pub fn texture(name: &str, width: usize, height: usize, format: D3DFormat) -> RawAsset {
+    let image = checkerboard(width, height, [0xFF, 0, 0xFF, 0xFF], [0, 0, 0, 0xFF]);
+
+    Texture::from_image(name, &image, format, &TextureImportOptions::default())
+        .map_err(std::io::Error::from)
+        .and_then(|texture| texture.to_raw_asset())
+        .unwrap_or_else(|e| panic!("Unable to build texture {}: {}", name, e))
+}
+
+/// Builds a script asset with the given bytecode as its resource, and a descriptor holding the
+/// bytecode's length.
+pub fn script(name: &str, bytecode: &[u8]) -> RawAsset {
+    RawAsset {
+        name: name.to_string(),
+        asset_type: AssetType::ResScript,
+        descriptor_bytes: (bytecode.len() as u32).to_le_bytes().to_vec(),
+        data_slices: vec![bytecode.to_vec()],
+    }
+}
+
+/// Returns the assets of [`sample_bnl`]: two textures named [`SAMPLE_TEXTURES`] and a script
+/// named [`SAMPLE_SCRIPT`].
+pub fn sample_assets() -> Vec<RawAsset> {
+    vec![
+        texture(
+            SAMPLE_TEXTURES[0],
+            4,
+            4,
+            D3DFormat::Swizzled(Swizzled::B8G8R8A8),
+        ),
+        texture(
+            SAMPLE_TEXTURES[1],
+            8,
+            8,
+            D3DFormat::Standard(StandardFormat::DXT1),
+        ),
+        script(
+            SAMPLE_SCRIPT,
+            &[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08],
+        ),
+    ]
+}
+
+/// Builds a [`BNLFile`] holding the given assets, in order.
+///
+/// # Panics
+/// Panics if an asset can't be added, such as when its name is invalid.
+pub fn bnl_with(assets: &[RawAsset]) -> BNLFile {
+    let mut bnl = BNLFile::new();
+
+    for raw_asset in assets {
+        bnl.add_asset(raw_asset)
+            .unwrap_or_else(|e| panic!("Unable to add {}: {}", raw_asset.name, e));
+    }
+
+    bnl
+}
+
+/// Builds a [`BNLFile`] holding the [`sample_assets`].
+pub fn sample_bnl() -> BNLFile {
+    bnl_with(&sample_assets())
+}
+
+/// Returns [`sample_bnl`] serialised as a zlib compressed BNL file.
+pub fn sample_bnl_bytes() -> Vec<u8> {
+    sample_bnl()
+        .to_bytes()
+        .expect("The sample BNL file can be serialised")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::Asset;
+
+    #[test]
+    fn sample_bnl_is_deterministic() {
+        let bytes = sample_bnl_bytes();
+        assert_eq!(bytes, sample_bnl_bytes());
+
+        let bnl = BNLFile::from_bytes(&bytes).unwrap();
+        assert_eq!(bnl.asset_descriptions().len(), 3);
+
+        let texture: Texture = bnl.get_asset(SAMPLE_TEXTURES[0]).unwrap();
+        let image = texture.to_rgba_image().unwrap();
+        assert_eq!(image.bytes()[..8], [0xFF, 0, 0xFF, 0xFF, 0, 0, 0, 0xFF]);
+
+        let texture: Texture = bnl.get_asset(SAMPLE_TEXTURES[1]).unwrap();
+        assert_eq!(texture.descriptor().width(), 8);
+
+        let script = bnl.get_raw_asset(SAMPLE_SCRIPT).unwrap();
+        assert_eq!(script.descriptor_bytes, 8u32.to_le_bytes());
+        assert_eq!(script.data_slices.concat(), [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert!(bnl.validate().is_ok());
+    }
+}
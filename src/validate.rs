@@ -0,0 +1,510 @@
+//! Checks a parsed [`BNLFile`] for internal consistency, such as sections and data views that
+//! point outside of the file, or descriptions that disagree with the data they describe.
+//!
+//! Parsing only reads as much as it needs to, so a file that parses can still contain problems
+//! that break individual assets, or that only the game notices.
+
+use std::fmt::{self, Display};
+
+use crate::{BNLFile, DataView, constraints::ConstraintViolation, game::AssetType};
+
+/// How serious an [`Issue`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Unusual, but the game may still load the file.
+    Warning,
+    /// Part of the file can't be read correctly.
+    Error,
+}
+
+/// A single problem found by [`BNLFile::validate`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum IssueKind {
+    /// The header gives a section a different size to the data held for it.
+    SectionSizeMismatch {
+        section: &'static str,
+        header_size: u32,
+        actual_size: usize,
+    },
+    /// Two sections of the file overlap.
+    SectionsOverlap {
+        first: &'static str,
+        second: &'static str,
+    },
+    /// The file count in the header doesn't match the number of asset descriptions.
+    FileCountMismatch { header: u16, descriptions: usize },
+    /// An asset name isn't NUL terminated.
+    UnterminatedName,
+    /// An asset name isn't valid UTF-8.
+    InvalidName,
+    /// The category in an asset's name (such as `texture` in `aid_texture_gribble`) stands for a
+    /// different type to the one the asset has, see [`crate::asset::id::AssetId::expected_type`].
+    CategoryMismatch {
+        category: String,
+        expected: AssetType,
+        found: AssetType,
+    },
+    /// A descriptor ends past the end of the descriptor section.
+    DescriptorOutOfBounds { offset: u32, size: u32 },
+    /// A descriptor partially overlaps the descriptor of another asset. Assets sharing the exact
+    /// same descriptor aren't reported.
+    DescriptorsOverlap { other: String },
+    /// The data view list of an asset can't be read.
+    InvalidDataViewList { offset: u32, reason: String },
+    /// A data view ends past the end of the buffer section.
+    DataViewOutOfBounds { index: usize, view: DataView },
+    /// An engine or format limit is broken, see [`crate::constraints`].
+    Constraint(ConstraintViolation),
+}
+
+/// A problem found by [`BNLFile::validate`], along with the asset it was found in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub severity: Severity,
+    /// The asset the issue was found in, or `None` for problems with the file as a whole.
+    pub asset: Option<String>,
+    pub kind: IssueKind,
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+
+        write!(f, "{}: ", severity)?;
+
+        if let Some(asset) = &self.asset {
+            write!(f, "{}: ", asset)?;
+        }
+
+        match &self.kind {
+            IssueKind::SectionSizeMismatch {
+                section,
+                header_size,
+                actual_size,
+            } => write!(
+                f,
+                "The header gives the {} section a size of {} bytes, but it holds {}",
+                section, header_size, actual_size
+            ),
+            IssueKind::SectionsOverlap { first, second } => {
+                write!(f, "The {} and {} sections overlap", first, second)
+            }
+            IssueKind::FileCountMismatch {
+                header,
+                descriptions,
+            } => write!(
+                f,
+                "The header counts {} files, but there are {} asset descriptions",
+                header, descriptions
+            ),
+            IssueKind::UnterminatedName => write!(f, "The asset name is not NUL terminated"),
+            IssueKind::InvalidName => write!(f, "The asset name is not valid UTF-8"),
+            IssueKind::CategoryMismatch {
+                category,
+                expected,
+                found,
+            } => write!(
+                f,
+                "The {} category of the name is used for {:?} assets, but this is a {:?}",
+                category, expected, found
+            ),
+            IssueKind::DescriptorOutOfBounds { offset, size } => write!(
+                f,
+                "The descriptor at {:#x} ({} bytes) is out of bounds",
+                offset, size
+            ),
+            IssueKind::DescriptorsOverlap { other } => {
+                write!(f, "The descriptor overlaps the descriptor of {}", other)
+            }
+            IssueKind::InvalidDataViewList { offset, reason } => write!(
+                f,
+                "The data view list at {:#x} can't be read: {}",
+                offset, reason
+            ),
+            IssueKind::DataViewOutOfBounds { index, view } => write!(
+                f,
+                "Data view {} at {:#x} ({} bytes) is out of bounds of the buffer",
+                index,
+                view.offset(),
+                view.size()
+            ),
+            IssueKind::Constraint(violation) => write!(f, "{}", violation),
+        }
+    }
+}
+
+/// Every issue found by [`BNLFile::validate`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    pub issues: Vec<Issue>,
+}
+
+impl ValidationReport {
+    pub fn errors(&self) -> impl Iterator<Item = &Issue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Issue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Warning)
+    }
+
+    /// Returns true if no errors were found. Warnings are allowed.
+    pub fn is_ok(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    fn push(&mut self, severity: Severity, asset: Option<&str>, kind: IssueKind) {
+        self.issues.push(Issue {
+            severity,
+            asset: asset.map(str::to_string),
+            kind,
+        });
+    }
+}
+
+impl BNLFile {
+    /// Checks this [`BNLFile`] for internal consistency, returning every issue found.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bnl::BNLFile;
+    /// let bytes = std::fs::read("./common.bnl").expect("Unable to read BNL.");
+    /// let bnl_file = BNLFile::from_bytes(&bytes).expect("Unable to parse BNL.");
+    /// let report = bnl_file.validate();
+    ///
+    /// for issue in &report.issues {
+    ///     println!("{}", issue);
+    /// }
+    ///
+    /// assert!(report.is_ok());
+    /// ```
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+
+        self.validate_sections(&mut report);
+        self.validate_names(&mut report);
+        self.validate_descriptors(&mut report);
+        self.validate_dataviews(&mut report);
+
+        for violation in self
+            .check_resource_sizes()
+            .into_iter()
+            .chain(self.check_chunk_counts())
+        {
+            let asset = match &violation {
+                ConstraintViolation::ResourceSizeMismatch { aid, .. }
+                | ConstraintViolation::ChunkCountMismatch { aid, .. } => Some(aid.clone()),
+                _ => None,
+            };
+
+            report.issues.push(Issue {
+                severity: Severity::Warning,
+                asset,
+                kind: IssueKind::Constraint(violation),
+            });
+        }
+
+        if let Err(violation) = crate::constraints::check_asset_count(self.asset_descriptions.len())
+        {
+            report.push(Severity::Error, None, IssueKind::Constraint(violation));
+        }
+
+        report
+    }
+
+    fn validate_sections(&self, report: &mut ValidationReport) {
+        let header = &self.header;
+
+        if header.file_count as usize != self.asset_descriptions.len() {
+            report.push(
+                Severity::Error,
+                None,
+                IssueKind::FileCountMismatch {
+                    header: header.file_count,
+                    descriptions: self.asset_descriptions.len(),
+                },
+            );
+        }
+
+        let mut sections = [
+            (
+                "asset description",
+                &header.asset_desc_loc,
+                self.asset_descriptions.len() * size_of::<crate::AssetDescription>(),
+            ),
+            (
+                "data view",
+                &header.buffer_views_loc,
+                self.buffer_views_bytes.len(),
+            ),
+            ("buffer", &header.buffer_loc, self.buffer_bytes.len()),
+            (
+                "descriptor",
+                &header.descriptor_loc,
+                self.descriptor_bytes.len(),
+            ),
+        ];
+
+        for (section, loc, actual_size) in &sections {
+            if loc.size as usize != *actual_size {
+                report.push(
+                    Severity::Error,
+                    None,
+                    IssueKind::SectionSizeMismatch {
+                        section,
+                        header_size: loc.size,
+                        actual_size: *actual_size,
+                    },
+                );
+            }
+        }
+
+        sections.sort_by_key(|(_, loc, _)| loc.offset);
+
+        for pair in sections.windows(2) {
+            let (first, first_loc, _) = pair[0];
+            let (second, second_loc, _) = pair[1];
+
+            if first_loc.offset as u64 + first_loc.size as u64 > second_loc.offset as u64 {
+                report.push(
+                    Severity::Error,
+                    None,
+                    IssueKind::SectionsOverlap { first, second },
+                );
+            }
+        }
+    }
+
+    fn validate_names(&self, report: &mut ValidationReport) {
+        for desc in &self.asset_descriptions {
+            let Some(len) = desc.name.iter().position(|&b| b == 0) else {
+                report.push(
+                    Severity::Error,
+                    Some(desc.name()),
+                    IssueKind::UnterminatedName,
+                );
+                continue;
+            };
+
+            if std::str::from_utf8(&desc.name[..len]).is_err() {
+                report.push(
+                    Severity::Error,
+                    Some(&String::from_utf8_lossy(&desc.name[..len])),
+                    IssueKind::InvalidName,
+                );
+                continue;
+            }
+
+            // Names that don't follow the naming convention have no category to check
+            if let Ok(id) = desc.id()
+                && let Some(expected) = id.expected_type()
+                && expected != desc.asset_type
+            {
+                report.push(
+                    Severity::Warning,
+                    Some(desc.name()),
+                    IssueKind::CategoryMismatch {
+                        category: id.category().to_string(),
+                        expected,
+                        found: desc.asset_type,
+                    },
+                );
+            }
+        }
+    }
+
+    fn validate_descriptors(&self, report: &mut ValidationReport) {
+        let mut ranges = Vec::new();
+
+        for desc in &self.asset_descriptions {
+            let (offset, size) = (desc.descriptor_ptr, desc.descriptor_size);
+
+            if offset as usize + size as usize > self.descriptor_bytes.len() {
+                report.push(
+                    Severity::Error,
+                    Some(desc.name()),
+                    IssueKind::DescriptorOutOfBounds { offset, size },
+                );
+            } else if size > 0 {
+                ranges.push((offset, offset + size, desc.name()));
+            }
+        }
+
+        ranges.sort();
+
+        // The range reaching furthest into the section so far, which later ranges may overlap
+        let mut furthest: Option<(u32, u32, &str)> = None;
+
+        for (start, end, name) in ranges {
+            if let Some((other_start, other_end, other)) = furthest
+                && start < other_end
+                && (start, end) != (other_start, other_end)
+            {
+                report.push(
+                    Severity::Warning,
+                    Some(name),
+                    IssueKind::DescriptorsOverlap {
+                        other: other.to_string(),
+                    },
+                );
+            }
+
+            if furthest.is_none_or(|(_, other_end, _)| end > other_end) {
+                furthest = Some((start, end, name));
+            }
+        }
+    }
+
+    fn validate_dataviews(&self, report: &mut ValidationReport) {
+        for desc in &self.asset_descriptions {
+            let dvl = match self.get_dataview_list(desc.dataview_list_ptr as usize) {
+                Ok(dvl) => dvl,
+                Err(e) => {
+                    report.push(
+                        Severity::Error,
+                        Some(desc.name()),
+                        IssueKind::InvalidDataViewList {
+                            offset: desc.dataview_list_ptr,
+                            reason: e.to_string(),
+                        },
+                    );
+                    continue;
+                }
+            };
+
+            for (index, view) in dvl.views().iter().enumerate() {
+                if view.offset as usize + view.size as usize > self.buffer_bytes.len() {
+                    report.push(
+                        Severity::Error,
+                        Some(desc.name()),
+                        IssueKind::DataViewOutOfBounds { index, view: *view },
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asset::RawAsset,
+        game::AssetType,
+        tests::{bnl_from_raw_assets, make_bnl},
+    };
+
+    #[test]
+    fn valid_bnl_has_no_issues() {
+        let bnl = BNLFile::from_bytes(&make_bnl()).unwrap();
+
+        assert_eq!(bnl.validate(), ValidationReport::default());
+    }
+
+    #[test]
+    fn reports_broken_descriptions() {
+        let raw_asset = |name: &str| RawAsset {
+            name: name.to_string(),
+            asset_type: AssetType::ResScript,
+            descriptor_bytes: vec![0; 8],
+            data_slices: vec![vec![1, 2]],
+        };
+
+        let mut bnl = bnl_from_raw_assets(&[raw_asset("aid_script_a"), raw_asset("aid_script_b")]);
+
+        bnl.header.file_count = 3;
+        bnl.asset_descriptions[0].name = [b'a'; 128];
+        bnl.asset_descriptions[1].descriptor_ptr = 12;
+        bnl.asset_descriptions[1].resource_size = 100;
+
+        let report = bnl.validate();
+        assert!(!report.is_ok());
+
+        let kinds: Vec<&IssueKind> = report.issues.iter().map(|issue| &issue.kind).collect();
+
+        assert!(kinds.contains(&&IssueKind::FileCountMismatch {
+            header: 3,
+            descriptions: 2
+        }));
+        assert!(kinds.contains(&&IssueKind::UnterminatedName));
+        assert!(kinds.contains(&&IssueKind::DescriptorOutOfBounds {
+            offset: 12,
+            size: 8
+        }));
+        assert_eq!(report.warnings().count(), 1);
+    }
+
+    #[test]
+    fn reports_miscategorised_names() {
+        let raw_asset = |name: &str, asset_type| RawAsset {
+            name: name.to_string(),
+            asset_type,
+            descriptor_bytes: vec![0; 8],
+            data_slices: vec![vec![1, 2]],
+        };
+
+        let bnl = bnl_from_raw_assets(&[
+            raw_asset("aid_script_a", AssetType::ResScript),
+            raw_asset("aid_texture_b", AssetType::ResModel),
+            raw_asset("aid_thing_c", AssetType::ResModel),
+        ]);
+
+        let report = bnl.validate();
+        assert!(report.is_ok());
+        assert_eq!(
+            report.issues,
+            [Issue {
+                severity: Severity::Warning,
+                asset: Some("aid_texture_b".to_string()),
+                kind: IssueKind::CategoryMismatch {
+                    category: "texture".to_string(),
+                    expected: AssetType::ResTexture,
+                    found: AssetType::ResModel,
+                }
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_overlapping_descriptors() {
+        let mut bnl = bnl_from_raw_assets(&[
+            RawAsset {
+                name: "aid_script_a".to_string(),
+                asset_type: AssetType::ResScript,
+                descriptor_bytes: vec![0; 8],
+                data_slices: vec![],
+            },
+            RawAsset {
+                name: "aid_script_b".to_string(),
+                asset_type: AssetType::ResScript,
+                descriptor_bytes: vec![0; 8],
+                data_slices: vec![],
+            },
+        ]);
+
+        bnl.asset_descriptions[1].descriptor_ptr = 4;
+        bnl.descriptor_bytes.resize(12, 0);
+        bnl.header.descriptor_loc.size = 12;
+
+        let report = bnl.validate();
+        assert!(report.is_ok());
+        assert_eq!(
+            report.issues,
+            [Issue {
+                severity: Severity::Warning,
+                asset: Some("aid_script_b".to_string()),
+                kind: IssueKind::DescriptorsOverlap {
+                    other: "aid_script_a".to_string()
+                }
+            }]
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Bindings for reading BNL files from JavaScript through wasm-bindgen, so that a viewer can be
+//! built for the browser. Nothing here touches the filesystem, and everything is read from the
+//! bytes handed over by the page.
+//!
+//! A crate built for `wasm32-unknown-unknown` that depends on this one with the `wasm` feature
+//! exports these bindings as they are, for example:
+//! ```js
+//! const bnl = new BnlFile(new Uint8Array(await file.arrayBuffer()));
+//!
+//! for (const name of bnl.assetNames()) {
+//!     if (bnl.assetType(name) === "ResTexture") {
+//!         const image = bnl.textureRgba(name);
+//!         context.putImageData(
+//!             new ImageData(new Uint8ClampedArray(image.pixels()), image.width, image.height),
+//!             0,
+//!             0,
+//!         );
+//!     }
+//! }
+//! ```
+
+use wasm_bindgen::prelude::*;
+
+use crate::{BNLFile, asset::texture::Texture};
+
+/// A parsed BNL file, exported to JavaScript as `BnlFile`.
+#[wasm_bindgen(js_name = BnlFile)]
+pub struct WasmBnlFile {
+    bnl: BNLFile,
+}
+
+#[wasm_bindgen(js_class = BnlFile)]
+impl WasmBnlFile {
+    /// Parses a BNL file from its bytes, see [`BNLFile::from_bytes`].
+    #[wasm_bindgen(constructor)]
+    pub fn new(bytes: &[u8]) -> Result<WasmBnlFile, JsError> {
+        Ok(WasmBnlFile {
+            bnl: BNLFile::from_bytes(bytes)?,
+        })
+    }
+
+    /// Returns the name of every asset in the file, in the order of the asset table.
+    #[wasm_bindgen(js_name = assetNames)]
+    pub fn asset_names(&self) -> Vec<String> {
+        self.bnl
+            .asset_descriptions()
+            .iter()
+            .map(|desc| desc.name().to_string())
+            .collect()
+    }
+
+    /// Returns the type of an asset by name, such as `"ResTexture"`, or undefined if there is no
+    /// asset with that name.
+    #[wasm_bindgen(js_name = assetType)]
+    pub fn asset_type(&self, name: &str) -> Option<String> {
+        self.bnl
+            .asset_description(name)
+            .map(|desc| format!("{:?}", desc.asset_type()))
+    }
+
+    /// Returns the asset table of the file as a JSON string, see [`crate::manifest::Manifest`].
+    #[wasm_bindgen(js_name = manifestJson)]
+    pub fn manifest_json(&self) -> String {
+        self.bnl.manifest().to_json().to_string()
+    }
+
+    /// Decodes a texture by name into RGBA pixels.
+    #[wasm_bindgen(js_name = textureRgba)]
+    pub fn texture_rgba(&self, name: &str) -> Result<RgbaImage, JsError> {
+        let texture = self.bnl.get_asset::<Texture>(name)?;
+        let image = texture.to_rgba_image()?;
+
+        Ok(RgbaImage {
+            width: image.width() as u32,
+            height: image.height() as u32,
+            pixels: image.bytes().to_vec(),
+        })
+    }
+}
+
+/// A decoded image with 4 bytes per pixel, in RGBA order.
+#[wasm_bindgen]
+pub struct RgbaImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl RgbaImage {
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns a copy of the pixels of the image, row by row from the top.
+    pub fn pixels(&self) -> Vec<u8> {
+        self.pixels.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::make_bnl;
+
+    #[test]
+    fn lists_and_decodes_assets() {
+        let bnl = WasmBnlFile::new(&make_bnl()).unwrap();
+
+        assert_eq!(bnl.asset_names(), ["aid_texture_test"]);
+        assert_eq!(
+            bnl.asset_type("aid_texture_test").as_deref(),
+            Some("ResTexture")
+        );
+        assert_eq!(bnl.asset_type("aid_missing"), None);
+
+        let image = bnl.texture_rgba("aid_texture_test").unwrap();
+        assert_eq!(
+            image.pixels().len(),
+            (image.width() * image.height() * 4) as usize
+        );
+    }
+}
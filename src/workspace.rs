@@ -0,0 +1,250 @@
+//! Loads a directory of [`BNLFile`]s together, so that assets can be looked up across bundles.
+//! Models and scripts often reference assets that are stored in a different bundle, so parsing a
+//! single file isn't enough to resolve them.
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    BNLError, BNLFile,
+    asset::{Asset, AssetDescription, AssetError, RawAsset},
+    format,
+};
+
+/// A [`BNLFile`] loaded as part of a [`Workspace`], along with the path it was loaded from.
+#[derive(Debug)]
+pub struct Bundle {
+    path: PathBuf,
+    bnl: BNLFile,
+}
+
+impl Bundle {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn bnl(&self) -> &BNLFile {
+        &self.bnl
+    }
+}
+
+/// An error that occurred while loading a [`Workspace`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum WorkspaceError {
+    /// A file or directory could not be read.
+    Io { path: PathBuf, error: io::Error },
+    /// A bundle was read, but could not be parsed.
+    Bundle { path: PathBuf, error: BNLError },
+}
+
+impl Display for WorkspaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkspaceError::Io { path, error } => {
+                write!(f, "Unable to read {}: {}", path.display(), error)
+            }
+            WorkspaceError::Bundle { path, error } => {
+                write!(f, "Unable to parse {}: {}", path.display(), error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for WorkspaceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WorkspaceError::Io { error, .. } => Some(error),
+            WorkspaceError::Bundle { error, .. } => Some(error),
+        }
+    }
+}
+
+/// A set of bundles, with every asset name indexed to the bundles containing it.
+///
+/// When the same asset name appears in more than one bundle, lookups return the bundle that was
+/// loaded first. [`Workspace::open`] loads bundles in path order.
+#[derive(Debug, Default)]
+pub struct Workspace {
+    bundles: Vec<Bundle>,
+    /// Every asset name, mapped to (bundle index, asset description index) in load order.
+    index: HashMap<String, Vec<(usize, usize)>>,
+}
+
+impl Workspace {
+    /// Loads every .bnl file directly inside of `dir`, in any of the supported
+    /// [`format::FORMATS`].
+    ///
+    /// # Errors
+    /// Returns an error if the directory or any bundle in it can't be read or parsed.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use std::path::Path;
+    ///
+    /// use bnl::workspace::Workspace;
+    ///
+    /// let workspace = Workspace::open(Path::new("./game/bundles")).expect("Unable to open.");
+    ///
+    /// if let Some((bundle, desc)) = workspace.find_asset("aid_texture_mytexture_a_b") {
+    ///     println!("Found {:?} in {}", desc.asset_type(), bundle.path().display());
+    /// }
+    /// ```
+    pub fn open(dir: &Path) -> Result<Workspace, WorkspaceError> {
+        let io_error = |path: &Path| {
+            let path = path.to_path_buf();
+            move |error| WorkspaceError::Io { path, error }
+        };
+
+        let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+            .map_err(io_error(dir))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.is_file()
+                    && path
+                        .extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case("bnl"))
+            })
+            .collect();
+
+        paths.sort();
+
+        let mut workspace = Workspace::default();
+
+        for path in paths {
+            let bytes = fs::read(&path).map_err(io_error(&path))?;
+
+            let (bnl, _) = format::parse(&bytes).map_err(|error| WorkspaceError::Bundle {
+                path: path.clone(),
+                error,
+            })?;
+
+            workspace.add_bundle(path, bnl);
+        }
+
+        Ok(workspace)
+    }
+
+    /// Adds an already parsed bundle to this workspace. Its assets are looked up after those of
+    /// every bundle added before it.
+    pub fn add_bundle(&mut self, path: PathBuf, bnl: BNLFile) {
+        let bundle_index = self.bundles.len();
+
+        for (asset_index, desc) in bnl.asset_descriptions().iter().enumerate() {
+            self.index
+                .entry(desc.name().to_string())
+                .or_default()
+                .push((bundle_index, asset_index));
+        }
+
+        self.bundles.push(Bundle { path, bnl });
+    }
+
+    pub fn bundles(&self) -> &[Bundle] {
+        &self.bundles
+    }
+
+    /// Returns the bundle loaded from `path`.
+    pub fn bundle(&self, path: &Path) -> Option<&Bundle> {
+        self.bundles.iter().find(|bundle| bundle.path == path)
+    }
+
+    /// Returns every unique asset name in this workspace, in no particular order.
+    pub fn asset_names(&self) -> impl Iterator<Item = &str> {
+        self.index.keys().map(String::as_str)
+    }
+
+    /// Finds an asset by name across every bundle, returning the first bundle containing it.
+    pub fn find_asset(&self, name: &str) -> Option<(&Bundle, &AssetDescription)> {
+        self.find_asset_all(name).next()
+    }
+
+    /// Finds every copy of an asset across every bundle, in load order.
+    pub fn find_asset_all(&self, name: &str) -> impl Iterator<Item = (&Bundle, &AssetDescription)> {
+        self.index
+            .get(name)
+            .into_iter()
+            .flatten()
+            .map(|&(bundle_index, asset_index)| {
+                let bundle = &self.bundles[bundle_index];
+                (bundle, &bundle.bnl.asset_descriptions()[asset_index])
+            })
+    }
+
+    /// Retrieves an asset by name and type from whichever bundle contains it, see
+    /// [`BNLFile::get_asset`].
+    ///
+    /// # Errors
+    /// See [`BNLFile::get_asset`].
+    pub fn get_asset<A: Asset>(&self, name: &str) -> Result<A, AssetError> {
+        let (bundle, _) = self.find_asset(name).ok_or(AssetError::NotFound)?;
+
+        bundle.bnl.get_asset(name)
+    }
+
+    /// Retrieves the raw bytes of an asset from whichever bundle contains it, see
+    /// [`BNLFile::get_raw_asset`].
+    ///
+    /// # Errors
+    /// See [`BNLFile::get_raw_asset`].
+    pub fn get_raw_asset(&self, name: &str) -> Result<RawAsset, AssetError> {
+        let (bundle, _) = self.find_asset(name).ok_or(AssetError::NotFound)?;
+
+        bundle.bnl.get_raw_asset(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game::AssetType, tests::bnl_from_raw_assets};
+
+    fn raw_asset(name: &str, data: &[u8]) -> RawAsset {
+        RawAsset {
+            name: name.to_string(),
+            asset_type: AssetType::ResScript,
+            descriptor_bytes: vec![],
+            data_slices: vec![data.to_vec()],
+        }
+    }
+
+    #[test]
+    fn finds_assets_across_bundles() {
+        let dir = std::env::temp_dir().join("bnl_workspace_test");
+        fs::create_dir_all(&dir).unwrap();
+
+        let common = bnl_from_raw_assets(&[
+            raw_asset("aid_script_shared", &[1]),
+            raw_asset("aid_script_common", &[2]),
+        ]);
+        let level = bnl_from_raw_assets(&[raw_asset("aid_script_shared", &[3])]);
+
+        fs::write(dir.join("a_common.bnl"), common.to_bytes().unwrap()).unwrap();
+        fs::write(dir.join("b_level.BNL"), level.to_bytes().unwrap()).unwrap();
+        fs::write(dir.join("readme.txt"), b"not a bundle").unwrap();
+
+        let workspace = Workspace::open(&dir).unwrap();
+        assert_eq!(workspace.bundles().len(), 2);
+        assert_eq!(workspace.asset_names().count(), 2);
+
+        let (bundle, desc) = workspace.find_asset("aid_script_shared").unwrap();
+        assert_eq!(bundle.path(), dir.join("a_common.bnl"));
+        assert_eq!(desc.asset_type(), AssetType::ResScript);
+        assert_eq!(workspace.find_asset_all("aid_script_shared").count(), 2);
+
+        let raw = workspace.get_raw_asset("aid_script_shared").unwrap();
+        assert_eq!(raw.data_slices, [[1]]);
+
+        assert!(workspace.find_asset("aid_script_missing").is_none());
+        assert!(matches!(
+            workspace.get_raw_asset("aid_script_missing"),
+            Err(AssetError::NotFound)
+        ));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
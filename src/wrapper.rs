@@ -0,0 +1,245 @@
+//! Detection of resources that are wrapped in a further container or compression layer, such as
+//! a zlib stream inside of an asset's data.
+//!
+//! Wrappers are found by their magic bytes, and can be removed by [`Unwrapper`]s registered with an
+//! [`UnwrapRegistry`]. Decoders for new wrappers can be added as [`Unwrapper`]s without changing
+//! how assets are parsed.
+
+use std::io;
+
+use crate::{BNLFile, asset::RawAsset};
+
+/// A known wrapper format, identified by the bytes its data starts with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WrapperSignature {
+    pub name: &'static str,
+    pub magic: &'static [u8],
+}
+
+/// The wrapper formats that [`detect`] looks for.
+pub const SIGNATURES: &[WrapperSignature] = &[
+    WrapperSignature {
+        name: "zlib (no compression)",
+        magic: &[0x78, 0x01],
+    },
+    WrapperSignature {
+        name: "zlib",
+        magic: &[0x78, 0x5e],
+    },
+    WrapperSignature {
+        name: "zlib",
+        magic: &[0x78, 0x9c],
+    },
+    WrapperSignature {
+        name: "zlib (best compression)",
+        magic: &[0x78, 0xda],
+    },
+    WrapperSignature {
+        name: "gzip",
+        magic: &[0x1f, 0x8b],
+    },
+    WrapperSignature {
+        name: "zip",
+        magic: b"PK\x03\x04",
+    },
+    WrapperSignature {
+        name: "Xbox packed resource (XPR0)",
+        magic: b"XPR0",
+    },
+    WrapperSignature {
+        name: "Xbox packed resource (XPR1)",
+        magic: b"XPR1",
+    },
+    WrapperSignature {
+        name: "RIFF",
+        magic: b"RIFF",
+    },
+];
+
+/// Returns the wrapper signature that `bytes` starts with, if any.
+pub fn detect(bytes: &[u8]) -> Option<&'static WrapperSignature> {
+    SIGNATURES
+        .iter()
+        .find(|signature| bytes.starts_with(signature.magic))
+}
+
+/// A resource slice that starts with a known wrapper signature.
+#[derive(Debug, Clone)]
+pub struct WrappedResource {
+    pub asset: String,
+    /// The index of the data slice within the asset.
+    pub slice_index: usize,
+    pub size: usize,
+    pub signature: &'static WrapperSignature,
+}
+
+/// Removes one kind of wrapper from resource data.
+pub trait Unwrapper {
+    /// A short name for the wrapper this removes, used in reports.
+    fn name(&self) -> &str;
+
+    /// Returns true if `bytes` look like they are wrapped in this unwrapper's format.
+    fn matches(&self, bytes: &[u8]) -> bool;
+
+    /// Removes the wrapper, returning the inner data.
+    fn unwrap(&self, bytes: &[u8]) -> Result<Vec<u8>, io::Error>;
+}
+
+/// Unwraps zlib streams.
+#[derive(Debug, Default)]
+pub struct ZlibUnwrapper;
+
+impl Unwrapper for ZlibUnwrapper {
+    fn name(&self) -> &str {
+        "zlib"
+    }
+
+    fn matches(&self, bytes: &[u8]) -> bool {
+        detect(bytes).is_some_and(|signature| signature.name.starts_with("zlib"))
+    }
+
+    fn unwrap(&self, bytes: &[u8]) -> Result<Vec<u8>, io::Error> {
+        miniz_oxide::inflate::decompress_to_vec_zlib(bytes).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unable to inflate zlib stream: {:?}", e.status),
+            )
+        })
+    }
+}
+
+/// A set of [`Unwrapper`]s, tried in the order they were registered.
+pub struct UnwrapRegistry {
+    unwrappers: Vec<Box<dyn Unwrapper>>,
+}
+
+impl Default for UnwrapRegistry {
+    /// Creates a registry holding the built in unwrappers (currently only [`ZlibUnwrapper`]).
+    fn default() -> Self {
+        UnwrapRegistry {
+            unwrappers: vec![Box::new(ZlibUnwrapper)],
+        }
+    }
+}
+
+impl UnwrapRegistry {
+    /// Creates a registry without any unwrappers.
+    pub fn empty() -> Self {
+        UnwrapRegistry { unwrappers: vec![] }
+    }
+
+    /// Adds an unwrapper, which is tried after every previously registered one.
+    pub fn register(&mut self, unwrapper: impl Unwrapper + 'static) {
+        self.unwrappers.push(Box::new(unwrapper));
+    }
+
+    /// Removes the outermost wrapper from `bytes`. Returns None if no registered unwrapper
+    /// matches, or the name of the unwrapper used and its result.
+    pub fn unwrap(&self, bytes: &[u8]) -> Option<(&str, Result<Vec<u8>, io::Error>)> {
+        self.unwrappers
+            .iter()
+            .find(|unwrapper| unwrapper.matches(bytes))
+            .map(|unwrapper| (unwrapper.name(), unwrapper.unwrap(bytes)))
+    }
+
+    /// Unwraps every data slice of a raw asset in place, repeatedly until no unwrapper matches.
+    /// Returns the number of wrappers removed.
+    ///
+    /// The result is for inspection only, as the unwrapped slices no longer match the sizes
+    /// stored in the BNL file.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use bnl::wrapper::UnwrapRegistry;
+    ///
+    /// # let bnl = bnl::BNLFile::new();
+    /// let mut raw_asset = bnl.get_raw_asset("aid_script_intro").expect("Unable to get asset.");
+    /// let removed = UnwrapRegistry::default().unwrap_raw_asset(&mut raw_asset)
+    ///                                        .expect("Unable to unwrap asset.");
+    /// ```
+    pub fn unwrap_raw_asset(&self, raw_asset: &mut RawAsset) -> Result<usize, io::Error> {
+        let mut removed = 0;
+
+        for slice in &mut raw_asset.data_slices {
+            while let Some((_, result)) = self.unwrap(slice) {
+                *slice = result?;
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+impl BNLFile {
+    /// Finds every resource slice in this file that starts with a known wrapper signature.
+    pub fn find_wrapped_resources(&self) -> Vec<WrappedResource> {
+        self.get_raw_assets()
+            .into_iter()
+            .flat_map(|raw_asset| {
+                raw_asset
+                    .data_slices
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(slice_index, slice)| {
+                        detect(slice).map(|signature| WrappedResource {
+                            asset: raw_asset.name.clone(),
+                            slice_index,
+                            size: slice.len(),
+                            signature,
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::AssetType;
+
+    struct ReverseUnwrapper;
+
+    impl Unwrapper for ReverseUnwrapper {
+        fn name(&self) -> &str {
+            "reverse"
+        }
+
+        fn matches(&self, bytes: &[u8]) -> bool {
+            bytes.starts_with(b"REV")
+        }
+
+        fn unwrap(&self, bytes: &[u8]) -> Result<Vec<u8>, io::Error> {
+            Ok(bytes[3..].iter().rev().copied().collect())
+        }
+    }
+
+    #[test]
+    fn unwraps_nested_layers() {
+        let inner = b"REVolleh".to_vec();
+        let wrapped = miniz_oxide::deflate::compress_to_vec_zlib(&inner, 6);
+
+        assert_eq!(detect(&wrapped).unwrap().name, "zlib");
+        assert!(detect(b"plain data").is_none());
+
+        let mut raw_asset = RawAsset {
+            name: "aid_script_test".to_string(),
+            asset_type: AssetType::ResScript,
+            descriptor_bytes: vec![],
+            data_slices: vec![wrapped, b"plain".to_vec()],
+        };
+
+        let mut registry = UnwrapRegistry::default();
+        registry.register(ReverseUnwrapper);
+
+        assert_eq!(registry.unwrap_raw_asset(&mut raw_asset).unwrap(), 2);
+        assert_eq!(
+            raw_asset.data_slices,
+            [b"hello".to_vec(), b"plain".to_vec()]
+        );
+
+        assert!(UnwrapRegistry::empty().unwrap(b"REVx").is_none());
+    }
+}